@@ -5,3 +5,5 @@ pub mod exe_unit;
 pub mod keystore;
 pub mod preset;
 pub mod profile;
+pub mod reputation;
+pub mod rules;
@@ -2,8 +2,11 @@ use std::collections::HashMap;
 
 use anyhow::{anyhow, bail, Result};
 use dialoguer::{Input, Select};
+use serde::Serialize;
 use structopt::StructOpt;
 
+use crate::hardware::validate_resource_name;
+use crate::market::repricing::PricingState;
 use crate::market::{Preset, PresetManager};
 use crate::startup_config::{PresetNoInteractive, ProviderConfig, UpdateNames};
 
@@ -229,6 +232,7 @@ pub fn create(config: ProviderConfig, params: PresetNoInteractive) -> anyhow::Re
             preset.usage_coeffs.insert(usage_coefficient, *price);
         }
     }
+    preset.resources.extend(params.resource.iter().cloned());
 
     validate_preset(&config, &preset)?;
 
@@ -244,19 +248,54 @@ pub fn create(config: ProviderConfig, params: PresetNoInteractive) -> anyhow::Re
 fn list(config: ProviderConfig) -> anyhow::Result<()> {
     let presets = PresetManager::load_or_create(&config.presets_file)?;
     let registry = config.registry()?;
+    let pricing = PricingState::load(&config.pricing_file)?;
 
     if config.json {
-        println!("{}", serde_json::to_string_pretty(&presets.list())?);
+        let presets = presets
+            .list()
+            .into_iter()
+            .map(|preset| EffectivePreset::new(preset, &pricing))
+            .collect::<Vec<_>>();
+        println!("{}", serde_json::to_string_pretty(&presets)?);
     } else {
         println!("Available Presets:");
 
         for preset in presets.list().iter() {
-            println!("\n{}", preset.display(&registry));
+            match pricing.is_adjusted() {
+                true => println!(
+                    "\n{}",
+                    preset
+                        .display(&registry)
+                        .effective_prices(pricing.multiplier)
+                ),
+                false => println!("\n{}", preset.display(&registry)),
+            }
         }
     }
     Ok(())
 }
 
+/// Preset together with prices currently offered on the market.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct EffectivePreset {
+    #[serde(flatten)]
+    preset: Preset,
+    effective_initial_price: f64,
+    effective_usage_coeffs: HashMap<String, f64>,
+}
+
+impl EffectivePreset {
+    fn new(preset: Preset, pricing: &PricingState) -> Self {
+        let effective = pricing.apply(&preset);
+        EffectivePreset {
+            preset,
+            effective_initial_price: effective.initial_price,
+            effective_usage_coeffs: effective.usage_coeffs,
+        }
+    }
+}
+
 fn active_presets(config: ProviderConfig) -> anyhow::Result<()> {
     let presets = PresetManager::load_or_create(&config.presets_file)?;
     if config.json {
@@ -322,6 +361,7 @@ fn update_presets(
                         .insert(exe_unit_desc.resolve_coefficient(&name)?, *price);
                 }
             }
+            preset.resources.extend(params.resource.iter().cloned());
 
             validate_preset(&config, &preset)?;
 
@@ -351,6 +391,13 @@ fn validate_preset(config: &ProviderConfig, preset: &Preset) -> anyhow::Result<(
         bail!("Not supported pricing model.")
     }
 
+    for (name, amount) in preset.resources.iter() {
+        validate_resource_name(name)?;
+        if *amount <= 0 {
+            bail!("Resource '{}' amount has to be positive.", name)
+        }
+    }
+
     Ok(())
 }
 
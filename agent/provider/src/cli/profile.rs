@@ -1,5 +1,7 @@
 use crate::hardware::ProfileError;
-use crate::hardware::{Profiles, Resources, UpdateResources};
+use crate::hardware::{
+    parse_custom_resource, CustomResource, Profiles, Resources, UpdateResources,
+};
 use crate::startup_config::{ProviderConfig, UpdateNames};
 use structopt::StructOpt;
 
@@ -15,6 +17,9 @@ pub enum ProfileConfig {
         name: String,
         #[structopt(flatten)]
         resources: Resources,
+        /// User-defined countable resource, e.g. `--resource seats=4:licence`
+        #[structopt(long = "resource", parse(try_from_str = parse_custom_resource))]
+        custom: Vec<(String, CustomResource)>,
     },
     /// Update a profile
     Update {
@@ -38,11 +43,16 @@ impl ProfileConfig {
                     let profiles = Profiles::load_or_create(&config)?.list();
                     println!("{}", serde_json::to_string_pretty(&profiles)?);
                 }
-                ProfileConfig::Create { name, resources } => {
+                ProfileConfig::Create {
+                    name,
+                    mut resources,
+                    custom,
+                } => {
                     let mut profiles = Profiles::load_or_create(&config)?;
                     if let Some(_) = profiles.get(&name) {
                         return Err(ProfileError::AlreadyExists(name).into());
                     }
+                    resources.custom.extend(custom);
                     profiles.add(name, resources)?;
                     profiles.save(path)?;
                 }
@@ -76,7 +86,7 @@ fn update_profiles(
 ) -> anyhow::Result<()> {
     let mut profiles = Profiles::load_or_create(&config)?;
 
-    fn update_profile(resources: &mut Resources, new_resources: UpdateResources) {
+    fn update_profile(resources: &mut Resources, new_resources: &UpdateResources) {
         if let Some(cpu_threads) = new_resources.cpu_threads {
             resources.cpu_threads = cpu_threads;
         }
@@ -86,16 +96,22 @@ fn update_profiles(
         if let Some(storage_gib) = new_resources.storage_gib {
             resources.storage_gib = storage_gib;
         }
+        for name in new_resources.remove_custom.iter() {
+            resources.custom.remove(name);
+        }
+        resources
+            .custom
+            .extend(new_resources.custom.iter().cloned());
     }
 
     if names.all {
         for resources in profiles.list().values_mut() {
-            update_profile(resources, new_resources);
+            update_profile(resources, &new_resources);
         }
     } else {
         for name in names.name {
             match profiles.get_mut(&name) {
-                Some(resources) => update_profile(resources, new_resources),
+                Some(resources) => update_profile(resources, &new_resources),
                 _ => return Err(ProfileError::Unknown(name).into()),
             }
         }
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Serialize};
+use structopt::StructOpt;
+
+use ya_client::model::NodeId;
+
+use crate::config::reputation::{ReputationState, RequestorRecord};
+use crate::startup_config::ProviderConfig;
+
+#[derive(StructOpt, Clone, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub enum ReputationConfig {
+    /// List scores of known requestors
+    List,
+    /// Show payment history of a requestor
+    Show(Show),
+    /// Reset requestor score
+    Reset(Reset),
+}
+
+#[derive(StructOpt, Clone, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub struct Show {
+    requestor: NodeId,
+}
+
+#[derive(StructOpt, Clone, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub struct Reset {
+    #[structopt(required_unless = "all")]
+    requestor: Option<NodeId>,
+    /// Reset scores of all requestors
+    #[structopt(long, conflicts_with = "requestor")]
+    all: bool,
+}
+
+impl ReputationConfig {
+    pub fn run(self, config: ProviderConfig) -> anyhow::Result<()> {
+        match self {
+            ReputationConfig::List => list(config),
+            ReputationConfig::Show(show_) => show(config, show_),
+            ReputationConfig::Reset(reset_) => reset(config, reset_),
+        }
+    }
+}
+
+fn list(config: ProviderConfig) -> anyhow::Result<()> {
+    let state = ReputationState::load(&config.reputation_file)?;
+    let entries: Vec<_> = state
+        .requestors
+        .into_iter()
+        .map(|(requestor, record)| FormattedRecord::new(requestor, record))
+        .collect();
+
+    if config.json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+    } else {
+        println!("Requestor\tScore\tSettled\tNotes overdue\tPayments overdue\tBroken");
+        for entry in entries {
+            println!(
+                "{}\t{:.2}\t{}\t{}\t{}\t{}",
+                entry.requestor,
+                entry.score,
+                entry.record.invoices_settled,
+                entry.record.debit_notes_overdue,
+                entry.record.payments_overdue,
+                entry.record.agreements_broken,
+            );
+        }
+    }
+
+    Ok(())
+}
+
+fn show(config: ProviderConfig, show: Show) -> anyhow::Result<()> {
+    let state = ReputationState::load(&config.reputation_file)?;
+    let entry = FormattedRecord::new(show.requestor.to_string(), state.get(&show.requestor));
+
+    if config.json {
+        println!("{}", serde_json::to_string_pretty(&entry)?);
+    } else {
+        println!("Requestor:        {}", entry.requestor);
+        println!("Score:            {:.2}", entry.score);
+        println!("Invoices settled: {}", entry.record.invoices_settled);
+        println!("Notes overdue:    {}", entry.record.debit_notes_overdue);
+        println!("Payments overdue: {}", entry.record.payments_overdue);
+        println!("Broken:           {}", entry.record.agreements_broken);
+        if let Some(ts) = entry.record.last_updated {
+            println!("Last updated:     {}", ts.to_rfc3339());
+        }
+    }
+
+    Ok(())
+}
+
+fn reset(config: ProviderConfig, reset: Reset) -> anyhow::Result<()> {
+    let mut state = ReputationState::load(&config.reputation_file)?;
+    match reset.requestor {
+        Some(requestor) => {
+            state
+                .reset(&requestor)
+                .ok_or_else(|| anyhow::anyhow!("no reputation record for {}", requestor))?;
+        }
+        None => state.requestors.clear(),
+    }
+    state.save(&config.reputation_file)
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FormattedRecord {
+    requestor: String,
+    score: f64,
+    #[serde(flatten)]
+    record: RequestorRecord,
+}
+
+impl FormattedRecord {
+    fn new(requestor: String, record: RequestorRecord) -> Self {
+        FormattedRecord {
+            requestor,
+            score: record.score(),
+            record,
+        }
+    }
+}
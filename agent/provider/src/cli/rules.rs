@@ -0,0 +1,112 @@
+use structopt::{clap, StructOpt};
+
+use ya_client::model::NodeId;
+use ya_manifest_utils::Keystore;
+
+use crate::config::rules::{RuleList, Rules};
+use crate::startup_config::ProviderConfig;
+
+#[derive(StructOpt, Clone, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub enum RulesConfig {
+    /// Show current rules
+    Show,
+    /// Accept only requestors matching the allow list
+    AllowOnly {
+        #[structopt(parse(try_from_str), possible_values = &["true", "false"])]
+        enabled: bool,
+    },
+    /// Manage allowed requestors
+    Allow(ListCommand),
+    /// Manage denied requestors
+    Deny(ListCommand),
+}
+
+#[derive(StructOpt, Clone, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub enum ListCommand {
+    /// Add an entry to the list
+    Add(Entry),
+    /// Remove an entry from the list
+    Remove(Entry),
+}
+
+#[derive(StructOpt, Clone, Debug)]
+#[structopt(rename_all = "kebab-case")]
+#[structopt(group = clap::ArgGroup::with_name("entry").required(true))]
+pub struct Entry {
+    /// Requestor node id
+    #[structopt(long, group = "entry")]
+    node_id: Option<NodeId>,
+    /// Name of a trusted key, which signs Demand manifests
+    #[structopt(long, group = "entry")]
+    key: Option<String>,
+}
+
+impl RulesConfig {
+    pub fn run(self, config: ProviderConfig) -> anyhow::Result<()> {
+        let mut rules = Rules::load(&config.rules_file)?;
+        match self {
+            RulesConfig::Show => return show(&config, &rules),
+            RulesConfig::AllowOnly { enabled } => rules.allow_only = enabled,
+            RulesConfig::Allow(cmd) => cmd.apply(&config, &mut rules.allow)?,
+            RulesConfig::Deny(cmd) => cmd.apply(&config, &mut rules.deny)?,
+        }
+        rules.save(&config.rules_file)
+    }
+}
+
+impl ListCommand {
+    fn apply(self, config: &ProviderConfig, list: &mut RuleList) -> anyhow::Result<()> {
+        match self {
+            ListCommand::Add(entry) => {
+                if let Some(node_id) = entry.node_id {
+                    list.node_ids.insert(node_id.to_string());
+                }
+                if let Some(key) = entry.key {
+                    ensure_trusted_key(config, &key)?;
+                    list.keys.insert(key);
+                }
+            }
+            ListCommand::Remove(entry) => {
+                let removed = match (entry.node_id, entry.key) {
+                    (Some(node_id), _) => list.node_ids.remove(&node_id.to_string()),
+                    (_, Some(key)) => list.keys.remove(&key),
+                    _ => false,
+                };
+                if !removed {
+                    anyhow::bail!("entry does not exist");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn show(config: &ProviderConfig, rules: &Rules) -> anyhow::Result<()> {
+    if config.json {
+        println!("{}", serde_json::to_string_pretty(rules)?);
+        return Ok(());
+    }
+
+    println!("Allow only:\t{}", rules.allow_only);
+    for (name, list) in vec![("Allow", &rules.allow), ("Deny", &rules.deny)] {
+        println!("\n{}:", name);
+        for node_id in &list.node_ids {
+            println!("\tnode\t{}", node_id);
+        }
+        for key in &list.keys {
+            println!("\tkey\t{}", key);
+        }
+    }
+    Ok(())
+}
+
+fn ensure_trusted_key(config: &ProviderConfig, name: &str) -> anyhow::Result<()> {
+    let data_dir = config.data_dir.get_or_create()?;
+    let keystore = Keystore::load(data_dir.join(config.trusted_keys_file.as_path()))?;
+    match keystore.keys().values().any(|meta| meta.name == name) {
+        true => Ok(()),
+        false => anyhow::bail!("key '{}' is not in the trusted keystore", name),
+    }
+}
@@ -1,2 +1,4 @@
 pub mod globals;
 pub mod presets;
+pub mod reputation;
+pub mod rules;
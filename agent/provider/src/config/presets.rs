@@ -159,6 +159,7 @@ impl From<PresetV0> for Preset {
                     _ => None,
                 })
                 .collect(),
+            resources: Default::default(),
         }
     }
 }
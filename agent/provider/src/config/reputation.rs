@@ -0,0 +1,267 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::{fs, io};
+use ya_client::model::NodeId;
+use ya_utils_path::SwapSave;
+
+use crate::startup_config::{FileMonitor, FileMonitorConfig};
+
+pub(crate) const REPUTATION_JSON: &'static str = "reputation.json";
+
+/// Events observed during Agreement execution, which affect Requestor score.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReputationEvent {
+    /// Invoice was fully paid.
+    InvoiceSettled,
+    /// Requestor didn't accept DebitNote in time.
+    DebitNoteOverdue,
+    /// Requestor didn't pay for DebitNote in time.
+    PaymentOverdue,
+    /// Agreement was broken because of Requestor behavior.
+    AgreementBroken,
+}
+
+/// Payment history of a single Requestor.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestorRecord {
+    pub invoices_settled: u32,
+    pub debit_notes_overdue: u32,
+    pub payments_overdue: u32,
+    pub agreements_broken: u32,
+    pub last_updated: Option<DateTime<Utc>>,
+}
+
+impl RequestorRecord {
+    /// Score in range (0, 1]. Requestors without history start with 1.0,
+    /// every penalty lowers the score and every settled invoice restores it.
+    pub fn score(&self) -> f64 {
+        let good = self.invoices_settled as f64 + 1.0;
+        let bad =
+            (self.debit_notes_overdue + self.payments_overdue + self.agreements_broken) as f64;
+        good / (good + bad)
+    }
+
+    fn update(&mut self, event: ReputationEvent) {
+        match event {
+            ReputationEvent::InvoiceSettled => self.invoices_settled += 1,
+            ReputationEvent::DebitNoteOverdue => self.debit_notes_overdue += 1,
+            ReputationEvent::PaymentOverdue => self.payments_overdue += 1,
+            ReputationEvent::AgreementBroken => self.agreements_broken += 1,
+        }
+        self.last_updated = Some(Utc::now());
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReputationState {
+    pub requestors: BTreeMap<String, RequestorRecord>,
+}
+
+impl ReputationState {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if path.exists() && fs::metadata(path)?.len() > 0 {
+            log::debug!("Loading requestor reputation from: {}", path.display());
+            Ok(serde_json::from_reader(io::BufReader::new(
+                fs::OpenOptions::new().read(true).open(path)?,
+            ))?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        Ok(path.swap_save(serde_json::to_string_pretty(self)?)?)
+    }
+
+    pub fn get(&self, requestor: &NodeId) -> RequestorRecord {
+        self.requestors
+            .get(&requestor.to_string())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn reset(&mut self, requestor: &NodeId) -> Option<RequestorRecord> {
+        self.requestors.remove(&requestor.to_string())
+    }
+}
+
+/// Shared, file backed store of Requestors payment history.
+/// Store created with `Default` is kept only in memory.
+#[derive(Clone, Debug, Default)]
+pub struct ReputationStore {
+    state: Arc<Mutex<StoreState>>,
+    path: Option<PathBuf>,
+}
+
+#[derive(Debug, Default)]
+struct StoreState {
+    reputation: ReputationState,
+    /// Content of the file written by the store most recently.
+    saved: Option<String>,
+}
+
+impl StoreState {
+    fn save(&mut self, path: &Path) -> anyhow::Result<()> {
+        let content = serde_json::to_string_pretty(&self.reputation)?;
+        path.swap_save(content.clone())?;
+        self.saved = Some(content);
+        Ok(())
+    }
+}
+
+impl ReputationStore {
+    pub fn get(&self, requestor: &NodeId) -> RequestorRecord {
+        self.state.lock().unwrap().reputation.get(requestor)
+    }
+
+    pub fn score(&self, requestor: &NodeId) -> f64 {
+        self.get(requestor).score()
+    }
+
+    pub fn record(&self, requestor: &NodeId, event: ReputationEvent) {
+        let mut state = self.state.lock().unwrap();
+        let record = state
+            .reputation
+            .requestors
+            .entry(requestor.to_string())
+            .or_insert_with(Default::default);
+        record.update(event);
+
+        log::debug!(
+            "Requestor [{}] reputation updated after {:?}. Score: {:.2}",
+            requestor,
+            event,
+            record.score()
+        );
+
+        if let Some(path) = &self.path {
+            if let Err(e) = state.save(path) {
+                log::warn!(
+                    "Failed to save requestor reputation to {}: {}",
+                    path.display(),
+                    e
+                );
+            }
+        }
+    }
+
+    /// Replaces reputation with the content of the file. Returns false,
+    /// when the file was written by the store itself and nothing changed.
+    pub fn reload(&self, path: &Path) -> anyhow::Result<bool> {
+        let mut state = self.state.lock().unwrap();
+        let content = fs::read_to_string(path)?;
+        if state.saved.as_ref() == Some(&content) {
+            return Ok(false);
+        }
+        state.reputation = match content.trim().is_empty() {
+            true => ReputationState::default(),
+            false => serde_json::from_str(&content)?,
+        };
+        state.saved = None;
+        Ok(true)
+    }
+}
+
+/// Responsible for loading reputation and keeping it up to date with reputation file.
+pub struct ReputationManager {
+    store: ReputationStore,
+    monitor: Option<FileMonitor>,
+}
+
+impl ReputationManager {
+    pub fn load_or_create(path: &Path) -> anyhow::Result<Self> {
+        let mut state = StoreState {
+            reputation: ReputationState::load(path)?,
+            saved: None,
+        };
+        if !path.exists() {
+            state.save(path)?;
+        }
+        let store = ReputationStore {
+            state: Arc::new(Mutex::new(state)),
+            path: Some(path.to_path_buf()),
+        };
+        Ok(Self {
+            store,
+            monitor: None,
+        })
+    }
+
+    pub fn spawn_monitor(&mut self, path: &Path) -> anyhow::Result<()> {
+        let store = self.store.clone();
+        let handler = move |p: PathBuf| match store.reload(&p) {
+            Ok(true) => log::info!("Requestor reputation updated from {}", p.display()),
+            Ok(false) => (),
+            Err(e) => log::warn!("Error updating requestor reputation from {:?}: {:?}", p, e),
+        };
+        let monitor = FileMonitor::spawn_with(
+            path,
+            FileMonitor::on_modified(handler),
+            FileMonitorConfig::silent(),
+        )?;
+        self.monitor = Some(monitor);
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(mut monitor) = self.monitor.take() {
+            monitor.stop();
+        }
+    }
+
+    pub fn store(&self) -> ReputationStore {
+        self.store.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn score_without_history() {
+        assert_eq!(RequestorRecord::default().score(), 1.0);
+    }
+
+    #[test]
+    fn score_penalties_and_recovery() {
+        let mut record = RequestorRecord::default();
+        record.update(ReputationEvent::PaymentOverdue);
+        assert_eq!(record.score(), 0.5);
+
+        record.update(ReputationEvent::AgreementBroken);
+        record.update(ReputationEvent::DebitNoteOverdue);
+        assert_eq!(record.score(), 0.25);
+
+        record.update(ReputationEvent::InvoiceSettled);
+        record.update(ReputationEvent::InvoiceSettled);
+        assert_eq!(record.score(), 0.5);
+    }
+
+    #[test]
+    fn reload_skips_own_writes() {
+        let dir = tempdir::TempDir::new("reputation").unwrap();
+        let path = dir.path().join(REPUTATION_JSON);
+        let manager = ReputationManager::load_or_create(&path).unwrap();
+        let store = manager.store();
+        let requestor: NodeId = "0x0000000000000000000000000000000000000001"
+            .parse()
+            .unwrap();
+
+        assert!(!store.reload(&path).unwrap());
+        store.record(&requestor, ReputationEvent::PaymentOverdue);
+        assert!(!store.reload(&path).unwrap());
+        assert_eq!(store.get(&requestor).payments_overdue, 1);
+
+        // Changes made by other processes, e.g. `ya-provider reputation reset`.
+        let mut state = ReputationState::load(&path).unwrap();
+        state.reset(&requestor);
+        state.save(&path).unwrap();
+        assert!(store.reload(&path).unwrap());
+        assert_eq!(store.get(&requestor), RequestorRecord::default());
+    }
+}
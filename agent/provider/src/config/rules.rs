@@ -0,0 +1,174 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::{fs, io};
+use ya_client::model::NodeId;
+use ya_utils_path::SwapSave;
+
+use crate::startup_config::FileMonitor;
+
+pub(crate) const RULES_JSON: &'static str = "rules.json";
+
+/// List of Requestors, identified by node id or by the name of a trusted key
+/// (from the Keystore), which signed Demand manifest.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleList {
+    #[serde(default)]
+    pub node_ids: BTreeSet<String>,
+    #[serde(default)]
+    pub keys: BTreeSet<String>,
+}
+
+impl RuleList {
+    pub fn matches(&self, requestor: &NodeId, signer: Option<&str>) -> bool {
+        self.node_ids.contains(&requestor.to_string())
+            || signer.map(|name| self.keys.contains(name)).unwrap_or(false)
+    }
+}
+
+/// Rules deciding which Requestors can negotiate with Provider.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Rules {
+    /// Accept only Requestors matching the allow list.
+    #[serde(default)]
+    pub allow_only: bool,
+    #[serde(default)]
+    pub allow: RuleList,
+    #[serde(default)]
+    pub deny: RuleList,
+}
+
+impl Rules {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if path.exists() && fs::metadata(path)?.len() > 0 {
+            log::debug!("Loading requestor rules from: {}", path.display());
+            Ok(serde_json::from_reader(io::BufReader::new(
+                fs::OpenOptions::new().read(true).open(path)?,
+            ))?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn load_or_create(path: &Path) -> anyhow::Result<Self> {
+        let rules = Self::load(path)?;
+        if !path.exists() {
+            rules.save(path)?;
+        }
+        Ok(rules)
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        Ok(path.swap_save(serde_json::to_string_pretty(self)?)?)
+    }
+
+    /// Returns reason of rejection, if Requestor isn't allowed to negotiate.
+    pub fn check(&self, requestor: &NodeId, signer: Option<&str>) -> Result<(), String> {
+        if self.deny.matches(requestor, signer) {
+            return Err(format!("Requestor [{}] is on the deny list", requestor));
+        }
+        if self.allow_only && !self.allow.matches(requestor, signer) {
+            return Err(format!(
+                "Requestor [{}] is not on the allow list",
+                requestor
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Shared handle to current rules.
+#[derive(Clone, Debug, Default)]
+pub struct RulesStore {
+    state: Arc<Mutex<Rules>>,
+}
+
+impl RulesStore {
+    pub fn get(&self) -> Rules {
+        self.state.lock().unwrap().clone()
+    }
+
+    pub fn replace(&self, rules: Rules) {
+        *self.state.lock().unwrap() = rules;
+    }
+}
+
+/// Responsible for loading rules and keeping them up to date with rules file.
+pub struct RulesManager {
+    store: RulesStore,
+    monitor: Option<FileMonitor>,
+}
+
+impl RulesManager {
+    pub fn load_or_create(path: &Path) -> anyhow::Result<Self> {
+        let store = RulesStore::default();
+        store.replace(Rules::load_or_create(path)?);
+        Ok(Self {
+            store,
+            monitor: None,
+        })
+    }
+
+    pub fn spawn_monitor(&mut self, path: &Path) -> anyhow::Result<()> {
+        let store = self.store.clone();
+        let handler = move |p: std::path::PathBuf| match Rules::load(&p) {
+            Ok(rules) => {
+                store.replace(rules);
+                log::info!("Requestor rules updated from {}", p.display());
+            }
+            Err(e) => log::warn!("Error updating requestor rules from {:?}: {:?}", p, e),
+        };
+        let monitor = FileMonitor::spawn(path, FileMonitor::on_modified(handler))?;
+        self.monitor = Some(monitor);
+        Ok(())
+    }
+
+    pub fn stop(&mut self) {
+        if let Some(mut monitor) = self.monitor.take() {
+            monitor.stop();
+        }
+    }
+
+    pub fn store(&self) -> RulesStore {
+        self.store.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const REQUESTOR: &str = "0x0000000000000000000000000000000000000001";
+
+    #[test]
+    fn deny_list_takes_precedence() {
+        let requestor: NodeId = REQUESTOR.parse().unwrap();
+        let mut rules = Rules::default();
+        assert!(rules.check(&requestor, None).is_ok());
+
+        rules.allow.node_ids.insert(requestor.to_string());
+        rules.deny.keys.insert("abuser".to_string());
+        assert!(rules.check(&requestor, None).is_ok());
+        assert!(rules.check(&requestor, Some("abuser")).is_err());
+    }
+
+    #[test]
+    fn allow_only_by_node_or_key() {
+        let requestor: NodeId = REQUESTOR.parse().unwrap();
+        let mut rules = Rules {
+            allow_only: true,
+            ..Default::default()
+        };
+        assert!(rules.check(&requestor, None).is_err());
+
+        rules.allow.keys.insert("company".to_string());
+        assert!(rules.check(&requestor, Some("company")).is_ok());
+        assert!(rules.check(&requestor, Some("other")).is_err());
+
+        rules.allow.node_ids.insert(requestor.to_string());
+        assert!(rules.check(&requestor, None).is_ok());
+    }
+}
@@ -1,5 +1,5 @@
 use std::cmp::Ordering;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 #[cfg(windows)]
 use std::ffi::OsStr;
 use std::io;
@@ -7,6 +7,7 @@ use std::ops::{Add, Not, Sub};
 use std::path::Path;
 #[cfg(windows)]
 use std::ptr;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 
 use serde::{Deserialize, Serialize};
@@ -21,11 +22,11 @@ use crate::startup_config::{FileMonitor, ProviderConfig};
 
 pub const DEFAULT_PROFILE_NAME: &str = "default";
 pub const CPU_THREADS_RESERVED: i32 = 1;
-pub static MIN_CAPS: Resources = Resources {
-    cpu_threads: 1,
-    mem_gib: 0.1,
-    storage_gib: 0.1,
-};
+pub const MIN_CPU_THREADS: i32 = 1;
+pub const MIN_MEM_GIB: f64 = 0.1;
+pub const MIN_STORAGE_GIB: f64 = 0.1;
+/// Names used by built-in `golem.inf` properties.
+const RESERVED_RESOURCE_NAMES: [&str; 3] = ["cpu", "mem", "storage"];
 
 #[derive(Debug, thiserror::Error)]
 pub enum ProfileError {
@@ -35,6 +36,8 @@ pub enum ProfileError {
     AlreadyExists(String),
     #[error("profile is active: '{0}'")]
     Active(String),
+    #[error("invalid resource: '{0}'")]
+    InvalidResource(String),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -57,7 +60,7 @@ pub enum Error {
     Io(#[from] io::Error),
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, StructOpt)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, StructOpt)]
 #[structopt(rename_all = "kebab-case")]
 pub struct Resources {
     /// Number of CPU logical cores
@@ -69,9 +72,13 @@ pub struct Resources {
     /// Free partition space
     #[structopt(long)]
     pub storage_gib: f64,
+    /// User-defined countable resources
+    #[structopt(skip)]
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, CustomResource>,
 }
 
-#[derive(Clone, Copy, Debug, Serialize, Deserialize, StructOpt)]
+#[derive(Clone, Debug, Serialize, Deserialize, StructOpt)]
 #[structopt(rename_all = "kebab-case")]
 #[structopt(group = clap::ArgGroup::with_name("up-res").multiple(true).required(true))]
 pub struct UpdateResources {
@@ -84,6 +91,61 @@ pub struct UpdateResources {
     /// Free partition space
     #[structopt(long, group = "up-res")]
     pub storage_gib: Option<f64>,
+    /// Add or replace user-defined resource, e.g. `--resource seats=4:licence`
+    #[structopt(long = "resource", group = "up-res", parse(try_from_str = parse_custom_resource))]
+    pub custom: Vec<(String, CustomResource)>,
+    /// Remove user-defined resource
+    #[structopt(long = "remove-resource", group = "up-res")]
+    pub remove_custom: Vec<String>,
+}
+
+/// Countable resource defined by the user, e.g. licensed software seats.
+/// Presets consume it per Agreement, and it is advertised in Offers
+/// as `golem.inf.<name>`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct CustomResource {
+    /// Amount available in a profile, or amount allocated for an Agreement
+    pub capacity: i64,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub unit: String,
+}
+
+impl FromStr for CustomResource {
+    type Err = ProfileError;
+
+    /// Parses `<capacity>[:<unit>]`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut split = s.splitn(2, ':');
+        let capacity = split
+            .next()
+            .and_then(|capacity| capacity.trim().parse::<i64>().ok())
+            .filter(|capacity| *capacity >= 0)
+            .ok_or_else(|| ProfileError::InvalidResource(s.to_string()))?;
+        let unit = split.next().unwrap_or_default().trim().to_string();
+        Ok(CustomResource { capacity, unit })
+    }
+}
+
+/// Parses `<name>=<capacity>[:<unit>]`.
+pub fn parse_custom_resource(s: &str) -> Result<(String, CustomResource), ProfileError> {
+    let pos = s
+        .find('=')
+        .ok_or_else(|| ProfileError::InvalidResource(s.to_string()))?;
+    let name = s[..pos].trim().to_string();
+    validate_resource_name(&name)?;
+    Ok((name, s[pos + 1..].parse()?))
+}
+
+/// Names become `golem.inf.<name>` properties, so they can't contain
+/// separators, nor shadow built-in properties.
+pub fn validate_resource_name(name: &str) -> Result<(), ProfileError> {
+    let valid_chars = name
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if name.is_empty() || !valid_chars || RESERVED_RESOURCE_NAMES.contains(&name) {
+        return Err(ProfileError::InvalidResource(name.to_string()));
+    }
+    Ok(())
 }
 
 impl Resources {
@@ -111,11 +173,7 @@ impl Resources {
     }
 
     fn new_empty() -> Self {
-        Resources {
-            cpu_threads: 0,
-            mem_gib: 0.,
-            storage_gib: 0.,
-        }
+        Resources::default()
     }
 
     fn max_caps<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
@@ -123,6 +181,7 @@ impl Resources {
             cpu_threads: num_cpus::get() as i32,
             mem_gib: 1000. * sys_info::mem_info()?.total as f64 / (1024. * 1024. * 1024.),
             storage_gib: partition_space(path)? as f64 / (1024. * 1024. * 1024.),
+            custom: Default::default(),
         })
     }
 
@@ -132,23 +191,68 @@ impl Resources {
             cpu_threads: 1.max(res.cpu_threads - CPU_THREADS_RESERVED),
             mem_gib: 0.7 * res.mem_gib,
             storage_gib: 0.8 * res.storage_gib,
+            custom: Default::default(),
         })
     }
 
+    /// Checks whether there is not enough left for the smallest reservation.
     pub fn depleted(&self) -> bool {
-        self.cpu_threads <= 0 || self.mem_gib <= 0. || self.storage_gib <= 0.
+        self.cpu_threads < MIN_CPU_THREADS
+            || self.mem_gib < MIN_MEM_GIB
+            || self.storage_gib < MIN_STORAGE_GIB
+    }
+
+    /// Smallest amount of built-in resources reserved for a single Agreement.
+    pub fn min_reservation() -> Self {
+        Resources {
+            cpu_threads: MIN_CPU_THREADS,
+            mem_gib: MIN_MEM_GIB,
+            storage_gib: MIN_STORAGE_GIB,
+            custom: Default::default(),
+        }
     }
 
+    /// Limits built-in resources. User-defined resources can't be detected,
+    /// so they are left as declared.
     pub fn cap(mut self, res: &Resources) -> Self {
-        self.cpu_threads = MIN_CAPS
-            .cpu_threads
-            .max(self.cpu_threads.min(res.cpu_threads));
-        self.mem_gib = MIN_CAPS.mem_gib.max(self.mem_gib.min(res.mem_gib));
-        self.storage_gib = MIN_CAPS
-            .storage_gib
-            .max(self.storage_gib.min(res.storage_gib));
+        self.cpu_threads = MIN_CPU_THREADS.max(self.cpu_threads.min(res.cpu_threads));
+        self.mem_gib = MIN_MEM_GIB.max(self.mem_gib.min(res.mem_gib));
+        self.storage_gib = MIN_STORAGE_GIB.max(self.storage_gib.min(res.storage_gib));
         self
     }
+
+    fn validate_custom(&self) -> Result<(), ProfileError> {
+        for (name, resource) in self.custom.iter() {
+            validate_resource_name(name)?;
+            if resource.capacity < 0 {
+                return Err(ProfileError::InvalidResource(name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    #[inline]
+    pub fn custom_capacity(&self, name: &str) -> i64 {
+        self.custom.get(name).map(|r| r.capacity).unwrap_or(0)
+    }
+
+    fn merge_custom(
+        &self,
+        other: &Resources,
+        f: impl Fn(i64, i64) -> i64,
+    ) -> BTreeMap<String, CustomResource> {
+        let mut custom = self.custom.clone();
+        for (name, resource) in other.custom.iter() {
+            let entry = custom
+                .entry(name.clone())
+                .or_insert_with(|| CustomResource {
+                    capacity: 0,
+                    unit: resource.unit.clone(),
+                });
+            entry.capacity = f(entry.capacity, resource.capacity);
+        }
+        custom
+    }
 }
 
 impl PartialEq for Resources {
@@ -156,6 +260,7 @@ impl PartialEq for Resources {
         self.cpu_threads == other.cpu_threads
             && self.mem_gib == other.mem_gib
             && self.storage_gib == other.storage_gib
+            && self.custom == other.custom
     }
 }
 
@@ -166,6 +271,10 @@ impl PartialOrd for Resources {
         } else if self.cpu_threads >= other.cpu_threads
             && self.mem_gib >= other.mem_gib
             && self.storage_gib >= other.storage_gib
+            && other
+                .custom
+                .iter()
+                .all(|(name, r)| self.custom_capacity(name) >= r.capacity)
         {
             Some(Ordering::Greater)
         } else {
@@ -184,6 +293,7 @@ impl Add for Resources {
             cpu_threads: self.cpu_threads + rhs.cpu_threads,
             mem_gib: self.mem_gib + rhs.mem_gib,
             storage_gib: self.storage_gib + rhs.storage_gib,
+            custom: self.merge_custom(&rhs, |a, b| a + b),
         }
     }
 }
@@ -196,6 +306,7 @@ impl Sub for Resources {
             cpu_threads: self.cpu_threads - rhs.cpu_threads,
             mem_gib: self.mem_gib - rhs.mem_gib,
             storage_gib: self.storage_gib - rhs.storage_gib,
+            custom: self.merge_custom(&rhs, |a, b| a - b),
         }
     }
 }
@@ -208,14 +319,19 @@ impl From<Resources> for InfNodeInfo {
             threads: res.cpu_threads as u32,
         };
 
-        InfNodeInfo::default()
+        let inf_node_info = InfNodeInfo::default()
             .with_mem(res.mem_gib)
             .with_storage(res.storage_gib)
-            .with_cpu(cpu_info)
+            .with_cpu(cpu_info);
+        res.custom
+            .into_iter()
+            .fold(inf_node_info, |inf_node_info, (name, resource)| {
+                inf_node_info.with_custom(name, resource.capacity)
+            })
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct Profiles {
     active: String,
     profiles: HashMap<String, Resources>,
@@ -247,18 +363,26 @@ impl Profiles {
         log::debug!("Loading profile from: {}", path.as_ref().display());
         let contents = std::fs::read_to_string(&path)?;
         let new: Profiles = serde_json::from_str(contents.as_str())?;
-        if new.profiles.contains_key(&new.active).not() {
-            return Err(ProfileError::Unknown(new.active).into());
-        }
-
-        Ok(serde_json::from_str(contents.as_str())?)
+        new.validate()?;
+        Ok(new)
     }
 
-    #[inline]
+    /// Profiles failing validation are never saved, since they couldn't be loaded back.
     pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        self.validate()?;
         Ok(path.swap_save(serde_json::to_string_pretty(self)?)?)
     }
 
+    fn validate(&self) -> Result<(), ProfileError> {
+        if self.profiles.contains_key(&self.active).not() {
+            return Err(ProfileError::Unknown(self.active.clone()));
+        }
+        for resources in self.profiles.values() {
+            resources.validate_custom()?;
+        }
+        Ok(())
+    }
+
     fn try_with_config<P: AsRef<Path>>(path: P, config: &ProviderConfig) -> Result<Self, Error> {
         let resources = Resources::try_with_config(path.as_ref(), &config)?;
         let active = DEFAULT_PROFILE_NAME.to_string();
@@ -288,6 +412,7 @@ impl Profiles {
         if resources < Resources::new_empty() {
             return Err(Error::InsufficientResources);
         }
+        resources.validate_custom()?;
         self.profiles.insert(name.to_string(), resources);
         Ok(())
     }
@@ -323,11 +448,11 @@ impl Profiles {
 pub struct Manager {
     state: Arc<Mutex<ManagerState>>,
     monitor: Option<FileMonitor>,
-    sender: Option<watch::Sender<Event>>,
+    sender: Arc<watch::Sender<Event>>,
     receiver: watch::Receiver<Event>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 struct ManagerState {
     profiles: Profiles,
     res_available: Resources,
@@ -357,14 +482,34 @@ impl ManagerState {
         if res == self.res_cap {
             Ok(false)
         } else {
-            let delta = self.res_cap - res;
+            let delta = self.res_cap.clone() - res.clone();
             self.res_cap = res;
-            self.res_remaining = self.res_remaining - delta;
+            self.res_remaining = self.res_remaining.clone() - delta;
             log::info!("Hardware resources cap: {:?}", self.res_cap);
             log::info!("Hardware resources remaining: {:?}", self.res_remaining);
             Ok(true)
         }
     }
+
+    fn allocate(&mut self, id: String, res: Resources) -> Result<(), Error> {
+        if self.res_alloc.contains_key(&id) {
+            return Err(Error::AlreadyAllocated(id));
+        }
+        if self.res_remaining < res {
+            return Err(Error::InsufficientResources);
+        }
+        self.res_remaining = self.res_remaining.clone() - res.clone();
+        self.res_alloc.insert(id, res);
+        Ok(())
+    }
+
+    fn release(&mut self, id: String) -> Result<(), Error> {
+        match self.res_alloc.remove(&id) {
+            Some(res) => self.res_remaining = self.res_remaining.clone() + res,
+            _ => return Err(Error::NotAllocated(id)),
+        }
+        Ok(())
+    }
 }
 
 impl Manager {
@@ -384,13 +529,14 @@ impl Manager {
         Ok(Manager {
             state: Arc::new(Mutex::new(state)),
             monitor: None,
-            sender: Some(tx),
+            sender: Arc::new(tx),
             receiver: rx,
         })
     }
 
+    /// Spawning monitor again replaces the previous one.
     pub fn spawn_monitor<P: AsRef<Path>>(&mut self, path: P) -> Result<(), Error> {
-        let tx = self.sender.take().unwrap();
+        let tx = self.sender.clone();
         let state = self.state.clone();
         let handler = move |p| match Profiles::load(&p) {
             Ok(profiles) => {
@@ -415,6 +561,13 @@ impl Manager {
     pub fn event_receiver(&self) -> watch::Receiver<Event> {
         self.receiver.clone()
     }
+
+    /// Shared handle, through which negotiator allocates resources for Agreements.
+    pub fn store(&self) -> HardwareStore {
+        HardwareStore {
+            state: self.state.clone(),
+        }
+    }
 }
 
 impl Manager {
@@ -424,28 +577,75 @@ impl Manager {
         state.res_cap.clone()
     }
 
+    /// Capped resources, which are not allocated for any Agreement.
+    pub fn remaining(&self) -> Resources {
+        let state = self.state.lock().unwrap();
+        state.res_remaining.clone()
+    }
+
+    /// Highest fraction of capped resources, that is currently allocated.
+    pub fn utilization(&self) -> f64 {
+        let state = self.state.lock().unwrap();
+        let (cap, remaining) = (&state.res_cap, &state.res_remaining);
+        let ratio = |cap: f64, remaining: f64| match cap > 0.0 {
+            true => ((cap - remaining) / cap).max(0.0).min(1.0),
+            false => 0.0,
+        };
+        ratio(cap.cpu_threads as f64, remaining.cpu_threads as f64)
+            .max(ratio(cap.mem_gib, remaining.mem_gib))
+            .max(ratio(cap.storage_gib, remaining.storage_gib))
+    }
+
     #[allow(dead_code)]
     pub fn allocate(&mut self, id: String, res: Resources) -> Result<(), Error> {
-        let mut state = self.state.lock().unwrap();
-        if state.res_alloc.contains_key(&id) {
-            return Err(Error::AlreadyAllocated(id));
-        }
-        if state.res_remaining < res {
-            return Err(Error::InsufficientResources);
-        }
-        state.res_remaining = state.res_remaining - res;
-        state.res_alloc.insert(id, res);
-        Ok(())
+        self.store().allocate(id, res)
     }
 
     #[allow(dead_code)]
     pub fn release(&mut self, id: String) -> Result<(), Error> {
-        let mut state = self.state.lock().unwrap();
-        match state.res_alloc.remove(&id) {
-            Some(res) => state.res_remaining = state.res_remaining + res,
-            _ => return Err(Error::NotAllocated(id)),
+        self.store().release(id)
+    }
+}
+
+/// Shared handle to hardware resources of active profile.
+#[derive(Clone, Debug, Default)]
+pub struct HardwareStore {
+    state: Arc<Mutex<ManagerState>>,
+}
+
+impl HardwareStore {
+    pub fn capped(&self) -> Resources {
+        self.state.lock().unwrap().res_cap.clone()
+    }
+
+    pub fn remaining(&self) -> Resources {
+        self.state.lock().unwrap().res_remaining.clone()
+    }
+
+    /// Checks whether resources can be allocated now.
+    pub fn available(&self, res: &Resources) -> bool {
+        &self.state.lock().unwrap().res_remaining >= res
+    }
+
+    pub fn allocate(&self, id: impl ToString, res: Resources) -> Result<(), Error> {
+        self.state.lock().unwrap().allocate(id.to_string(), res)
+    }
+
+    pub fn release(&self, id: impl ToString) -> Result<(), Error> {
+        self.state.lock().unwrap().release(id.to_string())
+    }
+
+    #[cfg(test)]
+    pub fn with_resources(res: Resources) -> Self {
+        let state = ManagerState {
+            res_available: res.clone(),
+            res_cap: res.clone(),
+            res_remaining: res,
+            ..Default::default()
+        };
+        HardwareStore {
+            state: Arc::new(Mutex::new(state)),
         }
-        Ok(())
     }
 }
 
@@ -513,6 +713,7 @@ mod tests {
             cpu_threads: 4,
             mem_gib: 8.,
             storage_gib: 100.,
+            custom: Default::default(),
         };
         let profiles = vec![(active.clone(), resources)].into_iter().collect();
         Profiles { active, profiles }
@@ -524,11 +725,13 @@ mod tests {
             cpu_threads: 16,
             mem_gib: 24.,
             storage_gib: 200.,
+            custom: Default::default(),
         }
         .cap(&Resources {
             cpu_threads: 4,
             mem_gib: 8.,
             storage_gib: 100.,
+            custom: Default::default(),
         });
 
         assert_eq!(res.cpu_threads, 4);
@@ -542,11 +745,13 @@ mod tests {
             cpu_threads: 2,
             mem_gib: 2.,
             storage_gib: 20.,
+            custom: Default::default(),
         }
         .cap(&Resources {
             cpu_threads: 4,
             mem_gib: 8.,
             storage_gib: 100.,
+            custom: Default::default(),
         });
 
         assert_eq!(res.cpu_threads, 2);
@@ -560,11 +765,13 @@ mod tests {
             cpu_threads: 2,
             mem_gib: 2.,
             storage_gib: 20.,
+            custom: Default::default(),
         }
         .cap(&Resources {
             cpu_threads: 0,
             mem_gib: 0.,
             storage_gib: 0.,
+            custom: Default::default(),
         });
 
         assert_eq!(res.cpu_threads, 1);
@@ -578,6 +785,7 @@ mod tests {
             cpu_threads: 8,
             mem_gib: 24.,
             storage_gib: 200.,
+            custom: Default::default(),
         };
         let state = ManagerState {
             res_available: res.clone(),
@@ -590,13 +798,14 @@ mod tests {
         let mut man = Manager {
             state: Arc::new(Mutex::new(state)),
             monitor: None,
-            sender: Some(tx),
+            sender: Arc::new(tx),
             receiver: rx,
         };
         let alloc = Resources {
             cpu_threads: 1,
             mem_gib: 1.51,
             storage_gib: 12.37,
+            custom: Default::default(),
         };
 
         man.allocate("1".into(), alloc.clone()).unwrap();
@@ -606,7 +815,7 @@ mod tests {
         man.release("2".into()).unwrap();
         man.release("3".into()).unwrap();
 
-        let remaining = man.state.lock().unwrap().res_remaining;
+        let remaining = man.state.lock().unwrap().res_remaining.clone();
         assert_eq!(remaining.cpu_threads, res.cpu_threads);
         assert_eq!(remaining.mem_gib, res.mem_gib);
         assert_eq!(remaining.storage_gib, res.storage_gib);
@@ -618,6 +827,7 @@ mod tests {
             cpu_threads: 8,
             mem_gib: 24.,
             storage_gib: 200.,
+            custom: Default::default(),
         };
         let state = ManagerState {
             res_available: res.clone(),
@@ -630,13 +840,14 @@ mod tests {
         let mut man = Manager {
             state: Arc::new(Mutex::new(state)),
             monitor: None,
-            sender: Some(tx),
+            sender: Arc::new(tx),
             receiver: rx,
         };
         let alloc = Resources {
             cpu_threads: 1,
             mem_gib: 1.51,
             storage_gib: 12.37,
+            custom: Default::default(),
         };
 
         man.allocate("1".into(), alloc.clone()).unwrap();
@@ -649,8 +860,151 @@ mod tests {
                     cpu_threads: 1000,
                     mem_gib: 10000.,
                     storage_gib: 10000.,
+                    custom: Default::default(),
                 }
             )
             .is_err());
     }
+
+    #[test]
+    fn reservation() {
+        let res = Resources {
+            cpu_threads: 4,
+            mem_gib: 8.,
+            storage_gib: 64.,
+            custom: Default::default(),
+        };
+        let state = ManagerState {
+            res_available: res.clone(),
+            res_cap: res.clone(),
+            res_remaining: res.clone(),
+            res_alloc: HashMap::new(),
+            profiles: profiles(),
+        };
+        let (tx, rx) = watch::channel(Event::Initialized);
+        let man = Manager {
+            state: Arc::new(Mutex::new(state)),
+            monitor: None,
+            sender: Arc::new(tx),
+            receiver: rx,
+        };
+        let store = man.store();
+        let half = Resources {
+            cpu_threads: 2,
+            mem_gib: 4.,
+            storage_gib: 32.,
+            custom: Default::default(),
+        };
+
+        store.allocate("1", half.clone()).unwrap();
+        assert_eq!(man.remaining(), half);
+        // Offers are updated after Agreement approval, not on allocation.
+        assert!(matches!(*man.event_receiver().borrow(), Event::Initialized));
+        assert!(store.available(&half));
+
+        store.allocate("2", half.clone()).unwrap();
+        assert!(man.remaining().depleted());
+        assert!(!store.available(&Resources::min_reservation()));
+
+        store.release("1").unwrap();
+        assert_eq!(man.remaining(), half);
+        assert_eq!(man.capped(), res);
+    }
+
+    #[test]
+    fn spawn_monitor_twice() {
+        let dir = tempdir::TempDir::new("hardware").unwrap();
+        let path = dir.path().join("hardware.json");
+        std::fs::write(&path, serde_json::to_string(&profiles()).unwrap()).unwrap();
+
+        let (tx, rx) = watch::channel(Event::Initialized);
+        let mut man = Manager {
+            state: Arc::new(Mutex::new(ManagerState {
+                res_available: Default::default(),
+                res_cap: Default::default(),
+                res_remaining: Default::default(),
+                res_alloc: HashMap::new(),
+                profiles: profiles(),
+            })),
+            monitor: None,
+            sender: Arc::new(tx),
+            receiver: rx,
+        };
+
+        man.spawn_monitor(&path).unwrap();
+        man.spawn_monitor(&path).unwrap();
+    }
+
+    #[test]
+    fn allocation_custom() {
+        let seats = |capacity| {
+            vec![(
+                "seats".to_string(),
+                CustomResource {
+                    capacity,
+                    unit: "licence".to_string(),
+                },
+            )]
+            .into_iter()
+            .collect()
+        };
+        let res = Resources {
+            cpu_threads: 8,
+            mem_gib: 24.,
+            storage_gib: 200.,
+            custom: seats(2),
+        };
+        let mut state = ManagerState {
+            res_available: res.clone(),
+            res_cap: res.clone(),
+            res_remaining: res.clone(),
+            res_alloc: HashMap::new(),
+            profiles: profiles(),
+        };
+        let alloc = Resources {
+            custom: seats(1),
+            ..Default::default()
+        };
+
+        state.allocate("1".into(), alloc.clone()).unwrap();
+        state.allocate("2".into(), alloc.clone()).unwrap();
+        assert!(state.allocate("3".into(), alloc.clone()).is_err());
+        state.release("1".into()).unwrap();
+        state.allocate("3".into(), alloc).unwrap();
+
+        assert_eq!(state.res_remaining.custom_capacity("seats"), 0);
+        assert_eq!(state.res_remaining.cpu_threads, res.cpu_threads);
+    }
+
+    #[test]
+    fn parse_custom() {
+        let (name, res) = parse_custom_resource("seats=4:licence").unwrap();
+        assert_eq!(name, "seats");
+        assert_eq!(res.capacity, 4);
+        assert_eq!(res.unit, "licence");
+        assert_eq!(parse_custom_resource("iops=20000").unwrap().1.unit, "");
+
+        assert!(parse_custom_resource("mem=4").is_err());
+        assert!(parse_custom_resource("disk.iops=4").is_err());
+        assert!(parse_custom_resource("seats=many").is_err());
+        assert!(parse_custom_resource("seats=-1").is_err());
+    }
+
+    #[test]
+    fn validate_profiles() {
+        let mut profiles = profiles();
+        assert!(profiles.validate().is_ok());
+
+        let resources = profiles.get_mut(DEFAULT_PROFILE_NAME).unwrap();
+        resources.custom.insert(
+            "seats".into(),
+            CustomResource {
+                capacity: -1,
+                unit: String::new(),
+            },
+        );
+        assert!(profiles.validate().is_err());
+        let result = profiles.save("/nonexistent/hardware.json");
+        assert!(matches!(result, Err(Error::Profile(_))));
+    }
 }
@@ -0,0 +1,34 @@
+use serde::Deserialize;
+
+use ya_client::model::NodeId;
+use ya_client::web::{WebClient, WebInterface};
+use ya_client::Result;
+
+/// Client of yagna `/me` endpoint, which isn't part of `ya-client`.
+#[derive(Clone)]
+pub struct IdentityApi {
+    client: WebClient,
+}
+
+/// Identity of app-key used by Provider Agent.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Me {
+    pub identity: NodeId,
+    pub name: String,
+    pub role: String,
+}
+
+impl WebInterface for IdentityApi {
+    const API_URL_ENV_VAR: &'static str = "YAGNA_API_URL";
+    const API_SUFFIX: &'static str = "/";
+
+    fn from_client(client: WebClient) -> Self {
+        IdentityApi { client }
+    }
+}
+
+impl IdentityApi {
+    pub async fn me(&self) -> Result<Me> {
+        self.client.get("me").send().json().await
+    }
+}
@@ -5,6 +5,7 @@ pub mod display;
 pub mod events;
 pub mod execution;
 pub mod hardware;
+pub mod identity;
 mod interval;
 pub mod market;
 pub mod payments;
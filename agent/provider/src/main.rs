@@ -10,7 +10,6 @@ use ya_utils_process::lock::ProcLock;
 #[actix_rt::main]
 async fn main() -> anyhow::Result<()> {
     dotenv::dotenv().ok();
-
     let cli_args = StartupConfig::from_args();
     match &cli_args.commands {
         Commands::Run(_) => (), // logging is handled by ProviderAgent
@@ -25,9 +24,26 @@ async fn main() -> anyhow::Result<()> {
     config.globals_file = data_dir.join(config.globals_file);
     config.presets_file = data_dir.join(config.presets_file);
     config.hardware_file = data_dir.join(config.hardware_file);
+    config.reputation_file = data_dir.join(config.reputation_file);
+    config.rules_file = data_dir.join(config.rules_file);
+    config.pricing_file = data_dir.join(config.pricing_file);
 
     match cli_args.commands {
         Commands::Run(args) => {
+            // `ya-client` connects only over TCP.
+            #[cfg(unix)]
+            {
+                if let Some(socket) = env::var("YAGNA_API_URL")
+                    .ok()
+                    .and_then(|url| url::Url::parse(&url).ok())
+                    .and_then(|url| ya_utils_networking::unix_socket::socket_path(&url))
+                {
+                    anyhow::bail!(
+                        "Provider agent can't connect to REST API socket {}. Set YAGNA_API_URL to http(s) URL.",
+                        socket.display()
+                    );
+                }
+            }
             let app_name = clap::crate_name!();
             let _lock = ProcLock::new(&app_name, &data_dir)?.lock(std::process::id())?;
             let agent = ProviderAgent::new(args, config).await?.start();
@@ -43,6 +59,8 @@ async fn main() -> anyhow::Result<()> {
         Commands::Profile(profile_cmd) => profile_cmd.run(config),
         Commands::ExeUnit(exe_unit_cmd) => exe_unit_cmd.run(config),
         Commands::Keystore(keystore_cmd) => keystore_cmd.run(config),
+        Commands::Reputation(reputation_cmd) => reputation_cmd.run(config),
+        Commands::Rules(rules_cmd) => rules_cmd.run(config),
         Commands::Clean(clean_cmd) => clean_cmd.run(config),
     }
 }
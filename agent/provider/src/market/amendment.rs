@@ -0,0 +1,45 @@
+use ya_client::model::market::{Reason, MARKET_API_PATH};
+use ya_client::web::{WebClient, WebInterface};
+use ya_client::Result;
+use ya_core_model::market::AgreementAmendmentEvent;
+
+/// Client of yagna endpoints for Agreement amendments, which aren't
+/// part of `MarketProviderApi` yet.
+#[derive(Clone)]
+pub struct AmendmentApi {
+    client: WebClient,
+}
+
+impl WebInterface for AmendmentApi {
+    const API_URL_ENV_VAR: &'static str = "YAGNA_MARKET_URL";
+    const API_SUFFIX: &'static str = MARKET_API_PATH;
+
+    fn from_client(client: WebClient) -> Self {
+        AmendmentApi { client }
+    }
+}
+
+impl AmendmentApi {
+    /// Collects amendments proposed by Requestors, that wait for our decision.
+    pub async fn collect(
+        &self,
+        timeout: f32,
+        max_events: i32,
+    ) -> Result<Vec<AgreementAmendmentEvent>> {
+        let url = format!(
+            "agreementAmendments?timeout={}&maxEvents={}",
+            timeout, max_events
+        );
+        self.client.get(&url).send().json().await
+    }
+
+    pub async fn approve(&self, amendment_id: &str) -> Result<()> {
+        let url = format!("agreementAmendments/{}/approve", amendment_id);
+        self.client.post(&url).send().json().await
+    }
+
+    pub async fn reject(&self, amendment_id: &str, reason: &Option<Reason>) -> Result<()> {
+        let url = format!("agreementAmendments/{}/reject", amendment_id);
+        self.client.post(&url).send_json(&reason).json().await
+    }
+}
@@ -1,6 +1,7 @@
 use structopt::StructOpt;
 
 use crate::market::negotiator::factory::NegotiatorsConfig;
+use crate::market::repricing::{AcceptanceStats, RepricingConfig};
 use ya_manifest_utils::Keystore;
 
 /// Configuration for ProviderMarket actor.
@@ -22,4 +23,8 @@ pub struct MarketConfig {
     pub process_market_events_timeout: std::time::Duration,
     #[structopt(skip)]
     pub keystore: Keystore,
+    #[structopt(flatten)]
+    pub repricing: RepricingConfig,
+    #[structopt(skip)]
+    pub acceptance_stats: AcceptanceStats,
 }
@@ -0,0 +1,26 @@
+use ya_client::web::{WebClient, WebInterface};
+use ya_client::Result;
+
+/// Client of yagna metrics endpoints, which aren't part of `ya-client`.
+#[derive(Clone)]
+pub struct MetricsApi {
+    client: WebClient,
+}
+
+impl WebInterface for MetricsApi {
+    const API_URL_ENV_VAR: &'static str = "YAGNA_METRICS_API_URL";
+    const API_SUFFIX: &'static str = "/metrics-api/v1/";
+
+    fn from_client(client: WebClient) -> Self {
+        MetricsApi { client }
+    }
+}
+
+impl MetricsApi {
+    /// Labels metrics of Agreement with name of preset, which isn't
+    /// published in Offer.
+    pub async fn set_preset(&self, agreement_id: &str, preset: &str) -> Result<()> {
+        let url = format!("agreements/{}/preset", agreement_id);
+        self.client.post(&url).send_json(&preset).json().await
+    }
+}
@@ -1,7 +1,10 @@
+pub mod amendment;
 pub mod config;
+pub mod metrics;
 pub mod negotiator;
 pub mod presets;
 pub mod provider_market;
+pub mod repricing;
 pub mod termination_reason;
 
 pub use presets::{Preset, PresetManager, Presets};
@@ -12,4 +12,7 @@ pub use common::{
     AgreementResponse, AgreementResult, Negotiator, NegotiatorAddr, ProposalResponse,
 };
 
-pub use component::{NegotiationResult, NegotiatorComponent, NegotiatorsPack, ProposalView};
+pub use component::{
+    AmendmentResult, NegotiationResult, NegotiatorComponent, NegotiatorsPack, ProposalView,
+    REQUESTOR_ID_POINTER,
+};
@@ -5,7 +5,7 @@ use ya_client::model::market::NewOffer;
 use super::common::offer_definition_to_offer;
 use super::common::{AgreementResponse, Negotiator, ProposalResponse};
 use crate::market::negotiator::common::{
-    AgreementFinalized, CreateOffer, ReactToAgreement, ReactToProposal,
+    AgreementFinalized, CreateOffer, ReactToAgreement, ReactToAmendment, ReactToProposal,
 };
 
 #[derive(Debug)]
@@ -41,6 +41,14 @@ impl Handler<ReactToAgreement> for AcceptAllNegotiator {
     }
 }
 
+impl Handler<ReactToAmendment> for AcceptAllNegotiator {
+    type Result = anyhow::Result<AgreementResponse>;
+
+    fn handle(&mut self, _: ReactToAmendment, _: &mut Context<Self>) -> Self::Result {
+        Ok(AgreementResponse::ApproveAgreement)
+    }
+}
+
 impl Handler<AgreementFinalized> for AcceptAllNegotiator {
     type Result = anyhow::Result<()>;
 
@@ -3,9 +3,15 @@ mod manifest;
 pub mod max_agreements;
 pub mod note_interval;
 pub mod payment_timeout;
+pub mod reputation;
+pub mod resources;
+pub mod rules;
 
 pub use expiration::LimitExpiration;
 pub use manifest::ManifestSignature;
 pub use max_agreements::MaxAgreements;
 pub use note_interval::DebitNoteInterval;
 pub use payment_timeout::PaymentTimeout;
+pub use reputation::RequestorReputation;
+pub use resources::ReserveResources;
+pub use rules::RequestorRules;
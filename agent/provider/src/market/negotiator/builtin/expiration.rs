@@ -1,12 +1,13 @@
 use anyhow::{anyhow, Result};
 use chrono::{DateTime, Duration, TimeZone, Utc};
 
-use ya_agreement_utils::{Error, OfferDefinition};
+use ya_agreement_utils::{AgreementView, Error, OfferDefinition};
+use ya_core_model::market::AgreementAmendment;
 
 use crate::display::EnableDisplay;
 use crate::market::negotiator::factory::AgreementExpirationNegotiatorConfig;
 use crate::market::negotiator::{
-    AgreementResult, NegotiationResult, NegotiatorComponent, ProposalView,
+    AgreementResult, AmendmentResult, NegotiationResult, NegotiatorComponent, ProposalView,
 };
 
 /// Negotiator that can reject Requestors, that request too long Agreement
@@ -187,9 +188,34 @@ impl NegotiatorComponent for LimitExpiration {
         Ok(())
     }
 
-    fn on_agreement_approved(&mut self, _agreement_id: &str) -> Result<()> {
+    fn on_agreement_approved(&mut self, _agreement: &AgreementView) -> Result<()> {
         Ok(())
     }
+
+    fn on_agreement_amendment(
+        &mut self,
+        agreement: &AgreementView,
+        amendment: &AgreementAmendment,
+    ) -> Result<AmendmentResult> {
+        // Requestor can't change DebitNotes acceptance deadline, so the same
+        // limits as during negotiations apply.
+        let deadline = format!("/demand/properties{}", DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY);
+        let max_expiration_delta = match agreement.pointer(&deadline) {
+            Some(_) => self.max_expiration,
+            None => self.max_expiration_without_deadline,
+        };
+
+        if amendment.valid_to > Utc::now() + max_expiration_delta {
+            return Ok(AmendmentResult::Reject {
+                message: format!(
+                    "Agreement can't be extended to {}, which is more than {} from now",
+                    amendment.valid_to,
+                    max_expiration_delta.display()
+                ),
+            });
+        }
+        Ok(AmendmentResult::Accept)
+    }
 }
 
 #[cfg(test)]
@@ -386,4 +412,52 @@ mod test_expiration_negotiator {
             result => panic!("Expected NegotiationResult::Negotiating. Got: {:?}", result),
         }
     }
+
+    /// Amendment can't extend Agreement above limits used during negotiations.
+    /// Lower limit applies, if Requestor didn't promise to accept DebitNotes.
+    #[test]
+    fn test_amendment_expiration_limits() {
+        let config = expiration_config();
+        let mut negotiator = LimitExpiration::new(&config).unwrap();
+
+        let amendment = |minutes| AgreementAmendment {
+            valid_to: Utc::now() + Duration::minutes(minutes),
+            payment_timeout_sec: None,
+            debit_note_interval_sec: None,
+        };
+        let agreement = AgreementView {
+            agreement_id: "2332850934yer".to_string(),
+            json: serde_json::json!({ "demand": { "properties": expand(serde_json::json!({
+                DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY_FLAT: 120,
+            }))}}),
+        };
+
+        assert_eq!(
+            negotiator
+                .on_agreement_amendment(&agreement, &amendment(20))
+                .unwrap(),
+            AmendmentResult::Accept
+        );
+        match negotiator
+            .on_agreement_amendment(&agreement, &amendment(40))
+            .unwrap()
+        {
+            AmendmentResult::Reject { message } => {
+                assert!(message.contains("Agreement can't be extended"))
+            }
+            result => panic!("Expected AmendmentResult::Reject. Got: {:?}", result),
+        }
+
+        let agreement = AgreementView {
+            agreement_id: "2332850934yer".to_string(),
+            json: serde_json::json!({ "demand": { "properties": {} } }),
+        };
+        match negotiator
+            .on_agreement_amendment(&agreement, &amendment(15))
+            .unwrap()
+        {
+            AmendmentResult::Reject { .. } => (),
+            result => panic!("Expected AmendmentResult::Reject. Got: {:?}", result),
+        }
+    }
 }
@@ -1,6 +1,6 @@
 use std::ops::Not;
 
-use ya_agreement_utils::{Error, OfferDefinition};
+use ya_agreement_utils::{AgreementView, Error, OfferDefinition};
 use ya_manifest_utils::manifest::{
     decode_manifest, Feature, Signature, CAPABILITIES_PROPERTY, DEMAND_MANIFEST_PROPERTY,
     DEMAND_MANIFEST_SIG_PROPERTY,
@@ -65,7 +65,7 @@ impl NegotiatorComponent for ManifestSignature {
         Ok(())
     }
 
-    fn on_agreement_approved(&mut self, _agreement_id: &str) -> anyhow::Result<()> {
+    fn on_agreement_approved(&mut self, _agreement: &AgreementView) -> anyhow::Result<()> {
         Ok(())
     }
 }
@@ -102,7 +102,7 @@ fn rejection(message: String) -> anyhow::Result<NegotiationResult> {
     })
 }
 
-fn verify_signature(demand: &ProposalView) -> anyhow::Result<Vec<u8>> {
+pub(super) fn verify_signature(demand: &ProposalView) -> anyhow::Result<Vec<u8>> {
     let manifest: String = demand.get_property(DEMAND_MANIFEST_PROPERTY)?;
     log::debug!("manifest: {}", manifest);
     let sig_hex: String = demand.get_property(DEMAND_MANIFEST_SIG_PROPERTY)?;
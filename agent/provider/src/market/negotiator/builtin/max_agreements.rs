@@ -1,7 +1,7 @@
 use anyhow::bail;
 use std::collections::HashSet;
 
-use ya_agreement_utils::OfferDefinition;
+use ya_agreement_utils::{AgreementView, OfferDefinition};
 
 use crate::market::negotiator::factory::LimitAgreementsNegotiatorConfig;
 use crate::market::negotiator::{
@@ -69,7 +69,8 @@ impl NegotiatorComponent for MaxAgreements {
         Ok(())
     }
 
-    fn on_agreement_approved(&mut self, agreement_id: &str) -> anyhow::Result<()> {
+    fn on_agreement_approved(&mut self, agreement: &AgreementView) -> anyhow::Result<()> {
+        let agreement_id = &agreement.agreement_id;
         if self.has_free_slot() {
             self.active_agreements.insert(agreement_id.to_string());
             Ok(())
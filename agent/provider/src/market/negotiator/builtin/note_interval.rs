@@ -1,11 +1,12 @@
 use chrono::Duration;
 
-use ya_agreement_utils::{Error, OfferDefinition};
+use ya_agreement_utils::{AgreementView, Error, OfferDefinition};
+use ya_core_model::market::AgreementAmendment;
 
 use crate::display::EnableDisplay;
 use crate::market::negotiator::factory::DebitNoteIntervalConfig;
 use crate::market::negotiator::{
-    AgreementResult, NegotiationResult, NegotiatorComponent, ProposalView,
+    AgreementResult, AmendmentResult, NegotiationResult, NegotiatorComponent, ProposalView,
 };
 
 pub const DEFAULT_DEBIT_NOTE_INTERVAL_SEC: u32 = 120;
@@ -102,9 +103,30 @@ impl NegotiatorComponent for DebitNoteInterval {
         Ok(())
     }
 
-    fn on_agreement_approved(&mut self, _agreement_id: &str) -> anyhow::Result<()> {
+    fn on_agreement_approved(&mut self, _agreement: &AgreementView) -> anyhow::Result<()> {
         Ok(())
     }
+
+    fn on_agreement_amendment(
+        &mut self,
+        _agreement: &AgreementView,
+        amendment: &AgreementAmendment,
+    ) -> anyhow::Result<AmendmentResult> {
+        if let Some(interval) = amendment.debit_note_interval_sec {
+            let interval = Duration::seconds(interval as i64);
+            if interval < self.min_interval || interval > self.max_interval {
+                return Ok(AmendmentResult::Reject {
+                    message: format!(
+                        "Amended DebitNote interval {} not in acceptable range of [{}; {}]",
+                        interval.display(),
+                        self.min_interval.display(),
+                        self.max_interval.display(),
+                    ),
+                });
+            }
+        }
+        Ok(AmendmentResult::Accept)
+    }
 }
 
 fn read_duration(pointer: &str, proposal: &ProposalView) -> anyhow::Result<Option<Duration>> {
@@ -1,11 +1,12 @@
 use chrono::{DateTime, Duration, NaiveDateTime, Utc};
 
-use ya_agreement_utils::{Error, OfferDefinition};
+use ya_agreement_utils::{AgreementView, Error, OfferDefinition};
+use ya_core_model::market::AgreementAmendment;
 
 use crate::display::EnableDisplay;
 use crate::market::negotiator::factory::PaymentTimeoutConfig;
 use crate::market::negotiator::{
-    AgreementResult, NegotiationResult, NegotiatorComponent, ProposalView,
+    AgreementResult, AmendmentResult, NegotiationResult, NegotiatorComponent, ProposalView,
 };
 
 const PAYMENT_TIMEOUT_PROPERTY_FLAT: &'static str = "golem.com.scheme.payu.payment-timeout-sec?";
@@ -127,9 +128,30 @@ impl NegotiatorComponent for PaymentTimeout {
         Ok(())
     }
 
-    fn on_agreement_approved(&mut self, _agreement_id: &str) -> anyhow::Result<()> {
+    fn on_agreement_approved(&mut self, _agreement: &AgreementView) -> anyhow::Result<()> {
         Ok(())
     }
+
+    fn on_agreement_amendment(
+        &mut self,
+        _agreement: &AgreementView,
+        amendment: &AgreementAmendment,
+    ) -> anyhow::Result<AmendmentResult> {
+        if let Some(timeout) = amendment.payment_timeout_sec {
+            let timeout = Duration::seconds(timeout as i64);
+            if timeout < self.min_timeout || timeout > self.max_timeout {
+                return Ok(AmendmentResult::Reject {
+                    message: format!(
+                        "Amended DebitNote payment timeout {} not in acceptable range of [{}; {}]",
+                        timeout.display(),
+                        self.min_timeout.display(),
+                        self.max_timeout.display(),
+                    ),
+                });
+            }
+        }
+        Ok(AmendmentResult::Accept)
+    }
 }
 
 fn read_duration(pointer: &str, proposal: &ProposalView) -> anyhow::Result<Option<Duration>> {
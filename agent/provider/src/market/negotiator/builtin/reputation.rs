@@ -0,0 +1,204 @@
+use chrono::Duration;
+
+use ya_agreement_utils::{AgreementView, Error, OfferDefinition};
+use ya_client::model::NodeId;
+
+use crate::config::reputation::ReputationStore;
+use crate::display::EnableDisplay;
+use crate::market::negotiator::builtin::note_interval::DEBIT_NOTE_INTERVAL_PROPERTY;
+use crate::market::negotiator::builtin::payment_timeout::PAYMENT_TIMEOUT_PROPERTY;
+use crate::market::negotiator::factory::RequestorReputationConfig;
+use crate::market::negotiator::{
+    AgreementResult, NegotiationResult, NegotiatorComponent, ProposalView, REQUESTOR_ID_POINTER,
+};
+
+/// RequestorReputation negotiator
+///
+/// Rejects Requestors with poor payment history and requires short
+/// payment timeout and DebitNote interval from Requestors, that are not trusted yet.
+/// Limits apply only to terms present in Demand. Agreements without mid-Agreement
+/// payments are limited by `LimitExpiration` negotiator.
+pub struct RequestorReputation {
+    store: ReputationStore,
+    min_score: f64,
+    trusted_score: f64,
+    max_payment_timeout: Duration,
+    max_debit_note_interval: Duration,
+}
+
+impl RequestorReputation {
+    pub fn new(config: &RequestorReputationConfig) -> anyhow::Result<Self> {
+        if config.min_requestor_score > config.trusted_requestor_score {
+            anyhow::bail!(
+                "Minimum requestor score {} is greater than the trusted requestor score {}",
+                config.min_requestor_score,
+                config.trusted_requestor_score
+            );
+        }
+
+        Ok(Self {
+            store: config.store.clone(),
+            min_score: config.min_requestor_score,
+            trusted_score: config.trusted_requestor_score,
+            max_payment_timeout: Duration::from_std(config.untrusted_max_payment_timeout)?,
+            max_debit_note_interval: Duration::from_std(config.untrusted_max_debit_note_interval)?,
+        })
+    }
+}
+
+impl NegotiatorComponent for RequestorReputation {
+    fn negotiate_step(
+        &mut self,
+        demand: &ProposalView,
+        offer: ProposalView,
+    ) -> anyhow::Result<NegotiationResult> {
+        let requestor = match demand.pointer_typed::<NodeId>(REQUESTOR_ID_POINTER) {
+            Ok(requestor) => requestor,
+            Err(_) => {
+                log::debug!(
+                    "'RequestorReputation' negotiator: Requestor of proposal [{}] unknown.",
+                    demand.agreement_id
+                );
+                return Ok(NegotiationResult::Ready { offer });
+            }
+        };
+
+        let score = self.store.score(&requestor);
+        if score < self.min_score {
+            log::info!(
+                "'RequestorReputation' negotiator: Reject proposal [{}] from Requestor [{}] with score {:.2}.",
+                demand.agreement_id,
+                requestor,
+                score
+            );
+            return Ok(NegotiationResult::Reject {
+                message: format!(
+                    "Requestor reputation {:.2} is below required minimum {:.2}",
+                    score, self.min_score
+                ),
+                is_final: true,
+            });
+        }
+
+        if score < self.trusted_score {
+            let payment_timeout = read_duration(PAYMENT_TIMEOUT_PROPERTY, demand)?;
+            let note_interval = read_duration(DEBIT_NOTE_INTERVAL_PROPERTY, demand)?;
+
+            if !within(payment_timeout, self.max_payment_timeout)
+                || !within(note_interval, self.max_debit_note_interval)
+            {
+                return Ok(NegotiationResult::Reject {
+                    message: format!(
+                        "Requestor reputation {:.2} requires DebitNote payment timeout of at most {} \
+                        and DebitNote interval of at most {}",
+                        score,
+                        self.max_payment_timeout.display(),
+                        self.max_debit_note_interval.display(),
+                    ),
+                    is_final: false,
+                });
+            }
+        }
+
+        Ok(NegotiationResult::Ready { offer })
+    }
+
+    fn fill_template(
+        &mut self,
+        offer_template: OfferDefinition,
+    ) -> anyhow::Result<OfferDefinition> {
+        Ok(offer_template)
+    }
+
+    fn on_agreement_terminated(
+        &mut self,
+        _agreement_id: &str,
+        _result: &AgreementResult,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn on_agreement_approved(&mut self, _agreement: &AgreementView) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+/// Missing property means that the term isn't negotiated, so it isn't limited.
+fn within(value: Option<Duration>, max: Duration) -> bool {
+    value.map(|value| value <= max).unwrap_or(true)
+}
+
+fn read_duration(pointer: &str, proposal: &ProposalView) -> anyhow::Result<Option<Duration>> {
+    match proposal.pointer_typed::<u32>(pointer) {
+        Ok(val) => Ok(Some(Duration::seconds(val as i64))),
+        Err(Error::NoKey { .. }) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod test_reputation_negotiator {
+    use super::*;
+    use crate::config::reputation::ReputationEvent;
+    use ya_agreement_utils::agreement::expand;
+
+    const REQUESTOR: &str = "0x0000000000000000000000000000000000000001";
+
+    fn untrusted_config() -> RequestorReputationConfig {
+        let store = ReputationStore::default();
+        let requestor: NodeId = REQUESTOR.parse().unwrap();
+        store.record(&requestor, ReputationEvent::PaymentOverdue);
+
+        RequestorReputationConfig {
+            min_requestor_score: 0.25,
+            trusted_requestor_score: 0.6,
+            untrusted_max_payment_timeout: std::time::Duration::from_secs(5 * 60),
+            untrusted_max_debit_note_interval: std::time::Duration::from_secs(2 * 60),
+            store,
+        }
+    }
+
+    fn demand(properties: serde_json::Value) -> ProposalView {
+        let mut properties = properties;
+        properties["requestorId"] = serde_json::json!(REQUESTOR);
+        ProposalView {
+            agreement_id: "2332850934yer".to_string(),
+            json: expand(properties),
+        }
+    }
+
+    fn offer() -> ProposalView {
+        ProposalView {
+            agreement_id: "sagdshgdfgd".to_string(),
+            json: serde_json::json!({}),
+        }
+    }
+
+    /// Demand without mid-Agreement payments isn't limited by reputation.
+    #[test]
+    fn test_untrusted_without_payment_terms() {
+        let mut negotiator = RequestorReputation::new(&untrusted_config()).unwrap();
+
+        match negotiator
+            .negotiate_step(&demand(serde_json::json!({})), offer())
+            .unwrap()
+        {
+            NegotiationResult::Ready { .. } => (),
+            result => panic!("Expected NegotiationResult::Ready. Got: {:?}", result),
+        }
+    }
+
+    #[test]
+    fn test_untrusted_with_long_payment_timeout() {
+        let mut negotiator = RequestorReputation::new(&untrusted_config()).unwrap();
+
+        let proposal = demand(serde_json::json!({
+            "golem.com.scheme.payu.debit-note.interval-sec?": 60,
+            "golem.com.scheme.payu.payment-timeout-sec?": 600,
+        }));
+        match negotiator.negotiate_step(&proposal, offer()).unwrap() {
+            NegotiationResult::Reject { is_final, .. } => assert!(!is_final),
+            result => panic!("Expected NegotiationResult::Reject. Got: {:?}", result),
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use ya_agreement_utils::{AgreementView, Error, OfferDefinition};
+
+use crate::hardware::{CustomResource, HardwareStore, Resources};
+use crate::market::negotiator::{
+    AgreementResult, NegotiationResult, NegotiatorComponent, ProposalView,
+};
+
+/// Lower bounds of resources reserved for a single Agreement. Upper bounds
+/// are advertised in `golem.inf` properties.
+pub const MIN_CPU_THREADS_PROPERTY: &str = "golem.srv.reservation.min.cpu-threads";
+pub const MIN_MEM_GIB_PROPERTY: &str = "golem.srv.reservation.min.mem-gib";
+pub const MIN_STORAGE_GIB_PROPERTY: &str = "golem.srv.reservation.min.storage-gib";
+
+/// Amounts of resources, which Requestor wants to reserve.
+const CPU_THREADS_PROPERTY: &str = "/golem/srv/reservation/cpu-threads";
+const MEM_GIB_PROPERTY: &str = "/golem/srv/reservation/mem-gib";
+const STORAGE_GIB_PROPERTY: &str = "/golem/srv/reservation/storage-gib";
+
+const INF_CPU_THREADS_PROPERTY: &str = "/golem/inf/cpu/threads";
+const INF_MEM_GIB_PROPERTY: &str = "/golem/inf/mem/gib";
+const INF_STORAGE_GIB_PROPERTY: &str = "/golem/inf/storage/gib";
+
+/// Negotiator reserving hardware resources of active profile for each Agreement.
+/// Requestor can demand amounts of CPU threads, memory and storage within bounds
+/// advertised in the Offer; resources not demanded are reserved as offered.
+/// Reserved amounts replace `golem.inf` properties of the Offer, so ExeUnit
+/// receives them with the Agreement. User-defined resources are reserved
+/// in amounts declared by Offer's preset.
+pub struct ReserveResources {
+    store: HardwareStore,
+}
+
+impl ReserveResources {
+    pub fn new(store: HardwareStore) -> ReserveResources {
+        ReserveResources { store }
+    }
+
+    /// Resources described by `golem.inf` properties.
+    fn offered(&self, properties: &Value) -> Resources {
+        let custom = self
+            .store
+            .capped()
+            .custom
+            .into_iter()
+            .filter_map(|(name, resource)| {
+                let capacity = properties
+                    .pointer(&format!("/golem/inf/{}", name))
+                    .and_then(Value::as_i64)?;
+                Some((
+                    name,
+                    CustomResource {
+                        capacity,
+                        unit: resource.unit,
+                    },
+                ))
+            })
+            .collect();
+
+        let number = |pointer: &str| properties.pointer(pointer).and_then(Value::as_f64);
+        Resources {
+            cpu_threads: number(INF_CPU_THREADS_PROPERTY).unwrap_or(0.) as i32,
+            mem_gib: number(INF_MEM_GIB_PROPERTY).unwrap_or(0.),
+            storage_gib: number(INF_STORAGE_GIB_PROPERTY).unwrap_or(0.),
+            custom,
+        }
+    }
+}
+
+impl NegotiatorComponent for ReserveResources {
+    fn negotiate_step(
+        &mut self,
+        demand: &ProposalView,
+        mut offer: ProposalView,
+    ) -> anyhow::Result<NegotiationResult> {
+        let offered = self.offered(&offer.json);
+        let reserved = Resources {
+            cpu_threads: requested(demand, CPU_THREADS_PROPERTY, offered.cpu_threads)?,
+            mem_gib: requested(demand, MEM_GIB_PROPERTY, offered.mem_gib)?,
+            storage_gib: requested(demand, STORAGE_GIB_PROPERTY, offered.storage_gib)?,
+            custom: offered.custom.clone(),
+        };
+
+        let min = Resources::min_reservation();
+        let max = self.store.capped();
+        let in_range = reserved >= min && max >= reserved;
+        if !in_range {
+            return Ok(NegotiationResult::Reject {
+                message: format!(
+                    "Requested {} not in acceptable range of [{}; {}]",
+                    describe(&reserved),
+                    describe(&min),
+                    describe(&max),
+                ),
+                is_final: true,
+            });
+        }
+
+        if !self.store.available(&reserved) {
+            return Ok(NegotiationResult::Reject {
+                message: format!("No capacity available to reserve {}", describe(&reserved)),
+                is_final: false,
+            });
+        }
+
+        if reserved != offered {
+            set_number(&mut offer, INF_CPU_THREADS_PROPERTY, reserved.cpu_threads)?;
+            set_number(&mut offer, INF_MEM_GIB_PROPERTY, reserved.mem_gib)?;
+            set_number(&mut offer, INF_STORAGE_GIB_PROPERTY, reserved.storage_gib)?;
+            return Ok(NegotiationResult::Negotiating { offer });
+        }
+        Ok(NegotiationResult::Ready { offer })
+    }
+
+    fn fill_template(
+        &mut self,
+        offer_template: OfferDefinition,
+    ) -> anyhow::Result<OfferDefinition> {
+        Ok(offer_template)
+    }
+
+    fn on_agreement_terminated(
+        &mut self,
+        agreement_id: &str,
+        _result: &AgreementResult,
+    ) -> anyhow::Result<()> {
+        // Agreements, which weren't approved, have nothing reserved.
+        self.store.release(agreement_id).ok();
+        Ok(())
+    }
+
+    fn on_agreement_approved(&mut self, agreement: &AgreementView) -> anyhow::Result<()> {
+        let properties = agreement
+            .pointer("/offer/properties")
+            .cloned()
+            .unwrap_or_default();
+        let reserved = self.offered(&properties);
+        self.store.allocate(&agreement.agreement_id, reserved)?;
+        Ok(())
+    }
+}
+
+/// Amount of resource demanded by Requestor or the offered one, when not specified.
+fn requested<T: DeserializeOwned>(
+    demand: &ProposalView,
+    pointer: &str,
+    offered: T,
+) -> anyhow::Result<T> {
+    match demand.pointer_typed(pointer) {
+        Ok(amount) => Ok(amount),
+        Err(Error::NoKey(_)) => Ok(offered),
+        Err(e) => Err(e.into()),
+    }
+}
+
+fn set_number(
+    offer: &mut ProposalView,
+    pointer: &str,
+    value: impl Into<Value>,
+) -> anyhow::Result<()> {
+    let property = offer
+        .pointer_mut(pointer)
+        .ok_or_else(|| anyhow::anyhow!("Property '{}' not found in the Offer", pointer))?;
+    *property = value.into();
+    Ok(())
+}
+
+fn describe(res: &Resources) -> String {
+    format!(
+        "{} CPU thread(s), {} GiB of memory and {} GiB of storage",
+        res.cpu_threads, res.mem_gib, res.storage_gib
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn proposal(json: Value) -> ProposalView {
+        ProposalView {
+            json,
+            agreement_id: "proposal".to_string(),
+        }
+    }
+
+    fn offer() -> ProposalView {
+        proposal(json!({
+            "golem": {
+                "inf": {
+                    "cpu": { "threads": 4 },
+                    "mem": { "gib": 8.0 },
+                    "storage": { "gib": 64.0 },
+                }
+            }
+        }))
+    }
+
+    #[test]
+    fn requested_amounts() {
+        let demand = proposal(json!({
+            "golem": { "srv": { "reservation": { "cpu-threads": 2, "mem-gib": 1.5 } } }
+        }));
+        let offer = offer();
+
+        let threads: i32 = requested(&demand, CPU_THREADS_PROPERTY, 4).unwrap();
+        let mem: f64 = requested(&demand, MEM_GIB_PROPERTY, 8.).unwrap();
+        let storage: f64 = requested(&demand, STORAGE_GIB_PROPERTY, 64.).unwrap();
+        assert_eq!((threads, mem, storage), (2, 1.5, 64.));
+
+        let invalid = proposal(json!({
+            "golem": { "srv": { "reservation": { "cpu-threads": "two" } } }
+        }));
+        assert!(requested(&invalid, CPU_THREADS_PROPERTY, 4).is_err());
+
+        let reserved = ReserveResources::new(HardwareStore::default()).offered(&offer.json);
+        assert_eq!(reserved.cpu_threads, 4);
+        assert_eq!(reserved.mem_gib, 8.);
+        assert_eq!(reserved.storage_gib, 64.);
+    }
+
+    #[test]
+    fn reservation_out_of_range() {
+        let mut negotiator = ReserveResources::new(HardwareStore::default());
+        let demand = proposal(json!({
+            "golem": { "srv": { "reservation": { "cpu-threads": 2 } } }
+        }));
+
+        match negotiator.negotiate_step(&demand, offer()).unwrap() {
+            NegotiationResult::Reject { is_final, .. } => assert!(is_final),
+            _ => panic!("Reservation exceeding profile capacity should be rejected"),
+        }
+    }
+
+    fn store_with_gpus(gpus: i64) -> HardwareStore {
+        let mut res = Resources {
+            cpu_threads: 8,
+            mem_gib: 16.,
+            storage_gib: 128.,
+            custom: Default::default(),
+        };
+        res.custom.insert(
+            "gpu".into(),
+            CustomResource {
+                capacity: gpus,
+                unit: String::new(),
+            },
+        );
+        HardwareStore::with_resources(res)
+    }
+
+    fn gpu_offer() -> ProposalView {
+        let mut offer = offer();
+        offer.json["golem"]["inf"]["gpu"] = json!(1);
+        offer
+    }
+
+    fn small_demand() -> ProposalView {
+        proposal(json!({
+            "golem": { "srv": { "reservation": {
+                "cpu-threads": 1, "mem-gib": 1.0, "storage-gib": 1.0
+            } } }
+        }))
+    }
+
+    fn agreement(id: &str, negotiator: &mut ReserveResources) -> AgreementView {
+        let offer = match negotiator.negotiate_step(&small_demand(), gpu_offer()) {
+            Ok(NegotiationResult::Negotiating { offer }) => offer,
+            _ => panic!("Reservation should be counter-proposed"),
+        };
+        AgreementView {
+            json: json!({ "offer": { "properties": offer.json } }),
+            agreement_id: id.to_string(),
+        }
+    }
+
+    #[test]
+    fn custom_resources_reserved() {
+        let store = store_with_gpus(2);
+        let mut negotiator = ReserveResources::new(store.clone());
+
+        let first = agreement("first", &mut negotiator);
+        negotiator.on_agreement_approved(&first).unwrap();
+        let second = agreement("second", &mut negotiator);
+        negotiator.on_agreement_approved(&second).unwrap();
+
+        let remaining = store.remaining();
+        assert_eq!(remaining.custom_capacity("gpu"), 0);
+        assert_eq!(remaining.cpu_threads, 6);
+
+        match negotiator
+            .negotiate_step(&small_demand(), gpu_offer())
+            .unwrap()
+        {
+            NegotiationResult::Reject { is_final, .. } => assert!(!is_final),
+            _ => panic!("Proposal should be rejected until GPU is released"),
+        }
+
+        negotiator
+            .on_agreement_terminated("first", &AgreementResult::ClosedByRequestor)
+            .unwrap();
+        assert_eq!(store.remaining().custom_capacity("gpu"), 1);
+        assert!(matches!(
+            negotiator.negotiate_step(&small_demand(), gpu_offer()),
+            Ok(NegotiationResult::Negotiating { .. })
+        ));
+
+        // Terminating not approved Agreement is a no-op.
+        negotiator
+            .on_agreement_terminated("unknown", &AgreementResult::ApprovalFailed)
+            .unwrap();
+    }
+
+    #[test]
+    fn two_small_agreements_back_to_back() {
+        let store = store_with_gpus(2);
+        let mut negotiator = ReserveResources::new(store.clone());
+
+        // Both Agreements are negotiated before any of them is approved.
+        let first = agreement("first", &mut negotiator);
+        let second = agreement("second", &mut negotiator);
+        negotiator.on_agreement_approved(&first).unwrap();
+        negotiator.on_agreement_approved(&second).unwrap();
+
+        let remaining = store.remaining();
+        assert_eq!(remaining.cpu_threads, 6);
+        assert_eq!(remaining.mem_gib, 14.);
+        assert_eq!(remaining.storage_gib, 126.);
+        assert!(negotiator.on_agreement_approved(&first).is_err());
+
+        negotiator
+            .on_agreement_terminated("first", &AgreementResult::ClosedByUs)
+            .unwrap();
+        negotiator
+            .on_agreement_terminated("second", &AgreementResult::ClosedByUs)
+            .unwrap();
+        assert_eq!(store.remaining(), store.capped());
+    }
+
+    #[test]
+    fn custom_resources_exceeding_profile() {
+        let mut negotiator = ReserveResources::new(store_with_gpus(0));
+        match negotiator
+            .negotiate_step(&small_demand(), gpu_offer())
+            .unwrap()
+        {
+            NegotiationResult::Reject { is_final, .. } => assert!(is_final),
+            _ => panic!("Offer requiring more GPUs than profile has should be rejected"),
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use ya_agreement_utils::{AgreementView, Error, OfferDefinition};
+use ya_client::model::NodeId;
+use ya_manifest_utils::manifest::DEMAND_MANIFEST_SIG_PROPERTY;
+use ya_manifest_utils::Keystore;
+
+use super::manifest::verify_signature;
+use crate::config::rules::RulesStore;
+use crate::market::negotiator::*;
+
+/// Negotiator, that allows only Requestors matching allow/deny rules.
+/// Requestors are matched by node id or by the trusted key, which signed Demand manifest.
+pub struct RequestorRules {
+    rules: RulesStore,
+    trusted_keys: Keystore,
+}
+
+impl RequestorRules {
+    pub fn new(rules: RulesStore, trusted_keys: Keystore) -> Self {
+        RequestorRules {
+            rules,
+            trusted_keys,
+        }
+    }
+
+    /// Name of a trusted key, which signed Demand manifest.
+    fn signer(&self, demand: &ProposalView) -> Option<String> {
+        if let Err(Error::NoKey(_)) = demand.get_property::<String>(DEMAND_MANIFEST_SIG_PROPERTY) {
+            return None;
+        }
+
+        let pub_key = verify_signature(demand)
+            .map_err(|e| log::debug!("Can't verify Demand manifest signature: {}", e))
+            .ok()?;
+        self.trusted_keys
+            .keys()
+            .get(pub_key.as_slice())
+            .map(|meta| meta.name.clone())
+    }
+}
+
+impl NegotiatorComponent for RequestorRules {
+    fn negotiate_step(
+        &mut self,
+        demand: &ProposalView,
+        offer: ProposalView,
+    ) -> anyhow::Result<NegotiationResult> {
+        let requestor = match demand.pointer_typed::<NodeId>(REQUESTOR_ID_POINTER) {
+            Ok(requestor) => requestor,
+            Err(e) => {
+                return Ok(NegotiationResult::Reject {
+                    message: format!("Unknown Requestor: {}", e),
+                    is_final: true,
+                })
+            }
+        };
+
+        let signer = self.signer(demand);
+        match self.rules.get().check(&requestor, signer.as_deref()) {
+            Ok(()) => Ok(NegotiationResult::Ready { offer }),
+            Err(message) => {
+                log::info!(
+                    "'RequestorRules' negotiator: Reject proposal [{}]. {}",
+                    demand.agreement_id,
+                    message
+                );
+                Ok(NegotiationResult::Reject {
+                    message,
+                    is_final: true,
+                })
+            }
+        }
+    }
+
+    fn fill_template(
+        &mut self,
+        offer_template: OfferDefinition,
+    ) -> anyhow::Result<OfferDefinition> {
+        Ok(offer_template)
+    }
+
+    fn on_agreement_terminated(
+        &mut self,
+        _agreement_id: &str,
+        _result: &AgreementResult,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn on_agreement_approved(&mut self, _agreement: &AgreementView) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ use derive_more::Display;
 use ya_agreement_utils::{AgreementView, OfferDefinition};
 use ya_client::model::market::Reason;
 use ya_client::model::market::{NewOffer, Proposal};
+use ya_core_model::market::AgreementAmendment;
 
 use crate::display::EnableDisplay;
 use crate::market::termination_reason::BreakReason;
@@ -82,6 +83,14 @@ pub struct ReactToAgreement {
     pub agreement: AgreementView,
 }
 
+/// Reactions to Requestor's attempt to change terms of approved Agreement.
+#[derive(Message)]
+#[rtype(result = "Result<AgreementResponse>")]
+pub struct ReactToAmendment {
+    pub agreement: AgreementView,
+    pub amendment: AgreementAmendment,
+}
+
 /// Agreement finished notifications. Negotiator can adjust his strategy based on it.
 #[derive(Message)]
 #[rtype(result = "Result<()>")]
@@ -112,6 +121,7 @@ pub trait Negotiator:
     + Handler<AgreementFinalized, Result = <AgreementFinalized as Message>::Result>
     + Handler<ReactToProposal, Result = <ReactToProposal as Message>::Result>
     + Handler<ReactToAgreement, Result = <ReactToAgreement as Message>::Result>
+    + Handler<ReactToAmendment, Result = <ReactToAmendment as Message>::Result>
 {
 }
 
@@ -126,6 +136,7 @@ pub struct NegotiatorAddr {
     pub on_finalized: Recipient<AgreementFinalized>,
     pub on_proposal: Recipient<ReactToProposal>,
     pub on_agreement: Recipient<ReactToAgreement>,
+    pub on_amendment: Recipient<ReactToAmendment>,
 }
 
 impl NegotiatorAddr {
@@ -161,6 +172,19 @@ impl NegotiatorAddr {
             .await?
     }
 
+    pub async fn react_to_amendment(
+        &self,
+        agreement_view: &AgreementView,
+        amendment: &AgreementAmendment,
+    ) -> Result<AgreementResponse> {
+        self.on_amendment
+            .send(ReactToAmendment {
+                agreement: agreement_view.clone(),
+                amendment: amendment.clone(),
+            })
+            .await?
+    }
+
     pub async fn agreement_finalized(
         &self,
         agreement_id: &str,
@@ -180,7 +204,8 @@ impl NegotiatorAddr {
             on_create: addr.clone().recipient(),
             on_finalized: addr.clone().recipient(),
             on_proposal: addr.clone().recipient(),
-            on_agreement: addr.recipient(),
+            on_agreement: addr.clone().recipient(),
+            on_amendment: addr.recipient(),
         }
     }
 }
@@ -3,11 +3,16 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use ya_agreement_utils::{AgreementView, OfferDefinition};
+use ya_core_model::market::AgreementAmendment;
 
 use crate::market::negotiator::AgreementResult;
 
 pub type ProposalView = AgreementView;
 
+/// Location of Requestor NodeId in Demand `ProposalView`, which isn't a part
+/// of Demand properties. Set by Negotiator before passing Demand to components.
+pub const REQUESTOR_ID_POINTER: &'static str = "/requestorId";
+
 /// Result returned by `NegotiatorComponent` during Proposals evaluation.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum NegotiationResult {
@@ -23,6 +28,14 @@ pub enum NegotiationResult {
     Reject { message: String, is_final: bool },
 }
 
+/// Result returned by `NegotiatorComponent` when Requestor wants to
+/// change terms of already approved Agreement.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum AmendmentResult {
+    Accept,
+    Reject { message: String },
+}
+
 /// `NegotiatorComponent` implements negotiation logic for part of Agreement
 /// specification. Components should be as granular as possible to allow composition
 /// with other Components.
@@ -56,7 +69,18 @@ pub trait NegotiatorComponent {
 
     /// Called when Negotiator decided to approve Agreement. It's only notification,
     /// `NegotiatorComponent` can't reject Agreement anymore.
-    fn on_agreement_approved(&mut self, agreement_id: &str) -> anyhow::Result<()>;
+    fn on_agreement_approved(&mut self, agreement: &AgreementView) -> anyhow::Result<()>;
+
+    /// Called when Requestor proposes new terms of already approved Agreement.
+    /// Components, which aren't responsible for amended properties, can leave
+    /// default implementation accepting all amendments.
+    fn on_agreement_amendment(
+        &mut self,
+        _agreement: &AgreementView,
+        _amendment: &AgreementAmendment,
+    ) -> anyhow::Result<AmendmentResult> {
+        Ok(AmendmentResult::Accept)
+    }
 }
 
 pub struct NegotiatorsPack {
@@ -151,15 +175,15 @@ impl NegotiatorComponent for NegotiatorsPack {
         Ok(())
     }
 
-    fn on_agreement_approved(&mut self, agreement_id: &str) -> anyhow::Result<()> {
+    fn on_agreement_approved(&mut self, agreement: &AgreementView) -> anyhow::Result<()> {
         for (name, component) in &mut self.components {
             component
-                .on_agreement_approved(agreement_id)
+                .on_agreement_approved(agreement)
                 .map_err(|e| {
                     log::warn!(
                         "Negotiator component '{}' failed handling Agreement [{}] approval. {}",
                         name,
-                        agreement_id,
+                        agreement.agreement_id,
                         e
                     )
                 })
@@ -167,4 +191,33 @@ impl NegotiatorComponent for NegotiatorsPack {
         }
         Ok(())
     }
+
+    fn on_agreement_amendment(
+        &mut self,
+        agreement: &AgreementView,
+        amendment: &AgreementAmendment,
+    ) -> anyhow::Result<AmendmentResult> {
+        for (name, component) in &mut self.components {
+            let result = component
+                .on_agreement_amendment(agreement, amendment)
+                .map_err(|e| {
+                    anyhow!(
+                        "Negotiator component '{}' failed handling Agreement [{}] amendment. {}",
+                        name,
+                        agreement.agreement_id,
+                        e
+                    )
+                })?;
+
+            if let AmendmentResult::Reject { .. } = &result {
+                log::info!(
+                    "Negotiator component '{}' rejected amendment of Agreement [{}].",
+                    name,
+                    agreement.agreement_id
+                );
+                return Ok(result);
+            }
+        }
+        Ok(AmendmentResult::Accept)
+    }
 }
@@ -4,18 +4,21 @@ use serde_json::Value;
 
 use ya_agreement_utils::agreement::{expand, flatten_value};
 use ya_agreement_utils::AgreementView;
-use ya_client::model::market::NewOffer;
+use ya_client::model::market::{NewOffer, Reason};
+use ya_client::model::NodeId;
 
 use super::builtin::{
     DebitNoteInterval, LimitExpiration, ManifestSignature, MaxAgreements, PaymentTimeout,
+    RequestorReputation, RequestorRules, ReserveResources,
 };
 use super::common::{offer_definition_to_offer, AgreementResponse, Negotiator, ProposalResponse};
-use super::{NegotiationResult, NegotiatorsPack};
+use super::{AmendmentResult, NegotiationResult, NegotiatorsPack};
 use crate::market::negotiator::common::{
-    reason_with_extra, AgreementFinalized, CreateOffer, ReactToAgreement, ReactToProposal,
+    reason_with_extra, AgreementFinalized, CreateOffer, ReactToAgreement, ReactToAmendment,
+    ReactToProposal,
 };
 use crate::market::negotiator::factory::CompositeNegotiatorConfig;
-use crate::market::negotiator::{NegotiatorComponent, ProposalView};
+use crate::market::negotiator::{NegotiatorComponent, ProposalView, REQUESTOR_ID_POINTER};
 use crate::market::ProviderMarket;
 
 /// Negotiator that can limit number of running agreements.
@@ -33,6 +36,10 @@ impl CompositeNegotiator {
                 "LimitAgreements",
                 Box::new(MaxAgreements::new(&config.limit_agreements_config)),
             )
+            .add_component(
+                "ReserveResources",
+                Box::new(ReserveResources::new(config.hardware.clone())),
+            )
             .add_component(
                 "LimitExpiration",
                 Box::new(LimitExpiration::new(&config.expire_agreements_config)?),
@@ -45,9 +52,24 @@ impl CompositeNegotiator {
                 "PaymentTimeout",
                 Box::new(PaymentTimeout::new(&config.payment_timeout_config)?),
             )
+            .add_component(
+                "RequestorReputation",
+                Box::new(RequestorReputation::new(&config.reputation_config)?),
+            )
             .add_component(
                 "ManifestSignature",
                 Box::new(ManifestSignature::from(config.policy_config.clone())),
+            )
+            .add_component(
+                "RequestorRules",
+                Box::new(RequestorRules::new(
+                    config.rules.clone(),
+                    config
+                        .policy_config
+                        .trusted_keys
+                        .clone()
+                        .unwrap_or_default(),
+                )),
             );
 
         Ok(CompositeNegotiator { components })
@@ -73,7 +95,7 @@ impl Handler<ReactToProposal> for CompositeNegotiator {
 
         let proposal = ProposalView {
             agreement_id: msg.demand.proposal_id,
-            json: expand(msg.demand.properties),
+            json: with_requestor_id(expand(msg.demand.properties), msg.demand.issuer_id),
         };
 
         let offer_proposal = ProposalView {
@@ -110,6 +132,7 @@ pub fn to_proposal_views(
     // TODO: We should get ProposalId here, but Agreement doen't store it anywhere.
     let offer_id = agreement.pointer_typed("/offer/offerId")?;
     let demand_id = agreement.pointer_typed("/demand/demandId")?;
+    let requestor_id = agreement.pointer_typed("/demand/requestorId")?;
     let offer_proposal = agreement
         .json
         .pointer_mut("/offer/properties")
@@ -128,18 +151,30 @@ pub fn to_proposal_views(
     };
 
     let demand_proposal = ProposalView {
-        json: demand_proposal,
+        json: with_requestor_id(demand_proposal, requestor_id),
         agreement_id: demand_id,
     };
 
     Ok((demand_proposal, offer_proposal))
 }
 
+/// Demand properties don't contain Requestor NodeId, so we attach it
+/// to let components make decisions based on Requestor identity.
+fn with_requestor_id(mut demand: Value, requestor_id: NodeId) -> Value {
+    if let Some(properties) = demand.as_object_mut() {
+        properties.insert(
+            REQUESTOR_ID_POINTER.trim_start_matches('/').to_string(),
+            Value::String(requestor_id.to_string()),
+        );
+    }
+    demand
+}
+
 impl Handler<ReactToAgreement> for CompositeNegotiator {
     type Result = anyhow::Result<AgreementResponse>;
 
     fn handle(&mut self, msg: ReactToAgreement, _: &mut Context<Self>) -> Self::Result {
-        let agreement_id = msg.agreement.agreement_id.clone();
+        let agreement = msg.agreement.clone();
         let (demand_proposal, offer_proposal) = to_proposal_views(msg.agreement).map_err(|e| {
             anyhow!(
                 "Negotiator failed to extract Proposals from Agreement. {}",
@@ -154,7 +189,7 @@ impl Handler<ReactToAgreement> for CompositeNegotiator {
             .negotiate_step(&demand_proposal, offer_proposal)?
         {
             NegotiationResult::Ready { .. } => {
-                self.components.on_agreement_approved(&agreement_id)?;
+                self.components.on_agreement_approved(&agreement)?;
                 Ok(AgreementResponse::ApproveAgreement)
             }
             NegotiationResult::Reject { message, is_final } => {
@@ -177,6 +212,23 @@ impl Handler<ReactToAgreement> for CompositeNegotiator {
     }
 }
 
+impl Handler<ReactToAmendment> for CompositeNegotiator {
+    type Result = anyhow::Result<AgreementResponse>;
+
+    fn handle(&mut self, msg: ReactToAmendment, _: &mut Context<Self>) -> Self::Result {
+        match self
+            .components
+            .on_agreement_amendment(&msg.agreement, &msg.amendment)?
+        {
+            AmendmentResult::Accept => Ok(AgreementResponse::ApproveAgreement),
+            AmendmentResult::Reject { message } => Ok(AgreementResponse::RejectAgreement {
+                reason: Some(Reason::new(message)),
+                is_final: true,
+            }),
+        }
+    }
+}
+
 impl Handler<AgreementFinalized> for CompositeNegotiator {
     type Result = anyhow::Result<()>;
 
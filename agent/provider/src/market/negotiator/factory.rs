@@ -6,6 +6,9 @@ use structopt::StructOpt;
 use ya_manifest_utils::PolicyConfig;
 
 use super::common::NegotiatorAddr;
+use crate::config::reputation::ReputationStore;
+use crate::config::rules::RulesStore;
+use crate::hardware::HardwareStore;
 use crate::market::config::MarketConfig;
 use crate::market::negotiator::{AcceptAllNegotiator, CompositeNegotiator};
 use crate::market::ProviderMarket;
@@ -54,6 +57,23 @@ pub struct PaymentTimeoutConfig {
     pub payment_timeout_required_duration: std::time::Duration,
 }
 
+/// Configuration for RequestorReputation negotiator
+#[derive(StructOpt, Clone, Debug)]
+pub struct RequestorReputationConfig {
+    /// Proposals from Requestors with lower score are rejected
+    #[structopt(long, env, default_value = "0.25")]
+    pub min_requestor_score: f64,
+    /// Requestors with lower score must agree on stricter payment terms
+    #[structopt(long, env, default_value = "0.6")]
+    pub trusted_requestor_score: f64,
+    #[structopt(long, env, parse(try_from_str = humantime::parse_duration), default_value = "5min")]
+    pub untrusted_max_payment_timeout: std::time::Duration,
+    #[structopt(long, env, parse(try_from_str = humantime::parse_duration), default_value = "2min")]
+    pub untrusted_max_debit_note_interval: std::time::Duration,
+    #[structopt(skip)]
+    pub store: ReputationStore,
+}
+
 /// Configuration for LimitAgreements Negotiator.
 #[derive(StructOpt, Clone, Debug)]
 pub struct CompositeNegotiatorConfig {
@@ -66,7 +86,13 @@ pub struct CompositeNegotiatorConfig {
     #[structopt(flatten)]
     pub payment_timeout_config: PaymentTimeoutConfig,
     #[structopt(flatten)]
+    pub reputation_config: RequestorReputationConfig,
+    #[structopt(flatten)]
     pub policy_config: PolicyConfig,
+    #[structopt(skip)]
+    pub rules: RulesStore,
+    #[structopt(skip)]
+    pub hardware: HardwareStore,
 }
 
 #[derive(StructOpt, Clone, Debug)]
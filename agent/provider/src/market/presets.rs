@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::fmt::Formatter;
 use std::path::Path;
@@ -22,6 +22,9 @@ pub struct Preset {
     pub pricing_model: String,
     pub initial_price: f64,
     pub usage_coeffs: HashMap<String, f64>,
+    /// User-defined hardware resources allocated for each Agreement
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub resources: BTreeMap<String, i64>,
 }
 
 impl Preset {
@@ -33,6 +36,7 @@ impl Preset {
         PresetDisplay {
             preset: self,
             registry,
+            multiplier: None,
         }
     }
 }
@@ -225,6 +229,7 @@ impl Default for Preset {
             exeunit_name: "wasmtime".to_string(),
             pricing_model: "linear".to_string(),
             usage_coeffs,
+            resources: Default::default(),
         }
     }
 }
@@ -235,17 +240,27 @@ impl PartialEq for Preset {
             && self.exeunit_name == other.exeunit_name
             && self.pricing_model == other.pricing_model
             && self.usage_coeffs == other.usage_coeffs
+            && self.resources == other.resources
     }
 }
 
 pub struct PresetDisplay<'a, 'b> {
     preset: &'a Preset,
     registry: &'b ExeUnitsRegistry,
+    multiplier: Option<f64>,
+}
+
+impl<'a, 'b> PresetDisplay<'a, 'b> {
+    /// Display prices adjusted by dynamic pricing next to preset prices.
+    pub fn effective_prices(mut self, multiplier: f64) -> Self {
+        self.multiplier = Some(multiplier);
+        self
+    }
 }
 
 impl<'a, 'b> fmt::Display for PresetDisplay<'a, 'b> {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        display_preset(f, self.preset, self.registry)
+        display_preset(f, self.preset, self.registry, self.multiplier)
     }
 }
 
@@ -253,6 +268,7 @@ fn display_preset(
     f: &mut fmt::Formatter,
     preset: &Preset,
     registry: &ExeUnitsRegistry,
+    multiplier: Option<f64>,
 ) -> fmt::Result {
     let align = 20;
     let align_coeff = align - 4; // Minus indent.
@@ -283,11 +299,22 @@ fn display_preset(
             .unwrap_or_else(|| name.to_string());
         write!(
             f,
-            "    {:width$}{} GLM\n",
+            "    {:width$}{} GLM",
             price_desc,
             coeff,
             width = align_coeff
         )?;
+        match multiplier {
+            Some(multiplier) => write!(f, " (effective: {} GLM)\n", coeff * multiplier)?,
+            None => write!(f, "\n")?,
+        }
+    }
+
+    if !preset.resources.is_empty() {
+        write!(f, "{}\n", "Resources:")?;
+        for (name, amount) in preset.resources.iter() {
+            write!(f, "    {:width$}{}\n", name, amount, width = align_coeff)?;
+        }
     }
 
     Ok(())
@@ -9,6 +9,7 @@ use futures_util::FutureExt;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::time::timeout;
 
@@ -20,12 +21,15 @@ use ya_client::model::market::{
     agreement_event::AgreementTerminator, Agreement, NewOffer, Proposal, ProviderEvent, Reason,
 };
 use ya_client::model::NodeId;
+use ya_core_model::market::{AgreementAmendment, AgreementAmendmentEvent, AmendmentEventType};
 use ya_std_utils::LogErr;
 use ya_utils_actix::{
     actix_handler::ResultTypeGetter, actix_signal::SignalSlot, actix_signal_handler,
     forward_actix_handler,
 };
 
+use super::amendment::AmendmentApi;
+use super::metrics::MetricsApi;
 use super::negotiator::factory;
 use super::negotiator::{AgreementResponse, AgreementResult, NegotiatorAddr, ProposalResponse};
 use super::Preset;
@@ -69,6 +73,20 @@ pub struct NewAgreement {
     pub agreement: AgreementView,
 }
 
+/// Emitted after approval of an Agreement completed or an Agreement was finalized,
+/// so resources reserved by negotiator won't change until the next such event.
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Result<()>")]
+pub struct CapacityChanged;
+
+/// Emitted after we accepted new terms of Agreement proposed by Requestor.
+#[derive(Clone, Debug, Message)]
+#[rtype(result = "Result<()>")]
+pub struct AgreementAmended {
+    pub agreement_id: String,
+    pub amendment: AgreementAmendment,
+}
+
 // =========================================== //
 // Internal messages
 // =========================================== //
@@ -80,6 +98,15 @@ struct Subscription {
     id: String,
     preset: Preset,
     offer: NewOffer,
+    /// Cleared on unsubscribe. Events already collected are still processed,
+    /// so pending Agreement approvals complete.
+    active: Arc<AtomicBool>,
+}
+
+impl Subscription {
+    fn is_active(&self) -> bool {
+        self.active.load(Ordering::SeqCst)
+    }
 }
 
 #[derive(Message)]
@@ -112,6 +139,8 @@ pub struct SubscriptionProposal {
 pub struct ProviderMarket {
     negotiator: Arc<NegotiatorAddr>,
     api: Arc<MarketProviderApi>,
+    amendment_api: Arc<AmendmentApi>,
+    metrics_api: Arc<MetricsApi>,
     subscriptions: HashMap<String, Subscription>,
     postponed_demands: Vec<SubscriptionProposal>,
     config: Arc<MarketConfig>,
@@ -119,6 +148,8 @@ pub struct ProviderMarket {
     /// External actors can listen on this signal.
     pub agreement_signed_signal: SignalSlot<NewAgreement>,
     pub agreement_terminated_signal: SignalSlot<CloseAgreement>,
+    pub agreement_amended_signal: SignalSlot<AgreementAmended>,
+    pub capacity_changed_signal: SignalSlot<CapacityChanged>,
 
     /// Infinite tasks requiring to be killed on shutdown.
     handles: HashMap<String, SpawnHandle>,
@@ -129,6 +160,8 @@ struct AsyncCtx {
     market: Addr<ProviderMarket>,
     config: Arc<MarketConfig>,
     api: Arc<MarketProviderApi>,
+    amendment_api: Arc<AmendmentApi>,
+    metrics_api: Arc<MetricsApi>,
     negotiator: Arc<NegotiatorAddr>,
 }
 
@@ -137,15 +170,24 @@ impl ProviderMarket {
     // Initialization
     // =========================================== //
 
-    pub fn new(api: MarketProviderApi, config: MarketConfig) -> ProviderMarket {
+    pub fn new(
+        api: MarketProviderApi,
+        amendment_api: AmendmentApi,
+        metrics_api: MetricsApi,
+        config: MarketConfig,
+    ) -> ProviderMarket {
         return ProviderMarket {
             api: Arc::new(api),
+            amendment_api: Arc::new(amendment_api),
+            metrics_api: Arc::new(metrics_api),
             negotiator: Arc::new(NegotiatorAddr::default()),
             config: Arc::new(config),
             subscriptions: HashMap::new(),
             postponed_demands: Vec::new(),
             agreement_signed_signal: SignalSlot::<NewAgreement>::new(),
             agreement_terminated_signal: SignalSlot::<CloseAgreement>::new(),
+            agreement_amended_signal: SignalSlot::<AgreementAmended>::new(),
+            capacity_changed_signal: SignalSlot::<CapacityChanged>::new(),
             handles: HashMap::new(),
         };
     }
@@ -154,6 +196,8 @@ impl ProviderMarket {
         AsyncCtx {
             config: self.config.clone(),
             api: self.api.clone(),
+            amendment_api: self.amendment_api.clone(),
+            metrics_api: self.metrics_api.clone(),
             market: ctx.address(),
             negotiator: self.negotiator.clone(),
         }
@@ -184,6 +228,27 @@ impl ProviderMarket {
         // At this moment we only forward agreement to outside world.
         self.agreement_signed_signal.send_signal(msg)
     }
+
+    fn on_agreement_amended(
+        &mut self,
+        msg: AgreementAmended,
+        _ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        log::info!(
+            "Agreement [{}] amended. New expiration: {}.",
+            msg.agreement_id,
+            msg.amendment.valid_to
+        );
+        self.agreement_amended_signal.send_signal(msg)
+    }
+
+    fn on_capacity_changed(
+        &mut self,
+        msg: CapacityChanged,
+        _ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        self.capacity_changed_signal.send_signal(msg)
+    }
 }
 
 async fn subscribe(
@@ -193,8 +258,16 @@ async fn subscribe(
     preset: Preset,
 ) -> Result<()> {
     let id = api.subscribe(&offer).await?;
-
-    let _ = market.send(Subscription { id, offer, preset }).await?;
+    let active = Arc::new(AtomicBool::new(true));
+
+    let _ = market
+        .send(Subscription {
+            id,
+            offer,
+            preset,
+            active,
+        })
+        .await?;
     Ok(())
 }
 
@@ -282,6 +355,7 @@ async fn process_proposal(
     demand: &Proposal,
 ) -> Result<()> {
     let proposal_id = &demand.proposal_id;
+    ctx.config.acceptance_stats.on_proposal();
 
     log::info!(
         "Got proposal [{}] from Requestor [{}] for subscription [{}].",
@@ -415,6 +489,19 @@ async fn process_agreement(
                 .await?
                 .ok();
 
+            // Preset isn't published in Offer, so yagna learns it from us.
+            if let Err(error) = ctx
+                .metrics_api
+                .set_preset(&agreement.agreement_id, &subscription.preset.name)
+                .await
+            {
+                log::debug!(
+                    "Failed to set preset of agreement [{}] in metrics. Error: {}",
+                    agreement.agreement_id,
+                    error
+                );
+            }
+
             // TODO: We should retry approval, but only a few times, than we should
             //       give up since it's better to take another agreement.
             let result = ctx
@@ -439,6 +526,10 @@ async fn process_agreement(
                     error
                 ));
             }
+            config
+                .acceptance_stats
+                .on_agreement(&agreement.agreement_id);
+            let _ = ctx.market.send(CapacityChanged).await;
 
             // We negotiated agreement and here responsibility of ProviderMarket ends.
             // Notify outside world about agreement for further processing.
@@ -506,13 +597,99 @@ async fn collect_agreement_events(ctx: AsyncCtx) {
     }
 }
 
+async fn process_amendment(ctx: AsyncCtx, event: AgreementAmendmentEvent) -> Result<()> {
+    log::info!(
+        "Got amendment [{}] of agreement [{}]. Requested expiration: {}.",
+        event.amendment_id,
+        event.agreement_id,
+        event.amendment.valid_to,
+    );
+
+    let agreement = ctx.api.get_agreement(&event.agreement_id).await?;
+    let agreement = AgreementView::try_from(&agreement)
+        .map_err(|e| anyhow!("Invalid agreement. Error: {}", e))?;
+
+    let action = ctx
+        .negotiator
+        .react_to_amendment(&agreement, &event.amendment)
+        .await
+        .map_err(|e| {
+            anyhow!(
+                "Negotiator error while processing amendment of agreement [{}]. Error: {}",
+                agreement.agreement_id,
+                e
+            )
+        })?;
+
+    log::info!(
+        "Decided to {} amendment [{}] of agreement [{}].",
+        action,
+        event.amendment_id,
+        agreement.agreement_id,
+    );
+
+    // New terms are applied after market stores the amendment and reports it as amended.
+    match action {
+        AgreementResponse::ApproveAgreement => {
+            ctx.amendment_api.approve(&event.amendment_id).await?
+        }
+        AgreementResponse::RejectAgreement { reason, .. } => {
+            ctx.amendment_api
+                .reject(&event.amendment_id, &reason)
+                .await?
+        }
+    }
+    Ok(())
+}
+
+async fn on_amended(ctx: AsyncCtx, event: AgreementAmendmentEvent) -> Result<()> {
+    log::info!(
+        "Agreement [{}] amended. Expiration: {}.",
+        event.agreement_id,
+        event.amendment.valid_to,
+    );
+    ctx.market
+        .send(AgreementAmended {
+            agreement_id: event.agreement_id,
+            amendment: event.amendment,
+        })
+        .await?
+}
+
+async fn collect_amendment_events(ctx: AsyncCtx) {
+    let timeout = ctx.config.agreement_events_interval;
+
+    loop {
+        let events = match ctx.amendment_api.collect(timeout, 5).await {
+            Err(e) => {
+                log::warn!("Can't query agreement amendments. Error: {}", e);
+                tokio::time::sleep(std::time::Duration::from_secs_f32(timeout)).await;
+                continue;
+            }
+            Ok(events) => events,
+        };
+
+        for event in events {
+            let amendment_id = event.amendment_id.clone();
+            let result = match event.event_type {
+                AmendmentEventType::Proposed => process_amendment(ctx.clone(), event).await,
+                AmendmentEventType::Amended => on_amended(ctx.clone(), event).await,
+            };
+            result
+                .map_err(|e| log::error!("Error processing amendment [{}]: {}", amendment_id, e))
+                .ok();
+        }
+    }
+}
+
 async fn collect_negotiation_events(ctx: AsyncCtx, subscription: Subscription) {
     let ctx = ctx.clone();
     let id = subscription.id.clone();
     let timeout = ctx.config.negotiation_events_interval;
 
-    loop {
+    while subscription.is_active() {
         match ctx.api.collect(&id, Some(timeout), Some(5)).await {
+            Err(_) if !subscription.is_active() => break,
             Err(error) => {
                 log::warn!("Can't query market events. Error: {}", error);
                 match error {
@@ -534,6 +711,7 @@ async fn collect_negotiation_events(ctx: AsyncCtx, subscription: Subscription) {
             Ok(events) => dispatch_events(ctx.clone(), events, &subscription).await,
         }
     }
+    log::debug!("Stopped collecting events of subscription [{}]", id);
 }
 
 #[derive(Message)]
@@ -589,7 +767,11 @@ impl Actor for ProviderMarket {
         // Note: There will be no collision with subscription ids stored normally here.
         self.handles.insert(
             "collect-agreement-events".to_string(),
-            ctx.spawn(collect_agreement_events(actx).into_actor(self)),
+            ctx.spawn(collect_agreement_events(actx.clone()).into_actor(self)),
+        );
+        self.handles.insert(
+            "collect-amendment-events".to_string(),
+            ctx.spawn(collect_amendment_events(actx).into_actor(self)),
         );
 
         self.negotiator = factory::create_negotiator(ctx.address(), &self.config);
@@ -810,6 +992,10 @@ impl Handler<AgreementFinalized> for ProviderMarket {
                 .ok();
         }
 
+        self.config
+            .acceptance_stats
+            .on_agreement_finalized(&agreement_id);
+
         let async_ctx = ctx.clone();
         let future = async move {
             ctx.negotiator
@@ -824,6 +1010,10 @@ impl Handler<AgreementFinalized> for ProviderMarket {
         .into_actor(self)
         .map(|_, myself, ctx| {
             ctx.spawn(terminate_agreement(myself.api.clone(), msg).into_actor(myself));
+            myself
+                .capacity_changed_signal
+                .send_signal(CapacityChanged)
+                .ok();
 
             log::info!("Re-negotiating all demands");
 
@@ -863,14 +1053,11 @@ impl Handler<AgreementBroken> for ProviderMarket {
 impl Handler<Unsubscribe> for ProviderMarket {
     type Result = ResponseFuture<Result<(), Error>>;
 
-    fn handle(&mut self, msg: Unsubscribe, ctx: &mut Context<Self>) -> Self::Result {
+    fn handle(&mut self, msg: Unsubscribe, _ctx: &mut Context<Self>) -> Self::Result {
         let subscriptions = match msg.0 {
             OfferKind::Any => {
                 log::info!("Unsubscribing all active offers");
-                std::mem::replace(&mut self.subscriptions, HashMap::new())
-                    .into_iter()
-                    .map(|(k, _)| k)
-                    .collect::<Vec<_>>()
+                self.subscriptions.keys().cloned().collect::<Vec<_>>()
             }
             OfferKind::WithPresets(preset_names) => {
                 let subs = self
@@ -891,15 +1078,14 @@ impl Handler<Unsubscribe> for ProviderMarket {
             }
         };
 
-        subscriptions.iter().for_each(|id| {
-            self.subscriptions.remove(id);
-        });
-        subscriptions
-            .iter()
-            .filter_map(|id| self.handles.remove(id))
-            .for_each(|handle| {
-                ctx.cancel_future(handle);
-            });
+        // Event loops aren't cancelled, since they might be approving Agreements.
+        // They stop on their own after processing events already collected.
+        for id in subscriptions.iter() {
+            if let Some(subscription) = self.subscriptions.remove(id) {
+                subscription.active.store(false, Ordering::SeqCst);
+            }
+            self.handles.remove(id);
+        }
 
         unsubscribe_all(self.api.clone(), subscriptions).boxed_local()
     }
@@ -907,8 +1093,12 @@ impl Handler<Unsubscribe> for ProviderMarket {
 
 forward_actix_handler!(ProviderMarket, Subscription, on_subscription);
 forward_actix_handler!(ProviderMarket, NewAgreement, on_agreement_approved);
+forward_actix_handler!(ProviderMarket, AgreementAmended, on_agreement_amended);
+forward_actix_handler!(ProviderMarket, CapacityChanged, on_capacity_changed);
 actix_signal_handler!(ProviderMarket, CloseAgreement, agreement_terminated_signal);
 actix_signal_handler!(ProviderMarket, NewAgreement, agreement_signed_signal);
+actix_signal_handler!(ProviderMarket, AgreementAmended, agreement_amended_signal);
+actix_signal_handler!(ProviderMarket, CapacityChanged, capacity_changed_signal);
 
 fn get_backoff() -> backoff::ExponentialBackoff {
     // TODO: We could have config for Market actor to be able to set at least initial interval.
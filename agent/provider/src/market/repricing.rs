@@ -0,0 +1,212 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::{fs, io};
+use structopt::StructOpt;
+use ya_utils_path::SwapSave;
+
+use crate::market::Preset;
+
+pub(crate) const PRICING_JSON: &'static str = "pricing.json";
+
+/// Configuration for dynamic repricing of Offers.
+#[derive(StructOpt, Clone, Debug)]
+pub struct RepricingConfig {
+    /// Periodically adjust prices based on utilization and agreements acceptance rate
+    #[structopt(long)]
+    pub dynamic_pricing: bool,
+    #[structopt(long, env, parse(try_from_str = humantime::parse_duration), default_value = "15min")]
+    pub repricing_interval: std::time::Duration,
+    /// Relative change of prices in single repricing step
+    #[structopt(long, env, default_value = "0.1")]
+    pub repricing_step: f64,
+    /// Lowest allowed price, relative to the preset price
+    #[structopt(long, env, default_value = "0.5")]
+    pub price_floor: f64,
+    /// Highest allowed price, relative to the preset price
+    #[structopt(long, env, default_value = "2.0")]
+    pub price_ceiling: f64,
+    #[structopt(long, env, default_value = "0.8")]
+    pub high_utilization: f64,
+    #[structopt(long, env, default_value = "0.2")]
+    pub low_utilization: f64,
+    #[structopt(long, env, default_value = "0.5")]
+    pub high_acceptance_rate: f64,
+    #[structopt(long, env, default_value = "0.1")]
+    pub low_acceptance_rate: f64,
+}
+
+impl RepricingConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if self.price_floor <= 0.0 || self.price_floor > self.price_ceiling {
+            anyhow::bail!(
+                "Invalid price bounds: floor {} and ceiling {}",
+                self.price_floor,
+                self.price_ceiling
+            );
+        }
+        if self.repricing_step <= 0.0 || self.repricing_step >= 1.0 {
+            anyhow::bail!("Repricing step {} not in range (0; 1)", self.repricing_step);
+        }
+        Ok(())
+    }
+
+    /// Raises prices, when Provider is busy or Requestors eagerly sign Agreements,
+    /// and lowers them, when Provider is idle and Offers aren't attractive.
+    pub fn next_multiplier(&self, current: f64, utilization: f64, acceptance_rate: f64) -> f64 {
+        let next = if utilization >= self.high_utilization
+            || acceptance_rate >= self.high_acceptance_rate
+        {
+            current * (1.0 + self.repricing_step)
+        } else if utilization <= self.low_utilization && acceptance_rate <= self.low_acceptance_rate
+        {
+            current * (1.0 - self.repricing_step)
+        } else {
+            current
+        };
+        next.max(self.price_floor).min(self.price_ceiling)
+    }
+}
+
+/// Counts incoming Proposals and approved Agreements between repricing steps
+/// and keeps track of Agreements, which weren't finalized yet.
+#[derive(Clone, Debug, Default)]
+pub struct AcceptanceStats {
+    inner: Arc<Mutex<Counters>>,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    proposals: u32,
+    agreements: u32,
+    active: HashSet<String>,
+}
+
+impl AcceptanceStats {
+    pub fn on_proposal(&self) {
+        self.inner.lock().unwrap().proposals += 1;
+    }
+
+    pub fn on_agreement(&self, agreement_id: &str) {
+        let mut counters = self.inner.lock().unwrap();
+        counters.agreements += 1;
+        counters.active.insert(agreement_id.to_string());
+    }
+
+    pub fn on_agreement_finalized(&self, agreement_id: &str) {
+        self.inner.lock().unwrap().active.remove(agreement_id);
+    }
+
+    /// Returns ratio of approved Agreements to received Proposals and resets counters.
+    pub fn take_rate(&self) -> f64 {
+        let mut counters = self.inner.lock().unwrap();
+        let proposals = std::mem::take(&mut counters.proposals);
+        let agreements = std::mem::take(&mut counters.agreements);
+        match proposals {
+            0 => 0.0,
+            _ => (agreements as f64 / proposals as f64).min(1.0),
+        }
+    }
+
+    /// Fraction of simultaneous Agreements limit, that is currently used.
+    pub fn utilization(&self, max_agreements: u32) -> f64 {
+        let active = self.inner.lock().unwrap().active.len();
+        match max_agreements {
+            0 => 0.0,
+            _ => (active as f64 / max_agreements as f64).min(1.0),
+        }
+    }
+}
+
+/// Price multiplier currently applied to all presets.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PricingState {
+    pub multiplier: f64,
+    pub updated: Option<DateTime<Utc>>,
+}
+
+impl Default for PricingState {
+    fn default() -> Self {
+        PricingState {
+            multiplier: 1.0,
+            updated: None,
+        }
+    }
+}
+
+impl PricingState {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        if path.exists() && fs::metadata(path)?.len() > 0 {
+            Ok(serde_json::from_reader(io::BufReader::new(
+                fs::OpenOptions::new().read(true).open(path)?,
+            ))?)
+        } else {
+            Ok(Self::default())
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        Ok(path.swap_save(serde_json::to_string_pretty(self)?)?)
+    }
+
+    pub fn is_adjusted(&self) -> bool {
+        self.multiplier != 1.0
+    }
+
+    /// Preset with prices, that are currently offered on the market.
+    pub fn apply(&self, preset: &Preset) -> Preset {
+        let mut preset = preset.clone();
+        preset.initial_price *= self.multiplier;
+        preset
+            .usage_coeffs
+            .values_mut()
+            .for_each(|price| *price *= self.multiplier);
+        preset
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> RepricingConfig {
+        RepricingConfig::from_iter(vec!["TEST"])
+    }
+
+    #[test]
+    fn multiplier_follows_load() {
+        let config = config();
+        assert!(config.next_multiplier(1.0, 0.9, 0.0) > 1.0);
+        assert!(config.next_multiplier(1.0, 0.0, 0.6) > 1.0);
+        assert!(config.next_multiplier(1.0, 0.1, 0.05) < 1.0);
+        assert_eq!(config.next_multiplier(1.0, 0.5, 0.3), 1.0);
+    }
+
+    #[test]
+    fn active_agreements_utilization() {
+        let stats = AcceptanceStats::default();
+        stats.on_proposal();
+        stats.on_proposal();
+        stats.on_agreement("first");
+        stats.on_agreement("second");
+        assert_eq!(stats.utilization(4), 0.5);
+        assert_eq!(stats.take_rate(), 1.0);
+        assert_eq!(stats.take_rate(), 0.0);
+
+        // Active Agreements outlive repricing steps.
+        stats.on_agreement_finalized("first");
+        stats.on_agreement_finalized("not-approved");
+        assert_eq!(stats.utilization(4), 0.25);
+        assert_eq!(stats.utilization(0), 0.0);
+    }
+
+    #[test]
+    fn multiplier_is_bounded() {
+        let config = config();
+        assert_eq!(config.next_multiplier(1.95, 1.0, 1.0), config.price_ceiling);
+        assert_eq!(config.next_multiplier(0.52, 0.0, 0.0), config.price_floor);
+    }
+}
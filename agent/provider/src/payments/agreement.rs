@@ -11,6 +11,7 @@ use crate::display::EnableDisplay;
 
 use ya_agreement_utils::AgreementView;
 use ya_client::activity::ActivityProviderApi;
+use ya_client::model::NodeId;
 
 const PAYMENT_PRECISION: i64 = 18; // decimal places
 
@@ -48,6 +49,7 @@ pub enum ActivityPayment {
 /// We must wait until agreement will be closed, before we send invoice.
 pub struct AgreementPayment {
     pub agreement_id: String,
+    pub requestor_id: NodeId,
     pub approved_ts: DateTime<Utc>,
     pub payment_model: Arc<dyn PaymentModel>,
     pub activities: HashMap<String, ActivityPayment>,
@@ -84,6 +86,7 @@ impl AgreementPayment {
         let accept_timeout = payment_description.get_debit_note_accept_timeout()?;
         let payment_timeout = payment_description.get_payment_timeout()?;
         let approved_ts = payment_description.get_approved_ts()?;
+        let requestor_id = payment_description.get_requestor_id()?;
 
         if let Some(deadline) = &accept_timeout {
             log::info!(
@@ -105,6 +108,7 @@ impl AgreementPayment {
 
         Ok(AgreementPayment {
             agreement_id: agreement.agreement_id.clone(),
+            requestor_id,
             approved_ts,
             activities: HashMap::new(),
             payment_model,
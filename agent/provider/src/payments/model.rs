@@ -3,6 +3,7 @@ use bigdecimal::BigDecimal;
 use std::time::Duration;
 
 use ya_agreement_utils::{AgreementView, Error};
+use ya_client::model::NodeId;
 
 use crate::market::negotiator::builtin::expiration::DEBIT_NOTE_ACCEPT_TIMEOUT_PROPERTY;
 use crate::market::negotiator::builtin::note_interval::{
@@ -69,6 +70,12 @@ impl<'a> PaymentDescription<'a> {
         }
     }
 
+    pub fn get_requestor_id(&self) -> Result<NodeId> {
+        Ok(self
+            .agreement
+            .pointer_typed::<NodeId>("/demand/requestorId")?)
+    }
+
     pub fn get_approved_ts(&self) -> Result<chrono::DateTime<chrono::Utc>> {
         let rfc3339 = self.agreement.pointer_typed::<String>("/timestamp")?;
         Ok(chrono::DateTime::parse_from_rfc3339(&rfc3339)?.with_timezone(&chrono::Utc))
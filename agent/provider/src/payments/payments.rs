@@ -18,6 +18,7 @@ use ya_client::model::payment::{DebitNote, Invoice, NewDebitNote, NewInvoice};
 use ya_client::model::payment::{DebitNoteEvent, DebitNoteEventType, InvoiceEventType};
 use ya_client::payment::PaymentApi;
 
+use ya_file_logging::{in_log_context, LogContext};
 use ya_std_utils::LogErr;
 use ya_utils_actix::actix_handler::ResultTypeGetter;
 use ya_utils_actix::actix_signal::{SignalSlot, Subscribe};
@@ -26,9 +27,10 @@ use ya_utils_actix::deadline_checker::{
 };
 use ya_utils_actix::{actix_signal_handler, forward_actix_handler};
 
+use crate::config::reputation::{ReputationEvent, ReputationStore};
 use crate::execution::{ActivityDestroyed, CreateActivity};
 use crate::interval::RelativeInterval;
-use crate::market::provider_market::NewAgreement;
+use crate::market::provider_market::{AgreementAmended, NewAgreement};
 use crate::market::termination_reason::BreakReason;
 use crate::tasks::{AgreementBroken, AgreementClosed, BreakAgreement};
 
@@ -124,6 +126,8 @@ pub struct PaymentsConfig {
     pub invoice_reissue_interval: Duration,
     #[structopt(skip = "you-forgot-to-set-session-id")]
     pub session_id: String,
+    #[structopt(skip)]
+    pub reputation: ReputationStore,
 }
 
 /// Yagna APIs and payments information about provider.
@@ -199,6 +203,31 @@ impl Payments {
             }
         }
     }
+
+    /// New payment terms apply to Activities created after amendment. DebitNotes
+    /// of already running Activities are computed according to previous terms.
+    pub fn on_agreement_amended(
+        &mut self,
+        msg: AgreementAmended,
+        _ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        let agreement = self
+            .agreements
+            .get_mut(&msg.agreement_id)
+            .ok_or(anyhow!(
+                "Agreement [{}] wasn't registered.",
+                &msg.agreement_id
+            ))
+            .log_warn_msg("[AgreementAmended]")?;
+
+        if let Some(timeout) = msg.amendment.payment_timeout_sec {
+            agreement.payment_timeout = Some(chrono::Duration::seconds(timeout as i64));
+        }
+        if let Some(interval) = msg.amendment.debit_note_interval_sec {
+            agreement.update_interval = std::time::Duration::from_secs(interval as u64);
+        }
+        Ok(())
+    }
 }
 
 async fn send_debit_note(
@@ -498,11 +527,14 @@ async fn compute_cost_and_send_debit_note(
 }
 
 forward_actix_handler!(Payments, NewAgreement, on_signed_agreement);
+forward_actix_handler!(Payments, AgreementAmended, on_agreement_amended);
 
 impl Handler<CreateActivity> for Payments {
     type Result = anyhow::Result<()>;
 
     fn handle(&mut self, msg: CreateActivity, ctx: &mut Context<Self>) -> Self::Result {
+        let _log_guard =
+            ya_file_logging::enter(&LogContext::activity(&msg.agreement_id, &msg.activity_id));
         let agreement = self
             .agreements
             .get_mut(&msg.agreement_id)
@@ -548,6 +580,8 @@ impl Handler<ActivityDestroyed> for Payments {
     type Result = ActorResponse<Self, Result<(), Error>>;
 
     fn handle(&mut self, msg: ActivityDestroyed, ctx: &mut Context<Self>) -> Self::Result {
+        let log_cx = LogContext::activity(&msg.agreement_id, &msg.activity_id);
+        let _log_guard = ya_file_logging::enter(&log_cx);
         let agreement = match self
             .agreements
             .get_mut(&msg.agreement_id)
@@ -582,7 +616,7 @@ impl Handler<ActivityDestroyed> for Payments {
             payment_timeout: agreement.payment_timeout,
         };
 
-        let future = async move {
+        let future = in_log_context(log_cx, async move {
             // Computing last DebitNote can't fail, so we must repeat it until
             // it reaches Requestor. DebitNote itself is not important so much, but
             // we must ensure that we send FinalizeActivity and Invoice in consequence.
@@ -618,7 +652,7 @@ impl Handler<ActivityDestroyed> for Payments {
             };
 
             let _ = address.send(msg).await;
-        }
+        })
         .into_actor(self);
 
         return ActorResponse::r#async(future.map(|_, _, _| Ok(())));
@@ -629,6 +663,11 @@ impl Handler<UpdateCost> for Payments {
     type Result = ActorResponse<Self, Result<(), Error>>;
 
     fn handle(&mut self, mut msg: UpdateCost, _ctx: &mut Context<Self>) -> Self::Result {
+        let log_cx = LogContext::activity(
+            &msg.invoice_info.agreement_id,
+            &msg.invoice_info.activity_id,
+        );
+        let _log_guard = ya_file_logging::enter(&log_cx);
         let agreement = match self
             .agreements
             .get(&msg.invoice_info.agreement_id)
@@ -651,7 +690,7 @@ impl Handler<UpdateCost> for Payments {
                 let payment_model = agreement.payment_model.clone();
                 let context = self.context.clone();
 
-                let debit_note_future = async move {
+                let debit_note_future = in_log_context(log_cx.clone(), async move {
                     let (debit_note, _cost) = compute_cost_and_send_debit_note(
                         context.clone(),
                         payment_model.clone(),
@@ -661,9 +700,10 @@ impl Handler<UpdateCost> for Payments {
                         .await
                         .log_err()?;
                     Ok(debit_note)
-                }
+                })
                     .into_actor(self)
                     .map(move |result: Result<_, anyhow::Error>, myself, ctx| {
+                        let _log_guard = ya_file_logging::enter(&log_cx);
                         // We break Agreement, if we weren't able to send any DebitNote lately.
                         match result {
                             Err(_) => {
@@ -729,6 +769,10 @@ impl Handler<FinalizeActivity> for Payments {
     type Result = <FinalizeActivity as Message>::Result;
 
     fn handle(&mut self, msg: FinalizeActivity, _ctx: &mut Context<Self>) -> Self::Result {
+        let _log_guard = ya_file_logging::enter(&LogContext::activity(
+            &msg.debit_info.agreement_id,
+            &msg.debit_info.activity_id,
+        ));
         if let Ok(agreement) = self
             .agreements
             .get_mut(&msg.debit_info.agreement_id)
@@ -752,6 +796,8 @@ impl Handler<AgreementClosed> for Payments {
     type Result = ActorResponse<Self, Result<(), Error>>;
 
     fn handle(&mut self, msg: AgreementClosed, ctx: &mut Context<Self>) -> Self::Result {
+        let log_cx = LogContext::agreement(&msg.agreement_id);
+        let _log_guard = ya_file_logging::enter(&log_cx);
         if let Some(agreement) = self.agreements.get_mut(&msg.agreement_id) {
             log::info!(
                 "Payments - agreement [{}] closed. Computing cost summary...",
@@ -764,7 +810,7 @@ impl Handler<AgreementClosed> for Payments {
             let myself = ctx.address().clone();
             let ctx = self.context.clone();
 
-            let future = async move {
+            let future = in_log_context(log_cx, async move {
                 let stop_tracking = StopTrackingCategory {
                     category: agreement_id.clone(),
                 };
@@ -787,7 +833,7 @@ impl Handler<AgreementClosed> for Payments {
                 myself.do_send(SendInvoice { invoice_id });
 
                 Ok(())
-            }
+            })
             .into_actor(self);
 
             return ActorResponse::r#async(future);
@@ -802,6 +848,8 @@ impl Handler<IssueInvoice> for Payments {
 
     fn handle(&mut self, msg: IssueInvoice, _ctx: &mut Context<Self>) -> Self::Result {
         let agreement_id = msg.costs_summary.agreement_id;
+        let log_cx = LogContext::agreement(&agreement_id);
+        let _log_guard = ya_file_logging::enter(&log_cx);
         let activity_ids = msg.costs_summary.activities;
         let cost_info = msg.costs_summary.cost_summary;
         log::info!(
@@ -822,7 +870,7 @@ impl Handler<IssueInvoice> for Payments {
         };
 
         let provider_ctx = self.context.clone();
-        async move {
+        in_log_context(log_cx, async move {
             log::debug!("Issuing invoice {}.", serde_json::to_string(&invoice)?);
 
             loop {
@@ -838,7 +886,7 @@ impl Handler<IssueInvoice> for Payments {
                     }
                 }
             }
-        }
+        })
         .boxed_local()
     }
 }
@@ -875,6 +923,7 @@ impl Handler<AgreementBroken> for Payments {
     type Result = ActorResponse<Self, Result<(), Error>>;
 
     fn handle(&mut self, msg: AgreementBroken, ctx: &mut Context<Self>) -> Self::Result {
+        let _log_guard = ya_file_logging::enter(&LogContext::agreement(&msg.agreement_id));
         if !self.agreements.contains_key(&msg.agreement_id) {
             log::warn!(
                 "Payments - agreement [{}] does not exist -- not broken.",
@@ -883,6 +932,20 @@ impl Handler<AgreementBroken> for Payments {
             return ActorResponse::reply(Ok(()));
         }
 
+        // Only reasons caused by Requestor affect his reputation. Missed deadlines
+        // were already accounted in `DeadlineElapsed` handler. Unreachable Requestor
+        // isn't penalized, since network problems may be on our side as well.
+        match &msg.reason {
+            BreakReason::DebitNoteRejected(_) => {
+                let requestor_id = self.agreements[&msg.agreement_id].requestor_id;
+                self.context
+                    .config
+                    .reputation
+                    .record(&requestor_id, ReputationEvent::AgreementBroken);
+            }
+            _ => (),
+        }
+
         let address = ctx.address().clone();
         let future = async move {
             let msg = AgreementClosed {
@@ -932,6 +995,11 @@ impl Handler<InvoiceSettled> for Payments {
                         invoice.agreement_id,
                         invoice.amount
                     );
+                    myself
+                        .context
+                        .config
+                        .reputation
+                        .record(&invoice.recipient_id, ReputationEvent::InvoiceSettled);
                     myself.agreements.remove(&invoice.agreement_id);
                     myself
                         .invoices_to_pay
@@ -979,6 +1047,10 @@ impl Handler<DeadlineElapsed> for Payments {
                     );
 
                     agreement.deadline_elapsed = true;
+                    self.context
+                        .config
+                        .reputation
+                        .record(&agreement.requestor_id, ReputationEvent::DebitNoteOverdue);
                     BreakReason::DebitNotesDeadline(timeout)
                 }
                 None => return,
@@ -992,6 +1064,10 @@ impl Handler<DeadlineElapsed> for Payments {
                         msg.id,
                         msg.category,
                     );
+                    self.context
+                        .config
+                        .reputation
+                        .record(&agreement.requestor_id, ReputationEvent::PaymentOverdue);
                     BreakReason::DebitNoteNotPaid(timeout)
                 }
                 None => return,
@@ -2,6 +2,7 @@ use actix::prelude::*;
 use anyhow::{anyhow, Error};
 use futures::{FutureExt, StreamExt, TryFutureExt};
 
+use std::collections::HashMap;
 use std::convert::TryFrom;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
@@ -11,18 +12,31 @@ use tokio_stream::wrappers::WatchStream;
 use ya_agreement_utils::agreement::TypedArrayPointer;
 use ya_agreement_utils::*;
 use ya_client::cli::ProviderApi;
+use ya_client::web::WebClient;
 use ya_core_model::payment::local::NetworkName;
-use ya_file_logging::{start_logger, LoggerHandle};
+use ya_file_logging::{start_logger, LogContext, LoggerHandle};
 use ya_manifest_utils::Keystore;
+use ya_utils_actix::actix_signal::Subscribe;
 
 use crate::config::globals::GlobalsState;
+use crate::config::reputation::ReputationManager;
+use crate::config::rules::RulesManager;
 use crate::dir::clean_provider_dir;
 use crate::events::Event;
 use crate::execution::{
     GetExeUnit, GetOfferTemplates, Shutdown as ShutdownExecution, TaskRunner, UpdateActivity,
 };
 use crate::hardware;
-use crate::market::provider_market::{OfferKind, Shutdown as MarketShutdown, Unsubscribe};
+use crate::identity::IdentityApi;
+use crate::market::amendment::AmendmentApi;
+use crate::market::metrics::MetricsApi;
+use crate::market::negotiator::builtin::resources::{
+    MIN_CPU_THREADS_PROPERTY, MIN_MEM_GIB_PROPERTY, MIN_STORAGE_GIB_PROPERTY,
+};
+use crate::market::provider_market::{
+    CapacityChanged, OfferKind, Shutdown as MarketShutdown, Unsubscribe,
+};
+use crate::market::repricing::{AcceptanceStats, PricingState, RepricingConfig};
 use crate::market::{CreateOffer, Preset, PresetManager, ProviderMarket};
 use crate::payments::{AccountView, LinearPricingOffer, Payments, PricingOffer};
 use crate::startup_config::{
@@ -75,8 +89,21 @@ pub struct ProviderAgent {
     log_handler: LoggerHandle,
     networks: Vec<NetworkName>,
     keystore_monitor: FileMonitor,
+    reputation: ReputationManager,
+    rules: RulesManager,
+    pricing: PricingState,
+    pricing_file: PathBuf,
+    repricing: RepricingConfig,
+    acceptance_stats: AcceptanceStats,
+    max_agreements: u32,
+    /// Resources advertised in the latest Offer of each preset.
+    advertised: HashMap<String, Option<hardware::Resources>>,
+    offers_update: Option<SpawnHandle>,
 }
 
+/// Delay of Offers update, which lets a burst of Agreements settle first.
+const OFFERS_UPDATE_DELAY: Duration = Duration::from_secs(5);
+
 impl ProviderAgent {
     pub async fn new(mut args: RunConfig, config: ProviderConfig) -> anyhow::Result<ProviderAgent> {
         let data_dir = config.data_dir.get_or_create()?;
@@ -109,6 +136,17 @@ impl ProviderAgent {
         }
 
         let api = ProviderApi::try_from(&args.api)?;
+        let amendment_api: AmendmentApi =
+            WebClient::with_token(&args.api.app_key).interface_at(args.api.market_url.clone())?;
+        let metrics_api: MetricsApi = WebClient::with_token(&args.api.app_key).interface()?;
+
+        let identity_api: IdentityApi = WebClient::with_token(&args.api.app_key).interface()?;
+        let me = identity_api.me().await?;
+        log::info!("Provider identity: {}", me.identity);
+        ya_file_logging::set_global(LogContext {
+            node_id: Some(me.identity.to_string()),
+            ..Default::default()
+        });
 
         log::info!("Loading payment accounts...");
         let accounts: Vec<AccountView> = api
@@ -142,6 +180,13 @@ impl ProviderAgent {
             }
         };
 
+        let mut reputation = ReputationManager::load_or_create(&config.reputation_file)?;
+        reputation.spawn_monitor(&config.reputation_file)?;
+        log::info!(
+            "Requestor reputation loaded from {}",
+            config.reputation_file.display()
+        );
+
         args.market.session_id = format!("{}-{}", name, std::process::id());
         args.runner.session_id = args.market.session_id.clone();
         args.payment.session_id = args.market.session_id.clone();
@@ -150,6 +195,16 @@ impl ProviderAgent {
             .composite_config
             .policy_config
             .trusted_keys = Some(keystore.clone());
+        args.market
+            .negotiator_config
+            .composite_config
+            .reputation_config
+            .store = reputation.store();
+        args.payment.reputation = reputation.store();
+
+        let mut rules = RulesManager::load_or_create(&config.rules_file)?;
+        rules.spawn_monitor(&config.rules_file)?;
+        args.market.negotiator_config.composite_config.rules = rules.store();
 
         let networks = args.node.account.networks.clone();
         for n in networks.iter() {
@@ -169,9 +224,26 @@ impl ProviderAgent {
         presets.spawn_monitor(&config.presets_file)?;
         let mut hardware = hardware::Manager::try_new(&config)?;
         hardware.spawn_monitor(&config.hardware_file)?;
+        args.market.negotiator_config.composite_config.hardware = hardware.store();
         let keystore_monitor = spawn_keystore_monitor(&config.trusted_keys_file, keystore)?;
 
-        let market = ProviderMarket::new(api.market, args.market).start();
+        args.market.repricing.validate()?;
+        let repricing = args.market.repricing.clone();
+        let acceptance_stats = args.market.acceptance_stats.clone();
+        let max_agreements = args
+            .market
+            .negotiator_config
+            .composite_config
+            .limit_agreements_config
+            .max_simultaneous_agreements;
+        let pricing = match repricing.dynamic_pricing {
+            true => PricingState::load(&config.pricing_file)?,
+            false => PricingState::default(),
+        };
+        pricing.save(&config.pricing_file)?;
+
+        let market =
+            ProviderMarket::new(api.market, amendment_api, metrics_api, args.market).start();
         let payments = Payments::new(api.activity.clone(), api.payment, args.payment).start();
         let runner = TaskRunner::new(api.activity, args.runner, registry, data_dir)?.start();
         let task_manager =
@@ -188,13 +260,22 @@ impl ProviderAgent {
             log_handler,
             networks,
             keystore_monitor,
+            reputation,
+            rules,
+            pricing,
+            pricing_file: config.pricing_file,
+            repricing,
+            acceptance_stats,
+            max_agreements,
+            advertised: HashMap::new(),
+            offers_update: None,
         })
     }
 
     async fn create_offers(
         presets: Vec<Preset>,
         node_info: NodeInfo,
-        inf_node_info: InfNodeInfo,
+        remaining: hardware::Resources,
         runner: Addr<TaskRunner>,
         market: Addr<ProviderMarket>,
         accounts: Vec<AccountView>,
@@ -202,13 +283,28 @@ impl ProviderAgent {
         if presets.is_empty() {
             return Err(anyhow!("No Presets were selected. Can't create offers."));
         }
+        if remaining.depleted() {
+            log::info!(
+                "All hardware resources are reserved. Offers will be created after Agreements terminate."
+            );
+            return Ok(());
+        }
 
         let preset_names = presets.iter().map(|p| &p.name).collect::<Vec<_>>();
         log::debug!("Preset names: {:?}", preset_names);
         let offer_templates = runner.send(GetOfferTemplates(presets.clone())).await??;
         let subnet = &node_info.subnet;
+        let min = hardware::Resources::min_reservation();
 
         for preset in presets {
+            let inf_node_info = match advertised(&preset, &remaining) {
+                Ok(resources) => InfNodeInfo::from(resources),
+                Err(e) => {
+                    log::info!("Skipping offer for preset [{}]: {}", preset.name, e);
+                    continue;
+                }
+            };
+
             let pricing_model: Box<dyn PricingOffer> = match preset.pricing_model.as_str() {
                 "linear" => Box::new(LinearPricingOffer::default()),
                 other => return Err(anyhow!("Unsupported pricing model: {}", other)),
@@ -220,6 +316,9 @@ impl ProviderAgent {
 
             let (initial_price, prices) = get_prices(pricing_model.as_ref(), &preset, &offer)?;
             offer.set_property("golem.com.usage.vector", get_usage_vector_value(&prices));
+            offer.set_property(MIN_CPU_THREADS_PROPERTY, min.cpu_threads.into());
+            offer.set_property(MIN_MEM_GIB_PROPERTY, min.mem_gib.into());
+            offer.set_property(MIN_STORAGE_GIB_PROPERTY, min.storage_gib.into());
             offer.add_constraints(Self::build_constraints(subnet.clone())?);
 
             let com_info = pricing_model.build(&accounts, initial_price, prices)?;
@@ -232,8 +331,8 @@ impl ProviderAgent {
                 )
             })?;
 
-            let srv_info = ServiceInfo::new(inf_node_info.clone(), exeunit_desc.build())
-                .support_multi_activity(true);
+            let srv_info =
+                ServiceInfo::new(inf_node_info, exeunit_desc.build()).support_multi_activity(true);
 
             // Create simple offer on market.
             let create_offer_message = CreateOffer {
@@ -251,6 +350,100 @@ impl ProviderAgent {
         Ok(())
     }
 
+    fn reprice(&mut self, ctx: &mut Context<Self>) {
+        let utilization = self
+            .acceptance_stats
+            .utilization(self.max_agreements)
+            .max(self.hardware.utilization());
+        let acceptance_rate = self.acceptance_stats.take_rate();
+        let current = self.pricing.multiplier;
+        let multiplier = self
+            .repricing
+            .next_multiplier(current, utilization, acceptance_rate);
+
+        if (multiplier - current).abs() < f64::EPSILON {
+            log::debug!(
+                "Prices unchanged (factor: {:.3}, utilization: {:.2}, acceptance rate: {:.2})",
+                current,
+                utilization,
+                acceptance_rate
+            );
+            return;
+        }
+
+        log::info!(
+            "Changing prices factor from {:.3} to {:.3} (utilization: {:.2}, acceptance rate: {:.2}). Resubscribing offers...",
+            current,
+            multiplier,
+            utilization,
+            acceptance_rate
+        );
+        self.pricing = PricingState {
+            multiplier,
+            updated: Some(chrono::Utc::now()),
+        };
+        if let Err(e) = self.pricing.save(&self.pricing_file) {
+            log::warn!("Failed to save effective prices: {}", e);
+        }
+
+        let market = self.market.clone();
+        let agent = ctx.address();
+        let future = async move {
+            let _ = market
+                .send(Unsubscribe(OfferKind::Any))
+                .map_err(|e| log::error!("Cannot unsubscribe offers: {}", e))
+                .await;
+            let _ = agent
+                .send(CreateOffers(OfferKind::Any))
+                .map_err(|e| log::error!("Cannot create offers: {}", e))
+                .await;
+        };
+        ctx.spawn(future.into_actor(self));
+    }
+
+    /// Replaces Offers of presets, which advertise resources different
+    /// from currently remaining capacity.
+    fn update_offers(&mut self, ctx: &mut Context<Self>) {
+        let remaining = self.hardware.remaining();
+        let presets = match self.presets.list_matching(&self.presets.active()) {
+            Ok(presets) => presets,
+            Err(e) => {
+                log::warn!("Cannot update offers: {}", e);
+                return;
+            }
+        };
+        let changed = presets
+            .iter()
+            .filter(|preset| {
+                let previous = self.advertised.get(&preset.name).cloned().flatten();
+                previous != advertised(preset, &remaining).ok()
+            })
+            .map(|preset| preset.name.clone())
+            .collect::<Vec<_>>();
+
+        if changed.is_empty() {
+            return;
+        }
+        log::info!(
+            "Hardware capacity changed. Updating offers for presets: {:?}",
+            changed
+        );
+
+        let market = self.market.clone();
+        let agent = ctx.address();
+        let future = async move {
+            let _ = market
+                .send(Unsubscribe(OfferKind::WithPresets(changed.clone())))
+                .map_err(|e| log::error!("Cannot unsubscribe offers: {}", e))
+                .await;
+            let _ = agent
+                .send(CreateOffers(OfferKind::WithPresets(changed)))
+                .map_err(|e| log::error!("Cannot create offers: {}", e))
+                .await;
+        };
+        ctx.spawn(future.into_actor(self));
+    }
+
     fn build_constraints(subnet: Option<String>) -> anyhow::Result<String> {
         let mut cnts =
             constraints!["golem.srv.comp.expiration" > chrono::Utc::now().timestamp_millis(),];
@@ -361,6 +554,44 @@ fn get_prices(
     Ok((initial_price, prices))
 }
 
+/// Resources advertised in the Offer of a preset: remaining built-in resources
+/// and user-defined resources allocated for each Agreement.
+fn advertised(
+    preset: &Preset,
+    remaining: &hardware::Resources,
+) -> anyhow::Result<hardware::Resources> {
+    if remaining.depleted() {
+        return Err(anyhow!("not enough hardware resources left"));
+    }
+
+    let mut resources = hardware::Resources {
+        custom: Default::default(),
+        ..remaining.clone()
+    };
+    for (name, amount) in preset.resources.iter() {
+        let capacity = remaining.custom_capacity(name);
+        if capacity < *amount {
+            return Err(anyhow!(
+                "resource '{}' required: {}, available: {}",
+                name,
+                amount,
+                capacity
+            ));
+        }
+        let unit = remaining
+            .custom
+            .get(name)
+            .map(|resource| resource.unit.clone())
+            .unwrap_or_default();
+        let resource = hardware::CustomResource {
+            capacity: *amount,
+            unit,
+        };
+        resources.custom.insert(name.clone(), resource);
+    }
+    Ok(resources)
+}
+
 fn get_usage_vector_value(prices: &[(String, f64)]) -> serde_json::Value {
     let vec = prices
         .iter()
@@ -479,9 +710,19 @@ impl Handler<Initialize> for ProviderAgent {
             .await;
         });
 
+        if self.repricing.dynamic_pricing {
+            ctx.run_interval(self.repricing.repricing_interval, |agent, ctx| {
+                agent.reprice(ctx)
+            });
+        }
+
         let agent = ctx.address();
+        let market = self.market.clone();
         let task_manager = self.task_manager.clone();
         async move {
+            market
+                .send(Subscribe::<CapacityChanged>(agent.clone().recipient()))
+                .await?;
             task_manager.send(InitializeTaskManager {}).await??;
             agent.send(CreateOffers(OfferKind::Any)).await??;
             Ok(())
@@ -498,6 +739,8 @@ impl Handler<Shutdown> for ProviderAgent {
         let runner = self.runner.clone();
         let log_handler = self.log_handler.clone();
         self.keystore_monitor.stop();
+        self.reputation.stop();
+        self.rules.stop();
 
         async move {
             market.send(MarketShutdown).await??;
@@ -521,7 +764,7 @@ impl Handler<CreateOffers> for ProviderAgent {
             Ok(acc) => acc,
             Err(e) => return Box::pin(async { Err(e) }),
         };
-        let inf_node_info = InfNodeInfo::from(self.hardware.capped());
+        let remaining = self.hardware.remaining();
         let preset_names = match msg.0 {
             OfferKind::Any => self.presets.active(),
             OfferKind::WithPresets(names) => names,
@@ -531,14 +774,41 @@ impl Handler<CreateOffers> for ProviderAgent {
             }
         };
 
-        let presets = self.presets.list_matching(&preset_names);
+        if let Ok(presets) = self.presets.list_matching(&preset_names) {
+            for preset in presets.iter() {
+                let resources = advertised(preset, &remaining).ok();
+                self.advertised.insert(preset.name.clone(), resources);
+            }
+        }
+
+        let pricing = self.pricing.clone();
+        let presets = self
+            .presets
+            .list_matching(&preset_names)
+            .map(|presets| presets.iter().map(|p| pricing.apply(p)).collect::<Vec<_>>());
         async move {
-            Self::create_offers(presets?, node_info, inf_node_info, runner, market, accounts).await
+            Self::create_offers(presets?, node_info, remaining, runner, market, accounts).await
         }
         .boxed_local()
     }
 }
 
+impl Handler<CapacityChanged> for ProviderAgent {
+    type Result = Result<(), Error>;
+
+    fn handle(&mut self, _: CapacityChanged, ctx: &mut Context<Self>) -> Self::Result {
+        if let Some(handle) = self.offers_update.take() {
+            ctx.cancel_future(handle);
+        }
+        let handle = ctx.run_later(OFFERS_UPDATE_DELAY, |agent, ctx| {
+            agent.offers_update = None;
+            agent.update_offers(ctx);
+        });
+        self.offers_update = Some(handle);
+        Ok(())
+    }
+}
+
 #[derive(Message)]
 #[rtype(result = "Result<(), Error>")]
 pub struct Initialize;
@@ -550,3 +820,45 @@ pub struct Shutdown;
 #[derive(Message)]
 #[rtype(result = "Result<(), Error>")]
 struct CreateOffers(pub OfferKind);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resources(cpu_threads: i32, gpus: i64) -> hardware::Resources {
+        let mut res = hardware::Resources {
+            cpu_threads,
+            mem_gib: 8.,
+            storage_gib: 64.,
+            custom: Default::default(),
+        };
+        res.custom.insert(
+            "gpu".into(),
+            hardware::CustomResource {
+                capacity: gpus,
+                unit: "card".into(),
+            },
+        );
+        res
+    }
+
+    #[test]
+    fn advertised_resources() {
+        let mut preset = Preset::default();
+        preset.resources.insert("gpu".into(), 1);
+
+        let offered = advertised(&preset, &resources(4, 2)).unwrap();
+        assert_eq!(offered.cpu_threads, 4);
+        assert_eq!(offered.custom_capacity("gpu"), 1);
+        assert_eq!(offered.custom["gpu"].unit, "card");
+
+        // Reservation of other Agreements changes Offer of every preset.
+        let plain = Preset::default();
+        assert_ne!(
+            advertised(&plain, &resources(4, 2)).ok(),
+            advertised(&plain, &resources(3, 2)).ok()
+        );
+        assert!(advertised(&preset, &resources(4, 0)).is_err());
+        assert!(advertised(&preset, &resources(0, 2)).is_err());
+    }
+}
@@ -21,9 +21,14 @@ use crate::cli::exe_unit::ExeUnitsConfig;
 use crate::cli::keystore::KeystoreConfig;
 pub use crate::cli::preset::PresetsConfig;
 use crate::cli::profile::ProfileConfig;
+use crate::cli::reputation::ReputationConfig;
+use crate::cli::rules::RulesConfig;
 pub(crate) use crate::config::globals::GLOBALS_JSON;
+pub(crate) use crate::config::reputation::REPUTATION_JSON;
+pub(crate) use crate::config::rules::RULES_JSON;
 use crate::execution::{ExeUnitsRegistry, TaskRunnerConfig};
 use crate::market::config::MarketConfig;
+pub(crate) use crate::market::repricing::PRICING_JSON;
 use crate::payments::PaymentsConfig;
 use crate::tasks::config::TaskConfig;
 
@@ -73,6 +78,12 @@ pub struct ProviderConfig {
     pub hardware_file: PathBuf,
     #[structopt(skip = TRUSTED_KEYS_FILE)]
     pub trusted_keys_file: PathBuf,
+    #[structopt(skip = REPUTATION_JSON)]
+    pub reputation_file: PathBuf,
+    #[structopt(skip = RULES_JSON)]
+    pub rules_file: PathBuf,
+    #[structopt(skip = PRICING_JSON)]
+    pub pricing_file: PathBuf,
     /// Max number of available CPU cores
     #[structopt(
         long,
@@ -165,6 +176,9 @@ pub struct PresetNoInteractive {
     pub pricing: Option<String>,
     #[structopt(long, parse(try_from_str = parse_key_val))]
     pub price: Vec<(String, f64)>,
+    /// Amount of user-defined hardware resource allocated for each Agreement
+    #[structopt(long, parse(try_from_str = parse_key_val))]
+    pub resource: Vec<(String, i64)>,
 }
 
 #[derive(StructOpt, Clone, Debug)]
@@ -204,6 +218,10 @@ pub enum Commands {
     ExeUnit(ExeUnitsConfig),
     /// Manage trusted keys
     Keystore(KeystoreConfig),
+    /// Inspect and reset requestors reputation
+    Reputation(ReputationConfig),
+    /// Manage requestors allowed to use this node
+    Rules(RulesConfig),
     /// Clean up disk space
     Clean(CleanConfig),
 }
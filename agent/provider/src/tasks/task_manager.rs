@@ -2,10 +2,11 @@
 
 use actix::prelude::*;
 use anyhow::{anyhow, bail, Error, Result};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use futures::future::TryFutureExt;
 use std::collections::HashMap;
 
+use ya_file_logging::{in_log_context, LogContext};
 use ya_std_utils::LogErr;
 use ya_utils_actix::actix_handler::ResultTypeGetter;
 use ya_utils_actix::actix_signal::Subscribe;
@@ -14,7 +15,7 @@ use ya_utils_actix::forward_actix_handler;
 use super::task_info::TaskInfo;
 use super::task_state::{AgreementState, TasksStates};
 use crate::execution::{ActivityDestroyed, CreateActivity, TaskRunner, TerminateActivity};
-use crate::market::provider_market::{NewAgreement, ProviderMarket};
+use crate::market::provider_market::{AgreementAmended, NewAgreement, ProviderMarket};
 use crate::market::termination_reason::BreakReason;
 use crate::payments::Payments;
 use crate::tasks::config::TaskConfig;
@@ -168,19 +169,60 @@ impl TaskManager {
             );
         }
 
-        // Schedule agreement termination after expiration time.
+        self.schedule_agreement_expiration(agreement_id, expiration, ctx)?;
+        self.schedule_idle_expiration(ScheduleIdleExpiration(msg.0), ctx)
+    }
+
+    /// Schedules agreement termination after expiration time.
+    fn schedule_agreement_expiration(
+        &mut self,
+        agreement_id: String,
+        expiration: DateTime<Utc>,
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
         let duration = (expiration - Utc::now()).to_std()?;
-        let agr_id = agreement_id;
         ctx.run_later(duration, move |myself, ctx| {
-            if !myself.tasks.is_agreement_finalized(&agr_id) {
+            // Agreement could be amended in the meantime. In this case
+            // termination is scheduled again with new expiration.
+            let amended = myself
+                .tasks_props
+                .get(&agreement_id)
+                .map(|props| props.expiration != expiration)
+                .unwrap_or(false);
+
+            if !amended && !myself.tasks.is_agreement_finalized(&agreement_id) {
                 ctx.address().do_send(BreakAgreement {
-                    agreement_id: agr_id,
+                    agreement_id,
                     reason: BreakReason::Expired(expiration),
                 });
             }
         });
+        Ok(())
+    }
 
-        self.schedule_idle_expiration(ScheduleIdleExpiration(msg.0), ctx)
+    fn on_agreement_amended(
+        &mut self,
+        msg: AgreementAmended,
+        ctx: &mut Context<Self>,
+    ) -> Result<()> {
+        if self.tasks.is_agreement_finalized(&msg.agreement_id) {
+            return Ok(());
+        }
+
+        let expiration = msg.amendment.valid_to;
+        let props = self
+            .tasks_props
+            .get_mut(&msg.agreement_id)
+            .ok_or_else(|| anyhow!("Amended agreement [{}] not found.", msg.agreement_id))?;
+        props.expiration = expiration;
+
+        log::info!(
+            "Rescheduling expiration of agreement [{}] to {}.",
+            msg.agreement_id,
+            expiration
+        );
+        self.payments.do_send(msg.clone());
+        self.schedule_agreement_expiration(msg.agreement_id, expiration, ctx)
     }
 
     fn schedule_idle_expiration(
@@ -271,6 +313,7 @@ forward_actix_handler!(
     ScheduleIdleExpiration,
     schedule_idle_expiration
 );
+forward_actix_handler!(TaskManager, AgreementAmended, on_agreement_amended);
 forward_actix_handler!(TaskManager, StartUpdateState, start_update_agreement_state);
 forward_actix_handler!(
     TaskManager,
@@ -293,6 +336,10 @@ impl Handler<InitializeTaskManager> for TaskManager {
             let msg = Subscribe::<CloseAgreement>(actx.myself.clone().recipient());
             actx.market.send(msg).await?;
 
+            // Listen to Agreement expiration changes.
+            let msg = Subscribe::<AgreementAmended>(actx.myself.clone().recipient());
+            actx.market.send(msg).await?;
+
             // Listen to BreakAgreement signals emitted by Payments
             let msg = Subscribe::<BreakAgreement>(actx.myself.clone().recipient());
             actx.payments.send(msg).await?;
@@ -318,6 +365,15 @@ impl Handler<NewAgreement> for TaskManager {
     type Result = ActorResponse<Self, Result<(), Error>>;
 
     fn handle(&mut self, msg: NewAgreement, ctx: &mut Context<Self>) -> Self::Result {
+        let log_cx = LogContext {
+            node_id: msg
+                .agreement
+                .pointer_typed::<String>("/offer/providerId")
+                .ok(),
+            ..LogContext::agreement(&msg.agreement.agreement_id)
+        };
+        let _log_guard = ya_file_logging::enter(&log_cx);
+
         // Add new agreement with it's state.
         let task_info = match self.add_new_agreement(&msg).log_err() {
             Err(e) => return ActorResponse::reply(Err(e)),
@@ -334,7 +390,7 @@ impl Handler<NewAgreement> for TaskManager {
         let actx = self.async_context(ctx);
         let agreement_id = task_info.agreement_id.clone();
 
-        let future = async move {
+        let future = in_log_context(log_cx, async move {
             actx.myself.send(ScheduleExpiration(task_info)).await??;
 
             actx.runner.send(msg.clone()).await??;
@@ -346,7 +402,7 @@ impl Handler<NewAgreement> for TaskManager {
                 AgreementState::Initialized,
             )
             .await
-        }
+        })
         .into_actor(self)
         .map(
             move |result: Result<(), anyhow::Error>, _, context: &mut Context<Self>| {
@@ -371,6 +427,8 @@ impl Handler<CreateActivity> for TaskManager {
     type Result = ActorResponse<Self, Result<(), Error>>;
 
     fn handle(&mut self, msg: CreateActivity, ctx: &mut Context<Self>) -> Self::Result {
+        let log_cx = LogContext::activity(&msg.agreement_id, &msg.activity_id);
+        let _log_guard = ya_file_logging::enter(&log_cx);
         let actx = self.async_context(ctx);
         let listener = self.tasks.changes_listener(&msg.agreement_id);
 
@@ -378,7 +436,7 @@ impl Handler<CreateActivity> for TaskManager {
         // after Activity will be destroyed.
         self.cancel_handles(ctx, &msg.agreement_id);
 
-        let future = async move {
+        let future = in_log_context(log_cx.clone(), async move {
             // ActivityCreated event can come, before Task initialization will be finished.
             // In this case we must wait, because otherwise transition to Computing will fail.
             let mut state = listener?;
@@ -402,9 +460,10 @@ impl Handler<CreateActivity> for TaskManager {
             // Activity will be created by runner here.
             actx.runner.send(msg.clone()).await??;
             Ok(msg)
-        }
+        })
         .into_actor(self)
         .map(move |result: Result<_, Error>, myself, _| {
+            let _log_guard = ya_file_logging::enter(&log_cx);
             // Return, if waiting for transition failed.
             // This indicates, that State was already dropped.
             let msg = result.map_err(|e| anyhow!("Can't change state to Computing. {}", e))?;
@@ -430,6 +489,8 @@ impl Handler<ActivityDestroyed> for TaskManager {
     type Result = ActorResponse<Self, Result<(), Error>>;
 
     fn handle(&mut self, msg: ActivityDestroyed, ctx: &mut Context<Self>) -> Self::Result {
+        let log_cx = LogContext::activity(&msg.agreement_id, &msg.activity_id);
+        let _log_guard = ya_file_logging::enter(&log_cx);
         let agreement_id = msg.agreement_id.clone();
         let actx = self.async_context(ctx);
 
@@ -450,7 +511,7 @@ impl Handler<ActivityDestroyed> for TaskManager {
         // set in Agreement. Otherwise Requestor should terminate.
         let need_close = closing_allowed && close_after_1st_activity;
 
-        let future = async move {
+        let future = in_log_context(log_cx, async move {
             // Forward information to Payments to send last DebitNote in activity.
             // Note: we do this no matter, if we will be able to make transition, because
             // payments must close activities anyway.
@@ -479,7 +540,7 @@ impl Handler<ActivityDestroyed> for TaskManager {
 
             finish_transition(&actx.myself, &agreement_id, AgreementState::Idle).await?;
             Ok(())
-        };
+        });
         ActorResponse::r#async(future.into_actor(self))
     }
 }
@@ -488,6 +549,8 @@ impl Handler<BreakAgreement> for TaskManager {
     type Result = ActorResponse<Self, Result<(), Error>>;
 
     fn handle(&mut self, msg: BreakAgreement, ctx: &mut Context<Self>) -> Self::Result {
+        let log_cx = LogContext::agreement(&msg.agreement_id);
+        let _log_guard = ya_file_logging::enter(&log_cx);
         let actx = self.async_context(ctx);
 
         self.cancel_handles(ctx, &msg.agreement_id);
@@ -525,6 +588,7 @@ impl Handler<BreakAgreement> for TaskManager {
             result
         }
         .map_err(move |error: Error| log::error!("Can't break agreement. Error: {}", error));
+        let future = in_log_context(log_cx, future);
 
         ActorResponse::r#async(future.into_actor(self).map(|_, _, _| Ok(())))
     }
@@ -534,6 +598,8 @@ impl Handler<CloseAgreement> for TaskManager {
     type Result = ActorResponse<Self, Result<(), Error>>;
 
     fn handle(&mut self, msg: CloseAgreement, ctx: &mut Context<Self>) -> Self::Result {
+        let log_cx = LogContext::agreement(&msg.agreement_id);
+        let _log_guard = ya_file_logging::enter(&log_cx);
         let actx = self.async_context(ctx);
 
         self.cancel_handles(ctx, &msg.agreement_id);
@@ -562,6 +628,7 @@ impl Handler<CloseAgreement> for TaskManager {
             Ok(())
         }
         .map_err(move |error: Error| log::error!("Can't close agreement. Error: {}", error));
+        let future = in_log_context(log_cx, future);
 
         ActorResponse::r#async(future.into_actor(self).map(|_, _, _| Ok(())))
     }
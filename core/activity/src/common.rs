@@ -20,6 +20,7 @@ use ya_service_bus::{timeout::IntoDuration, typed as bus, RpcEndpoint, RpcMessag
 pub type RpcMessageResult<T> = Result<<T as RpcMessage>::Item, <T as RpcMessage>::Error>;
 pub const DEFAULT_REQUEST_TIMEOUT: f32 = 5.0;
 const DEFAULT_TIMEOUT_MARGIN: f32 = 1.0;
+pub const DRAINING_ERROR: &str = "Node is shutting down and doesn't accept new Activities";
 
 #[derive(Deserialize)]
 pub struct PathActivity {
@@ -112,6 +112,30 @@ impl<'c> ActivityStateDao<'c> {
         .await
     }
 
+    /// Activities in transition to Terminated state are still counted.
+    pub async fn count_active(&self, agreement_ids: Vec<String>) -> Result<u64> {
+        use schema::activity::dsl;
+
+        readonly_transaction(self.pool, move |conn| {
+            let states: Vec<String> = dsl::activity
+                .inner_join(schema::activity_state::table)
+                .filter(dsl::agreement_id.eq_any(agreement_ids))
+                .select(schema::activity_state::name)
+                .load(conn)
+                .map_err(DaoError::from)?;
+
+            let mut active = 0;
+            for state in states {
+                let pair: StatePair = serde_json::from_str(&state)?;
+                if pair.0 != State::Terminated {
+                    active += 1;
+                }
+            }
+            Ok(active)
+        })
+        .await
+    }
+
     pub async fn get_state_wait(
         &self,
         activity_id: &str,
@@ -167,3 +191,62 @@ impl<'c> ActivityStateDao<'c> {
         .await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::ActivityDao;
+    use ya_persistence::executor::DbExecutor;
+
+    async fn set_state(db: &DbExecutor, activity_id: &str, state: StatePair) {
+        let state = ActivityState {
+            state,
+            reason: None,
+            error_message: None,
+        };
+        db.as_dao::<ActivityStateDao>()
+            .set(activity_id, state)
+            .await
+            .unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn test_count_active() {
+        let db = DbExecutor::in_memory("count_active").unwrap();
+        db.apply_migration(crate::db::migrations::run_with_output)
+            .unwrap();
+        let activities = [
+            ("ready", "agreement-1"),
+            ("terminating", "agreement-1"),
+            ("terminated", "agreement-1"),
+            ("new", "agreement-2"),
+            ("other", "agreement-3"),
+        ];
+        for (activity_id, agreement_id) in activities.iter() {
+            db.as_dao::<ActivityDao>()
+                .create(activity_id, agreement_id)
+                .await
+                .unwrap();
+        }
+        set_state(&db, "ready", StatePair(State::Ready, None)).await;
+        set_state(
+            &db,
+            "terminating",
+            StatePair(State::Ready, Some(State::Terminated)),
+        )
+        .await;
+        set_state(&db, "terminated", StatePair(State::Terminated, None)).await;
+
+        let dao = db.as_dao::<ActivityStateDao>();
+        let ids = |ids: &[&str]| ids.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(dao.count_active(ids(&["agreement-1"])).await.unwrap(), 2);
+        assert_eq!(
+            dao.count_active(ids(&["agreement-1", "agreement-2"]))
+                .await
+                .unwrap(),
+            3
+        );
+        assert_eq!(dao.count_active(ids(&["unknown"])).await.unwrap(), 0);
+        assert_eq!(dao.count_active(vec![]).await.unwrap(), 0);
+    }
+}
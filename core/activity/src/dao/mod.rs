@@ -8,7 +8,7 @@ pub use activity::ActivityDao;
 pub use activity_credentials::ActivityCredentialsDao;
 pub use activity_state::ActivityStateDao;
 pub use activity_usage::ActivityUsageDao;
-pub use event::{Event, EventDao};
+pub use event::{Event, EventDao, MAX_EVENTS};
 use thiserror::Error;
 
 type Result<T> = std::result::Result<T, DaoError>;
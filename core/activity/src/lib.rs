@@ -18,6 +18,7 @@ mod provider;
 mod requestor;
 pub mod service;
 mod tracker;
+pub mod webhook;
 
 pub type Result<T> = std::result::Result<T, error::Error>;
 pub use self::tracker::TrackerRef;
@@ -18,7 +18,7 @@ use ya_service_bus::{timeout::*, typed::ServiceBinder};
 use crate::common::{
     authorize_activity_initiator, authorize_agreement_initiator, generate_id,
     get_activity_agreement, get_agreement, get_persisted_state, get_persisted_usage,
-    set_persisted_state, RpcMessageResult,
+    set_persisted_state, RpcMessageResult, DRAINING_ERROR,
 };
 use crate::dao::*;
 use crate::db::models::ActivityEventType;
@@ -83,6 +83,9 @@ async fn create_activity_gsb(
     caller: String,
     msg: activity::Create,
 ) -> RpcMessageResult<activity::Create> {
+    if ya_service_api::drain::is_draining() {
+        return Err(RpcMessageError::Service(DRAINING_ERROR.to_string()));
+    }
     authorize_agreement_initiator(caller, &msg.agreement_id, Role::Provider).await?;
 
     let activity_id = generate_id();
@@ -400,7 +403,8 @@ mod local {
             .bind_with_processor(set_activity_state_gsb)
             .bind_with_processor(set_activity_usage_gsb)
             .bind(get_agreement_id_gsb)
-            .bind(activity_status);
+            .bind(activity_status)
+            .bind(count_active_gsb);
     }
 
     async fn activity_status(
@@ -427,6 +431,18 @@ mod local {
         })
     }
 
+    async fn count_active_gsb(
+        db: DbExecutor,
+        _caller: String,
+        msg: activity::local::CountActive,
+    ) -> RpcMessageResult<activity::local::CountActive> {
+        Ok(db
+            .as_dao::<ActivityStateDao>()
+            .count_active(msg.agreement_ids)
+            .await
+            .map_err(Error::from)?)
+    }
+
     /// Pass activity state (which may include error details).
     /// Called by ExeUnits.
     ///
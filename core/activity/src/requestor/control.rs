@@ -18,6 +18,7 @@ use ya_net::{self as net, RemoteEndpoint};
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_web::middleware::Identity;
 use ya_service_bus::{timeout::IntoTimeoutFuture, RpcEndpoint};
+use ya_utils_telemetry as telemetry;
 
 use crate::common::*;
 use crate::dao::ActivityDao;
@@ -78,6 +79,10 @@ async fn create_activity(
     body: web::Json<CreateActivityJson>,
     id: Identity,
 ) -> impl Responder {
+    if ya_service_api::drain::is_draining() {
+        return Err(Error::Service(DRAINING_ERROR.to_string()));
+    }
+
     let agreement_id = body.agreement_id();
     authorize_agreement_initiator(id.identity, agreement_id, Role::Requestor).await?;
 
@@ -184,6 +189,7 @@ async fn exec(
         batch_id: batch_id.clone(),
         exe_script: commands,
         timeout: query.timeout.clone(),
+        trace_context: telemetry::inject(&telemetry::Context::current()),
     };
 
     ya_net::from(id.identity)
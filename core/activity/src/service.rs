@@ -1,7 +1,7 @@
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_interfaces::{Provider, Service};
 
-use crate::{api, db::migrations, provider, TrackerRef};
+use crate::{api, db::migrations, provider, webhook, TrackerRef};
 
 pub struct Activity;
 
@@ -17,6 +17,7 @@ impl Activity {
         let tracker_ref: TrackerRef = ctx.component();
         db.apply_migration(migrations::run_with_output)?;
         provider::service::bind_gsb(&db, tracker_ref);
+        webhook::register_sources(&db);
         Ok(())
     }
 
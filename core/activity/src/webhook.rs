@@ -0,0 +1,65 @@
+//! Provider's Activity events delivered by webhooks.
+//! Cursor is the timestamp of last delivered event, the same way as
+//! `afterTimestamp` of REST API.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::time::Duration;
+
+use ya_client_model::NodeId;
+use ya_persistence::executor::DbExecutor;
+use ya_service_api_web::webhook::{register_source, EventBatch, EventSource};
+
+use crate::dao::{EventDao, MAX_EVENTS};
+
+pub const ACTIVITY_EVENTS_SOURCE: &str = "activities";
+
+pub fn register_sources(db: &DbExecutor) {
+    register_source(ACTIVITY_EVENTS_SOURCE, ActivityEvents { db: db.clone() });
+}
+
+struct ActivityEvents {
+    db: DbExecutor,
+}
+
+#[async_trait::async_trait(?Send)]
+impl EventSource for ActivityEvents {
+    async fn poll(
+        &self,
+        owner: NodeId,
+        subject: Option<&str>,
+        _event_types: &[String],
+        cursor: Option<String>,
+        timeout: Duration,
+    ) -> anyhow::Result<EventBatch> {
+        // New webhooks get only events created after their registration.
+        let after_timestamp = match cursor {
+            Some(cursor) => DateTime::parse_from_rfc3339(&cursor)?.with_timezone(&Utc),
+            None => Utc::now(),
+        };
+        let app_session_id = subject.map(ToString::to_string);
+
+        let events = tokio::time::timeout(
+            timeout,
+            self.db.as_dao::<EventDao>().get_events_wait(
+                &owner,
+                &app_session_id,
+                after_timestamp,
+                Some(MAX_EVENTS as u32),
+            ),
+        )
+        .await
+        .unwrap_or_else(|_| Ok(vec![]))?;
+
+        let last_timestamp = events
+            .last()
+            .map(|event| event.event_date)
+            .unwrap_or(after_timestamp);
+        Ok(EventBatch {
+            events: events
+                .iter()
+                .map(serde_json::to_value)
+                .collect::<Result<_, _>>()?,
+            cursor: Some(last_timestamp.to_rfc3339_opts(SecondsFormat::Micros, true)),
+        })
+    }
+}
@@ -20,6 +20,7 @@ async fn main() -> anyhow::Result<()> {
         alias: None,
         note: None,
         created_date: Utc::now().naive_utc(),
+        is_external: false,
     };
 
     db.as_dao::<IdentityDao>().create_identity(identity).await?;
@@ -0,0 +1,23 @@
+PRAGMA foreign_keys=off;
+
+CREATE TABLE identity_tmp(
+    identity_id varchar(50) not null primary key,
+    key_file_json text not null,
+    is_default boolean not null default false,
+    is_deleted boolean not null default false,
+    alias varchar(50),
+    note text,
+    "created_date" DATETIME NOT NULL,
+
+    CONSTRAINT id_unk1 UNIQUE (identity_id),
+    CONSTRAINT id_unk2 UNIQUE (alias)
+);
+
+INSERT INTO identity_tmp(identity_id, key_file_json, is_default, is_deleted, alias, note, created_date)
+SELECT identity_id, key_file_json, is_default, is_deleted, alias, note, created_date FROM identity;
+
+DROP TABLE identity;
+
+ALTER TABLE identity_tmp RENAME TO identity;
+
+PRAGMA foreign_keys=on;
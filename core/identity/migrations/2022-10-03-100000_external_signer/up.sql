@@ -0,0 +1 @@
+ALTER TABLE identity ADD COLUMN is_external BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,112 @@
+//! Password-encrypted archive of all identities, their aliases and app-keys.
+//!
+//! Key files are stored as they are, so restoring an identity requires also
+//! its own password, if it was set.
+
+use anyhow::Context;
+use ethsign::keyfile::Crypto;
+use ethsign::{KeyFile, Protected};
+use serde::{Deserialize, Serialize};
+
+use ya_client_model::NodeId;
+use ya_core_model::appkey::AppKey;
+
+use crate::id_key::KEY_ITERATIONS;
+
+const BACKUP_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Backup {
+    pub identities: Vec<IdentityBackup>,
+    pub app_keys: Vec<AppKey>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityBackup {
+    pub alias: Option<String>,
+    pub is_default: bool,
+    /// Empty for external identities.
+    pub key_file: String,
+    /// Address of identity held by external signer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external: Option<NodeId>,
+}
+
+impl IdentityBackup {
+    pub fn node_id(&self) -> anyhow::Result<NodeId> {
+        if let Some(node_id) = self.external {
+            return Ok(node_id);
+        }
+        let key_file: KeyFile =
+            serde_json::from_str(&self.key_file).context("invalid key file in backup")?;
+        match key_file.address {
+            Some(address) => Ok(NodeId::from(address.0.as_slice())),
+            None => anyhow::bail!("missing address of key file in backup"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Archive {
+    version: u32,
+    crypto: Crypto,
+}
+
+impl Backup {
+    pub fn encrypt(&self, password: &Protected) -> anyhow::Result<String> {
+        let plain = serde_json::to_vec(self)?;
+        let crypto = Crypto::encrypt(&plain, password, KEY_ITERATIONS)
+            .map_err(|e| anyhow::anyhow!("failed to encrypt backup: {}", e))?;
+
+        Ok(serde_json::to_string_pretty(&Archive {
+            version: BACKUP_VERSION,
+            crypto,
+        })?)
+    }
+
+    pub fn decrypt(archive: &str, password: &Protected) -> anyhow::Result<Self> {
+        let archive: Archive = serde_json::from_str(archive).context("invalid backup file")?;
+        if archive.version != BACKUP_VERSION {
+            anyhow::bail!("unsupported backup version {}", archive.version)
+        }
+        let plain = match archive.crypto.decrypt(password) {
+            Ok(plain) => plain,
+            Err(ethsign::Error::InvalidPassword) => anyhow::bail!("invalid backup password"),
+            Err(e) => anyhow::bail!("failed to decrypt backup: {}", e),
+        };
+
+        Ok(serde_json::from_slice(&plain).context("invalid backup content")?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_backup_roundtrip() -> anyhow::Result<()> {
+        let backup = Backup {
+            identities: vec![IdentityBackup {
+                alias: Some("requestor".to_string()),
+                is_default: true,
+                key_file: crate::id_key::generate_new_keyfile(Protected::new(""))?,
+                external: None,
+            }],
+            app_keys: vec![],
+        };
+
+        let archive = backup.encrypt(&Protected::new("secret"))?;
+        assert!(Backup::decrypt(&archive, &Protected::new("wrong")).is_err());
+
+        let restored = Backup::decrypt(&archive, &Protected::new("secret"))?;
+        assert_eq!(restored.identities.len(), 1);
+        assert_eq!(restored.identities[0].alias, backup.identities[0].alias);
+        assert_eq!(
+            restored.identities[0].key_file,
+            backup.identities[0].key_file
+        );
+        Ok(())
+    }
+}
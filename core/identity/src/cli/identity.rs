@@ -10,12 +10,16 @@ use structopt::*;
 use tokio::io::{AsyncReadExt, BufReader};
 
 use ya_client_model::NodeId;
+use ya_core_model::appkey;
 use ya_core_model::identity::{self};
 use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
 use ya_service_bus::typed as bus;
 use ya_service_bus::RpcEndpoint;
 
+use crate::backup::{Backup, IdentityBackup};
+
 const FILE_CHUNK_SIZE: usize = 40960;
+const APP_KEYS_PAGE_SIZE: u32 = 100;
 
 #[derive(Debug, Clone)]
 pub enum NodeOrAlias {
@@ -130,6 +134,22 @@ pub enum IdentityCommand {
         #[structopt(long = "from-keystore")]
         from_keystore: Option<PathBuf>,
 
+        /// Derive key from BIP-39 mnemonic, read from terminal
+        #[structopt(long, conflicts_with = "from-keystore")]
+        from_mnemonic: bool,
+
+        /// Address of key held by external signer (see YAGNA_SIGNER_URL)
+        #[structopt(long, conflicts_with_all = &["from-keystore", "from-mnemonic"])]
+        external: Option<NodeId>,
+
+        /// BIP-32 derivation path of the key [default: m/44'/60'/0'/0/0]
+        #[structopt(long, requires = "from-mnemonic")]
+        derivation_path: Option<String>,
+
+        /// Ask for BIP-39 passphrase protecting the mnemonic
+        #[structopt(long, requires = "from-mnemonic")]
+        mnemonic_passphrase: bool,
+
         /// password for keystore
         #[structopt(long = "no-password")]
         no_password: bool,
@@ -160,6 +180,22 @@ pub enum IdentityCommand {
         #[structopt(long = "file-path")]
         file_path: Option<PathBuf>,
     },
+
+    /// Writes all identities, their aliases and app-keys to password encrypted file
+    Backup {
+        /// Backup file path
+        file_path: PathBuf,
+    },
+
+    /// Restores identities, aliases and app-keys from backup file
+    Restore {
+        /// Backup file path
+        file_path: PathBuf,
+
+        /// Make default identity of the backup the default one
+        #[structopt(long)]
+        set_default: bool,
+    },
 }
 
 #[derive(StructOpt, Debug)]
@@ -188,6 +224,7 @@ impl IdentityCommand {
                     columns: vec![
                         "default".into(),
                         "locked".into(),
+                        "external".into(),
                         "alias".into(),
                         "address".into(),
                     ],
@@ -197,6 +234,7 @@ impl IdentityCommand {
                             serde_json::json! {[
                                 if identity.is_default { "X" } else { "" },
                                 if identity.is_locked { "X" } else { "" },
+                                if identity.is_external { "X" } else { "" },
                                 identity.alias,
                                 identity.node_id
                             ]}
@@ -289,23 +327,44 @@ impl IdentityCommand {
             IdentityCommand::Create {
                 alias,
                 from_keystore,
+                from_mnemonic,
+                external,
+                derivation_path,
+                mnemonic_passphrase,
                 no_password,
             } => {
+                if let Some(node_id) = external {
+                    let id = bus::service(identity::BUS_ID)
+                        .send(identity::CreateExternal {
+                            alias: alias.clone(),
+                            node_id: *node_id,
+                        })
+                        .await
+                        .map_err(|e| anyhow::Error::msg(e))?;
+                    return CommandOutput::object(id);
+                }
+
                 let key_file = if let Some(keystore) = from_keystore {
                     std::fs::read_to_string(keystore)?
-                } else {
-                    let password = if *no_password {
-                        Protected::from("")
+                } else if *from_mnemonic {
+                    let phrase = rpassword::read_password_from_tty(Some("Mnemonic: "))?;
+                    let passphrase = if *mnemonic_passphrase {
+                        rpassword::read_password_from_tty(Some("Mnemonic passphrase: "))?
                     } else {
-                        let password: Protected =
-                            rpassword::read_password_from_tty(Some("Password: "))?.into();
-                        let password2: Protected =
-                            rpassword::read_password_from_tty(Some("Confirm password: "))?.into();
-                        if password.as_ref() != password2.as_ref() {
-                            anyhow::bail!("Password and confirmation do not match.")
-                        }
-                        password
+                        String::new()
                     };
+                    let derivation_path = derivation_path
+                        .as_deref()
+                        .unwrap_or(crate::id_key::DEFAULT_DERIVATION_PATH);
+                    let password = read_new_password(*no_password)?;
+                    crate::id_key::keyfile_from_mnemonic(
+                        &phrase,
+                        &passphrase,
+                        derivation_path,
+                        password,
+                    )?
+                } else {
+                    let password = read_new_password(*no_password)?;
                     crate::id_key::generate_new_keyfile(password)?
                 };
 
@@ -396,6 +455,171 @@ impl IdentityCommand {
                     None => CommandOutput::object(key_file),
                 }
             }
+            IdentityCommand::Backup { file_path } => {
+                if file_path.is_file() {
+                    anyhow::bail!("File already exists")
+                }
+
+                let identities: Vec<identity::IdentityInfo> = bus::service(identity::BUS_ID)
+                    .send(identity::List::default())
+                    .await
+                    .map_err(|e| anyhow::Error::msg(e))??;
+                let mut backup = Backup {
+                    identities: Vec::with_capacity(identities.len()),
+                    app_keys: list_app_keys().await?,
+                };
+                for id in identities {
+                    if id.is_external {
+                        backup.identities.push(IdentityBackup {
+                            alias: id.alias,
+                            is_default: id.is_default,
+                            key_file: String::new(),
+                            external: Some(id.node_id),
+                        });
+                        continue;
+                    }
+                    let key_file = bus::service(identity::BUS_ID)
+                        .send(identity::GetKeyFile(id.node_id))
+                        .await?
+                        .map_err(|e| anyhow::Error::msg(e))?;
+                    backup.identities.push(IdentityBackup {
+                        alias: id.alias,
+                        is_default: id.is_default,
+                        key_file,
+                        external: None,
+                    });
+                }
+
+                let password = read_new_password(false)?;
+                std::fs::write(file_path, backup.encrypt(&password)?)?;
+                CommandOutput::object(format!(
+                    "{} identities and {} app-keys written to '{}'",
+                    backup.identities.len(),
+                    backup.app_keys.len(),
+                    file_path.display()
+                ))
+            }
+            IdentityCommand::Restore {
+                file_path,
+                set_default,
+            } => {
+                let archive = std::fs::read_to_string(file_path)?;
+                let password: Protected =
+                    rpassword::read_password_from_tty(Some("Backup password: "))?.into();
+                let backup = Backup::decrypt(&archive, &password)?;
+
+                let mut values = Vec::new();
+                for id in backup.identities {
+                    let node_id = id.node_id()?;
+                    let status = restore_identity(node_id, &id).await?;
+                    values.push(serde_json::json! {["identity", node_id, status]});
+
+                    if id.is_default && *set_default {
+                        bus::service(identity::BUS_ID)
+                            .send(identity::Update::with_id(node_id).with_default(true))
+                            .await
+                            .map_err(|e| anyhow::Error::msg(e))??;
+                    }
+                }
+                for app_key in backup.app_keys {
+                    let imported = bus::service(appkey::BUS_ID)
+                        .send(appkey::Import {
+                            name: app_key.name.clone(),
+                            key: app_key.key,
+                            role: app_key.role,
+                            identity: app_key.identity,
+                        })
+                        .await
+                        .map_err(|e| anyhow::Error::msg(e))??;
+                    let status = if imported {
+                        "restored"
+                    } else {
+                        "already exists"
+                    };
+                    values.push(serde_json::json! {["app-key", app_key.name, status]});
+                }
+
+                Ok(ResponseTable {
+                    columns: vec!["type".into(), "name".into(), "status".into()],
+                    values,
+                }
+                .into())
+            }
         }
     }
 }
+
+fn read_new_password(no_password: bool) -> Result<Protected> {
+    if no_password {
+        return Ok(Protected::from(""));
+    }
+    let password: Protected = rpassword::read_password_from_tty(Some("Password: "))?.into();
+    let password2: Protected =
+        rpassword::read_password_from_tty(Some("Confirm password: "))?.into();
+    if password.as_ref() != password2.as_ref() {
+        anyhow::bail!("Password and confirmation do not match.")
+    }
+    Ok(password)
+}
+
+async fn list_app_keys() -> Result<Vec<appkey::AppKey>> {
+    let mut app_keys = Vec::new();
+    let mut page = 1;
+    loop {
+        let (keys, pages) = bus::service(appkey::BUS_ID)
+            .send(appkey::List {
+                identity: None,
+                page,
+                per_page: APP_KEYS_PAGE_SIZE,
+            })
+            .await
+            .map_err(|e| anyhow::Error::msg(e))??;
+        app_keys.extend(keys);
+        if page >= pages {
+            return Ok(app_keys);
+        }
+        page += 1;
+    }
+}
+
+/// Identities already present are left untouched. Alias taken by
+/// another identity is dropped.
+async fn restore_identity(node_id: NodeId, id: &IdentityBackup) -> Result<&'static str> {
+    let existing = bus::service(identity::BUS_ID)
+        .send(identity::Get::ByNodeId(node_id))
+        .await
+        .map_err(|e| anyhow::Error::msg(e))??;
+    if existing.is_some() {
+        return Ok("already exists");
+    }
+
+    let (alias, status) = match id.alias.clone() {
+        Some(alias) => {
+            let taken = bus::service(identity::BUS_ID)
+                .send(identity::Get::ByAlias(alias.clone()))
+                .await
+                .map_err(|e| anyhow::Error::msg(e))??;
+            match taken {
+                Some(_) => (None, "restored, alias already taken"),
+                None => (Some(alias), "restored"),
+            }
+        }
+        None => (None, "restored"),
+    };
+
+    if id.external.is_some() {
+        bus::service(identity::BUS_ID)
+            .send(identity::CreateExternal { alias, node_id })
+            .await
+            .map_err(|e| anyhow::Error::msg(e))??;
+    } else {
+        bus::service(identity::BUS_ID)
+            .send(identity::CreateGenerated {
+                alias,
+                from_keystore: Some(id.key_file.clone()),
+            })
+            .await
+            .map_err(|e| anyhow::Error::msg(e))??;
+    }
+    Ok(status)
+}
@@ -17,6 +17,7 @@ pub struct Identity {
     pub alias: Option<String>,
     pub note: Option<String>,
     pub created_date: NaiveDateTime,
+    pub is_external: bool,
 }
 
 #[derive(Queryable, Debug, Associations, Identifiable)]
@@ -18,6 +18,7 @@ table! {
         alias -> Nullable<Text>,
         note -> Nullable<Text>,
         created_date -> Timestamp,
+        is_external -> Bool,
     }
 }
 
@@ -3,9 +3,11 @@
 use std::convert::TryFrom;
 
 use anyhow::Context;
+use bip39::{Language, Mnemonic, Seed};
 use ethsign::keyfile::Bytes;
 use ethsign::{KeyFile, Protected, PublicKey, SecretKey};
 use rand::Rng;
+use tiny_hderive::bip32::ExtendedPrivKey;
 use ya_client_model::NodeId;
 
 use crate::dao::identity::Identity;
@@ -14,7 +16,8 @@ use crate::dao::Error;
 pub struct IdentityKey {
     id: NodeId,
     alias: Option<String>,
-    key_file: KeyFile,
+    /// `None` for identities held by external signer.
+    key_file: Option<KeyFile>,
     secret: Option<SecretKey>,
 }
 
@@ -40,16 +43,27 @@ impl IdentityKey {
         }
     }
 
-    pub fn to_key_file(&self) -> Result<String, serde_json::Error> {
-        serde_json::to_string_pretty(&self.key_file)
+    pub fn to_key_file(&self) -> anyhow::Result<String> {
+        match &self.key_file {
+            Some(key_file) => Ok(serde_json::to_string_pretty(key_file)?),
+            None => anyhow::bail!("external identity doesn't have key file"),
+        }
     }
 
     pub fn is_locked(&self) -> bool {
-        self.secret.is_none()
+        self.key_file.is_some() && self.secret.is_none()
+    }
+
+    pub fn is_external(&self) -> bool {
+        self.key_file.is_none()
     }
 
     pub fn unlock(&mut self, password: Protected) -> Result<bool, Error> {
-        let secret = match self.key_file.to_secret_key(&password) {
+        let key_file = match &self.key_file {
+            Some(key_file) => key_file,
+            None => return Err(Error::internal("external identity can't be unlocked")),
+        };
+        let secret = match key_file.to_secret_key(&password) {
             Ok(secret) => secret,
             Err(ethsign::Error::InvalidPassword) => return Ok(false),
             Err(e) => return Err(Error::internal(e)),
@@ -76,10 +90,14 @@ impl IdentityKey {
     }
 
     pub fn lock(&mut self, new_password: Option<String>) -> anyhow::Result<()> {
+        let key_file = match &mut self.key_file {
+            Some(key_file) => key_file,
+            None => anyhow::bail!("external identity can't be locked"),
+        };
         if let Some(new_password) = new_password {
             if let Some(secret) = self.secret.take() {
                 let crypto = secret.to_crypto(&Protected::new(new_password), KEY_ITERATIONS)?;
-                self.key_file.crypto = crypto;
+                key_file.crypto = crypto;
             } else {
                 anyhow::bail!("key already locked")
             }
@@ -95,16 +113,28 @@ impl IdentityKey {
         IdentityKey {
             id,
             alias,
-            key_file,
+            key_file: Some(key_file),
             secret: Some(secret),
         }
     }
+
+    pub fn external(id: NodeId, alias: Option<String>) -> Self {
+        IdentityKey {
+            id,
+            alias,
+            key_file: None,
+            secret: None,
+        }
+    }
 }
 
 impl TryFrom<Identity> for IdentityKey {
     type Error = serde_json::Error;
 
     fn try_from(value: Identity) -> Result<Self, Self::Error> {
+        if value.is_external {
+            return Ok(IdentityKey::external(value.identity_id, value.alias));
+        }
         let key_file: KeyFile = serde_json::from_str(&value.key_file_json)?;
         let id = value.identity_id;
         let alias = value.alias;
@@ -112,15 +142,18 @@ impl TryFrom<Identity> for IdentityKey {
         Ok(IdentityKey {
             id,
             alias,
-            key_file,
+            key_file: Some(key_file),
             secret,
         })
     }
 }
 
-const KEY_ITERATIONS: u32 = 10240;
+pub(crate) const KEY_ITERATIONS: u32 = 10240;
 const KEYSTORE_VERSION: u64 = 3;
 
+/// First account of Ethereum wallets (MetaMask, Ledger Live, etc.).
+pub const DEFAULT_DERIVATION_PATH: &str = "m/44'/60'/0'/0/0";
+
 pub fn default_password() -> Protected {
     Protected::new(Vec::default())
 }
@@ -131,7 +164,7 @@ pub fn generate_new(alias: Option<String>, password: Protected) -> IdentityKey {
     IdentityKey {
         id,
         alias,
-        key_file,
+        key_file: Some(key_file),
         secret: Some(secret),
     }
 }
@@ -158,6 +191,32 @@ pub fn generate_new_keyfile(password: Protected) -> anyhow::Result<String> {
     Ok(serde_json::to_string(&key_file).context("serialize keyfile")?)
 }
 
+/// Derives key from BIP-39 mnemonic along BIP-32 derivation path.
+pub fn secret_from_mnemonic(
+    phrase: &str,
+    passphrase: &str,
+    derivation_path: &str,
+) -> anyhow::Result<SecretKey> {
+    let mnemonic = Mnemonic::from_phrase(phrase.trim(), Language::English)
+        .map_err(|e| anyhow::anyhow!("invalid mnemonic: {}", e))?;
+    let seed = Seed::new(&mnemonic, passphrase);
+    let key = ExtendedPrivKey::derive(seed.as_bytes(), derivation_path)
+        .map_err(|e| anyhow::anyhow!("invalid derivation path {}: {:?}", derivation_path, e))?;
+    Ok(SecretKey::from_raw(&key.secret())?)
+}
+
+pub fn keyfile_from_mnemonic(
+    phrase: &str,
+    passphrase: &str,
+    derivation_path: &str,
+    password: Protected,
+) -> anyhow::Result<String> {
+    let secret = secret_from_mnemonic(phrase, passphrase, derivation_path)?;
+    let key_file = key_file_from_secret(&secret, password);
+
+    Ok(serde_json::to_string(&key_file).context("serialize keyfile")?)
+}
+
 #[cfg(test)]
 mod test {
     use rustc_hex::FromHex;
@@ -174,4 +233,27 @@ mod test {
         println!("{}", serde_json::to_string_pretty(&key_file)?);
         Ok(())
     }
+    #[test]
+    fn test_derive_from_mnemonic() -> anyhow::Result<()> {
+        let phrase = "test test test test test test test test test test test junk";
+
+        let secret = secret_from_mnemonic(phrase, "", DEFAULT_DERIVATION_PATH)?;
+        assert_eq!(
+            NodeId::from(secret.public().address().as_ref()),
+            "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".parse::<NodeId>()?
+        );
+
+        let secret = secret_from_mnemonic(phrase, "", "m/44'/60'/0'/0/1")?;
+        assert_eq!(
+            NodeId::from(secret.public().address().as_ref()),
+            "0x70997970c51812dc3a010c7d01b50e0d17dc79c8".parse::<NodeId>()?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_mnemonic() {
+        let phrase = "test test test test test test test test test test test golem";
+        assert!(secret_from_mnemonic(phrase, "", DEFAULT_DERIVATION_PATH).is_err());
+    }
 }
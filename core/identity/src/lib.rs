@@ -7,6 +7,8 @@ pub mod cli;
 pub mod service;
 
 mod autoconf;
+mod backup;
 pub mod dao;
 mod db;
 mod id_key;
+pub mod signer;
@@ -85,6 +85,29 @@ pub async fn activate(db: &DbExecutor) -> anyhow::Result<()> {
         }
     });
 
+    let dbx = db.clone();
+    let import_tx = tx.clone();
+    // Store an application key entry with the given key, e.g. restored from backup
+    let _ = bus::bind(&model::BUS_ID, move |import: model::Import| {
+        let db = dbx.clone();
+        let mut import_tx = import_tx.clone();
+        async move {
+            let dao = db.as_dao::<AppKeyDao>();
+            if dao.get(import.key.clone()).await.is_ok() {
+                return Ok(false);
+            }
+            dao.create(import.key, import.name, import.role, import.identity)
+                .await
+                .map_err(|e| model::Error::internal(e))?;
+            let _ = import_tx
+                .send(model::event::Event::NewKey {
+                    identity: import.identity,
+                })
+                .await;
+            Ok(true)
+        }
+    });
+
     let dbx = db.clone();
     let preconfigured_appkey = crate::autoconf::preconfigured_appkey()?;
     let preconfigured_node_id = crate::autoconf::preconfigured_node_id()?;
@@ -18,6 +18,7 @@ use ya_persistence::executor::DbExecutor;
 use crate::dao::identity::Identity;
 use crate::dao::{Error as DaoError, IdentityDao};
 use crate::id_key::{default_password, generate_new, IdentityKey};
+use crate::signer::{Signer, SignerError};
 
 #[derive(Default)]
 struct Subscription {
@@ -37,6 +38,7 @@ pub struct IdentityService {
     sender: futures::channel::mpsc::UnboundedSender<model::event::Event>,
     subscription: Rc<RefCell<Subscription>>,
     db: DbExecutor,
+    signer: Option<Signer>,
 }
 
 fn to_info(default_key: &NodeId, key: &IdentityKey) -> model::IdentityInfo {
@@ -47,6 +49,7 @@ fn to_info(default_key: &NodeId, key: &IdentityKey) -> model::IdentityInfo {
         node_id,
         is_locked: key.is_locked(),
         is_default,
+        is_external: key.is_external(),
     }
 }
 
@@ -95,6 +98,7 @@ impl IdentityService {
                         alias: None,
                         note: None,
                         created_date: Utc::now().naive_utc(),
+                        is_external: false,
                     })
                     .await?
                     .identity_id
@@ -112,6 +116,7 @@ impl IdentityService {
                             alias: None,
                             note: None,
                             created_date: Utc::now().naive_utc(),
+                            is_external: false,
                         })
                     })
                     .await?
@@ -131,6 +136,11 @@ impl IdentityService {
             let _ = ids.insert(key.id(), key);
         }
 
+        let signer = Signer::from_env()?;
+        if let Some(signer) = &signer {
+            log::info!("using external signer: {:?}", signer);
+        }
+
         Ok(IdentityService {
             default_key,
             db,
@@ -138,6 +148,7 @@ impl IdentityService {
             sender,
             subscription,
             alias_to_id,
+            signer,
         })
     }
 
@@ -197,6 +208,7 @@ impl IdentityService {
             alias: key.alias().map(ToOwned::to_owned),
             note: None,
             created_date: Utc::now().naive_utc(),
+            is_external: false,
         };
 
         self.db
@@ -231,6 +243,7 @@ impl IdentityService {
             alias: alias.clone(),
             note: None,
             created_date: Utc::now().naive_utc(),
+            is_external: false,
         };
 
         self.db
@@ -249,6 +262,66 @@ impl IdentityService {
         Ok(output)
     }
 
+    pub async fn create_external(
+        &mut self,
+        alias: Option<String>,
+        node_id: NodeId,
+    ) -> Result<model::IdentityInfo, model::Error> {
+        let signer = self
+            .signer
+            .clone()
+            .ok_or_else(|| model::Error::new_err_msg(SignerError::NotConfigured))?;
+        let accounts = signer
+            .list_accounts()
+            .await
+            .map_err(model::Error::new_err_msg)?;
+        if !accounts.contains(&node_id) {
+            return Err(model::Error::new_err_msg(format!(
+                "external signer doesn't hold key of {}",
+                node_id
+            )));
+        }
+
+        let new_identity = Identity {
+            identity_id: node_id,
+            key_file_json: String::new(),
+            is_default: false,
+            is_deleted: false,
+            alias: alias.clone(),
+            note: None,
+            created_date: Utc::now().naive_utc(),
+            is_external: true,
+        };
+
+        self.db
+            .as_dao::<IdentityDao>()
+            .create_identity(new_identity)
+            .await
+            .map_err(|e| model::Error::InternalErr(e.to_string()))?;
+
+        let key = IdentityKey::external(node_id, alias.clone());
+        let output = to_info(&self.default_key, &key);
+
+        if let Some(alias) = alias {
+            let _ = self.alias_to_id.insert(alias, node_id);
+        }
+        let _ = self.ids.insert(node_id, key);
+        Ok(output)
+    }
+
+    /// Signer of external identity. Requests to signer are sent without
+    /// holding the service lock, since they may wait for manual approval.
+    fn external_signer(&self, node_id: &NodeId) -> Result<Option<Signer>, model::Error> {
+        match self.ids.get(node_id) {
+            Some(key) if key.is_external() => match &self.signer {
+                Some(signer) => Ok(Some(signer.clone())),
+                None => Err(model::Error::new_err_msg(SignerError::NotConfigured)),
+            },
+            Some(_) => Ok(None),
+            None => Err(model::Error::NodeNotFound(Box::new(node_id.clone()))),
+        }
+    }
+
     fn get_key_by_id(&mut self, node_id: &NodeId) -> Result<&mut IdentityKey, model::Error> {
         Ok(match self.ids.get_mut(node_id) {
             Some(v) => v,
@@ -356,6 +429,7 @@ impl IdentityService {
             node_id,
             is_locked: key.is_locked(),
             is_default: self.default_key == node_id,
+            is_external: key.is_external(),
         })
     }
 
@@ -428,6 +502,17 @@ impl IdentityService {
             }
         });
 
+        let this = me.clone();
+        let _ = bus::bind(model::BUS_ID, move |create: model::CreateExternal| {
+            let this = this.clone();
+            async move {
+                this.lock()
+                    .await
+                    .create_external(create.alias, create.node_id)
+                    .await
+            }
+        });
+
         let this = me.clone();
         let _ = bus::bind(model::BUS_ID, move |update: model::Update| {
             let this = this.clone();
@@ -479,7 +564,30 @@ impl IdentityService {
         let this = me.clone();
         let _ = bus::bind(model::BUS_ID, move |sign: model::Sign| {
             let this = this.clone();
-            async move { this.lock().await.sign(sign.node_id, sign.payload).await }
+            async move {
+                let signer = this.lock().await.external_signer(&sign.node_id)?;
+                match signer {
+                    Some(signer) => signer
+                        .sign_hash(sign.node_id, &sign.payload)
+                        .await
+                        .map_err(model::Error::new_err_msg),
+                    None => this.lock().await.sign(sign.node_id, sign.payload).await,
+                }
+            }
+        });
+        let this = me.clone();
+        let _ = bus::bind(model::BUS_ID, move |sign: model::SignTx| {
+            let this = this.clone();
+            async move {
+                let signer = this.lock().await.external_signer(&sign.node_id)?;
+                match signer {
+                    Some(signer) => signer
+                        .sign_transaction(&sign.tx)
+                        .await
+                        .map_err(model::Error::new_err_msg),
+                    None => this.lock().await.sign(sign.node_id, sign.hash).await,
+                }
+            }
         });
         let this = me.clone();
         let _ = bus::bind(model::BUS_ID, move |subscribe: model::Subscribe| {
@@ -490,11 +598,15 @@ impl IdentityService {
         let _ = bus::bind(model::BUS_ID, move |node_id: model::GetPubKey| {
             let this = this.clone();
             async move {
-                this.lock()
-                    .await
-                    .get_pub_key(node_id)
-                    .await
-                    .map(|key| key.bytes().to_vec())
+                let signer = this.lock().await.external_signer(&node_id.0)?;
+                let key = match signer {
+                    Some(signer) => signer
+                        .pub_key(node_id.0)
+                        .await
+                        .map_err(model::Error::new_err_msg)?,
+                    None => this.lock().await.get_pub_key(node_id).await?,
+                };
+                Ok(key.bytes().to_vec())
             }
         });
         let this = me.clone();
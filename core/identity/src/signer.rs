@@ -0,0 +1,545 @@
+//! Client of external signer holding keys of `external` identities.
+//!
+//! Signer is reached with JSON-RPC over IPC socket or HTTP, the same way as
+//! Clef. Transactions are signed with `account_signTransaction`, so signer
+//! can review them, and public key is recovered from `account_signData`
+//! signature, both available in Clef. Raw hashes (node identity, app-key
+//! tokens, gasless transfers) are signed with `account_signHash`, which is
+//! not part of Clef API and has to be provided by signer (or proxy in front
+//! of Clef).
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use ethsign::{PublicKey, Signature};
+use rlp::RlpStream;
+use rustc_hex::{FromHex, ToHex};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tiny_keccak::{Hasher, Keccak};
+
+use ya_client_model::NodeId;
+use ya_core_model::identity::EthTransaction;
+
+#[cfg(any(test, feature = "mock-signer"))]
+pub mod mock;
+
+pub const ENV_SIGNER_URL: &str = "YAGNA_SIGNER_URL";
+
+const READ_CHUNK_SIZE: usize = 4096;
+/// Signed to recover public key of external identity.
+const PUB_KEY_CHALLENGE: [u8; 32] = [0x01; 32];
+const EIP1559_TX_TYPE: u8 = 2;
+/// JSON-RPC error code of unknown method.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SignerError {
+    #[error("external signer is not configured, set {}", ENV_SIGNER_URL)]
+    NotConfigured,
+    #[error("external signer connection error: {0}")]
+    Connection(String),
+    #[error("external signer error [{code}]: {message}")]
+    Rpc { code: i64, message: String },
+    #[error("external signer doesn't support {0} (Clef needs a proxy providing it)")]
+    Unsupported(&'static str),
+    #[error("invalid response of external signer: {0}")]
+    InvalidResponse(String),
+}
+
+impl SignerError {
+    fn invalid(e: impl std::fmt::Display) -> Self {
+        SignerError::InvalidResponse(e.to_string())
+    }
+}
+
+/// Clones share cache of recovered public keys.
+#[derive(Clone)]
+pub struct Signer {
+    endpoint: Endpoint,
+    pub_keys: Arc<Mutex<HashMap<NodeId, PublicKey>>>,
+}
+
+#[derive(Clone, Debug)]
+enum Endpoint {
+    Http(String),
+    Ipc(PathBuf),
+}
+
+impl fmt::Debug for Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.endpoint.fmt(f)
+    }
+}
+
+#[derive(Serialize)]
+struct Request<'a> {
+    jsonrpc: &'static str,
+    id: u64,
+    method: &'a str,
+    params: Value,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct Response<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct SignedTx {
+    tx: SignedTxFields,
+}
+
+#[derive(Deserialize)]
+struct SignedTxFields {
+    v: String,
+    r: String,
+    s: String,
+}
+
+impl From<Endpoint> for Signer {
+    fn from(endpoint: Endpoint) -> Self {
+        Signer {
+            endpoint,
+            pub_keys: Default::default(),
+        }
+    }
+}
+
+impl Signer {
+    pub fn from_env() -> anyhow::Result<Option<Self>> {
+        match env::var(ENV_SIGNER_URL) {
+            Ok(url) => Ok(Some(Self::from_url(&url)?)),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Accepts `http(s)://` URL, `unix://` URL or path to IPC socket.
+    pub fn from_url(url: &str) -> anyhow::Result<Self> {
+        if url.starts_with("http://") || url.starts_with("https://") {
+            return Ok(Endpoint::Http(url.to_string()).into());
+        }
+        let path = url.strip_prefix("unix://").unwrap_or(url);
+        if path.is_empty() {
+            anyhow::bail!("Invalid {}: {}", ENV_SIGNER_URL, url)
+        }
+        Ok(Endpoint::Ipc(PathBuf::from(path)).into())
+    }
+
+    pub async fn list_accounts(&self) -> Result<Vec<NodeId>, SignerError> {
+        let accounts: Vec<String> = self.call("account_list", Value::Array(vec![])).await?;
+        accounts
+            .iter()
+            .map(|address| address.to_lowercase().parse().map_err(SignerError::invalid))
+            .collect()
+    }
+
+    /// Returns signature in the same format as local keys: `v`, `r`, `s`.
+    pub async fn sign_hash(&self, node_id: NodeId, hash: &[u8]) -> Result<Vec<u8>, SignerError> {
+        let params = serde_json::json!([node_id, to_hex(hash)]);
+        let signature: String = match self.call("account_signHash", params).await {
+            Err(SignerError::Rpc { code, .. }) if code == METHOD_NOT_FOUND => {
+                return Err(SignerError::Unsupported("account_signHash"))
+            }
+            result => result?,
+        };
+        vrs(&from_hex(&signature)?)
+    }
+
+    pub async fn sign_transaction(&self, tx: &EthTransaction) -> Result<Vec<u8>, SignerError> {
+        let params = serde_json::to_value(vec![tx]).map_err(SignerError::invalid)?;
+        let signed: SignedTx = self.call("account_signTransaction", params).await?;
+
+        let v = quantity(&signed.tx.v)?;
+        let v = if tx.max_priority_fee_per_gas.is_some() {
+            v
+        } else if v >= 35 {
+            v - 35 - 2 * quantity(&tx.chain_id)?
+        } else {
+            v.saturating_sub(27)
+        };
+        if v > 1 {
+            return Err(SignerError::invalid("invalid signature v"));
+        }
+
+        let signature = Signature {
+            v: v as u8,
+            r: word(&signed.tx.r)?,
+            s: word(&signed.tx.s)?,
+        };
+
+        // Signer may change fields (e.g. gas) before signing, then signature
+        // doesn't match the transaction which is going to be sent.
+        let from: NodeId = tx
+            .from
+            .to_lowercase()
+            .parse()
+            .map_err(SignerError::invalid)?;
+        let signed_by = signature
+            .recover(&tx_hash(tx)?)
+            .map_err(SignerError::invalid)?;
+        if NodeId::from(signed_by.address().as_ref()) != from {
+            return Err(SignerError::invalid(format!(
+                "signature of transaction doesn't match {}, transaction was modified by signer",
+                from
+            )));
+        }
+
+        let mut result = Vec::with_capacity(65);
+        result.push(signature.v);
+        result.extend_from_slice(&signature.r);
+        result.extend_from_slice(&signature.s);
+        Ok(result)
+    }
+
+    /// Signers don't expose public keys, so it is recovered from signature
+    /// of `text/plain` data, once per identity.
+    pub async fn pub_key(&self, node_id: NodeId) -> Result<PublicKey, SignerError> {
+        if let Some(pub_key) = self.pub_keys.lock().unwrap().get(&node_id) {
+            return Ok(pub_key.clone());
+        }
+
+        let params = serde_json::json!(["text/plain", node_id, to_hex(&PUB_KEY_CHALLENGE)]);
+        let signature: String = self.call("account_signData", params).await?;
+        let signature = vrs(&from_hex(&signature)?)?;
+        let mut r = [0u8; 32];
+        let mut s = [0u8; 32];
+        r.copy_from_slice(&signature[1..33]);
+        s.copy_from_slice(&signature[33..65]);
+        let signature = Signature {
+            v: signature[0],
+            r,
+            s,
+        };
+        let pub_key = signature
+            .recover(&text_hash(&PUB_KEY_CHALLENGE))
+            .map_err(SignerError::invalid)?;
+        if NodeId::from(pub_key.address().as_ref()) != node_id {
+            return Err(SignerError::invalid(format!(
+                "data was not signed by {}",
+                node_id
+            )));
+        }
+
+        self.pub_keys
+            .lock()
+            .unwrap()
+            .insert(node_id, pub_key.clone());
+        Ok(pub_key)
+    }
+
+    async fn call<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T, SignerError> {
+        let request = Request {
+            jsonrpc: "2.0",
+            id: 1,
+            method,
+            params,
+        };
+        log::debug!("Calling external signer: {}", method);
+
+        let response: Response<T> = match &self.endpoint {
+            Endpoint::Http(url) => awc::Client::new()
+                .post(url)
+                .send_json(&request)
+                .await
+                .map_err(|e| SignerError::Connection(e.to_string()))?
+                .json()
+                .await
+                .map_err(SignerError::invalid)?,
+            Endpoint::Ipc(path) => call_ipc(path, &request).await?,
+        };
+
+        match (response.result, response.error) {
+            (_, Some(e)) => Err(SignerError::Rpc {
+                code: e.code,
+                message: e.message,
+            }),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(SignerError::invalid("missing result")),
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn call_ipc<T: DeserializeOwned>(
+    path: &PathBuf,
+    request: &Request<'_>,
+) -> Result<Response<T>, SignerError> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let connection_err = |e: std::io::Error| SignerError::Connection(e.to_string());
+
+    let mut stream = tokio::net::UnixStream::connect(path)
+        .await
+        .map_err(connection_err)?;
+    let mut message = serde_json::to_vec(request).map_err(SignerError::invalid)?;
+    message.push(b'\n');
+    stream.write_all(&message).await.map_err(connection_err)?;
+
+    // Responses are not delimited, so read until a whole JSON value arrives.
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; READ_CHUNK_SIZE];
+    loop {
+        let count = stream.read(&mut chunk).await.map_err(connection_err)?;
+        if count == 0 {
+            return Err(SignerError::Connection("connection closed".to_string()));
+        }
+        buf.extend_from_slice(&chunk[..count]);
+        match serde_json::from_slice(&buf) {
+            Ok(response) => return Ok(response),
+            Err(e) if e.is_eof() => continue,
+            Err(e) => return Err(SignerError::invalid(e)),
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn call_ipc<T: DeserializeOwned>(
+    _path: &PathBuf,
+    _request: &Request<'_>,
+) -> Result<Response<T>, SignerError> {
+    Err(SignerError::Connection(
+        "IPC signer is supported only on unix".to_string(),
+    ))
+}
+
+/// Converts `r || s || v` signature returned by signer to `v || r || s`.
+fn vrs(signature: &[u8]) -> Result<Vec<u8>, SignerError> {
+    if signature.len() != 65 {
+        return Err(SignerError::invalid("signature has to be 65 bytes long"));
+    }
+    let v = match signature[64] {
+        v @ 0..=1 => v,
+        v @ 27..=28 => v - 27,
+        v => return Err(SignerError::invalid(format!("invalid signature v {}", v))),
+    };
+
+    let mut result = Vec::with_capacity(65);
+    result.push(v);
+    result.extend_from_slice(&signature[..64]);
+    Ok(result)
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Keccak::v256();
+    hasher.update(data);
+    let mut hash = [0u8; 32];
+    hasher.finalize(&mut hash);
+    hash
+}
+
+/// Hash of `text/plain` data, as signed by `account_signData` (EIP-191).
+fn text_hash(data: &[u8]) -> [u8; 32] {
+    let mut message = format!("\x19Ethereum Signed Message:\n{}", data.len()).into_bytes();
+    message.extend_from_slice(data);
+    keccak256(&message)
+}
+
+/// Hash of unsigned transaction, as signed by Ethereum wallets.
+pub fn tx_hash(tx: &EthTransaction) -> Result<[u8; 32], SignerError> {
+    let to = match &tx.to {
+        Some(to) => from_hex(to)?,
+        None => vec![],
+    };
+    let chain_id = rlp_quantity(&tx.chain_id)?;
+
+    let mut stream = RlpStream::new_list(9);
+    let payload = match &tx.max_priority_fee_per_gas {
+        Some(max_priority_fee_per_gas) => {
+            stream.append(&chain_id);
+            stream.append(&rlp_quantity(&tx.nonce)?);
+            stream.append(&rlp_quantity(max_priority_fee_per_gas)?);
+            stream.append(&rlp_quantity(
+                tx.max_fee_per_gas.as_deref().unwrap_or("0x0"),
+            )?);
+            stream.append(&rlp_quantity(&tx.gas)?);
+            stream.append(&to);
+            stream.append(&rlp_quantity(&tx.value)?);
+            stream.append(&from_hex(&tx.data)?);
+            stream.begin_list(0);
+
+            let mut payload = vec![EIP1559_TX_TYPE];
+            payload.extend_from_slice(&stream.out());
+            payload
+        }
+        None => {
+            stream.append(&rlp_quantity(&tx.nonce)?);
+            stream.append(&rlp_quantity(tx.gas_price.as_deref().unwrap_or("0x0"))?);
+            stream.append(&rlp_quantity(&tx.gas)?);
+            stream.append(&to);
+            stream.append(&rlp_quantity(&tx.value)?);
+            stream.append(&from_hex(&tx.data)?);
+            stream.append(&chain_id);
+            stream.append(&Vec::<u8>::new());
+            stream.append(&Vec::<u8>::new());
+            stream.out().to_vec()
+        }
+    };
+    Ok(keccak256(&payload))
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    format!("0x{}", bytes.to_hex::<String>())
+}
+
+fn from_hex(value: &str) -> Result<Vec<u8>, SignerError> {
+    let mut value = value.trim_start_matches("0x").to_string();
+    if value.len() % 2 == 1 {
+        value.insert(0, '0');
+    }
+    value.from_hex().map_err(SignerError::invalid)
+}
+
+/// Big endian bytes without leading zeros, as RLP encodes integers.
+fn rlp_quantity(value: &str) -> Result<Vec<u8>, SignerError> {
+    let bytes = from_hex(value)?;
+    let zeros = bytes.iter().take_while(|b| **b == 0).count();
+    Ok(bytes[zeros..].to_vec())
+}
+
+fn quantity(value: &str) -> Result<u64, SignerError> {
+    u64::from_str_radix(value.trim_start_matches("0x"), 16).map_err(SignerError::invalid)
+}
+
+/// Left pads hex quantity to 32 bytes.
+fn word(value: &str) -> Result<[u8; 32], SignerError> {
+    let mut value = value.trim_start_matches("0x").to_string();
+    if value.len() > 64 {
+        return Err(SignerError::invalid("value exceeds 32 bytes"));
+    }
+    while value.len() < 64 {
+        value.insert(0, '0');
+    }
+    let bytes: Vec<u8> = value.from_hex().map_err(SignerError::invalid)?;
+    let mut word = [0u8; 32];
+    word.copy_from_slice(&bytes);
+    Ok(word)
+}
+
+#[cfg(test)]
+mod test {
+    use ethsign::SecretKey;
+
+    use super::mock::MockSigner;
+    use super::*;
+
+    fn secret() -> SecretKey {
+        SecretKey::from_raw(&[0x42; 32]).unwrap()
+    }
+
+    fn node_id(secret: &SecretKey) -> NodeId {
+        NodeId::from(secret.public().address().as_ref())
+    }
+
+    fn sample_tx(secret: &SecretKey, eip1559: bool) -> EthTransaction {
+        EthTransaction {
+            from: node_id(secret).to_string(),
+            to: Some("0x0b220f4ae8bb6ba2bdc1f1d5e0a3b2e0a3b2e0a3".to_string()),
+            gas: "0xea60".to_string(),
+            gas_price: if eip1559 {
+                None
+            } else {
+                Some("0x3b9aca00".to_string())
+            },
+            max_fee_per_gas: if eip1559 {
+                Some("0x3b9aca00".to_string())
+            } else {
+                None
+            },
+            max_priority_fee_per_gas: if eip1559 {
+                Some("0x59682f00".to_string())
+            } else {
+                None
+            },
+            value: "0x0".to_string(),
+            nonce: "0x7".to_string(),
+            data: "0xa9059cbb".to_string(),
+            chain_id: "0x89".to_string(),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_sign_with_mock_signer() -> anyhow::Result<()> {
+        let secret = secret();
+        let mock = MockSigner::start(vec![self::secret()]).await?;
+        let signer = Signer::from_url(&mock.url())?;
+
+        assert_eq!(signer.list_accounts().await?, vec![node_id(&secret)]);
+
+        let hash = [0x11u8; 32];
+        let expected = secret.sign(&hash)?;
+        let signature = signer.sign_hash(node_id(&secret), &hash).await?;
+        assert_eq!(signature[0], expected.v);
+        assert_eq!(&signature[1..33], &expected.r[..]);
+        assert_eq!(&signature[33..], &expected.s[..]);
+
+        let pub_key = signer.pub_key(node_id(&secret)).await?;
+        assert_eq!(pub_key.address(), secret.public().address());
+
+        for eip1559 in &[false, true] {
+            let tx = sample_tx(&secret, *eip1559);
+            let expected = secret.sign(&tx_hash(&tx)?)?;
+            let signature = signer.sign_transaction(&tx).await?;
+            assert_eq!(signature[0], expected.v);
+            assert_eq!(&signature[1..33], &expected.r[..]);
+            assert_eq!(&signature[33..], &expected.s[..]);
+        }
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_pub_key_is_cached() -> anyhow::Result<()> {
+        let secret = secret();
+        let mock = MockSigner::start(vec![self::secret()]).await?;
+        let signer = Signer::from_url(&mock.url())?;
+
+        let pub_key = signer.pub_key(node_id(&secret)).await?;
+        drop(mock);
+
+        let cached = signer.clone().pub_key(node_id(&secret)).await?;
+        assert_eq!(cached.address(), pub_key.address());
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_reject_modified_transaction() -> anyhow::Result<()> {
+        let secret = secret();
+        let mock = MockSigner::start_with_gas(vec![self::secret()], "0x1d4c0").await?;
+        let signer = Signer::from_url(&mock.url())?;
+
+        for eip1559 in &[false, true] {
+            match signer.sign_transaction(&sample_tx(&secret, *eip1559)).await {
+                Err(SignerError::InvalidResponse(_)) => (),
+                other => anyhow::bail!("expected invalid response, got {:?}", other),
+            }
+        }
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_unknown_account() -> anyhow::Result<()> {
+        let mock = MockSigner::start(vec![secret()]).await?;
+        let signer = Signer::from_url(&mock.url())?;
+
+        let other = SecretKey::from_raw(&[0x43; 32]).unwrap();
+        match signer.sign_hash(node_id(&other), &[0x11; 32]).await {
+            Err(SignerError::Rpc { .. }) => Ok(()),
+            other => anyhow::bail!("expected RPC error, got {:?}", other),
+        }
+    }
+}
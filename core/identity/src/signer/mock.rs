@@ -0,0 +1,179 @@
+//! In-memory signer listening on IPC socket, for tests of external identities.
+
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use ethsign::SecretKey;
+use rustc_hex::{FromHex, ToHex};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{UnixListener, UnixStream};
+
+use ya_client_model::NodeId;
+use ya_core_model::identity::EthTransaction;
+
+use super::{text_hash, tx_hash};
+
+pub struct MockSigner {
+    path: PathBuf,
+}
+
+#[derive(Default)]
+struct Config {
+    secrets: Vec<SecretKey>,
+    /// Replaces gas of transactions before signing, like signer user could.
+    gas: Option<String>,
+}
+
+impl MockSigner {
+    pub async fn start(secrets: Vec<SecretKey>) -> anyhow::Result<Self> {
+        Self::start_with(Config {
+            secrets,
+            ..Default::default()
+        })
+    }
+
+    /// Signer which changes gas of signed transactions to `gas`.
+    pub async fn start_with_gas(secrets: Vec<SecretKey>, gas: &str) -> anyhow::Result<Self> {
+        Self::start_with(Config {
+            secrets,
+            gas: Some(gas.to_string()),
+        })
+    }
+
+    fn start_with(config: Config) -> anyhow::Result<Self> {
+        let path = std::env::temp_dir().join(format!("mock-signer-{}.ipc", uuid::Uuid::new_v4()));
+        let listener = UnixListener::bind(&path)?;
+        let config = Rc::new(config);
+
+        tokio::task::spawn_local(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let config = config.clone();
+                tokio::task::spawn_local(async move {
+                    if let Err(e) = serve(stream, &config).await {
+                        log::warn!("Mock signer connection failed: {}", e);
+                    }
+                });
+            }
+        });
+        Ok(MockSigner { path })
+    }
+
+    pub fn url(&self) -> String {
+        format!("unix://{}", self.path.display())
+    }
+}
+
+impl Drop for MockSigner {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+async fn serve(mut stream: UnixStream, config: &Config) -> anyhow::Result<()> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    loop {
+        let count = stream.read(&mut chunk).await?;
+        if count == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..count]);
+        let request: Value = match serde_json::from_slice(&buf) {
+            Ok(request) => request,
+            Err(e) if e.is_eof() => continue,
+            Err(e) => return Err(e.into()),
+        };
+        buf.clear();
+
+        let response = match handle(&request, config) {
+            Ok(result) => json!({"jsonrpc": "2.0", "id": request["id"], "result": result}),
+            Err(e) => json!({
+                "jsonrpc": "2.0",
+                "id": request["id"],
+                "error": {"code": -32000, "message": e.to_string()}
+            }),
+        };
+        stream.write_all(&serde_json::to_vec(&response)?).await?;
+    }
+}
+
+fn handle(request: &Value, config: &Config) -> anyhow::Result<Value> {
+    let secrets = &config.secrets;
+    let params = &request["params"];
+    match request["method"].as_str().unwrap_or_default() {
+        "account_list" => Ok(json!(secrets
+            .iter()
+            .map(|secret| node_id(secret))
+            .collect::<Vec<_>>())),
+        "account_signHash" => {
+            let secret = find(secrets, &params[0])?;
+            let hash = bytes(params[1].as_str().unwrap_or_default())?;
+            rsv(secret, &hash)
+        }
+        "account_signData" => {
+            if params[0] != "text/plain" {
+                anyhow::bail!("unsupported content type {}", params[0]);
+            }
+            let secret = find(secrets, &params[1])?;
+            let data = bytes(params[2].as_str().unwrap_or_default())?;
+            rsv(secret, &text_hash(&data))
+        }
+        "account_signTransaction" => {
+            let mut tx: EthTransaction = serde_json::from_value(params[0].clone())?;
+            if let Some(gas) = &config.gas {
+                tx.gas = gas.clone();
+            }
+            let secret = find(secrets, &json!(tx.from))?;
+            let signature = secret.sign(&tx_hash(&tx)?)?;
+
+            let v = if tx.max_priority_fee_per_gas.is_some() {
+                signature.v as u64
+            } else {
+                signature.v as u64
+                    + 35
+                    + 2 * u64::from_str_radix(tx.chain_id.trim_start_matches("0x"), 16)?
+            };
+            let mut signed = serde_json::to_value(&tx)?;
+            signed["v"] = json!(format!("0x{:x}", v));
+            signed["r"] = json!(format!("0x{}", signature.r.to_hex::<String>()));
+            signed["s"] = json!(format!("0x{}", signature.s.to_hex::<String>()));
+            Ok(json!({ "tx": signed }))
+        }
+        method => anyhow::bail!("the method {} does not exist", method),
+    }
+}
+
+/// Signature in `r || s || v` format returned by Clef.
+fn rsv(secret: &SecretKey, hash: &[u8]) -> anyhow::Result<Value> {
+    let signature = secret.sign(hash)?;
+
+    let mut result = signature.r.to_vec();
+    result.extend_from_slice(&signature.s);
+    result.push(signature.v + 27);
+    Ok(json!(format!("0x{}", result.to_hex::<String>())))
+}
+
+fn node_id(secret: &SecretKey) -> NodeId {
+    NodeId::from(secret.public().address().as_ref())
+}
+
+fn find<'a>(secrets: &'a [SecretKey], address: &Value) -> anyhow::Result<&'a SecretKey> {
+    let address: NodeId = address
+        .as_str()
+        .unwrap_or_default()
+        .to_lowercase()
+        .parse()?;
+    secrets
+        .iter()
+        .find(|secret| node_id(secret) == address)
+        .ok_or_else(|| anyhow::anyhow!("unknown account {}", address))
+}
+
+fn bytes(value: &str) -> anyhow::Result<Vec<u8>> {
+    let mut value = value.trim_start_matches("0x").to_string();
+    if value.len() % 2 == 1 {
+        value.insert(0, '0');
+    }
+    Ok(value.from_hex()?)
+}
@@ -0,0 +1,24 @@
+-- Agreement can be amended multiple times, so `Amended` events can't be unique.
+ALTER TABLE market_agreement_event RENAME TO market_agreement_event_old;
+
+CREATE TABLE market_agreement_event(
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    agreement_id INTEGER NOT NULL,
+    event_type VARCHAR(10) NOT NULL,
+    timestamp DATETIME NOT NULL DEFAULT(STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW')),
+    issuer VARCHAR(1) NOT NULL,
+    reason TEXT,
+    signature TEXT,
+
+    FOREIGN KEY(agreement_id) REFERENCES market_agreement (id),
+    CHECK (event_type in ('Terminated', 'Approved', 'Cancelled', 'Rejected', 'Amended'))
+    CHECK (issuer in ('P', 'R'))
+);
+
+CREATE UNIQUE INDEX market_agreement_event_type_idx ON market_agreement_event (agreement_id, event_type)
+    WHERE event_type != 'Amended';
+
+INSERT INTO market_agreement_event(id, agreement_id, event_type, timestamp, issuer, reason, signature)
+    SELECT id, agreement_id, event_type, timestamp, issuer, reason, signature FROM market_agreement_event_old;
+
+DROP TABLE market_agreement_event_old;
@@ -0,0 +1,23 @@
+PRAGMA foreign_keys=off;
+
+CREATE TABLE market_offer_old (
+    id VARCHAR(97) NOT NULL PRIMARY KEY,
+    properties TEXT NOT NULL,
+    constraints TEXT NOT NULL,
+    node_id VARCHAR(20) NOT NULL,
+
+    creation_ts DATETIME NOT NULL,
+    insertion_ts DATETIME NOT NULL DEFAULT(STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW')),
+    expiration_ts DATETIME NOT NULL
+);
+
+INSERT INTO market_offer_old(id, properties, constraints, node_id, creation_ts, insertion_ts, expiration_ts)
+SELECT id, properties, constraints, node_id, creation_ts, insertion_ts, expiration_ts FROM market_offer;
+
+DROP TABLE market_offer;
+ALTER TABLE market_offer_old RENAME TO market_offer;
+
+CREATE INDEX IF NOT EXISTS market_offer_expiration_idx ON market_offer (expiration_ts);
+CREATE INDEX IF NOT EXISTS market_offer_insertion_idx ON market_offer (insertion_ts);
+
+PRAGMA foreign_keys=on;
@@ -0,0 +1,3 @@
+-- Provider's signature of Offer subscription id. Offers without signature
+-- are no longer accepted from other nodes.
+ALTER TABLE market_offer ADD COLUMN signature TEXT;
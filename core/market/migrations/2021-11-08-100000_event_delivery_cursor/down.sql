@@ -0,0 +1,23 @@
+PRAGMA foreign_keys=off;
+
+CREATE TABLE market_negotiation_event_old(
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    subscription_id VARCHAR(100) NOT NULL,
+    timestamp DATETIME NOT NULL DEFAULT(STRFTIME('%Y-%m-%d %H:%M:%f', 'NOW')),
+    event_type VARCHAR(20) NOT NULL,
+    artifact_id VARCHAR(100) NOT NULL,
+    reason TEXT,
+
+    CHECK (event_type in ('P-NewProposal', 'P-ProposalRejected', 'P-Agreement', 'P-PropertyQuery', 'R-NewProposal', 'R-ProposalRejected', 'R-PropertyQuery'))
+);
+
+INSERT INTO market_negotiation_event_old(id, subscription_id, timestamp, event_type, artifact_id, reason)
+SELECT id, subscription_id, timestamp, event_type, artifact_id, reason FROM market_negotiation_event;
+
+DROP TABLE market_negotiation_event;
+ALTER TABLE market_negotiation_event_old RENAME TO market_negotiation_event;
+
+CREATE INDEX IF NOT EXISTS market_negotiation_event_subscription_idx ON market_negotiation_event (subscription_id);
+CREATE INDEX IF NOT EXISTS market_negotiation_event_timestamp_idx ON market_negotiation_event ("timestamp");
+
+PRAGMA foreign_keys=on;
@@ -0,0 +1,7 @@
+-- Negotiation Events returned to clients, that acknowledge them explicitly.
+-- Such Events are kept in queue until acknowledged and delivered again,
+-- when acknowledgement doesn't come in time.
+ALTER TABLE market_negotiation_event ADD COLUMN delivery_cursor VARCHAR(36);
+ALTER TABLE market_negotiation_event ADD COLUMN delivery_ts DATETIME;
+
+CREATE INDEX IF NOT EXISTS market_negotiation_event_cursor_idx ON market_negotiation_event (delivery_cursor);
@@ -0,0 +1,124 @@
+use chrono::{DateTime, Utc};
+use structopt::StructOpt;
+
+use ya_client::model::NodeId;
+use ya_core_model::identity as idm;
+use ya_core_model::market::{local, AgreementListFilter, AgreementState, ListAgreements};
+use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
+use ya_service_bus::{typed as bus, RpcEndpoint};
+
+/// Market management.
+#[derive(StructOpt, Debug)]
+pub enum MarketCli {
+    /// List Agreements
+    Agreements(AgreementsCli),
+}
+
+#[derive(StructOpt, Debug)]
+#[structopt(rename_all = "kebab-case")]
+pub struct AgreementsCli {
+    /// Identity (node id or alias) to list Agreements for [default: default identity]
+    #[structopt(long)]
+    id: Option<String>,
+    #[structopt(
+        long,
+        parse(try_from_str = parse_state),
+        possible_values = &["Proposal", "Pending", "Cancelled", "Rejected", "Approved", "Expired", "Terminated"]
+    )]
+    state: Option<AgreementState>,
+    /// Node on the other side of the Agreement
+    #[structopt(long)]
+    peer_id: Option<NodeId>,
+    #[structopt(long)]
+    app_session_id: Option<String>,
+    /// RFC 3339 timestamp
+    #[structopt(long)]
+    created_after: Option<DateTime<Utc>>,
+    /// RFC 3339 timestamp
+    #[structopt(long)]
+    created_before: Option<DateTime<Utc>>,
+    /// RFC 3339 timestamp
+    #[structopt(long)]
+    valid_to_after: Option<DateTime<Utc>>,
+    /// RFC 3339 timestamp
+    #[structopt(long)]
+    valid_to_before: Option<DateTime<Utc>>,
+    #[structopt(long, default_value = "0")]
+    offset: u32,
+    /// At most 1000
+    #[structopt(long, default_value = "100")]
+    limit: u32,
+}
+
+fn parse_state(s: &str) -> serde_json::Result<AgreementState> {
+    serde_json::from_value(serde_json::Value::String(s.to_string()))
+}
+
+impl MarketCli {
+    pub async fn run_command(self, ctx: &CliCtx) -> anyhow::Result<CommandOutput> {
+        match self {
+            MarketCli::Agreements(cli) => {
+                let node_id = node_id(cli.id).await?;
+                let filter = AgreementListFilter {
+                    state: cli.state,
+                    peer_id: cli.peer_id,
+                    app_session_id: cli.app_session_id,
+                    created_after: cli.created_after,
+                    created_before: cli.created_before,
+                    valid_to_after: cli.valid_to_after,
+                    valid_to_before: cli.valid_to_before,
+                    offset: Some(cli.offset),
+                    limit: Some(cli.limit),
+                };
+                let agreements = bus::service(local::BUS_ID)
+                    .send(ListAgreements { node_id, filter })
+                    .await??;
+                if ctx.json_output {
+                    return CommandOutput::object(agreements);
+                }
+
+                Ok(ResponseTable {
+                    columns: vec![
+                        "id".to_owned(),
+                        "role".to_owned(),
+                        "state".to_owned(),
+                        "peer".to_owned(),
+                        "app session".to_owned(),
+                        "created".to_owned(),
+                        "valid to".to_owned(),
+                    ],
+                    values: agreements
+                        .into_iter()
+                        .map(|agreement| {
+                            serde_json::json! {[
+                                agreement.agreement_id,
+                                agreement.role.to_string(),
+                                agreement.state,
+                                agreement.peer_id,
+                                agreement.app_session_id.unwrap_or_default(),
+                                agreement.timestamp.to_rfc3339(),
+                                agreement.valid_to.to_rfc3339(),
+                            ]}
+                        })
+                        .collect(),
+                }
+                .into())
+            }
+        }
+    }
+}
+
+async fn node_id(id: Option<String>) -> anyhow::Result<NodeId> {
+    let get_by = match id {
+        Some(id) if id.starts_with("0x") => return Ok(id.parse()?),
+        Some(alias) => idm::Get::ByAlias(alias),
+        None => idm::Get::ByDefault,
+    };
+    bus::service(idm::BUS_ID)
+        .send(get_by)
+        .await
+        .map_err(anyhow::Error::msg)?
+        .map_err(anyhow::Error::msg)?
+        .map(|identity| identity.node_id)
+        .ok_or_else(|| anyhow::anyhow!("Identity not found"))
+}
@@ -11,6 +11,10 @@ pub struct Config {
     pub events: EventsConfig,
     #[structopt(flatten)]
     pub db: DbConfig,
+    #[structopt(flatten)]
+    pub offer_filter: OfferFilterConfig,
+    #[structopt(flatten)]
+    pub offer_signature: OfferSignatureConfig,
 }
 
 #[derive(StructOpt, Clone)]
@@ -42,6 +46,10 @@ pub struct EventsConfig {
     pub max_events_default: i32,
     #[structopt(env = "MARKET_MAX_EVENTS_MAX", default_value = "100")]
     pub max_events_max: i32,
+    /// Time after which Negotiation Events, that weren't acknowledged by client
+    /// are delivered again. Applies only to clients querying with acknowledgement.
+    #[structopt(env = "MARKET_EVENTS_ACK_TIMEOUT", parse(try_from_str = parse_chrono_duration), default_value = "1min")]
+    pub ack_timeout: chrono::Duration,
 }
 
 #[derive(StructOpt, Clone)]
@@ -57,6 +65,39 @@ pub struct DbConfig {
     pub event_store_days: i32,
 }
 
+#[derive(StructOpt, Clone)]
+pub struct OfferFilterConfig {
+    /// Store only Offers from other nodes, that match our active Demands
+    /// or filter expression. Remaining Offers are kept in memory cache only
+    /// to be re-broadcasted.
+    #[structopt(
+        env = "MARKET_OFFER_FILTER_ENABLED",
+        parse(try_from_str),
+        default_value = "false"
+    )]
+    pub enabled: bool,
+    /// Constraints expression (in the same format as Demand constraints)
+    /// selecting Offers to store regardless of Demands.
+    #[structopt(env = "MARKET_OFFER_FILTER")]
+    pub constraints: Option<String>,
+    /// Number of not matching Offers kept in memory for re-broadcasts.
+    #[structopt(env = "MARKET_OFFER_FILTER_CACHE_SIZE", default_value = "500")]
+    pub cache_size: usize,
+}
+
+#[derive(StructOpt, Clone)]
+pub struct OfferSignatureConfig {
+    /// Reject Offers without signature. Until all Providers sign their Offers,
+    /// unsigned Offers are accepted and only counted; Offers with invalid
+    /// or forged signature are always rejected.
+    #[structopt(
+        env = "MARKET_OFFER_SIGNATURE_REQUIRED",
+        parse(try_from_str),
+        default_value = "false"
+    )]
+    pub required: bool,
+}
+
 impl Config {
     pub fn from_env() -> Result<Config, structopt::clap::Error> {
         // Empty command line arguments, because we want to use ENV fallback
@@ -93,4 +134,18 @@ mod test {
         assert_eq!(90, c.db.agreement_store_days);
         assert_eq!(1, c.db.event_store_days);
     }
+
+    #[test]
+    fn test_default_structopt_offer_filter_config() {
+        let c = Config::from_env().unwrap();
+        assert_eq!(false, c.offer_filter.enabled);
+        assert_eq!(None, c.offer_filter.constraints);
+        assert_eq!(500, c.offer_filter.cache_size);
+    }
+
+    #[test]
+    fn test_default_structopt_offer_signature_config() {
+        let c = Config::from_env().unwrap();
+        assert_eq!(false, c.offer_signature.required);
+    }
 }
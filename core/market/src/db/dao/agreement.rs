@@ -3,10 +3,11 @@ use diesel::prelude::*;
 
 use ya_client::model::market::Reason;
 use ya_client::model::NodeId;
+use ya_core_model::market::{AgreementAmendment, AgreementListFilter};
 use ya_persistence::executor::{do_with_transaction, readonly_transaction, ConnType, PoolType};
 
 use crate::config::DbConfig;
-use crate::db::dao::agreement_events::create_event;
+use crate::db::dao::agreement_events::{create_amended_event, create_event};
 use crate::db::dao::proposal::{has_counter_proposal, update_proposal_state};
 use crate::db::dao::sql_functions::datetime;
 use crate::db::model::{
@@ -19,6 +20,13 @@ use crate::db::schema::market_agreement_event::dsl as event;
 use crate::db::schema::market_agreement_event::dsl::market_agreement_event;
 use crate::db::{AsMixedDao, DbError, DbResult};
 
+const DEFAULT_LIST_LIMIT: u32 = 100;
+const MAX_LIST_LIMIT: u32 = 1000;
+
+const EXPIRATION_PROPERTY: &str = "golem.srv.comp.expiration";
+const PAYMENT_TIMEOUT_PROPERTY: &str = "golem.com.scheme.payu.payment-timeout-sec?";
+const DEBIT_NOTE_INTERVAL_PROPERTY: &str = "golem.com.scheme.payu.debit-note.interval-sec?";
+
 #[derive(thiserror::Error, Debug)]
 pub enum SaveAgreementError {
     #[error("Can't create Agreement for already countered Proposal [{0}].")]
@@ -58,6 +66,12 @@ pub enum AgreementDaoError {
     EventError(String),
     #[error("Invalid Agreement id: {0}")]
     InvalidId(#[from] ProposalIdParseError),
+    #[error("Can't amend Agreement in state {0}.")]
+    NotAmendable(AgreementState),
+    #[error("Failed to update Agreement properties. Error: {0}")]
+    Properties(String),
+    #[error("Invalid limit {0}, at most {1} Agreements can be listed.")]
+    InvalidLimit(u32, u32),
 }
 
 impl<'c> AgreementDao<'c> {
@@ -132,6 +146,80 @@ impl<'c> AgreementDao<'c> {
         .await
     }
 
+    /// Lists Agreements owned by the node, newest first.
+    pub async fn list(
+        &self,
+        node_id: NodeId,
+        filter: AgreementListFilter,
+        validation_ts: NaiveDateTime,
+    ) -> Result<Vec<Agreement>, AgreementDaoError> {
+        let limit = filter.limit.unwrap_or(DEFAULT_LIST_LIMIT);
+        if limit > MAX_LIST_LIMIT {
+            return Err(AgreementDaoError::InvalidLimit(limit, MAX_LIST_LIMIT));
+        }
+
+        do_with_transaction(self.pool, move |conn| {
+            let owned_by_node = agreement::id
+                .like(format!("{}-%", Owner::Provider))
+                .and(agreement::provider_id.eq(node_id))
+                .or(agreement::id
+                    .like(format!("{}-%", Owner::Requestor))
+                    .and(agreement::requestor_id.eq(node_id)));
+
+            // The same as in `select`: Agreements past their validity period are
+            // marked as expired, so filtering by state gives consistent results.
+            diesel::update(
+                market_agreement
+                    .filter(owned_by_node.clone())
+                    .filter(agreement::state.eq_any(vec![
+                        AgreementState::Proposal,
+                        AgreementState::Pending,
+                        AgreementState::Approving,
+                    ]))
+                    .filter(agreement::valid_to.lt(validation_ts)),
+            )
+            .set(agreement::state.eq(AgreementState::Expired))
+            .execute(conn)?;
+
+            let mut query = market_agreement.filter(owned_by_node).into_boxed();
+
+            if let Some(state) = filter.state {
+                query = query.filter(agreement::state.eq_any(AgreementState::from_client(state)));
+            }
+            if let Some(peer_id) = filter.peer_id {
+                // Agreements between the same identities are disallowed, so the peer
+                // is always on the other side of the Agreement.
+                query = query.filter(
+                    agreement::provider_id
+                        .eq(peer_id)
+                        .or(agreement::requestor_id.eq(peer_id)),
+                );
+            }
+            if let Some(session_id) = filter.app_session_id {
+                query = query.filter(agreement::session_id.eq(session_id));
+            }
+            if let Some(after) = filter.created_after {
+                query = query.filter(agreement::creation_ts.ge(after.naive_utc()));
+            }
+            if let Some(before) = filter.created_before {
+                query = query.filter(agreement::creation_ts.lt(before.naive_utc()));
+            }
+            if let Some(after) = filter.valid_to_after {
+                query = query.filter(agreement::valid_to.ge(after.naive_utc()));
+            }
+            if let Some(before) = filter.valid_to_before {
+                query = query.filter(agreement::valid_to.lt(before.naive_utc()));
+            }
+
+            Ok(query
+                .order(agreement::creation_ts.desc())
+                .offset(filter.offset.unwrap_or(0) as i64)
+                .limit(limit as i64)
+                .load::<Agreement>(conn)?)
+        })
+        .await
+    }
+
     pub async fn save(&self, agreement: Agreement) -> Result<Agreement, SaveAgreementError> {
         // Agreement is always created for last Provider Proposal.
         // TODO: Accessing two databases can cause race conditions in some edge cases.
@@ -319,6 +407,59 @@ impl<'c> AgreementDao<'c> {
         .await
     }
 
+    /// Changes terms of approved Agreement and adds `Amended` event with
+    /// amendment details stored in place of the Reason.
+    pub async fn amend(
+        &self,
+        id: &AgreementId,
+        amendment: &AgreementAmendment,
+        issuer: Owner,
+    ) -> Result<Agreement, AgreementDaoError> {
+        let id = id.clone();
+        let amendment = amendment.clone();
+
+        do_with_transaction(self.pool, move |conn| {
+            let mut agreement: Agreement =
+                market_agreement.filter(agreement::id.eq(&id)).first(conn)?;
+
+            if agreement.state != AgreementState::Approved {
+                return Err(AgreementDaoError::NotAmendable(agreement.state));
+            }
+
+            let mut properties =
+                serde_json::from_str::<serde_json::Map<_, _>>(&agreement.demand_properties)
+                    .map_err(|e| AgreementDaoError::Properties(e.to_string()))?;
+            properties.insert(
+                EXPIRATION_PROPERTY.to_string(),
+                amendment.valid_to.timestamp_millis().into(),
+            );
+            if let Some(timeout) = amendment.payment_timeout_sec {
+                properties.insert(PAYMENT_TIMEOUT_PROPERTY.to_string(), timeout.into());
+            }
+            if let Some(interval) = amendment.debit_note_interval_sec {
+                properties.insert(DEBIT_NOTE_INTERVAL_PROPERTY.to_string(), interval.into());
+            }
+            agreement.demand_properties = serde_json::to_string(&properties)
+                .map_err(|e| AgreementDaoError::Properties(e.to_string()))?;
+            agreement.valid_to = amendment.valid_to.naive_utc();
+
+            diesel::update(market_agreement.find(&id))
+                .set((
+                    agreement::valid_to.eq(&agreement.valid_to),
+                    agreement::demand_properties.eq(&agreement.demand_properties),
+                ))
+                .execute(conn)?;
+
+            let details = Reason {
+                message: "Agreement amended".to_string(),
+                extra: serde_json::to_value(&amendment).unwrap_or_default(),
+            };
+            create_amended_event(conn, &agreement, details, issuer)?;
+            Ok(agreement)
+        })
+        .await
+    }
+
     pub async fn revert_approving(&self, id: &AgreementId) -> Result<bool, AgreementDaoError> {
         let id = id.clone();
 
@@ -7,7 +7,9 @@ use ya_persistence::executor::PoolType;
 use ya_persistence::executor::{readonly_transaction, ConnType};
 
 use crate::db::dao::AgreementDaoError;
-use crate::db::model::{Agreement, AgreementEvent, AgreementId, NewAgreementEvent};
+use crate::db::model::{
+    Agreement, AgreementEvent, AgreementEventType, AgreementId, NewAgreementEvent,
+};
 use crate::db::model::{AppSessionId, Owner};
 use crate::db::schema::market_agreement::dsl as agreement;
 use crate::db::schema::market_agreement::dsl::market_agreement;
@@ -56,6 +58,7 @@ impl<'c> AgreementEventsDao<'c> {
 
             Ok(market_agreement_event
                 .filter(event::agreement_id.eq_any(select_corresponding_agreement))
+                .filter(event::event_type.ne(AgreementEventType::Amended))
                 .filter(event::timestamp.gt(after_timestamp))
                 .order_by(event::timestamp.asc())
                 .limit(max_events as i64)
@@ -108,3 +111,17 @@ pub(crate) fn create_event(
 
     Ok(())
 }
+
+pub(crate) fn create_amended_event(
+    conn: &ConnType,
+    agreement: &Agreement,
+    amendment: Reason,
+    issuer: Owner,
+) -> Result<(), AgreementDaoError> {
+    let event = NewAgreementEvent::amended(agreement, amendment, issuer);
+    diesel::insert_into(market_agreement_event)
+        .values(&event)
+        .execute(conn)
+        .map_err(|e| AgreementDaoError::EventError(e.to_string()))?;
+    Ok(())
+}
@@ -1,7 +1,8 @@
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
 use diesel::dsl::sql;
 use diesel::{sql_types, ExpressionMethods, QueryDsl, RunQueryDsl};
 use thiserror::Error;
+use uuid::Uuid;
 
 use ya_client::model::market::Reason;
 use ya_persistence::executor::ConnType;
@@ -84,33 +85,7 @@ impl<'c> NegotiationEventsDao<'c> {
             // Check subscription wasn't unsubscribed or expired.
             validate_subscription(conn, &subscription_id, owner)?;
 
-            // Only ProposalEvents should be in random order.
-            //  AgreementEvent and rejections events should be sorted with higher
-            //  priority.
-            let basic_query =
-                dsl::market_negotiation_event.filter(dsl::subscription_id.eq(&subscription_id));
-            let mut events = basic_query
-                .clone()
-                .filter(dsl::event_type.ne_all(vec![
-                    EventType::ProviderNewProposal,
-                    EventType::RequestorNewProposal,
-                ]))
-                .order_by(dsl::timestamp.asc())
-                .limit(max_events as i64)
-                .load::<MarketEvent>(conn)?;
-            if (events.len() as i32) < max_events {
-                let limit_left: i32 = max_events - (events.len() as i32);
-                let proposal_events = basic_query
-                    .filter(dsl::event_type.eq_any(vec![
-                        EventType::ProviderNewProposal,
-                        EventType::RequestorNewProposal,
-                    ]))
-                    .order_by(sql::<sql_types::Bool>("RANDOM()"))
-                    .limit(limit_left as i64)
-                    .load::<MarketEvent>(conn)?;
-
-                events.extend(proposal_events.into_iter());
-            }
+            let events = select_events(conn, &subscription_id, max_events, None, &[])?;
 
             // Remove returned events from queue.
             if !events.is_empty() {
@@ -124,6 +99,84 @@ impl<'c> NegotiationEventsDao<'c> {
         .await
     }
 
+    /// Returns Events without removing them from queue. Events are marked with
+    /// cursor, that should be used to acknowledge them. Not acknowledged Events
+    /// are returned again after `ack_timeout`.
+    /// Events delivered with `resume` cursor and still not acknowledged, are
+    /// returned first, so client can continue after restart.
+    /// Only Events of `event_types` are returned, unless it is empty.
+    pub async fn deliver_events(
+        &self,
+        subscription_id: &SubscriptionId,
+        max_events: i32,
+        owner: Owner,
+        resume: Option<String>,
+        ack_timeout: chrono::Duration,
+        event_types: Vec<EventType>,
+    ) -> Result<(String, Vec<MarketEvent>), TakeEventsError> {
+        let subscription_id = subscription_id.clone();
+        do_with_transaction(self.pool, move |conn| {
+            validate_subscription(conn, &subscription_id, owner)?;
+
+            let now = Utc::now().naive_utc();
+            if let Some(cursor) = resume {
+                let events = dsl::market_negotiation_event
+                    .filter(dsl::subscription_id.eq(&subscription_id))
+                    .filter(dsl::delivery_cursor.eq(&cursor))
+                    .order_by(dsl::timestamp.asc())
+                    .limit(max_events as i64)
+                    .load::<MarketEvent>(conn)?;
+
+                if !events.is_empty() {
+                    diesel::update(
+                        dsl::market_negotiation_event.filter(dsl::delivery_cursor.eq(&cursor)),
+                    )
+                    .set(dsl::delivery_ts.eq(now))
+                    .execute(conn)?;
+                    return Ok((cursor, events));
+                }
+            }
+
+            let events = select_events(
+                conn,
+                &subscription_id,
+                max_events,
+                Some(now - ack_timeout),
+                &event_types,
+            )?;
+
+            let cursor = Uuid::new_v4().to_simple().to_string();
+            if !events.is_empty() {
+                let ids = events.iter().map(|event| event.id).collect::<Vec<_>>();
+                diesel::update(dsl::market_negotiation_event.filter(dsl::id.eq_any(ids)))
+                    .set((dsl::delivery_cursor.eq(&cursor), dsl::delivery_ts.eq(now)))
+                    .execute(conn)?;
+            }
+
+            Ok((cursor, events))
+        })
+        .await
+    }
+
+    /// Removes Events delivered with given cursor. Returns number of removed Events.
+    pub async fn ack_events(
+        &self,
+        subscription_id: &SubscriptionId,
+        cursor: &str,
+    ) -> DbResult<usize> {
+        let subscription_id = subscription_id.clone();
+        let cursor = cursor.to_string();
+        do_with_transaction(self.pool, move |conn| {
+            Ok(diesel::delete(
+                dsl::market_negotiation_event
+                    .filter(dsl::subscription_id.eq(&subscription_id))
+                    .filter(dsl::delivery_cursor.eq(&cursor)),
+            )
+            .execute(conn)?)
+        })
+        .await
+    }
+
     pub async fn remove_events(&self, subscription_id: &SubscriptionId) -> DbResult<()> {
         let subscription_id = subscription_id.clone();
         do_with_transaction(self.pool, move |conn| {
@@ -156,6 +209,58 @@ impl<'c> NegotiationEventsDao<'c> {
     }
 }
 
+/// Only ProposalEvents should be in random order. AgreementEvent and rejections
+/// events should be sorted with higher priority.
+/// With `redeliver_before` set, Events delivered later than this timestamp
+/// are skipped, since they are still waiting for acknowledgement.
+fn select_events(
+    conn: &ConnType,
+    subscription_id: &SubscriptionId,
+    max_events: i32,
+    redeliver_before: Option<NaiveDateTime>,
+    event_types: &[EventType],
+) -> DbResult<Vec<MarketEvent>> {
+    let basic_query = || {
+        let mut query = dsl::market_negotiation_event
+            .filter(dsl::subscription_id.eq(subscription_id))
+            .into_boxed();
+        if !event_types.is_empty() {
+            query = query.filter(dsl::event_type.eq_any(event_types.to_vec()));
+        }
+        match redeliver_before {
+            Some(timestamp) => query.filter(
+                dsl::delivery_ts
+                    .is_null()
+                    .or(dsl::delivery_ts.lt(timestamp)),
+            ),
+            None => query,
+        }
+    };
+
+    let mut events = basic_query()
+        .filter(dsl::event_type.ne_all(vec![
+            EventType::ProviderNewProposal,
+            EventType::RequestorNewProposal,
+        ]))
+        .order_by(dsl::timestamp.asc())
+        .limit(max_events as i64)
+        .load::<MarketEvent>(conn)?;
+    if (events.len() as i32) < max_events {
+        let limit_left: i32 = max_events - (events.len() as i32);
+        let proposal_events = basic_query()
+            .filter(dsl::event_type.eq_any(vec![
+                EventType::ProviderNewProposal,
+                EventType::RequestorNewProposal,
+            ]))
+            .order_by(sql::<sql_types::Bool>("RANDOM()"))
+            .limit(limit_left as i64)
+            .load::<MarketEvent>(conn)?;
+
+        events.extend(proposal_events.into_iter());
+    }
+    Ok(events)
+}
+
 fn validate_subscription(
     conn: &ConnType,
     subscription_id: &SubscriptionId,
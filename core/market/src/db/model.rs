@@ -11,7 +11,7 @@ pub use agreement::{check_transition, Agreement, AgreementId, AgreementState, Ap
 pub use agreement_events::{AgreementEvent, AgreementEventType, NewAgreementEvent};
 pub use demand::Demand;
 pub use negotiation_events::{EventError, EventType, MarketEvent};
-pub use offer::{Offer, OfferUnsubscribed};
+pub use offer::{Offer, OfferSignatureError, OfferUnsubscribed};
 pub use proposal::{DbProposal, Issuer, Negotiation, Proposal, ProposalState};
 
 pub use proposal_id::{Owner, ProposalId, ProposalIdParseError, ProposalIdValidationError};
@@ -8,7 +8,10 @@ use ya_client::model::market::agreement::{
 use ya_client::model::market::demand::Demand as ClientDemand;
 use ya_client::model::market::offer::Offer as ClientOffer;
 use ya_client::model::{ErrorMessage, NodeId};
+use ya_core_model::market::AgreementListEntry;
+use ya_core_model::Role;
 use ya_diesel_utils::DbTextField;
+use ya_metrics::agreement::AgreementLabels;
 
 use crate::db::dao::AgreementDaoError;
 use crate::db::model::{Owner, Proposal, ProposalId, SubscriptionId};
@@ -177,6 +180,56 @@ impl Agreement {
             committed_signature: self.committed_signature,
         })
     }
+
+    /// Labels of Agreement counters.
+    pub fn metric_labels(&self) -> AgreementLabels {
+        let (role, peer) = match self.id.owner() {
+            Owner::Provider => ("provider", self.requestor_id),
+            Owner::Requestor => ("requestor", self.provider_id),
+        };
+        let parse = |properties: &str| serde_json::from_str(properties).unwrap_or_default();
+        AgreementLabels::from_properties(
+            role,
+            &self.id.into_client(),
+            peer.to_string(),
+            &parse(&self.demand_properties),
+            &parse(&self.offer_properties),
+        )
+    }
+
+    pub fn into_list_entry(self) -> AgreementListEntry {
+        let (role, peer_id) = match self.id.owner() {
+            Owner::Provider => (Role::Provider, self.requestor_id),
+            Owner::Requestor => (Role::Requestor, self.provider_id),
+        };
+        AgreementListEntry {
+            agreement_id: self.id.into_client(),
+            role,
+            state: self.state.into(),
+            peer_id,
+            app_session_id: self.session_id,
+            timestamp: Utc.from_utc_datetime(&self.creation_ts),
+            valid_to: Utc.from_utc_datetime(&self.valid_to),
+            approved_date: self.approved_ts.map(|d| Utc.from_utc_datetime(&d)),
+        }
+    }
+}
+
+impl AgreementState {
+    /// Database states, that are visible as the given client state.
+    pub fn from_client(state: ClientAgreementState) -> Vec<AgreementState> {
+        match state {
+            ClientAgreementState::Proposal => vec![AgreementState::Proposal],
+            ClientAgreementState::Pending => {
+                vec![AgreementState::Pending, AgreementState::Approving]
+            }
+            ClientAgreementState::Cancelled => vec![AgreementState::Cancelled],
+            ClientAgreementState::Rejected => vec![AgreementState::Rejected],
+            ClientAgreementState::Approved => vec![AgreementState::Approved],
+            ClientAgreementState::Expired => vec![AgreementState::Expired],
+            ClientAgreementState::Terminated => vec![AgreementState::Terminated],
+        }
+    }
 }
 
 impl From<AgreementState> for ClientAgreementState {
@@ -30,6 +30,8 @@ pub enum AgreementEventType {
     Rejected,
     Cancelled,
     Terminated,
+    /// Agreement terms were changed after approval.
+    Amended,
 }
 
 #[derive(DbTextField, Debug, Clone, AsExpression, FromSqlRow)]
@@ -97,13 +99,26 @@ impl NewAgreementEvent {
     }
 }
 
+impl NewAgreementEvent {
+    pub(crate) fn amended(agreement: &Agreement, amendment: Reason, issuer: Owner) -> Self {
+        Self {
+            agreement_id: agreement.id.clone(),
+            event_type: AgreementEventType::Amended,
+            timestamp: Utc::now().naive_utc(),
+            issuer,
+            reason: Some(DbReason(amendment)),
+        }
+    }
+}
+
 impl AgreementEvent {
-    pub fn into_client(self) -> ClientEvent {
+    /// Returns `None` for events, that have no representation in client API.
+    pub fn into_client(self) -> Option<ClientEvent> {
         let agreement_id = self.agreement_id.into_client();
         let event_date = DateTime::<Utc>::from_utc(self.timestamp, Utc);
         let reason = self.reason.map(|reason| reason.0);
 
-        match self.event_type {
+        Some(match self.event_type {
             AgreementEventType::Approved => ClientEvent {
                 agreement_id,
                 event_date,
@@ -135,7 +150,8 @@ impl AgreementEvent {
                     }),
                 }
             },
-        }
+            AgreementEventType::Amended => return None,
+        })
     }
 }
 
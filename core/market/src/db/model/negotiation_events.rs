@@ -55,6 +55,39 @@ pub enum EventType {
     RequestorPropertyQuery,
 }
 
+impl EventType {
+    /// Event types, that can be found in queues of `owner`'s subscriptions.
+    pub fn of_owner(owner: Owner) -> &'static [EventType] {
+        match owner {
+            Owner::Provider => &[
+                EventType::ProviderNewProposal,
+                EventType::ProviderProposalRejected,
+                EventType::ProviderAgreement,
+                EventType::ProviderPropertyQuery,
+            ],
+            Owner::Requestor => &[
+                EventType::RequestorNewProposal,
+                EventType::RequestorProposalRejected,
+                EventType::RequestorPropertyQuery,
+            ],
+        }
+    }
+
+    /// Value of `eventType` field of client event created from this event.
+    pub fn client_event_type(&self) -> &'static str {
+        match self {
+            EventType::ProviderNewProposal | EventType::RequestorNewProposal => "ProposalEvent",
+            EventType::ProviderProposalRejected | EventType::RequestorProposalRejected => {
+                "ProposalRejectedEvent"
+            }
+            EventType::ProviderAgreement => "AgreementEvent",
+            EventType::ProviderPropertyQuery | EventType::RequestorPropertyQuery => {
+                "PropertyQueryEvent"
+            }
+        }
+    }
+}
+
 #[derive(Clone, Debug, Queryable)]
 pub struct MarketEvent {
     pub id: i32,
@@ -65,6 +98,9 @@ pub struct MarketEvent {
     /// that will represent PropertyQuery.
     pub artifact_id: ProposalId,
     pub reason: Option<DbReason>,
+    /// Set for Events returned to client, that will acknowledge them.
+    pub delivery_cursor: Option<String>,
+    pub delivery_ts: Option<NaiveDateTime>,
 }
 
 #[derive(Clone, Debug, Insertable)]
@@ -1,6 +1,8 @@
 use chrono::{NaiveDateTime, TimeZone, Utc};
+use digest::Digest;
 use serde::{Deserialize, Serialize};
 use serde_json;
+use sha3::Sha3_256;
 
 use ya_client::model::{market::Offer as ClientOffer, ErrorMessage, NodeId};
 use ya_service_api_web::middleware::Identity;
@@ -24,6 +26,21 @@ pub struct Offer {
     pub insertion_ts: Option<NaiveDateTime>,
     /// Time when Offer expires; set by Provider.
     pub expiration_ts: NaiveDateTime,
+    /// Hex encoded Provider's signature of Offer id. Since id contains hash
+    /// of Offer content, signature proves that Offer wasn't modified by nodes,
+    /// that broadcasted it to us.
+    #[serde(default)]
+    pub signature: Option<String>,
+}
+
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum OfferSignatureError {
+    #[error("Offer [{0}] is not signed.")]
+    Missing(SubscriptionId),
+    #[error("Offer [{0}] signature has invalid format: {1}.")]
+    InvalidFormat(SubscriptionId, String),
+    #[error("Offer [{0}] signature doesn't match Provider [{1}]. Signed by [{2}].")]
+    Forged(SubscriptionId, NodeId, NodeId),
 }
 
 /// Keeps track of Offers, that were already unsubscribed.
@@ -76,6 +93,7 @@ impl Offer {
             creation_ts,
             insertion_ts: None, // Database will insert this timestamp.
             expiration_ts,
+            signature: None, // Signed by SubscriptionStore using Provider's identity.
         })
     }
 
@@ -114,6 +132,50 @@ impl Offer {
             &self.expiration_ts,
         )
     }
+
+    /// 32-byte digest of Offer id, that is signed by Provider.
+    pub fn signed_payload(&self) -> Vec<u8> {
+        Sha3_256::digest(self.id.to_string().as_bytes()).to_vec()
+    }
+
+    pub fn set_signature(&mut self, signature: &[u8]) {
+        self.signature = Some(hex::encode(signature));
+    }
+
+    /// Recovers signer of the Offer and checks if it is the Provider, that
+    /// created this Offer. Doesn't validate Offer content hash, so it should
+    /// be used together with `validate`.
+    pub fn verify_signature(&self) -> Result<(), OfferSignatureError> {
+        let signature = self
+            .signature
+            .as_ref()
+            .ok_or_else(|| OfferSignatureError::Missing(self.id.clone()))?;
+        let invalid = |e: String| OfferSignatureError::InvalidFormat(self.id.clone(), e);
+
+        let bytes = hex::decode(signature).map_err(|e| invalid(e.to_string()))?;
+        if bytes.len() != 65 {
+            return Err(invalid(format!("expected 65 bytes, got {}", bytes.len())));
+        }
+
+        let mut r = [0; 32];
+        let mut s = [0; 32];
+        r.copy_from_slice(&bytes[1..33]);
+        s.copy_from_slice(&bytes[33..65]);
+
+        let signer = ethsign::Signature { v: bytes[0], r, s }
+            .recover(&self.signed_payload())
+            .map_err(|e| invalid(e.to_string()))?;
+        let signer = NodeId::from(signer.address().as_ref());
+
+        if signer != self.node_id {
+            return Err(OfferSignatureError::Forged(
+                self.id.clone(),
+                self.node_id,
+                signer,
+            ));
+        }
+        Ok(())
+    }
 }
 
 /// PartialEq implementation that ignores insertion_ts.
@@ -125,6 +187,7 @@ impl PartialEq for Offer {
             && self.expiration_ts == other.expiration_ts
             && self.properties == other.properties
             && self.node_id == other.node_id
+            && self.signature == other.signature
     }
 }
 
@@ -155,6 +218,7 @@ mod tests {
                 NaiveDate::from_ymd(1970, 1, 1),
                 NaiveTime::from_hms(15, 1, 1),
             ),
+            signature: None,
         };
         assert!(offer.validate().is_err());
     }
@@ -178,6 +242,7 @@ mod tests {
                 NaiveDate::from_ymd(1970, 1, 1),
                 NaiveTime::from_hms(15, 1, 1),
             ),
+            signature: None,
         };
         let id = SubscriptionId::generate_id(
             &offer.properties,
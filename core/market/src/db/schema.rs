@@ -21,6 +21,7 @@ table! {
         creation_ts -> Timestamp,
         insertion_ts -> Nullable<Timestamp>,
         expiration_ts -> Timestamp,
+        signature -> Nullable<Text>,
     }
 }
 
@@ -42,6 +43,8 @@ table! {
         event_type -> Text,
         artifact_id -> Text,
         reason -> Nullable<Text>,
+        delivery_cursor -> Nullable<Text>,
+        delivery_ts -> Nullable<Timestamp>,
     }
 }
 
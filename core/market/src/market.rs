@@ -24,7 +24,7 @@ use ya_client::model::market::{
     Agreement, AgreementOperationEvent as ClientAgreementEvent, Demand, NewDemand, NewOffer, Offer,
     Reason,
 };
-use ya_core_model::market::{local, BUS_ID};
+use ya_core_model::market::{local, AgreementListEntry, AgreementListFilter, BUS_ID};
 use ya_service_api_interfaces::{Provider, Service};
 use ya_service_api_web::middleware::Identity;
 
@@ -32,6 +32,7 @@ use crate::db::DbMixedExecutor;
 use ya_service_api_web::scope::ExtendableScope;
 
 pub mod agreement;
+mod webhook;
 
 #[derive(Error, Debug)]
 pub enum MarketError {
@@ -47,6 +48,8 @@ pub enum MarketError {
     DemandError(#[from] DemandError),
     #[error(transparent)]
     Negotiation(#[from] NegotiationError),
+    #[error("Node is shutting down and doesn't accept new {0}.")]
+    Draining(&'static str),
 }
 
 #[derive(Error, Debug)]
@@ -89,7 +92,7 @@ impl MarketService {
         db.disk_db
             .apply_migration(crate::db::migrations::run_with_output)?;
 
-        let store = SubscriptionStore::new(db.clone(), config.clone());
+        let store = SubscriptionStore::new(db.clone(), identity_api.clone(), config.clone());
         let (matcher, listeners) = Matcher::new(store.clone(), identity_api, config.clone())?;
 
         // We need the same notifier for both Provider and Requestor implementation since we have
@@ -142,6 +145,8 @@ impl MarketService {
         ctx: &Context,
     ) -> anyhow::Result<()> {
         let market = MARKET.get_or_init_market(&ctx.component())?;
+        webhook::register_sources(market.clone());
+        agreement::bind_terminate_gsb(market.clone(), local::BUS_ID);
         Ok(market.bind_gsb(BUS_ID, local::BUS_ID).await?)
     }
 
@@ -187,6 +192,9 @@ impl MarketService {
         offer: &NewOffer,
         id: &Identity,
     ) -> Result<SubscriptionId, MarketError> {
+        if ya_service_api::drain::is_draining() {
+            return Err(MarketError::Draining("Offers"));
+        }
         let offer = self.matcher.subscribe_offer(offer, id).await?;
         self.provider_engine.subscribe_offer(&offer).await?;
 
@@ -212,6 +220,9 @@ impl MarketService {
         demand: &NewDemand,
         id: &Identity,
     ) -> Result<SubscriptionId, MarketError> {
+        if ya_service_api::drain::is_draining() {
+            return Err(MarketError::Draining("Demands"));
+        }
         let demand = self.matcher.subscribe_demand(demand, id).await?;
         self.requestor_engine.subscribe_demand(&demand).await?;
 
@@ -253,6 +264,22 @@ impl MarketService {
         }
     }
 
+    pub async fn list_agreements(
+        &self,
+        id: &Identity,
+        filter: AgreementListFilter,
+    ) -> Result<Vec<AgreementListEntry>, AgreementError> {
+        Ok(self
+            .db
+            .as_dao::<AgreementDao>()
+            .list(id.identity, filter, Utc::now().naive_utc())
+            .await
+            .map_err(AgreementError::List)?
+            .into_iter()
+            .map(|agreement| agreement.into_list_entry())
+            .collect())
+    }
+
     pub async fn query_agreement_events(
         &self,
         session_id: &AppSessionId,
@@ -267,7 +294,7 @@ impl MarketService {
             .query_agreement_events(session_id, timeout, max_events, after_timestamp, id)
             .await?
             .into_iter()
-            .map(|event| event.into_client())
+            .filter_map(|event| event.into_client())
             .collect())
     }
 
@@ -285,7 +312,7 @@ impl MarketService {
 }
 
 impl Service for MarketService {
-    type Cli = ();
+    type Cli = crate::cli::MarketCli;
 }
 
 // =========================================== //
@@ -1,19 +1,25 @@
 use chrono;
+use std::sync::Arc;
 
 use ya_client::model::market::Agreement as ClientAgreement;
 use ya_core_model::{
-    market::{GetAgreement, RpcMessageError},
+    market::{
+        AgreementListEntry, GetAgreement, ListAgreements, RpcMessageError, TerminateAgreement,
+    },
     Role,
 };
+use ya_service_api_web::middleware::Identity;
 use ya_service_bus::typed::ServiceBinder;
 
 use crate::db::dao::AgreementDao;
 use crate::db::model::{AgreementId, Owner};
 use crate::db::DbMixedExecutor;
+use crate::MarketService;
 
-pub async fn bind_gsb(db: DbMixedExecutor, public_prefix: &str, _local_prefix: &str) {
+pub async fn bind_gsb(db: DbMixedExecutor, public_prefix: &str, local_prefix: &str) {
     log::trace!("Binding market agreement public service to service bus");
     ServiceBinder::new(public_prefix, &db, ()).bind(get_agreement);
+    ServiceBinder::new(local_prefix, &db, ()).bind(list_agreements);
     log::debug!("Successfully bound market agreement public service to service bus");
 }
 
@@ -42,3 +48,41 @@ async fn get_agreement(
         .into_client()
         .map_err(|e| RpcMessageError::Market(e.to_string()))?)
 }
+
+async fn list_agreements(
+    db: DbMixedExecutor,
+    _sender_id: String,
+    msg: ListAgreements,
+) -> Result<Vec<AgreementListEntry>, RpcMessageError> {
+    let dao = db.as_dao::<AgreementDao>();
+    let now = chrono::Utc::now().naive_utc();
+    Ok(dao
+        .list(msg.node_id, msg.filter, now)
+        .await
+        .map_err(|e| RpcMessageError::Market(e.to_string()))?
+        .into_iter()
+        .map(|agreement| agreement.into_list_entry())
+        .collect())
+}
+
+pub(crate) fn bind_terminate_gsb(market: Arc<MarketService>, local_prefix: &str) {
+    ServiceBinder::new(local_prefix, &market.db.clone(), market)
+        .bind_with_processor(terminate_agreement);
+}
+
+async fn terminate_agreement(
+    _db: DbMixedExecutor,
+    market: Arc<MarketService>,
+    _sender_id: String,
+    msg: TerminateAgreement,
+) -> Result<(), RpcMessageError> {
+    let id = Identity {
+        identity: msg.node_id,
+        name: String::new(),
+        role: String::new(),
+    };
+    market
+        .terminate_agreement(id, msg.agreement_id, msg.reason)
+        .await
+        .map_err(|e| RpcMessageError::Market(e.to_string()))
+}
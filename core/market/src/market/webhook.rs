@@ -0,0 +1,122 @@
+//! Negotiation events of Offers and Demands delivered by webhooks.
+//! Events are fetched with acknowledgement, so they aren't lost, when
+//! delivery is interrupted. Acknowledged events are removed from queue, so
+//! webhook fetches only events of its types and leaves others for REST API.
+
+use anyhow::anyhow;
+use serde::Serialize;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use ya_client::model::NodeId;
+use ya_service_api_web::webhook::{register_source, EventBatch, EventSource};
+
+use crate::db::model::{EventType, Owner, SubscriptionId};
+use crate::MarketService;
+
+pub const OFFER_EVENTS_SOURCE: &str = "offers";
+pub const DEMAND_EVENTS_SOURCE: &str = "demands";
+
+pub(crate) fn register_sources(market: Arc<MarketService>) {
+    register_source(
+        OFFER_EVENTS_SOURCE,
+        SubscriptionEvents {
+            market: market.clone(),
+            owner: Owner::Provider,
+        },
+    );
+    register_source(
+        DEMAND_EVENTS_SOURCE,
+        SubscriptionEvents {
+            market,
+            owner: Owner::Requestor,
+        },
+    );
+}
+
+struct SubscriptionEvents {
+    market: Arc<MarketService>,
+    owner: Owner,
+}
+
+fn subscription_id(subject: Option<&str>) -> anyhow::Result<SubscriptionId> {
+    let subject = subject.ok_or_else(|| anyhow!("subscription id is required"))?;
+    Ok(SubscriptionId::from_str(subject)?)
+}
+
+fn parse_event_types(owner: Owner, names: &[String]) -> anyhow::Result<Vec<EventType>> {
+    let all = EventType::of_owner(owner);
+    names
+        .iter()
+        .map(|name| {
+            all.iter()
+                .find(|event_type| event_type.client_event_type() == name)
+                .cloned()
+                .ok_or_else(|| anyhow!("unknown event type '{}'", name))
+        })
+        .collect()
+}
+
+fn to_values<T: Serialize>(events: Vec<T>) -> serde_json::Result<Vec<serde_json::Value>> {
+    events.iter().map(serde_json::to_value).collect()
+}
+
+#[async_trait::async_trait(?Send)]
+impl EventSource for SubscriptionEvents {
+    async fn validate(&self, owner: NodeId, subject: Option<&str>) -> anyhow::Result<()> {
+        let id = subscription_id(subject)?;
+        let node_id = match self.owner {
+            Owner::Provider => self.market.matcher.store.get_offer(&id).await?.node_id,
+            Owner::Requestor => self.market.matcher.store.get_demand(&id).await?.node_id,
+        };
+
+        if node_id != owner {
+            anyhow::bail!("subscription [{}] doesn't belong to {}", id, owner);
+        }
+        Ok(())
+    }
+
+    async fn poll(
+        &self,
+        _owner: NodeId,
+        subject: Option<&str>,
+        event_types: &[String],
+        _cursor: Option<String>,
+        timeout: Duration,
+    ) -> anyhow::Result<EventBatch> {
+        // Previous batch is always acknowledged, so there is nothing to resume.
+        let id = subscription_id(subject)?;
+        let event_types = parse_event_types(self.owner, event_types)?;
+        let timeout = timeout.as_secs_f32();
+        let (cursor, events) = match self.owner {
+            Owner::Provider => {
+                let (cursor, events) = self
+                    .market
+                    .provider_engine
+                    .query_events_with_ack(&id, timeout, None, None, event_types)
+                    .await?;
+                (cursor, to_values(events)?)
+            }
+            Owner::Requestor => {
+                let (cursor, events) = self
+                    .market
+                    .requestor_engine
+                    .query_events_with_ack(&id, timeout, None, None, event_types)
+                    .await?;
+                (cursor, to_values(events)?)
+            }
+        };
+
+        Ok(EventBatch { events, cursor })
+    }
+
+    async fn ack(&self, _owner: NodeId, subject: Option<&str>, cursor: &str) -> anyhow::Result<()> {
+        let id = subscription_id(subject)?;
+        match self.owner {
+            Owner::Provider => self.market.provider_engine.ack_events(&id, cursor).await?,
+            Owner::Requestor => self.market.requestor_engine.ack_events(&id, cursor).await?,
+        }
+        Ok(())
+    }
+}
@@ -16,6 +16,7 @@ use crate::db::model::{Demand, Offer, SubscriptionId};
 use crate::identity::IdentityApi;
 use crate::protocol::discovery::{builder::DiscoveryBuilder, Discovery};
 
+pub(crate) mod cache;
 pub(crate) mod cyclic;
 pub mod error;
 pub(crate) mod handlers;
@@ -85,6 +86,9 @@ impl Matcher {
         // Initialize counters to 0 value. Otherwise they won't appear on metrics endpoint
         // until first change to value will be made.
         counter!("market.offers.incoming", 0);
+        counter!("market.offers.incoming.forged", 0);
+        counter!("market.offers.incoming.unsigned", 0);
+        counter!("market.offers.incoming.cached", 0);
         counter!("market.offers.broadcasts", 0);
         counter!("market.offers.broadcasts.skip", 0);
         counter!("market.offers.broadcasts.net", 0);
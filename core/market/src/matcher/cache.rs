@@ -0,0 +1,153 @@
+use chrono::NaiveDateTime;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use crate::db::model::{Offer, SubscriptionId};
+
+/// Keeps limited number of Offers from other nodes, that we aren't interested in.
+/// We don't store them in database, but we still must be able to serve them
+/// to nodes, that will ask for them after our re-broadcast.
+#[derive(Clone)]
+pub struct OfferCache {
+    inner: Arc<Mutex<OfferCacheInner>>,
+}
+
+struct OfferCacheInner {
+    capacity: usize,
+    offers: HashMap<SubscriptionId, Offer>,
+    /// Insertion order used to evict the oldest Offers.
+    order: VecDeque<SubscriptionId>,
+}
+
+impl OfferCache {
+    pub fn new(capacity: usize) -> Self {
+        OfferCache {
+            inner: Arc::new(Mutex::new(OfferCacheInner {
+                capacity,
+                offers: HashMap::new(),
+                order: VecDeque::new(),
+            })),
+        }
+    }
+
+    /// Returns false, if Offer was already in cache.
+    pub fn insert(&self, offer: Offer, now: NaiveDateTime) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.capacity == 0 || inner.offers.contains_key(&offer.id) {
+            return false;
+        }
+
+        inner.remove_expired(now);
+        while inner.offers.len() >= inner.capacity {
+            match inner.order.pop_front() {
+                Some(id) => inner.offers.remove(&id),
+                None => break,
+            };
+        }
+
+        inner.order.push_back(offer.id.clone());
+        inner.offers.insert(offer.id.clone(), offer);
+        true
+    }
+
+    pub fn contains(&self, id: &SubscriptionId) -> bool {
+        self.inner.lock().unwrap().offers.contains_key(id)
+    }
+
+    /// Returns not expired Offers with given ids, that are present in cache.
+    pub fn get(&self, ids: &[SubscriptionId], now: NaiveDateTime) -> Vec<Offer> {
+        let inner = self.inner.lock().unwrap();
+        ids.iter()
+            .filter_map(|id| inner.offers.get(id))
+            .filter(|offer| offer.expiration_ts >= now)
+            .cloned()
+            .collect()
+    }
+
+    pub fn remove(&self, id: &SubscriptionId) -> Option<Offer> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.order.retain(|cached| cached != id);
+        inner.offers.remove(id)
+    }
+
+    /// Removes from cache and returns all not expired Offers fulfilling predicate.
+    pub fn take_matching(
+        &self,
+        now: NaiveDateTime,
+        predicate: impl Fn(&Offer) -> bool,
+    ) -> Vec<Offer> {
+        let mut inner = self.inner.lock().unwrap();
+        inner.remove_expired(now);
+
+        let taken = inner
+            .offers
+            .values()
+            .filter(|offer| predicate(offer))
+            .map(|offer| offer.id.clone())
+            .collect::<Vec<_>>();
+
+        inner.order.retain(|id| !taken.contains(id));
+        taken
+            .iter()
+            .filter_map(|id| inner.offers.remove(id))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.lock().unwrap().offers.len()
+    }
+}
+
+impl OfferCacheInner {
+    fn remove_expired(&mut self, now: NaiveDateTime) {
+        let offers = &mut self.offers;
+        self.order.retain(|id| match offers.get(id) {
+            Some(offer) if offer.expiration_ts < now => {
+                offers.remove(id);
+                false
+            }
+            _ => true,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, Utc};
+
+    use crate::testing::mock_offer::{sample_offer, sample_offer_with_expiration};
+
+    #[test]
+    fn test_cache_evicts_oldest() {
+        let now = Utc::now().naive_utc();
+        let cache = OfferCache::new(2);
+        let offers = vec![sample_offer(), sample_offer(), sample_offer()];
+
+        for offer in offers.iter() {
+            assert!(cache.insert(offer.clone(), now));
+        }
+        assert!(!cache.insert(offers[2].clone(), now));
+
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains(&offers[0].id));
+        assert!(cache.contains(&offers[1].id));
+        assert!(cache.contains(&offers[2].id));
+    }
+
+    #[test]
+    fn test_cache_skips_expired() {
+        let now = Utc::now().naive_utc();
+        let cache = OfferCache::new(10);
+        let expired = sample_offer_with_expiration(now - Duration::minutes(1));
+        let active = sample_offer();
+
+        cache.insert(expired.clone(), now);
+        cache.insert(active.clone(), now);
+
+        let ids = vec![expired.id.clone(), active.id.clone()];
+        assert_eq!(cache.get(&ids, now), vec![active.clone()]);
+        assert_eq!(cache.take_matching(now, |_| true), vec![active]);
+        assert_eq!(cache.len(), 0);
+    }
+}
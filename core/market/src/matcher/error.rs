@@ -1,4 +1,4 @@
-use crate::db::model::{SubscriptionId, SubscriptionValidationError};
+use crate::db::model::{OfferSignatureError, SubscriptionId, SubscriptionValidationError};
 use crate::db::DbError;
 use crate::identity::IdentityError;
 use crate::protocol::discovery::error::DiscoveryInitError;
@@ -58,6 +58,10 @@ pub enum SaveOfferError {
     #[error(transparent)]
     SubscriptionValidation(#[from] SubscriptionValidationError),
     #[error(transparent)]
+    Signature(#[from] OfferSignatureError),
+    #[error("Failed to sign Offer [{1}]. Error: {0}.")]
+    Sign(IdentityError, SubscriptionId),
+    #[error(transparent)]
     JsonObjectExpected(#[from] serde_json::error::Error),
     #[error("Wrong Offer [{id}] state {state:?} after inserted: {inserted}.")]
     WrongState {
@@ -3,13 +3,16 @@ use futures::StreamExt;
 use metrics::{counter, value};
 
 use crate::db::model::{Offer, SubscriptionId};
-use crate::matcher::error::ModifyOfferError;
+use crate::matcher::error::{ModifyOfferError, SaveOfferError};
 use crate::protocol::discovery::{
     error::DiscoveryRemoteError,
     message::{OffersBcast, OffersRetrieved, RetrieveOffers, UnsubscribedOffersBcast},
 };
 
-use super::{resolver::Resolver, store::SubscriptionStore};
+use super::{
+    resolver::Resolver,
+    store::{ReceivedOffer, SubscriptionStore},
+};
 
 /// Returns only those of input offers ids, that were not yet known.
 pub(super) async fn filter_out_known_offer_ids(
@@ -26,37 +29,57 @@ pub(super) async fn filter_out_known_offer_ids(
         .map_err(|e| log::warn!("Error filtering Offers. Error: {}", e))?)
 }
 
-/// Returns only ids of those from input offers, that was successfully stored locally.
-/// Also triggers Resolver to match newly stored Offers against local Demands.
+/// Returns only ids of those from input offers, that was successfully stored locally
+/// or cached for re-broadcast. Also triggers Resolver to match newly stored Offers
+/// against local Demands.
 pub(super) async fn receive_remote_offers(
     resolver: Resolver,
     caller: String,
     msg: OffersRetrieved,
 ) -> Result<Vec<SubscriptionId>, ()> {
+    let mut num_stored = 0;
     let added_offers_ids = futures::stream::iter(msg.offers.into_iter())
         .filter_map(|offer| {
             let resolver = resolver.clone();
+            let caller = &caller;
             async move {
                 resolver
                     .store
-                    .save_offer(offer)
+                    .receive_offer(offer)
                     .await
-                    .map(|offer| {
-                        resolver.receive(&offer);
-                        offer.id
+                    .map(|received| match received {
+                        ReceivedOffer::Stored(offer) => {
+                            resolver.receive(&offer);
+                            (true, offer.id)
+                        }
+                        ReceivedOffer::Cached(id) => (false, id),
+                    })
+                    .map_err(|e| match e {
+                        SaveOfferError::SubscriptionValidation(_)
+                        | SaveOfferError::Signature(_) => {
+                            counter!("market.offers.incoming.forged", 1);
+                            log::warn!("Rejecting forged Offer from [{}]: {}", caller, e)
+                        }
+                        _ => log::info!("Skipping foreign Offer: {}", e),
                     })
-                    .map_err(|e| log::info!("Skipping foreign Offer: {}", e))
                     .ok()
             }
         })
+        .map(|(stored, id)| {
+            num_stored += stored as u64;
+            id
+        })
         .collect::<Vec<SubscriptionId>>()
         .await;
 
-    counter!("market.offers.incoming", added_offers_ids.len() as u64);
+    let num_cached = added_offers_ids.len() as u64 - num_stored;
+    counter!("market.offers.incoming", num_stored);
+    counter!("market.offers.incoming.cached", num_cached);
     log::trace!(
-        "Received {} new Offers from [{}]",
+        "Received {} new Offers from [{}], {} of them cached only",
         added_offers_ids.len(),
-        caller
+        caller,
+        num_cached,
     );
     Ok(added_offers_ids)
 }
@@ -109,7 +109,7 @@ impl Resolver {
     }
 }
 
-fn matches(offer: &Offer, demand: &Demand) -> bool {
+pub(crate) fn matches(offer: &Offer, demand: &Demand) -> bool {
     if offer.node_id == demand.node_id {
         log::info!(
             "Rejecting Demand Offer pair from single identity. node_id: {}",
@@ -133,6 +133,24 @@ fn matches(offer: &Offer, demand: &Demand) -> bool {
     }
 }
 
+/// Checks Offer properties against constraints expression, that isn't
+/// related to any Demand.
+pub(crate) fn matches_filter(offer: &Offer, constraints: &str) -> bool {
+    match match_demand_offer("{}", constraints, &offer.properties, "()") {
+        Ok(Match::Yes) => true,
+        Err(e) => {
+            log::warn!(
+                "Matching [{}] against filter [{}] error: {}",
+                offer.id,
+                constraints,
+                e
+            );
+            false
+        }
+        _ => false,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::matcher::resolver::matches;
@@ -8,22 +8,41 @@ use ya_service_api_web::middleware::Identity;
 
 use crate::config::Config;
 use crate::db::dao::*;
-use crate::db::model::{Demand, Offer, SubscriptionId};
+use crate::db::model::{Demand, Offer, OfferSignatureError, SubscriptionId};
 use crate::db::DbMixedExecutor;
+use crate::identity::IdentityApi;
+use crate::matcher::cache::OfferCache;
 use crate::matcher::error::{
     DemandError, ModifyOfferError, QueryDemandsError, QueryOfferError, QueryOffersError,
     SaveOfferError,
 };
+use crate::matcher::resolver::{matches, matches_filter};
+
+/// Result of receiving Offer from other node.
+pub enum ReceivedOffer {
+    /// Offer was saved in database.
+    Stored(Offer),
+    /// Offer isn't interesting for us. It was kept in memory cache only,
+    /// so we are able to re-broadcast it.
+    Cached(SubscriptionId),
+}
 
 #[derive(Clone)]
 pub struct SubscriptionStore {
     pub(crate) db: DbMixedExecutor,
+    identity: Arc<dyn IdentityApi>,
+    offer_cache: OfferCache,
     config: Arc<Config>,
 }
 
 impl SubscriptionStore {
-    pub fn new(db: DbMixedExecutor, config: Arc<Config>) -> Self {
-        SubscriptionStore { db, config }
+    pub fn new(db: DbMixedExecutor, identity: Arc<dyn IdentityApi>, config: Arc<Config>) -> Self {
+        SubscriptionStore {
+            db,
+            identity,
+            offer_cache: OfferCache::new(config.offer_filter.cache_size),
+            config,
+        }
     }
 
     /// returns newly created offer with insertion_ts
@@ -35,14 +54,64 @@ impl SubscriptionStore {
         let creation_ts = Utc::now().naive_utc();
         // TODO: provider agent should set expiration.
         let expiration_ts = creation_ts + self.config.subscription.default_ttl;
-        let offer = Offer::from_new(offer, &id, creation_ts, expiration_ts)?;
+        let mut offer = Offer::from_new(offer, &id, creation_ts, expiration_ts)?;
+
+        // Signature allows other nodes to check, that Offer wasn't modified
+        // by anyone on it's way through the network.
+        let signature = self
+            .identity
+            .sign(&offer.node_id, offer.signed_payload())
+            .await
+            .map_err(|e| SaveOfferError::Sign(e, offer.id.clone()))?;
+        offer.set_signature(&signature);
+
         self.insert_offer(offer).await
     }
 
-    /// returns saved offer with insertion_ts
-    pub async fn save_offer(&self, offer: Offer) -> Result<Offer, SaveOfferError> {
+    /// Saves Offer received from other node. Stored Offer is returned with insertion_ts.
+    /// If Offers filtering is enabled, Offers not matching any of our Demands
+    /// are only cached in memory.
+    pub async fn receive_offer(&self, offer: Offer) -> Result<ReceivedOffer, SaveOfferError> {
         offer.validate()?;
-        self.insert_offer(offer).await
+        match offer.verify_signature() {
+            Err(OfferSignatureError::Missing(id)) if !self.config.offer_signature.required => {
+                counter!("market.offers.incoming.unsigned", 1);
+                log::debug!("Accepting unsigned Offer [{}].", id);
+            }
+            result => result?,
+        }
+
+        if !self.config.offer_filter.enabled || self.is_interesting(&offer).await? {
+            return Ok(ReceivedOffer::Stored(self.insert_offer(offer).await?));
+        }
+
+        let id = offer.id.clone();
+        let now = Utc::now().naive_utc();
+        if offer.expiration_ts < now {
+            return Err(SaveOfferError::Expired(id));
+        }
+        match self.offer_cache.insert(offer, now) {
+            true => Ok(ReceivedOffer::Cached(id)),
+            false => Err(SaveOfferError::Exists(id)),
+        }
+    }
+
+    /// Checks if Offer matches filter expression from config or any of our Demands.
+    async fn is_interesting(&self, offer: &Offer) -> Result<bool, SaveOfferError> {
+        if let Some(filter) = &self.config.offer_filter.constraints {
+            if matches_filter(offer, filter) {
+                return Ok(true);
+            }
+        }
+
+        Ok(self
+            .db
+            .as_dao::<DemandDao>()
+            .get_demands(None, None, Utc::now().naive_utc())
+            .await
+            .map_err(|e| SaveOfferError::Save(e, offer.id.clone()))?
+            .iter()
+            .any(|demand| matches(offer, demand)))
     }
 
     async fn insert_offer(&self, mut offer: Offer) -> Result<Offer, SaveOfferError> {
@@ -120,16 +189,21 @@ impl SubscriptionStore {
             .collect())
     }
 
+    /// Returns Offers with given ids, including Offers we only cached for re-broadcasts.
     pub async fn get_offers(
         &self,
         ids: Vec<SubscriptionId>,
     ) -> Result<Vec<Offer>, QueryOffersError> {
-        Ok(self
+        let now = Utc::now().naive_utc();
+        let mut offers = self
             .db
             .as_dao::<OfferDao>()
-            .get_offers(Some(ids), None, None, Utc::now().naive_utc())
+            .get_offers(Some(ids.clone()), None, None, now)
             .await
-            .map_err(QueryOffersError::from)?)
+            .map_err(QueryOffersError::from)?;
+
+        offers.extend(self.offer_cache.get(&ids, now));
+        Ok(offers)
     }
 
     pub async fn get_offers_before(
@@ -167,6 +241,7 @@ impl SubscriptionStore {
             .into_iter()
             .collect::<HashSet<SubscriptionId>>()
             .difference(&known_ids)
+            .filter(|id| !self.offer_cache.contains(id))
             .cloned()
             .collect())
     }
@@ -217,6 +292,12 @@ impl SubscriptionStore {
         //     }
         // }
 
+        // Offers, that we only cached, have no state in database.
+        if !local_caller && self.offer_cache.remove(offer_id).is_some() {
+            log::debug!("Removing cached unsubscribed Offer [{}].", offer_id);
+            return Ok(());
+        }
+
         // If this fn was called before, we won't remove our Offer below,
         // because `Unsubscribed` error will pop-up here.
         self.mark_offer_unsubscribed(offer_id).await?;
@@ -243,6 +324,19 @@ impl SubscriptionStore {
         // TODO: requestor agent should set expiration.
         let expiration_ts = creation_ts + self.config.subscription.default_ttl;
         let demand = Demand::from_new(demand, &id, creation_ts, expiration_ts)?;
+
+        // Offers matching new Demand could have been only cached, so we must
+        // store them before Demand, to be resolved together with it.
+        for offer in self
+            .offer_cache
+            .take_matching(creation_ts, |offer| matches(offer, &demand))
+        {
+            let id = offer.id.clone();
+            if let Err(e) = self.insert_offer(offer).await {
+                log::warn!("Failed to store cached Offer [{}]. Error: {}", id, e);
+            }
+        }
+
         self.db
             .as_dao::<DemandDao>()
             .insert(&demand)
@@ -1,3 +1,4 @@
+mod amendment;
 mod common;
 pub mod error;
 mod notifier;
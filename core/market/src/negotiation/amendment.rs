@@ -0,0 +1,223 @@
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{oneshot, Notify};
+
+use ya_client::model::market::Reason;
+use ya_client::model::NodeId;
+use ya_core_model::market::{AgreementAmendment, AgreementAmendmentEvent, AmendmentEventType};
+
+use crate::db::model::AgreementId;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AmendmentDecision {
+    Approved,
+    Rejected(Option<Reason>),
+}
+
+struct PendingAmendment {
+    provider_id: NodeId,
+    event: AgreementAmendmentEvent,
+    collected: bool,
+    /// `None` for amendments, which were already applied.
+    decision: Option<oneshot::Sender<AmendmentDecision>>,
+}
+
+/// Agreement amendments received from Requestors, that wait for Provider's decision.
+/// Requestor waits for the decision synchronously, so amendments don't outlive
+/// GSB call and there's no need to store them in database.
+/// Amendments applied to the Agreement are queued once more as `Amended` events,
+/// which are removed after Provider collects them.
+#[derive(Clone, Default)]
+pub struct AmendmentQueue {
+    pending: Arc<Mutex<HashMap<String, PendingAmendment>>>,
+    notify: Arc<Notify>,
+}
+
+impl AmendmentQueue {
+    pub fn add(
+        &self,
+        provider_id: NodeId,
+        agreement_id: &AgreementId,
+        amendment: AgreementAmendment,
+    ) -> (String, oneshot::Receiver<AmendmentDecision>) {
+        let (sender, receiver) = oneshot::channel();
+        let amendment_id = self.insert(
+            provider_id,
+            agreement_id,
+            amendment,
+            AmendmentEventType::Proposed,
+            Some(sender),
+        );
+        (amendment_id, receiver)
+    }
+
+    /// Notifies Provider, that amendment was stored and applies to the Agreement.
+    pub fn amended(
+        &self,
+        provider_id: NodeId,
+        agreement_id: &AgreementId,
+        amendment: AgreementAmendment,
+    ) {
+        self.insert(
+            provider_id,
+            agreement_id,
+            amendment,
+            AmendmentEventType::Amended,
+            None,
+        );
+    }
+
+    fn insert(
+        &self,
+        provider_id: NodeId,
+        agreement_id: &AgreementId,
+        amendment: AgreementAmendment,
+        event_type: AmendmentEventType,
+        decision: Option<oneshot::Sender<AmendmentDecision>>,
+    ) -> String {
+        let amendment_id = uuid::Uuid::new_v4().to_simple().to_string();
+        let event = AgreementAmendmentEvent {
+            amendment_id: amendment_id.clone(),
+            agreement_id: agreement_id.into_client(),
+            event_date: Utc::now(),
+            event_type,
+            amendment,
+        };
+
+        self.pending.lock().unwrap().insert(
+            amendment_id.clone(),
+            PendingAmendment {
+                provider_id,
+                event,
+                collected: false,
+                decision,
+            },
+        );
+        self.notify.notify_waiters();
+        amendment_id
+    }
+
+    pub fn remove(&self, amendment_id: &str) {
+        self.pending.lock().unwrap().remove(amendment_id);
+    }
+
+    /// Passes decision to waiting Requestor. Returns false, if amendment
+    /// doesn't exist or Requestor stopped waiting.
+    pub fn decide(
+        &self,
+        provider_id: NodeId,
+        amendment_id: &str,
+        decision: AmendmentDecision,
+    ) -> bool {
+        let mut pending = self.pending.lock().unwrap();
+        match pending.get(amendment_id) {
+            Some(amendment)
+                if amendment.provider_id == provider_id && amendment.decision.is_some() => {}
+            _ => return false,
+        }
+        pending
+            .remove(amendment_id)
+            .and_then(|amendment| amendment.decision)
+            .map(|decision_sender| decision_sender.send(decision).is_ok())
+            .unwrap_or(false)
+    }
+
+    /// Waits until amendments not yet collected by Provider appear.
+    pub async fn collect(
+        &self,
+        provider_id: NodeId,
+        timeout: f32,
+        max_events: Option<i32>,
+    ) -> Vec<AgreementAmendmentEvent> {
+        let deadline = Instant::now() + Duration::from_secs_f32(timeout.max(0.0));
+        let max_events = max_events.unwrap_or(i32::MAX).max(0) as usize;
+        loop {
+            // Create future before checking queue, so we won't miss notification.
+            let notified = self.notify.notified();
+            let events = self.take_uncollected(provider_id, max_events);
+            let now = Instant::now();
+            if !events.is_empty() || now >= deadline {
+                return events;
+            }
+            tokio::time::timeout(deadline - now, notified).await.ok();
+        }
+    }
+
+    fn take_uncollected(
+        &self,
+        provider_id: NodeId,
+        max_events: usize,
+    ) -> Vec<AgreementAmendmentEvent> {
+        let mut pending = self.pending.lock().unwrap();
+        let mut uncollected = pending
+            .values_mut()
+            .filter(|amendment| amendment.provider_id == provider_id && !amendment.collected)
+            .collect::<Vec<_>>();
+        uncollected.sort_by_key(|amendment| amendment.event.event_date);
+        let events = uncollected
+            .into_iter()
+            .take(max_events)
+            .map(|amendment| {
+                amendment.collected = true;
+                amendment.event.clone()
+            })
+            .collect::<Vec<_>>();
+
+        // Nobody waits for decision about applied amendments.
+        events
+            .iter()
+            .filter(|event| event.event_type == AmendmentEventType::Amended)
+            .for_each(|event| {
+                pending.remove(&event.amendment_id);
+            });
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+    use std::str::FromStr;
+
+    fn amendment() -> AgreementAmendment {
+        AgreementAmendment {
+            valid_to: Utc::now() + ChronoDuration::hours(1),
+            payment_timeout_sec: None,
+            debit_note_interval_sec: None,
+        }
+    }
+
+    fn agreement_id() -> AgreementId {
+        AgreementId::from_str(&format!("P-{}", "0a".repeat(32))).unwrap()
+    }
+
+    #[tokio::test]
+    async fn amended_event_collected_once() {
+        let queue = AmendmentQueue::default();
+        let provider_id = NodeId::default();
+        let agreement_id = agreement_id();
+
+        let (amendment_id, decision) = queue.add(provider_id, &agreement_id, amendment());
+        let events = queue.collect(provider_id, 0.0, None).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, AmendmentEventType::Proposed);
+        assert!(queue.decide(provider_id, &amendment_id, AmendmentDecision::Approved));
+        assert_eq!(decision.await.unwrap(), AmendmentDecision::Approved);
+
+        queue.amended(provider_id, &agreement_id, amendment());
+        let events = queue.collect(provider_id, 0.0, None).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, AmendmentEventType::Amended);
+        assert_eq!(events[0].agreement_id, agreement_id.into_client());
+        // Applied amendment can't be decided about.
+        assert!(!queue.decide(
+            provider_id,
+            &events[0].amendment_id,
+            AmendmentDecision::Approved
+        ));
+        assert!(queue.collect(provider_id, 0.0, None).await.is_empty());
+    }
+}
@@ -7,6 +7,7 @@ use std::time::{Duration, Instant};
 use ya_client::model::market::{proposal::Proposal as ClientProposal, reason::Reason, NewProposal};
 use ya_client::model::NodeId;
 use ya_market_resolver::{match_demand_offer, Match};
+use ya_metrics::agreement_counter;
 use ya_service_api_web::middleware::Identity;
 
 use crate::config::Config;
@@ -17,8 +18,8 @@ use crate::db::{
         TakeEventsError,
     },
     model::{
-        Agreement, AgreementEvent, AgreementId, AgreementState, AppSessionId, MarketEvent, Owner,
-        Proposal, ProposalId, ProposalState, SubscriptionId,
+        Agreement, AgreementEvent, AgreementId, AgreementState, AppSessionId, EventType,
+        MarketEvent, Owner, Proposal, ProposalId, ProposalState, SubscriptionId,
     },
     DbMixedExecutor,
 };
@@ -51,6 +52,26 @@ use crate::utils::AgreementLock;
 
 type IsFirst = bool;
 
+/// Negotiation events returned by single query.
+#[derive(Default)]
+pub struct EventsBatch {
+    /// Cursor acknowledging these events. Set only for queries
+    /// with acknowledgement, that returned any events.
+    pub cursor: Option<String>,
+    pub events: Vec<MarketEvent>,
+}
+
+enum Delivery {
+    /// Events are removed from queue, when returned.
+    AtMostOnce,
+    /// Events are removed from queue, when acknowledged by client.
+    /// Client can receive only some types of events, leaving others in queue.
+    AtLeastOnce {
+        resume: Option<String>,
+        event_types: Vec<EventType>,
+    },
+}
+
 #[derive(Clone)]
 pub struct CommonBroker {
     pub(super) db: DbMixedExecutor,
@@ -178,6 +199,66 @@ impl CommonBroker {
         max_events: Option<i32>,
         owner: Owner,
     ) -> Result<Vec<MarketEvent>, QueryEventsError> {
+        Ok(self
+            .poll_events(
+                subscription_id,
+                timeout,
+                max_events,
+                owner,
+                Delivery::AtMostOnce,
+            )
+            .await?
+            .events)
+    }
+
+    /// Returned events stay in queue until acknowledged with batch cursor.
+    /// Events not acknowledged in time are returned again. Passing cursor
+    /// of previous batch in `resume` returns this batch once more, if it wasn't
+    /// acknowledged yet. Only events of `event_types` are returned, unless
+    /// it is empty.
+    pub async fn query_events_with_ack(
+        &self,
+        subscription_id: &SubscriptionId,
+        timeout: f32,
+        max_events: Option<i32>,
+        owner: Owner,
+        resume: Option<String>,
+        event_types: Vec<EventType>,
+    ) -> Result<EventsBatch, QueryEventsError> {
+        self.poll_events(
+            subscription_id,
+            timeout,
+            max_events,
+            owner,
+            Delivery::AtLeastOnce {
+                resume,
+                event_types,
+            },
+        )
+        .await
+    }
+
+    pub async fn ack_events(
+        &self,
+        subscription_id: &SubscriptionId,
+        cursor: &str,
+    ) -> Result<usize, QueryEventsError> {
+        Ok(self
+            .db
+            .as_dao::<NegotiationEventsDao>()
+            .ack_events(subscription_id, cursor)
+            .await
+            .map_err(TakeEventsError::from)?)
+    }
+
+    async fn poll_events(
+        &self,
+        subscription_id: &SubscriptionId,
+        timeout: f32,
+        max_events: Option<i32>,
+        owner: Owner,
+        mut delivery: Delivery,
+    ) -> Result<EventsBatch, QueryEventsError> {
         let mut timeout = Duration::from_secs_f32(timeout.max(0.0));
         let stop_time = Instant::now() + timeout;
         let max_events = max_events.unwrap_or(self.config.events.max_events_default);
@@ -191,25 +272,46 @@ impl CommonBroker {
 
         let mut notifier = self.negotiation_notifier.listen(subscription_id);
         loop {
-            let events = self
-                .db
-                .as_dao::<NegotiationEventsDao>()
-                .take_events(subscription_id, max_events, owner)
-                .await?;
+            let dao = self.db.as_dao::<NegotiationEventsDao>();
+            let batch = match &mut delivery {
+                Delivery::AtMostOnce => EventsBatch {
+                    cursor: None,
+                    events: dao.take_events(subscription_id, max_events, owner).await?,
+                },
+                Delivery::AtLeastOnce {
+                    resume,
+                    event_types,
+                } => {
+                    let (cursor, events) = dao
+                        .deliver_events(
+                            subscription_id,
+                            max_events,
+                            owner,
+                            resume.take(),
+                            self.config.events.ack_timeout,
+                            event_types.clone(),
+                        )
+                        .await?;
+                    EventsBatch {
+                        cursor: Some(cursor),
+                        events,
+                    }
+                }
+            };
 
-            if events.len() > 0 {
-                return Ok(events);
+            if batch.events.len() > 0 {
+                return Ok(batch);
             }
 
             // Solves panic 'supplied instant is later than self'.
             if stop_time < Instant::now() {
-                return Ok(vec![]);
+                return Ok(EventsBatch::default());
             }
             timeout = stop_time - Instant::now();
 
             if let Err(e) = notifier.wait_for_event_with_timeout(timeout).await {
                 return match e {
-                    NotifierError::Timeout(_) => Ok(vec![]),
+                    NotifierError::Timeout(_) => Ok(EventsBatch::default()),
                     NotifierError::ChannelClosed(_) => {
                         Err(QueryEventsError::Internal(e.to_string()))
                     }
@@ -385,7 +487,7 @@ impl CommonBroker {
         self.notify_agreement(&agreement).await;
         self.agreement_lock.clear_locks(&agreement.id).await;
 
-        inc_terminate_metrics(&reason, agreement.id.owner());
+        inc_terminate_metrics(&reason, &agreement);
         log::info!(
             "{:?} {} terminated Agreement [{}]. Reason: {}",
             agreement.id.owner(),
@@ -488,7 +590,7 @@ impl CommonBroker {
         self.notify_agreement(&agreement).await;
         self.agreement_lock.clear_locks(&agreement_id).await;
 
-        inc_terminate_metrics(&msg.reason, agreement.id.owner());
+        inc_terminate_metrics(&msg.reason, &agreement);
         log::info!(
             "Received terminate Agreement [{}] from [{}]. Reason: {}",
             &agreement_id,
@@ -833,10 +935,14 @@ fn get_reason_code(reason: &Option<Reason>, key: &str) -> Option<String> {
 /// This function extract from Reason additional information about termination reason
 /// and increments metric counter. Note that Reason isn't required to have any fields
 /// despite 'message'.
-pub fn inc_terminate_metrics(reason: &Option<Reason>, owner: Owner) {
+pub fn inc_terminate_metrics(reason: &Option<Reason>, agreement: &Agreement) {
+    let owner = agreement.id.owner();
+    let labels = agreement.metric_labels();
     match owner {
-        Owner::Provider => counter!("market.agreements.provider.terminated", 1),
-        Owner::Requestor => counter!("market.agreements.requestor.terminated", 1),
+        Owner::Provider => agreement_counter!("market.agreements.provider.terminated", 1, labels),
+        Owner::Requestor => {
+            agreement_counter!("market.agreements.requestor.terminated", 1, labels)
+        }
     };
 
     let p_code = get_reason_code(reason, "golem.provider.code");
@@ -14,9 +14,9 @@ use crate::db::{
 };
 use crate::matcher::error::{DemandError, QueryOfferError};
 use crate::protocol::negotiation::error::{
-    AgreementProtocolError, CommitAgreementError, CounterProposalError as ProtocolProposalError,
-    GsbAgreementError, NegotiationApiInitError, ProposeAgreementError, RejectProposalError,
-    TerminateAgreementError,
+    AgreementProtocolError, AmendAgreementError, CommitAgreementError,
+    CounterProposalError as ProtocolProposalError, GsbAgreementError, NegotiationApiInitError,
+    ProposeAgreementError, RejectProposalError, TerminateAgreementError,
 };
 
 #[derive(Error, Debug)]
@@ -66,6 +66,8 @@ pub enum AgreementError {
     Save(ProposalId, DbError),
     #[error("Failed to get Agreement [{0}]. Error: {1}")]
     Get(String, AgreementDaoError),
+    #[error("Failed to list Agreements. Error: {0}")]
+    List(AgreementDaoError),
     #[error("Agreement [{0}]. Error: {1}")]
     UpdateState(AgreementId, AgreementDaoError),
     #[error("Invalid Agreement id. {0}")]
@@ -80,6 +82,12 @@ pub enum AgreementError {
     ProtocolTerminate(#[from] TerminateAgreementError),
     #[error("Protocol error while committing: {0}")]
     ProtocolCommit(#[from] CommitAgreementError),
+    #[error("Protocol error while amending: {0}")]
+    ProtocolAmend(#[from] AmendAgreementError),
+    #[error("Agreement amendment [{0}] not found.")]
+    AmendmentNotFound(String),
+    #[error("Invalid amendment of Agreement [{0}]. {1}")]
+    InvalidAmendment(AgreementId, String),
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -2,22 +2,25 @@ use chrono::Utc;
 use futures::stream::StreamExt;
 use metrics::counter;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use ya_client::model::market::{event::ProviderEvent, NewProposal, Reason};
+use ya_core_model::market::AgreementAmendmentEvent;
 use ya_core_model::NodeId;
+use ya_metrics::agreement_counter;
 use ya_service_api_web::middleware::Identity;
 use ya_std_utils::LogErr;
 
 use crate::db::{
     dao::{AgreementDao, NegotiationEventsDao, ProposalDao, SaveAgreementError},
-    model::{Agreement, AgreementId, AgreementState, AppSessionId},
+    model::{Agreement, AgreementId, AgreementState, AppSessionId, EventType, MarketEvent},
     model::{Issuer, Offer, Owner, Proposal, ProposalId, SubscriptionId},
     DbMixedExecutor,
 };
 use crate::matcher::store::SubscriptionStore;
 use crate::protocol::negotiation::{error::*, messages::*, provider::NegotiationApi};
 
+use super::amendment::{AmendmentDecision, AmendmentQueue};
 use super::common::CommonBroker;
 use super::error::*;
 use super::notifier::EventNotifier;
@@ -40,6 +43,7 @@ pub enum ApprovalResult {
 pub struct ProviderBroker {
     pub(crate) common: CommonBroker,
     api: NegotiationApi,
+    amendments: AmendmentQueue,
 }
 
 impl ProviderBroker {
@@ -58,6 +62,9 @@ impl ProviderBroker {
         let broker_proposal_reject = broker.clone();
         let broker_terminated = broker.clone();
         let commit_broker = broker.clone();
+        let amend_broker = broker.clone();
+        let amendments = AmendmentQueue::default();
+        let amendments1 = amendments.clone();
 
         let api = NegotiationApi::new(
             move |caller: String, msg: InitialProposalReceived| {
@@ -87,6 +94,14 @@ impl ProviderBroker {
             move |caller: String, msg: AgreementCommitted| {
                 on_agreement_committed(commit_broker.clone(), caller, msg)
             },
+            move |caller: String, msg: AgreementAmendmentReceived| {
+                on_agreement_amendment_received(
+                    amend_broker.clone(),
+                    amendments1.clone(),
+                    caller,
+                    msg,
+                )
+            },
         );
 
         // Initialize counters to 0 value. Otherwise they won't appear on metrics endpoint
@@ -100,6 +115,7 @@ impl ProviderBroker {
         counter!("market.agreements.provider.committing", 0);
         counter!("market.agreements.provider.rejected", 0);
         counter!("market.agreements.provider.cancelled", 0);
+        counter!("market.agreements.provider.amended", 0);
         counter!("market.events.provider.queried", 0);
         counter!("market.proposals.provider.countered", 0);
         counter!("market.proposals.provider.init-negotiation", 0);
@@ -111,6 +127,7 @@ impl ProviderBroker {
         Ok(ProviderBroker {
             api,
             common: broker,
+            amendments,
         })
     }
 
@@ -202,8 +219,51 @@ impl ProviderBroker {
             .query_events(offer_id, timeout, max_events, Owner::Provider)
             .await?;
 
-        // Map model events to client RequestorEvent.
-        let events = futures::stream::iter(events)
+        let events = self.into_client_events(events).await;
+        counter!("market.events.provider.queried", events.len() as u64);
+        Ok(events)
+    }
+
+    /// Returns events together with cursor, that must be used to acknowledge them.
+    /// Events of other types than `event_types` stay in queue, unless it is empty.
+    pub async fn query_events_with_ack(
+        &self,
+        offer_id: &SubscriptionId,
+        timeout: f32,
+        max_events: Option<i32>,
+        resume: Option<String>,
+        event_types: Vec<EventType>,
+    ) -> Result<(Option<String>, Vec<ProviderEvent>), QueryEventsError> {
+        let batch = self
+            .common
+            .query_events_with_ack(
+                offer_id,
+                timeout,
+                max_events,
+                Owner::Provider,
+                resume,
+                event_types,
+            )
+            .await?;
+
+        let events = self.into_client_events(batch.events).await;
+        counter!("market.events.provider.queried", events.len() as u64);
+        Ok((batch.cursor, events))
+    }
+
+    pub async fn ack_events(
+        &self,
+        offer_id: &SubscriptionId,
+        cursor: &str,
+    ) -> Result<(), QueryEventsError> {
+        let acked = self.common.ack_events(offer_id, cursor).await?;
+        counter!("market.events.provider.acknowledged", acked as u64);
+        Ok(())
+    }
+
+    async fn into_client_events(&self, events: Vec<MarketEvent>) -> Vec<ProviderEvent> {
+        // Map model events to client ProviderEvent.
+        futures::stream::iter(events)
             .then(|event| event.into_client_provider_event(&self.common.db))
             .inspect(|result| {
                 if let Err(error) = result {
@@ -212,10 +272,7 @@ impl ProviderBroker {
             })
             .filter_map(|event| async move { event.ok() })
             .collect::<Vec<ProviderEvent>>()
-            .await;
-
-        counter!("market.events.provider.queried", events.len() as u64);
-        Ok(events)
+            .await
     }
 
     pub async fn approve_agreement(
@@ -252,7 +309,11 @@ impl ProviderBroker {
                 .await
                 .map_err(|e| AgreementError::UpdateState(agreement.id.clone(), e))?;
 
-            counter!("market.agreements.provider.approving", 1);
+            agreement_counter!(
+                "market.agreements.provider.approving",
+                1,
+                agreement.metric_labels()
+            );
             if let Some(session) = app_session_id {
                 log::info!(
                     "AppSession id [{}] set for Agreement [{}].",
@@ -296,7 +357,11 @@ impl ProviderBroker {
             }
         }
 
-        counter!("market.agreements.provider.committing", 1);
+        agreement_counter!(
+            "market.agreements.provider.committing",
+            1,
+            agreement.metric_labels()
+        );
         log::info!(
             "Provider {} approved Agreement [{}]. Waiting for commit from Requestor [{}].",
             id.display(),
@@ -373,7 +438,11 @@ impl ProviderBroker {
                 .map_err(|e| AgreementError::UpdateState((&agreement.id).clone(), e))?
         };
 
-        counter!("market.agreements.provider.rejected", 1);
+        agreement_counter!(
+            "market.agreements.provider.rejected",
+            1,
+            agreement.metric_labels()
+        );
         log::info!(
             "Provider {} rejected Agreement [{}]. Reason: {}",
             id.display(),
@@ -384,6 +453,140 @@ impl ProviderBroker {
     }
 }
 
+impl ProviderBroker {
+    /// Returns amendments of Provider's Agreements, that are waiting for decision.
+    pub async fn query_amendments(
+        &self,
+        id: &Identity,
+        timeout: f32,
+        max_events: Option<i32>,
+    ) -> Vec<AgreementAmendmentEvent> {
+        self.amendments
+            .collect(id.identity, timeout, max_events)
+            .await
+    }
+
+    pub async fn approve_amendment(
+        &self,
+        id: &Identity,
+        amendment_id: &str,
+    ) -> Result<(), AgreementError> {
+        self.decide_amendment(id, amendment_id, AmendmentDecision::Approved)
+    }
+
+    pub async fn reject_amendment(
+        &self,
+        id: &Identity,
+        amendment_id: &str,
+        reason: Option<Reason>,
+    ) -> Result<(), AgreementError> {
+        self.decide_amendment(id, amendment_id, AmendmentDecision::Rejected(reason))
+    }
+
+    fn decide_amendment(
+        &self,
+        id: &Identity,
+        amendment_id: &str,
+        decision: AmendmentDecision,
+    ) -> Result<(), AgreementError> {
+        // Requestor could stop waiting for our decision in the meantime.
+        match self.amendments.decide(id.identity, amendment_id, decision) {
+            true => Ok(()),
+            false => Err(AgreementError::AmendmentNotFound(amendment_id.to_string())),
+        }
+    }
+}
+
+async fn on_agreement_amendment_received(
+    broker: CommonBroker,
+    amendments: AmendmentQueue,
+    caller: String,
+    msg: AgreementAmendmentReceived,
+) -> Result<(), AmendAgreementError> {
+    let agreement_id = msg.agreement_id.clone();
+    let caller_id = CommonBroker::parse_caller(&caller)?;
+    agreement_amendment_received(broker, amendments, caller_id, msg)
+        .await
+        .map_err(|e| AmendAgreementError::Remote(e, agreement_id))
+}
+
+async fn agreement_amendment_received(
+    broker: CommonBroker,
+    amendments: AmendmentQueue,
+    caller: NodeId,
+    msg: AgreementAmendmentReceived,
+) -> Result<(), RemoteAmendAgreementError> {
+    let dao = broker.db.as_dao::<AgreementDao>();
+    let agreement = dao
+        .select(&msg.agreement_id, None, Utc::now().naive_utc())
+        .await
+        .map_err(|e| RemoteAmendAgreementError::Unexpected {
+            public_msg: "Internal Error getting Agreement".to_string(),
+            original_msg: e.to_string(),
+        })
+        .log_err()?
+        .ok_or(RemoteAmendAgreementError::NotFound)?;
+
+    if agreement.requestor_id != caller {
+        // Don't reveal, that we know this Agreement id.
+        Err(RemoteAmendAgreementError::NotFound)?
+    }
+    if agreement.state != AgreementState::Approved {
+        Err(RemoteAmendAgreementError::InvalidState(agreement.state))?
+    }
+
+    // Provider Agent will get amendment as an event and will decide, if he accepts new terms.
+    let (amendment_id, decision) =
+        amendments.add(agreement.provider_id, &agreement.id, msg.amendment.clone());
+    let timeout = Duration::from_secs_f32(msg.timeout.max(0.0));
+    let decision = tokio::time::timeout(timeout, decision).await;
+    amendments.remove(&amendment_id);
+
+    match decision {
+        Ok(Ok(AmendmentDecision::Approved)) => (),
+        Ok(Ok(AmendmentDecision::Rejected(reason))) => {
+            log::info!(
+                "Amendment of Agreement [{}] rejected. Reason: {}",
+                &agreement.id,
+                reason.display()
+            );
+            Err(RemoteAmendAgreementError::Rejected(reason))?
+        }
+        Ok(Err(_)) | Err(_) => Err(RemoteAmendAgreementError::Timeout)?,
+    }
+
+    {
+        let _hold = broker.agreement_lock.lock(&msg.agreement_id).await;
+        dao.amend(&msg.agreement_id, &msg.amendment, Owner::Requestor)
+            .await
+            .map_err(|e| match e {
+                AgreementDaoError::NotAmendable(state) => {
+                    RemoteAmendAgreementError::InvalidState(state)
+                }
+                e => RemoteAmendAgreementError::Unexpected {
+                    public_msg: "Failed to amend Agreement.".to_string(),
+                    original_msg: e.to_string(),
+                },
+            })
+            .log_err()?;
+    }
+    // Provider Agent reacts to new terms only after they were stored.
+    amendments.amended(agreement.provider_id, &agreement.id, msg.amendment.clone());
+
+    agreement_counter!(
+        "market.agreements.provider.amended",
+        1,
+        agreement.metric_labels()
+    );
+    log::info!(
+        "Agreement [{}] amended by [{}]. Valid to: {}",
+        &agreement.id,
+        &caller,
+        msg.amendment.valid_to
+    );
+    Ok(())
+}
+
 async fn on_agreement_committed(
     broker: CommonBroker,
     caller: String,
@@ -455,7 +658,11 @@ async fn agreement_committed(
 
     broker.notify_agreement(&agreement).await;
 
-    counter!("market.agreements.provider.approved", 1);
+    agreement_counter!(
+        "market.agreements.provider.approved",
+        1,
+        agreement.metric_labels()
+    );
     log::info!(
         "Agreement [{}] approved (committed) by [{}].",
         &agreement.id,
@@ -606,7 +813,11 @@ async fn agreement_received(
     // Send channel message to wake all query_events waiting for proposals.
     broker.negotiation_notifier.notify(&offer_id).await;
 
-    counter!("market.agreements.provider.proposed", 1);
+    agreement_counter!(
+        "market.agreements.provider.proposed",
+        1,
+        agreement.metric_labels()
+    );
     log::info!(
         "Agreement proposal [{}] received from [{}].",
         &msg.agreement_id,
@@ -664,7 +875,11 @@ async fn agreement_cancelled(
 
     broker.notify_agreement(&agreement).await;
 
-    counter!("market.agreements.provider.cancelled", 1);
+    agreement_counter!(
+        "market.agreements.provider.cancelled",
+        1,
+        agreement.metric_labels()
+    );
     log::info!(
         "Agreement [{}] cancelled by [{}]. Reason: {}",
         &agreement.id,
@@ -7,12 +7,14 @@ use tokio::sync::mpsc::UnboundedReceiver;
 
 use ya_client::model::market::{event::RequestorEvent, NewProposal, Reason};
 use ya_client::model::NodeId;
+use ya_core_model::market::AgreementAmendment;
+use ya_metrics::agreement_counter;
 use ya_service_api_web::middleware::Identity;
 use ya_std_utils::LogErr;
 
 use crate::db::{
     dao::{AgreementDao, AgreementDaoError, SaveAgreementError},
-    model::{Agreement, AgreementId, AgreementState, AppSessionId},
+    model::{Agreement, AgreementId, AgreementState, AppSessionId, EventType, MarketEvent},
     model::{Demand, Issuer, Owner, ProposalId, SubscriptionId},
     DbMixedExecutor,
 };
@@ -89,6 +91,7 @@ impl RequestorBroker {
         // Initialize counters to 0 value. Otherwise they won't appear on metrics endpoint
         // until first change to value will be made.
         counter!("market.agreements.events.queried", 0);
+        counter!("market.agreements.requestor.amended", 0);
         counter!("market.agreements.requestor.approved", 0);
         counter!("market.agreements.requestor.cancelled", 0);
         counter!("market.agreements.requestor.confirmed", 0);
@@ -205,8 +208,51 @@ impl RequestorBroker {
             .query_events(demand_id, timeout, max_events, Owner::Requestor)
             .await?;
 
+        let events = self.into_client_events(events).await;
+        counter!("market.events.requestor.queried", events.len() as u64);
+        Ok(events)
+    }
+
+    /// Returns events together with cursor, that must be used to acknowledge them.
+    /// Events of other types than `event_types` stay in queue, unless it is empty.
+    pub async fn query_events_with_ack(
+        &self,
+        demand_id: &SubscriptionId,
+        timeout: f32,
+        max_events: Option<i32>,
+        resume: Option<String>,
+        event_types: Vec<EventType>,
+    ) -> Result<(Option<String>, Vec<RequestorEvent>), QueryEventsError> {
+        let batch = self
+            .common
+            .query_events_with_ack(
+                demand_id,
+                timeout,
+                max_events,
+                Owner::Requestor,
+                resume,
+                event_types,
+            )
+            .await?;
+
+        let events = self.into_client_events(batch.events).await;
+        counter!("market.events.requestor.queried", events.len() as u64);
+        Ok((batch.cursor, events))
+    }
+
+    pub async fn ack_events(
+        &self,
+        demand_id: &SubscriptionId,
+        cursor: &str,
+    ) -> Result<(), QueryEventsError> {
+        let acked = self.common.ack_events(demand_id, cursor).await?;
+        counter!("market.events.requestor.acknowledged", acked as u64);
+        Ok(())
+    }
+
+    async fn into_client_events(&self, events: Vec<MarketEvent>) -> Vec<RequestorEvent> {
         // Map model events to client RequestorEvent.
-        let events = futures::stream::iter(events)
+        futures::stream::iter(events)
             .then(|event| event.into_client_requestor_event(&self.common.db))
             .inspect(|result| {
                 if let Err(error) = result {
@@ -215,10 +261,7 @@ impl RequestorBroker {
             })
             .filter_map(|event| async move { event.ok() })
             .collect::<Vec<RequestorEvent>>()
-            .await;
-
-        counter!("market.events.requestor.queried", events.len() as u64);
-        Ok(events)
+            .await
     }
 
     /// Initiates the Agreement handshake phase.
@@ -286,6 +329,7 @@ impl RequestorBroker {
             Owner::Requestor,
         );
         let agreement_id = agreement.id.clone();
+        let labels = agreement.metric_labels();
         self.common
             .db
             .as_dao::<AgreementDao>()
@@ -299,7 +343,7 @@ impl RequestorBroker {
                 }
             })?;
 
-        counter!("market.agreements.requestor.created", 1);
+        agreement_counter!("market.agreements.requestor.created", 1, labels);
         log::info!(
             "Requestor {} created Agreement [{}] from Proposal [{}].",
             id.display(),
@@ -339,7 +383,11 @@ impl RequestorBroker {
 
         self.common.notify_agreement(&agreement).await;
 
-        counter!("market.agreements.requestor.cancelled", 1);
+        agreement_counter!(
+            "market.agreements.requestor.cancelled",
+            1,
+            agreement.metric_labels()
+        );
         log::info!(
             "Provider {} cancelled Agreement [{}]. Reason: {}",
             id.display(),
@@ -349,6 +397,62 @@ impl RequestorBroker {
         Ok(())
     }
 
+    /// Proposes new terms of already approved Agreement to the Provider.
+    /// Agreement is changed only if Provider accepts them within `timeout`.
+    pub async fn amend_agreement(
+        &self,
+        id: &Identity,
+        agreement_id: &AgreementId,
+        amendment: AgreementAmendment,
+        timeout: f32,
+    ) -> Result<Agreement, AgreementError> {
+        let dao = self.common.db.as_dao::<AgreementDao>();
+        let agreement = dao
+            .select(agreement_id, Some(id.identity), Utc::now().naive_utc())
+            .await
+            .map_err(|e| AgreementError::Get(agreement_id.to_string(), e))?
+            .ok_or(AgreementError::NotFound(agreement_id.to_string()))?;
+
+        if agreement.state != AgreementState::Approved {
+            return Err(AgreementError::UpdateState(
+                agreement.id,
+                AgreementDaoError::NotAmendable(agreement.state),
+            ));
+        }
+        if amendment.valid_to <= Utc::now() {
+            return Err(AgreementError::InvalidAmendment(
+                agreement.id,
+                "New expiration date is in the past.".to_string(),
+            ));
+        }
+
+        // We can't hold lock here, because Provider can terminate Agreement
+        // while making decision about amendment.
+        self.api
+            .amend_agreement(&agreement, amendment.clone(), timeout)
+            .await?;
+
+        let agreement = {
+            let _hold = self.common.agreement_lock.lock(&agreement_id).await;
+            dao.amend(agreement_id, &amendment, Owner::Requestor)
+                .await
+                .map_err(|e| AgreementError::UpdateState(agreement_id.clone(), e))?
+        };
+
+        agreement_counter!(
+            "market.agreements.requestor.amended",
+            1,
+            agreement.metric_labels()
+        );
+        log::info!(
+            "Requestor {} amended Agreement [{}]. Valid to: {}",
+            id.display(),
+            &agreement.id,
+            amendment.valid_to,
+        );
+        Ok(agreement)
+    }
+
     pub async fn wait_for_approval(
         &self,
         id: &AgreementId,
@@ -414,7 +518,7 @@ impl RequestorBroker {
         app_session_id: AppSessionId,
     ) -> Result<(), AgreementError> {
         let dao = self.common.db.as_dao::<AgreementDao>();
-        {
+        let labels = {
             // We won't be able to process `on_agreement_approved`, before we
             // finish execution under this lock. This avoids errors related to
             // Provider approving Agreement before we set proper state in database.
@@ -443,9 +547,10 @@ impl RequestorBroker {
             dao.confirm(agreement_id, &app_session_id, &signature)
                 .await
                 .map_err(|e| AgreementError::UpdateState(agreement_id.clone(), e))?;
-        }
+            agreement.metric_labels()
+        };
 
-        counter!("market.agreements.requestor.confirmed", 1);
+        agreement_counter!("market.agreements.requestor.confirmed", 1, labels);
         log::info!(
             "Requestor {} confirmed Agreement [{}] and sent to Provider.",
             id.display(),
@@ -556,7 +661,11 @@ async fn agreement_approved(
             })?
     };
 
-    counter!("market.agreements.requestor.committing", 1);
+    agreement_counter!(
+        "market.agreements.requestor.committing",
+        1,
+        agreement.metric_labels()
+    );
 
     // Commit Agreement. We must spawn committing later, because we need to
     // return from this function to provider.
@@ -631,7 +740,11 @@ async fn commit_agreement(broker: CommonBroker, agreement_id: AgreementId) {
 
     broker.notify_agreement(&agreement).await;
 
-    counter!("market.agreements.requestor.approved", 1);
+    agreement_counter!(
+        "market.agreements.requestor.approved",
+        1,
+        agreement.metric_labels()
+    );
     log::info!(
         "Agreement [{}] committed (approved) by [{}].",
         &agreement.id,
@@ -687,7 +800,11 @@ async fn agreement_rejected(
 
     broker.notify_agreement(&agreement).await;
 
-    counter!("market.agreements.requestor.rejected", 1);
+    agreement_counter!(
+        "market.agreements.requestor.rejected",
+        1,
+        agreement.metric_labels()
+    );
     log::info!(
         "Agreement [{}] rejected by [{}]. Reason: {}",
         &agreement.id,
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use ya_client::model::market::Reason;
+
 use crate::db::dao::ChangeProposalStateError;
 use crate::db::model::{AgreementId, AgreementState, ProposalId, ProposalIdValidationError};
 use crate::matcher::error::QueryOfferError;
@@ -176,6 +178,36 @@ pub enum RemoteCommitAgreementError {
     },
 }
 
+#[derive(Error, Debug, Serialize, Deserialize)]
+pub enum AmendAgreementError {
+    #[error("Amend {0}.")]
+    Gsb(#[from] GsbAgreementError),
+    #[error("Remote amend Agreement [{1}] error: {0}")]
+    Remote(RemoteAmendAgreementError, AgreementId),
+    #[error(transparent)]
+    CallerParse(#[from] CallerParseError),
+    #[error("Timeout while waiting for amendment of Agreement [{0}]")]
+    Timeout(AgreementId),
+}
+
+#[derive(Error, Debug, Serialize, Deserialize)]
+#[non_exhaustive]
+pub enum RemoteAmendAgreementError {
+    #[error("Agreement not found.")]
+    NotFound,
+    #[error("Agreement in state {0}, can't be amended.")]
+    InvalidState(AgreementState),
+    #[error("Amendment rejected by Provider.")]
+    Rejected(Option<Reason>),
+    #[error("Provider didn't decide about amendment in time.")]
+    Timeout,
+    #[error("Unexpected error: {public_msg} {original_msg}.")]
+    Unexpected {
+        public_msg: String,
+        original_msg: String,
+    },
+}
+
 impl RemoteSensitiveError for RemoteProposeAgreementError {
     fn hide_sensitive_info(self) -> RemoteProposeAgreementError {
         match self {
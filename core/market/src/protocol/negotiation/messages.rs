@@ -2,11 +2,12 @@ use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
 use ya_client::model::market::Reason;
+use ya_core_model::market::AgreementAmendment;
 use ya_service_bus::RpcMessage;
 
 use crate::db::model::{AgreementId, DbProposal, Owner, Proposal, ProposalId, SubscriptionId};
 use crate::protocol::negotiation::error::{
-    CommitAgreementError, ProposeAgreementError, RejectProposalError,
+    AmendAgreementError, CommitAgreementError, ProposeAgreementError, RejectProposalError,
 };
 
 use super::super::callback::CallbackMessage;
@@ -205,6 +206,21 @@ impl RpcMessage for AgreementCommitted {
     type Error = CommitAgreementError;
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgreementAmendmentReceived {
+    pub agreement_id: AgreementId,
+    pub amendment: AgreementAmendment,
+    /// Number of seconds Requestor waits for Provider's decision.
+    pub timeout: f32,
+}
+
+impl RpcMessage for AgreementAmendmentReceived {
+    const ID: &'static str = "AgreementAmendmentReceived";
+    type Item = ();
+    type Error = AmendAgreementError;
+}
+
 /// The same messaged will be used on GSB and as messages in callbacks.
 impl<Message: RpcMessage> CallbackMessage for Message {
     type Ok = <Message as RpcMessage>::Item;
@@ -287,3 +303,10 @@ impl AgreementCommitted {
         self
     }
 }
+
+impl AgreementAmendmentReceived {
+    pub fn translate(mut self, owner: Owner) -> Self {
+        self.agreement_id = self.agreement_id.translate(owner);
+        self
+    }
+}
@@ -11,13 +11,13 @@ use crate::db::model::{Agreement, Owner, Proposal};
 
 use super::super::callback::{CallbackHandler, HandlerSlot};
 use super::error::{
-    AgreementProtocolError, CounterProposalError, GsbAgreementError, GsbProposalError,
-    NegotiationApiInitError, TerminateAgreementError,
+    AgreementProtocolError, AmendAgreementError, CounterProposalError, GsbAgreementError,
+    GsbProposalError, NegotiationApiInitError, TerminateAgreementError,
 };
 use super::messages::{
-    provider, requestor, AgreementApproved, AgreementCancelled, AgreementCommitted,
-    AgreementReceived, AgreementRejected, AgreementTerminated, InitialProposalReceived,
-    ProposalContent, ProposalReceived, ProposalRejected,
+    provider, requestor, AgreementAmendmentReceived, AgreementApproved, AgreementCancelled,
+    AgreementCommitted, AgreementReceived, AgreementRejected, AgreementTerminated,
+    InitialProposalReceived, ProposalContent, ProposalReceived, ProposalRejected,
 };
 use crate::protocol::negotiation::error::{
     CommitAgreementError, ProposeAgreementError, RejectProposalError,
@@ -39,6 +39,7 @@ struct NegotiationImpl {
     agreement_cancelled: HandlerSlot<AgreementCancelled>,
     agreement_terminated: HandlerSlot<AgreementTerminated>,
     agreement_committed: HandlerSlot<AgreementCommitted>,
+    agreement_amendment_received: HandlerSlot<AgreementAmendmentReceived>,
 }
 
 // TODO: Most of these functions don't need to be members of NegotiationApi.
@@ -53,6 +54,7 @@ impl NegotiationApi {
         agreement_cancelled: impl CallbackHandler<AgreementCancelled>,
         agreement_terminated: impl CallbackHandler<AgreementTerminated>,
         agreement_committed: impl CallbackHandler<AgreementCommitted>,
+        agreement_amendment_received: impl CallbackHandler<AgreementAmendmentReceived>,
     ) -> NegotiationApi {
         let negotiation_impl = NegotiationImpl {
             initial_proposal_received: HandlerSlot::new(initial_proposal_received),
@@ -62,6 +64,7 @@ impl NegotiationApi {
             agreement_cancelled: HandlerSlot::new(agreement_cancelled),
             agreement_terminated: HandlerSlot::new(agreement_terminated),
             agreement_committed: HandlerSlot::new(agreement_committed),
+            agreement_amendment_received: HandlerSlot::new(agreement_amendment_received),
         };
         NegotiationApi {
             inner: Arc::new(negotiation_impl),
@@ -280,6 +283,22 @@ impl NegotiationApi {
             .await
     }
 
+    async fn on_agreement_amendment_received(
+        self,
+        caller: String,
+        msg: AgreementAmendmentReceived,
+    ) -> Result<(), AmendAgreementError> {
+        log::debug!(
+            "Negotiation API: Agreement [{}] amendment received from [{}].",
+            &msg.agreement_id,
+            &caller
+        );
+        self.inner
+            .agreement_amendment_received
+            .call(caller, msg.translate(Owner::Provider))
+            .await
+    }
+
     pub async fn bind_gsb(
         &self,
         public_prefix: &str,
@@ -319,7 +338,13 @@ impl NegotiationApi {
             .bind_with_processor(move |_, myself, caller: String, msg: AgreementCommitted| {
                 let myself = myself.clone();
                 myself.on_agreement_committed(caller, msg)
-            });
+            })
+            .bind_with_processor(
+                move |_, myself, caller: String, msg: AgreementAmendmentReceived| {
+                    let myself = myself.clone();
+                    myself.on_agreement_amendment_received(caller, msg)
+                },
+            );
         Ok(())
     }
 }
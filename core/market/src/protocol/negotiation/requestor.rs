@@ -1,9 +1,10 @@
 use futures::future::TryFutureExt;
 use std::sync::Arc;
+use std::time::Duration;
 
 use ya_client::model::market::Reason;
 use ya_client::model::NodeId;
-use ya_core_model::market::BUS_ID;
+use ya_core_model::market::{AgreementAmendment, BUS_ID};
 use ya_net::{self as net, RemoteEndpoint};
 use ya_service_bus::{typed::ServiceBinder, RpcEndpoint};
 
@@ -11,13 +12,13 @@ use crate::db::model::{Agreement, Owner, Proposal};
 
 use super::super::callback::{CallbackHandler, HandlerSlot};
 use super::error::{
-    AgreementProtocolError, CounterProposalError, GsbAgreementError, GsbProposalError,
-    NegotiationApiInitError, TerminateAgreementError,
+    AgreementProtocolError, AmendAgreementError, CounterProposalError, GsbAgreementError,
+    GsbProposalError, NegotiationApiInitError, TerminateAgreementError,
 };
 use super::messages::{
-    provider, requestor, AgreementApproved, AgreementCancelled, AgreementReceived,
-    AgreementRejected, AgreementTerminated, InitialProposalReceived, ProposalContent,
-    ProposalReceived, ProposalRejected,
+    provider, requestor, AgreementAmendmentReceived, AgreementApproved, AgreementCancelled,
+    AgreementReceived, AgreementRejected, AgreementTerminated, InitialProposalReceived,
+    ProposalContent, ProposalReceived, ProposalRejected,
 };
 use crate::protocol::negotiation::error::{
     CommitAgreementError, ProposeAgreementError, RejectProposalError,
@@ -201,6 +202,33 @@ impl NegotiationApi {
         Ok(())
     }
 
+    /// Sent to Provider, when Requestor wants to change terms of approved Agreement.
+    /// Returns after Provider decides about amendment.
+    pub async fn amend_agreement(
+        &self,
+        agreement: &Agreement,
+        amendment: AgreementAmendment,
+        timeout: f32,
+    ) -> Result<(), AmendAgreementError> {
+        let id = agreement.id.clone();
+        let msg = AgreementAmendmentReceived {
+            agreement_id: id.clone(),
+            amendment,
+            timeout,
+        };
+        let net_send_fut = net::from(agreement.requestor_id)
+            .to(agreement.provider_id)
+            .service(&provider::agreement_addr(BUS_ID))
+            .send(msg);
+        // Give the message some time to travel to Provider and back.
+        let timeout = Duration::from_secs_f32(timeout.max(0.0)) + Duration::from_secs(5);
+        tokio::time::timeout(timeout, net_send_fut)
+            .await
+            .map_err(|_| AmendAgreementError::Timeout(id.clone()))?
+            .map_err(|e| GsbAgreementError(e.to_string(), id))??;
+        Ok(())
+    }
+
     async fn on_proposal_received(
         self,
         caller: String,
@@ -23,6 +23,9 @@ pub(crate) mod requestor;
 const DEFAULT_EVENT_TIMEOUT: f32 = 5.0; // seconds
 const DEFAULT_QUERY_TIMEOUT: f32 = 5.0;
 
+/// Response header with cursor acknowledging returned Negotiation events.
+pub const EVENTS_CURSOR_HEADER: &str = "X-Events-Cursor";
+
 pub fn path_config() -> PathConfig {
     PathConfig::default().error_handler(|err, _req| {
         InternalError::new(
@@ -48,6 +51,11 @@ pub struct PathAgreement {
     pub agreement_id: String,
 }
 
+#[derive(Deserialize)]
+pub struct PathAmendment {
+    pub amendment_id: String,
+}
+
 #[derive(Deserialize)]
 pub struct PathSubscription {
     pub subscription_id: SubscriptionId,
@@ -97,6 +105,34 @@ pub struct QueryTimeoutMaxEvents {
     pub max_events: Option<i32>,
 }
 
+#[derive(Deserialize, Debug)]
+pub struct QueryNegotiationEvents {
+    /// number of seconds to wait
+    #[serde(rename = "timeout", default = "default_event_timeout")]
+    pub timeout: f32,
+    /// maximum count of events to return
+    #[serde(rename = "maxEvents")]
+    pub max_events: Option<i32>,
+    /// keep events until acknowledged with cursor returned in response header
+    #[serde(rename = "ack", default)]
+    pub ack: bool,
+    /// cursor of not acknowledged batch to resume from; implies `ack`
+    #[serde(rename = "cursor")]
+    pub cursor: Option<String>,
+}
+
+impl QueryNegotiationEvents {
+    pub fn with_ack(&self) -> bool {
+        self.ack || self.cursor.is_some()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct QueryEventsCursor {
+    #[serde(rename = "cursor")]
+    pub cursor: String,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct QueryAgreementEvents {
     /// number of seconds to wait
@@ -4,6 +4,7 @@ use chrono::{TimeZone, Utc};
 use std::sync::Arc;
 
 use ya_client::model::market::Reason;
+use ya_core_model::market::AgreementListFilter;
 use ya_service_api_web::middleware::Identity;
 use ya_std_utils::LogErr;
 
@@ -16,10 +17,26 @@ use crate::rest_api::QueryAgreementEvents;
 pub fn register_endpoints(scope: Scope) -> Scope {
     scope
         .service(collect_agreement_events)
+        .service(list_agreements)
         .service(get_agreement)
         .service(terminate_agreement)
 }
 
+#[actix_web::get("/agreements")]
+async fn list_agreements(
+    market: Data<Arc<MarketService>>,
+    query: Query<AgreementListFilter>,
+    id: Identity,
+) -> impl Responder {
+    // Agreements are listed for both sides, the `role` field of each entry
+    // tells, if we are Provider or Requestor.
+    market
+        .list_agreements(&id, query.into_inner())
+        .await
+        .log_err()
+        .map(|agreements| HttpResponse::Ok().json(agreements))
+}
+
 #[actix_web::get("/agreements/{agreement_id}")]
 async fn get_agreement(
     market: Data<Arc<MarketService>>,
@@ -5,7 +5,9 @@ use ya_client::model::ErrorMessage;
 use crate::db::dao::{AgreementDaoError, SaveProposalError};
 use crate::db::model::AgreementState;
 use crate::negotiation::error::{AgreementEventsError, ProposalValidationError};
-use crate::protocol::negotiation::error::RejectProposalError;
+use crate::protocol::negotiation::error::{
+    AmendAgreementError, RejectProposalError, RemoteAmendAgreementError,
+};
 use crate::{
     db::dao::TakeEventsError,
     market::MarketError,
@@ -34,6 +36,9 @@ impl ResponseError for MarketError {
             MarketError::QueryOffersError(e) => e.error_response(),
             MarketError::DemandError(e) => e.error_response(),
             MarketError::Negotiation(e) => e.error_response(),
+            MarketError::Draining(_) => {
+                HttpResponse::ServiceUnavailable().json(ErrorMessage::new(self.to_string()))
+            }
         }
     }
 }
@@ -205,18 +210,31 @@ impl ResponseError for AgreementError {
     fn error_response(&self) -> HttpResponse {
         let msg = ErrorMessage::new(self.to_string());
         match self {
-            AgreementError::NotFound(_) => HttpResponse::NotFound().json(msg).into(),
+            AgreementError::NotFound(_) | AgreementError::AmendmentNotFound(_) => {
+                HttpResponse::NotFound().json(msg).into()
+            }
             AgreementError::Expired(_) => HttpResponse::Gone().json(msg).into(),
             AgreementError::ProposalAlreadyAccepted(..) => {
                 HttpResponse::Conflict().json(msg).into()
             }
-            AgreementError::UpdateState(_, e) => e.error_response(),
+            AgreementError::UpdateState(_, e) | AgreementError::List(e) => e.error_response(),
             AgreementError::NoNegotiations(_)
             | AgreementError::ProposalRejected(..)
             | AgreementError::OwnProposal(..)
             | AgreementError::ProposalNotFound(..)
             | AgreementError::ProposalCountered(..)
-            | AgreementError::InvalidId(..) => HttpResponse::BadRequest().json(msg).into(),
+            | AgreementError::InvalidId(..)
+            | AgreementError::InvalidAmendment(..) => HttpResponse::BadRequest().json(msg).into(),
+            AgreementError::ProtocolAmend(e) => match e {
+                AmendAgreementError::Remote(RemoteAmendAgreementError::Rejected(_), _) => {
+                    HttpResponse::Conflict().json(msg).into()
+                }
+                AmendAgreementError::Remote(RemoteAmendAgreementError::Timeout, _)
+                | AmendAgreementError::Timeout(_) => {
+                    HttpResponse::RequestTimeout().json(msg).into()
+                }
+                _ => HttpResponse::InternalServerError().json(msg).into(),
+            },
             AgreementError::GetProposal(..)
             | AgreementError::Save(..)
             | AgreementError::Get(..)
@@ -244,10 +262,14 @@ impl ResponseError for AgreementDaoError {
                 | AgreementState::Approved
                 | AgreementState::Terminated => HttpResponse::Gone().json(msg),
             },
-            AgreementDaoError::InvalidId(_) => HttpResponse::BadRequest().json(msg),
+            AgreementDaoError::InvalidId(_) | AgreementDaoError::InvalidLimit(..) => {
+                HttpResponse::BadRequest().json(msg)
+            }
+            AgreementDaoError::NotAmendable(_) => HttpResponse::Conflict().json(msg),
             AgreementDaoError::DbError(_)
             | AgreementDaoError::SessionId(_)
-            | AgreementDaoError::EventError(_) => HttpResponse::InternalServerError().json(msg),
+            | AgreementDaoError::EventError(_)
+            | AgreementDaoError::Properties(_) => HttpResponse::InternalServerError().json(msg),
         }
         .into()
     }
@@ -9,7 +9,10 @@ use ya_std_utils::LogErr;
 use crate::db::model::Owner;
 use crate::market::MarketService;
 
-use super::{PathAgreement, PathSubscription, PathSubscriptionProposal, QueryTimeoutMaxEvents};
+use super::{
+    PathAgreement, PathAmendment, PathSubscription, PathSubscriptionProposal, QueryEventsCursor,
+    QueryNegotiationEvents, QueryTimeoutMaxEvents, EVENTS_CURSOR_HEADER,
+};
 use crate::negotiation::ApprovalResult;
 use crate::rest_api::QueryTimeoutAppSessionId;
 use ya_client::model::ErrorMessage;
@@ -20,11 +23,15 @@ pub fn register_endpoints(scope: Scope) -> Scope {
         .service(get_offers)
         .service(unsubscribe)
         .service(collect)
+        .service(ack_events)
         .service(counter_proposal)
         .service(get_proposal)
         .service(reject_proposal)
         .service(approve_agreement)
         .service(reject_agreement)
+        .service(collect_amendments)
+        .service(approve_amendment)
+        .service(reject_amendment)
 }
 
 #[actix_web::post("/offers")]
@@ -66,18 +73,54 @@ async fn unsubscribe(
 async fn collect(
     market: Data<Arc<MarketService>>,
     path: Path<PathSubscription>,
-    query: Query<QueryTimeoutMaxEvents>,
+    query: Query<QueryNegotiationEvents>,
     _id: Identity,
 ) -> impl Responder {
     let subscription_id = path.into_inner().subscription_id;
     let timeout = query.timeout;
     let max_events = query.max_events;
+    if !query.with_ack() {
+        return market
+            .provider_engine
+            .query_events(&subscription_id, timeout, max_events)
+            .await
+            .log_err()
+            .map(|events| HttpResponse::Ok().json(events));
+    }
+
     market
         .provider_engine
-        .query_events(&subscription_id, timeout, max_events)
+        .query_events_with_ack(
+            &subscription_id,
+            timeout,
+            max_events,
+            query.into_inner().cursor,
+            vec![],
+        )
         .await
         .log_err()
-        .map(|events| HttpResponse::Ok().json(events))
+        .map(|(cursor, events)| {
+            let mut response = HttpResponse::Ok();
+            if let Some(cursor) = cursor {
+                response.insert_header((EVENTS_CURSOR_HEADER, cursor));
+            }
+            response.json(events)
+        })
+}
+
+#[actix_web::post("/offers/{subscription_id}/events/ack")]
+async fn ack_events(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathSubscription>,
+    query: Query<QueryEventsCursor>,
+    _id: Identity,
+) -> impl Responder {
+    market
+        .provider_engine
+        .ack_events(&path.into_inner().subscription_id, &query.cursor)
+        .await
+        .log_err()
+        .map(|_| HttpResponse::NoContent().finish())
 }
 
 #[actix_web::post("/offers/{subscription_id}/proposals/{proposal_id}")]
@@ -176,3 +219,45 @@ async fn reject_agreement(
         .log_err()
         .map(|_| HttpResponse::Ok().finish())
 }
+
+#[actix_web::get("/agreementAmendments")]
+async fn collect_amendments(
+    market: Data<Arc<MarketService>>,
+    query: Query<QueryTimeoutMaxEvents>,
+    id: Identity,
+) -> HttpResponse {
+    let events = market
+        .provider_engine
+        .query_amendments(&id, query.timeout, query.max_events)
+        .await;
+    HttpResponse::Ok().json(events)
+}
+
+#[actix_web::post("/agreementAmendments/{amendment_id}/approve")]
+async fn approve_amendment(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathAmendment>,
+    id: Identity,
+) -> impl Responder {
+    market
+        .provider_engine
+        .approve_amendment(&id, &path.into_inner().amendment_id)
+        .await
+        .log_err()
+        .map(|_| HttpResponse::NoContent().finish())
+}
+
+#[actix_web::post("/agreementAmendments/{amendment_id}/reject")]
+async fn reject_amendment(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathAmendment>,
+    id: Identity,
+    body: Json<Option<Reason>>,
+) -> impl Responder {
+    market
+        .provider_engine
+        .reject_amendment(&id, &path.into_inner().amendment_id, body.into_inner())
+        .await
+        .log_err()
+        .map(|_| HttpResponse::NoContent().finish())
+}
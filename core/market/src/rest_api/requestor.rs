@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use ya_client::model::market::{AgreementProposal, NewDemand, NewProposal, Reason};
 use ya_client::model::ErrorMessage;
+use ya_core_model::market::AgreementAmendment;
 use ya_service_api_web::middleware::Identity;
 use ya_std_utils::LogErr;
 
@@ -12,10 +13,10 @@ use crate::db::model::Owner;
 use crate::market::MarketService;
 
 use super::{
-    PathAgreement, PathSubscription, PathSubscriptionProposal, ProposalId, QueryTimeout,
-    QueryTimeoutMaxEvents,
+    PathAgreement, PathSubscription, PathSubscriptionProposal, ProposalId, QueryEventsCursor,
+    QueryNegotiationEvents, QueryTimeout, EVENTS_CURSOR_HEADER,
 };
-use crate::negotiation::ApprovalStatus;
+use crate::negotiation::{error::AgreementError, ApprovalStatus};
 use crate::rest_api::QueryAppSessionId;
 
 pub fn register_endpoints(scope: Scope) -> Scope {
@@ -24,6 +25,7 @@ pub fn register_endpoints(scope: Scope) -> Scope {
         .service(get_demands)
         .service(unsubscribe)
         .service(collect)
+        .service(ack_events)
         .service(counter_proposal)
         .service(get_proposal)
         .service(reject_proposal)
@@ -31,6 +33,7 @@ pub fn register_endpoints(scope: Scope) -> Scope {
         .service(confirm_agreement)
         .service(wait_for_approval)
         .service(cancel_agreement)
+        .service(amend_agreement)
 }
 
 #[actix_web::post("/demands")]
@@ -73,18 +76,54 @@ async fn unsubscribe(
 async fn collect(
     market: Data<Arc<MarketService>>,
     path: Path<PathSubscription>,
-    query: Query<QueryTimeoutMaxEvents>,
+    query: Query<QueryNegotiationEvents>,
     _id: Identity, // TODO: use it
 ) -> impl Responder {
     let subscription_id = path.into_inner().subscription_id;
     let timeout = query.timeout;
     let max_events = query.max_events;
+    if !query.with_ack() {
+        return market
+            .requestor_engine
+            .query_events(&subscription_id, timeout, max_events)
+            .await
+            .log_err()
+            .map(|events| HttpResponse::Ok().json(events));
+    }
+
     market
         .requestor_engine
-        .query_events(&subscription_id, timeout, max_events)
+        .query_events_with_ack(
+            &subscription_id,
+            timeout,
+            max_events,
+            query.into_inner().cursor,
+            vec![],
+        )
         .await
         .log_err()
-        .map(|events| HttpResponse::Ok().json(events))
+        .map(|(cursor, events)| {
+            let mut response = HttpResponse::Ok();
+            if let Some(cursor) = cursor {
+                response.insert_header((EVENTS_CURSOR_HEADER, cursor));
+            }
+            response.json(events)
+        })
+}
+
+#[actix_web::post("/demands/{subscription_id}/events/ack")]
+async fn ack_events(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathSubscription>,
+    query: Query<QueryEventsCursor>,
+    _id: Identity, // TODO: use it
+) -> impl Responder {
+    market
+        .requestor_engine
+        .ack_events(&path.into_inner().subscription_id, &query.cursor)
+        .await
+        .log_err()
+        .map(|_| HttpResponse::NoContent().finish())
 }
 
 #[actix_web::post("/demands/{subscription_id}/proposals/{proposal_id}")]
@@ -214,3 +253,23 @@ async fn cancel_agreement(
         .log_err()
         .map(|_| HttpResponse::Ok().finish())
 }
+
+#[actix_web::post("/agreements/{agreement_id}/amend")]
+async fn amend_agreement(
+    market: Data<Arc<MarketService>>,
+    path: Path<PathAgreement>,
+    query: Query<QueryTimeout>,
+    id: Identity,
+    body: Json<AgreementAmendment>,
+) -> impl Responder {
+    let agreement_id = path.into_inner().to_id(Owner::Requestor)?;
+    let agreement = market
+        .requestor_engine
+        .amend_agreement(&id, &agreement_id, body.into_inner(), query.timeout)
+        .await
+        .log_err()?;
+    agreement
+        .into_client()
+        .map(|agreement| HttpResponse::Ok().json(agreement))
+        .map_err(|e| AgreementError::Internal(e.to_string()))
+}
@@ -17,6 +17,8 @@ pub struct TestMarketEvent {
     pub event_type: EventType,
     pub artifact_id: ProposalId,
     pub reason: Option<String>,
+    pub delivery_cursor: Option<String>,
+    pub delivery_ts: Option<NaiveDateTime>,
 }
 
 pub fn generate_event(id: i32, timestamp: NaiveDateTime) -> TestMarketEvent {
@@ -32,6 +34,8 @@ pub fn generate_event(id: i32, timestamp: NaiveDateTime) -> TestMarketEvent {
         ),
         timestamp,
         reason: None,
+        delivery_cursor: None,
+        delivery_ts: None,
     }
 }
 
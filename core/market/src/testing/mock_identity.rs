@@ -1,4 +1,4 @@
-use rand::distributions::Alphanumeric;
+use ethsign::SecretKey;
 use rand::{thread_rng, Rng};
 use std::sync::{Arc, Mutex};
 
@@ -15,6 +15,7 @@ pub struct MockIdentity {
 struct MockIdentityInner {
     pub default: Identity,
     pub identities: HashMap<String, Identity>,
+    pub keys: HashMap<NodeId, SecretKey>,
 }
 
 #[async_trait::async_trait(?Send)]
@@ -30,19 +31,31 @@ impl IdentityApi for MockIdentity {
             .map(|(_, id)| id.identity)
             .collect())
     }
+
+    async fn sign(&self, node_id: &NodeId, payload: Vec<u8>) -> Result<Vec<u8>, IdentityError> {
+        let inner = self.inner.lock().unwrap();
+        let key = inner
+            .keys
+            .get(node_id)
+            .ok_or_else(|| IdentityError::SignError(format!("Unknown identity [{}]", node_id)))?;
+        sign_with_key(key, &payload)
+    }
 }
 
 impl MockIdentity {
     pub fn new(name: &str) -> Arc<MockIdentity> {
-        let default = generate_identity(name);
+        let (default, key) = generate_key_identity(name);
         let mut identities = HashMap::new();
         identities
             .entry(name.to_string())
             .or_insert(default.clone());
+        let mut keys = HashMap::new();
+        keys.insert(default.identity, key);
 
         let mock_identity = MockIdentityInner {
             default,
             identities,
+            keys,
         };
 
         Arc::new(MockIdentity {
@@ -50,14 +63,15 @@ impl MockIdentity {
         })
     }
     pub fn new_identity(&self, name: &str) -> Identity {
-        let new_id = generate_identity(name);
-        self.inner
-            .lock()
-            .unwrap()
-            .identities
-            .entry(name.to_string())
-            .or_insert(new_id)
-            .clone()
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(id) = inner.identities.get(name) {
+            return id.clone();
+        }
+
+        let (new_id, key) = generate_key_identity(name);
+        inner.keys.insert(new_id.identity, key);
+        inner.identities.insert(name.to_string(), new_id.clone());
+        new_id
     }
 
     pub fn get_default_id(&self) -> Identity {
@@ -70,15 +84,35 @@ impl MockIdentity {
 }
 
 pub fn generate_identity(name: &str) -> Identity {
-    let random_node_id: String = thread_rng()
-        .sample_iter(&Alphanumeric)
-        .map(char::from)
-        .take(20)
-        .collect();
+    generate_key_identity(name).0
+}
 
-    Identity {
+/// Generates Identity together with secret key, that can be used
+/// to sign data in the name of this Identity.
+pub fn generate_key_identity(name: &str) -> (Identity, SecretKey) {
+    let key = loop {
+        let raw: [u8; 32] = thread_rng().gen();
+        if let Ok(key) = SecretKey::from_raw(&raw) {
+            break key;
+        }
+    };
+
+    let identity = Identity {
         name: name.to_string(),
         role: "manager".to_string(),
-        identity: NodeId::from(random_node_id.as_bytes()),
-    }
+        identity: NodeId::from(key.public().address().as_ref()),
+    };
+    (identity, key)
+}
+
+pub fn sign_with_key(key: &SecretKey, payload: &[u8]) -> Result<Vec<u8>, IdentityError> {
+    let signature = key
+        .sign(payload)
+        .map_err(|e| IdentityError::SignError(e.to_string()))?;
+
+    let mut bytes = Vec::with_capacity(65);
+    bytes.push(signature.v);
+    bytes.extend_from_slice(&signature.r[..]);
+    bytes.extend_from_slice(&signature.s[..]);
+    Ok(bytes)
 }
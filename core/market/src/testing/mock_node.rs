@@ -210,8 +210,12 @@ impl MarketsNetwork {
     pub async fn add_matcher_instance(self, name: &str) -> Self {
         let db = self.init_database(name);
 
-        let store = SubscriptionStore::new(db.clone(), self.config.clone());
         let identity_api = MockIdentity::new(name);
+        let store = SubscriptionStore::new(
+            db.clone(),
+            identity_api.clone() as Arc<dyn IdentityApi>,
+            self.config.clone(),
+        );
 
         let (matcher, listeners) =
             Matcher::new(store, identity_api.clone(), self.config.clone()).unwrap();
@@ -322,6 +326,7 @@ impl MarketsNetwork {
             prov_agreement_cancelled,
             prov_agreement_terminated,
             prov_agreement_committed,
+            default::empty_on_agreement_amendment_received,
         );
 
         let requestor = requestor::NegotiationApi::new(
@@ -597,8 +602,8 @@ impl MarketServiceExt for MarketService {
 pub mod default {
     use super::*;
     use crate::protocol::negotiation::error::{
-        AgreementProtocolError, CommitAgreementError, CounterProposalError, ProposeAgreementError,
-        RejectProposalError, TerminateAgreementError,
+        AgreementProtocolError, AmendAgreementError, CommitAgreementError, CounterProposalError,
+        ProposeAgreementError, RejectProposalError, TerminateAgreementError,
     };
 
     pub async fn empty_on_offers_retrieved(
@@ -691,6 +696,13 @@ pub mod default {
     ) -> Result<(), TerminateAgreementError> {
         Ok(())
     }
+
+    pub async fn empty_on_agreement_amendment_received(
+        _caller: String,
+        _msg: AgreementAmendmentReceived,
+    ) -> Result<(), AmendAgreementError> {
+        Ok(())
+    }
 }
 
 pub fn create_market_config_for_test() -> Config {
@@ -7,7 +7,7 @@ use ya_client::model::NodeId;
 
 use crate::db::model::{Demand, Offer};
 use crate::protocol::discovery::message::RetrieveOffers;
-use crate::testing::mock_identity::generate_identity;
+use crate::testing::mock_identity::{generate_identity, generate_key_identity, sign_with_key};
 use crate::testing::SubscriptionId;
 
 pub fn flatten_json(json: &Value) -> Value {
@@ -25,24 +25,21 @@ pub fn sample_retrieve_offers() -> RetrieveOffers {
 pub fn sample_offer() -> Offer {
     let creation_ts = Utc::now().naive_utc();
     let expiration_ts = creation_ts + Duration::hours(1);
-    Offer::from_new(
-        &client::sample_offer(),
-        &generate_identity(""),
-        creation_ts,
-        expiration_ts,
-    )
-    .unwrap()
+    signed_sample_offer(creation_ts, expiration_ts)
 }
 
 pub fn sample_offer_with_expiration(expiration_ts: NaiveDateTime) -> Offer {
     let creation_ts = Utc::now().naive_utc();
-    Offer::from_new(
-        &client::sample_offer(),
-        &generate_identity(""),
-        creation_ts,
-        expiration_ts,
-    )
-    .unwrap()
+    signed_sample_offer(creation_ts, expiration_ts)
+}
+
+/// Creates Offer signed by randomly generated Provider identity.
+fn signed_sample_offer(creation_ts: NaiveDateTime, expiration_ts: NaiveDateTime) -> Offer {
+    let (id, key) = generate_key_identity("");
+    let mut offer =
+        Offer::from_new(&client::sample_offer(), &id, creation_ts, expiration_ts).unwrap();
+    offer.set_signature(&sign_with_key(&key, &offer.signed_payload()).unwrap());
+    offer
 }
 
 pub fn generate_offer(id: &str, expiration_ts: NaiveDateTime) -> Offer {
@@ -54,6 +51,7 @@ pub fn generate_offer(id: &str, expiration_ts: NaiveDateTime) -> Offer {
         creation_ts: Utc::now().naive_utc(),
         insertion_ts: None,
         expiration_ts,
+        signature: None,
     }
 }
 
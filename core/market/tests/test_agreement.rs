@@ -1214,3 +1214,251 @@ async fn test_terminate_invalid_reason() {
     let resp = actix_web::test::call_service(&mut app, req).await;
     assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
 }
+
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_list_agreements() {
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance(REQ_NAME)
+        .await
+        .add_market_instance(PROV_NAME)
+        .await;
+    let req_market = network.get_market(REQ_NAME);
+    let prov_market = network.get_market(PROV_NAME);
+    let req_id = network.get_default_id(REQ_NAME);
+    let prov_id = network.get_default_id(PROV_NAME);
+
+    let agreement_id = negotiate_agreement(
+        &network,
+        REQ_NAME,
+        PROV_NAME,
+        "negotiation",
+        "r-session",
+        "p-session",
+    )
+    .await
+    .unwrap()
+    .r_agreement
+    .into_client();
+
+    let r_list = req_market
+        .list_agreements(&req_id, Default::default())
+        .await
+        .unwrap();
+    assert_eq!(r_list.len(), 1);
+    assert_eq!(r_list[0].agreement_id, agreement_id);
+    assert_eq!(r_list[0].role, Role::Requestor);
+    assert_eq!(r_list[0].peer_id, prov_id.identity);
+    assert_eq!(r_list[0].state, market::AgreementState::Approved);
+
+    let p_list = prov_market
+        .list_agreements(&prov_id, Default::default())
+        .await
+        .unwrap();
+    assert_eq!(p_list.len(), 1);
+    assert_eq!(p_list[0].role, Role::Provider);
+    assert_eq!(p_list[0].app_session_id, Some("p-session".to_string()));
+
+    let filter = market::AgreementListFilter {
+        state: Some(market::AgreementState::Terminated),
+        ..Default::default()
+    };
+    let list = req_market.list_agreements(&req_id, filter).await.unwrap();
+    assert!(list.is_empty());
+
+    let filter = market::AgreementListFilter {
+        app_session_id: Some("r-session".to_string()),
+        created_after: Some(Utc::now() - Duration::hours(1)),
+        ..Default::default()
+    };
+    let list = req_market.list_agreements(&req_id, filter).await.unwrap();
+    assert_eq!(list.len(), 1);
+
+    // Provider's identity doesn't own any Agreement on Requestor's node.
+    let list = req_market
+        .list_agreements(&prov_id, Default::default())
+        .await
+        .unwrap();
+    assert!(list.is_empty());
+
+    // Limit is bounded, like maxEvents.
+    let filter = market::AgreementListFilter {
+        limit: Some(1_000_000),
+        ..Default::default()
+    };
+    assert!(req_market.list_agreements(&req_id, filter).await.is_err());
+
+    let mut app = network.get_rest_app(REQ_NAME).await;
+    let req = actix_web::test::TestRequest::get()
+        .uri("/market-api/v1/agreements?limit=1000000")
+        .to_request();
+    let resp = actix_web::test::call_service(&mut app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+}
+
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_amend_agreement() {
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance(REQ_NAME)
+        .await
+        .add_market_instance(PROV_NAME)
+        .await;
+    let req_market = network.get_market(REQ_NAME);
+    let prov_market = network.get_market(PROV_NAME);
+    let req_id = network.get_default_id(REQ_NAME);
+    let prov_id = network.get_default_id(PROV_NAME);
+
+    let negotiation = negotiate_agreement(
+        &network,
+        REQ_NAME,
+        PROV_NAME,
+        "negotiation",
+        "r-session",
+        "p-session",
+    )
+    .await
+    .unwrap();
+
+    let new_valid_to = Utc::now() + Duration::hours(5);
+    let amendment = market::AgreementAmendment {
+        valid_to: new_valid_to,
+        payment_timeout_sec: Some(600),
+        debit_note_interval_sec: None,
+    };
+
+    let prov_engine = prov_market.provider_engine.clone();
+    let prov_id1 = prov_id.clone();
+    let decision = tokio::task::spawn_local(async move {
+        let events = prov_engine.query_amendments(&prov_id1, 2.0, Some(5)).await;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].amendment.payment_timeout_sec, Some(600));
+        prov_engine
+            .approve_amendment(&prov_id1, &events[0].amendment_id)
+            .await
+    });
+
+    let r_agreement = req_market
+        .requestor_engine
+        .amend_agreement(&req_id, &negotiation.r_agreement, amendment, 3.0)
+        .await
+        .unwrap();
+    decision.await.unwrap().unwrap();
+    assert_eq!(r_agreement.state, AgreementState::Approved);
+
+    // Provider is notified after amendment was stored.
+    let events = prov_market
+        .provider_engine
+        .query_amendments(&prov_id, 0.0, Some(5))
+        .await;
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].event_type, market::AmendmentEventType::Amended);
+    assert_eq!(
+        events[0].agreement_id,
+        negotiation.p_agreement.into_client()
+    );
+    assert_eq!(events[0].amendment.payment_timeout_sec, Some(600));
+    assert_eq!(r_agreement.valid_to.timestamp(), new_valid_to.timestamp());
+
+    let p_agreement = prov_market
+        .get_agreement(&negotiation.p_agreement, &prov_id)
+        .await
+        .unwrap();
+    assert_eq!(p_agreement.valid_to.timestamp(), new_valid_to.timestamp());
+    assert_eq!(
+        p_agreement.demand.properties["golem.com.scheme.payu.payment-timeout-sec?"],
+        serde_json::json!(600)
+    );
+
+    // Amendment is not visible as client Agreement event.
+    let events = req_market
+        .query_agreement_events(
+            &Some("r-session".to_string()),
+            0.0,
+            Some(10),
+            Utc::now() - Duration::hours(1),
+            &req_id,
+        )
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+}
+
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_amend_agreement_rejected() {
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance(REQ_NAME)
+        .await
+        .add_market_instance(PROV_NAME)
+        .await;
+    let req_market = network.get_market(REQ_NAME);
+    let prov_market = network.get_market(PROV_NAME);
+    let req_id = network.get_default_id(REQ_NAME);
+    let prov_id = network.get_default_id(PROV_NAME);
+
+    let negotiation = negotiate_agreement(
+        &network,
+        REQ_NAME,
+        PROV_NAME,
+        "negotiation",
+        "r-session",
+        "p-session",
+    )
+    .await
+    .unwrap();
+    let amendment = market::AgreementAmendment {
+        valid_to: Utc::now() + Duration::hours(5),
+        payment_timeout_sec: None,
+        debit_note_interval_sec: None,
+    };
+
+    // Nobody makes decision on Provider side.
+    let result = req_market
+        .requestor_engine
+        .amend_agreement(&req_id, &negotiation.r_agreement, amendment.clone(), 0.5)
+        .await;
+    assert!(result.is_err());
+
+    let prov_engine = prov_market.provider_engine.clone();
+    let prov_id1 = prov_id.clone();
+    tokio::task::spawn_local(async move {
+        let events = prov_engine.query_amendments(&prov_id1, 2.0, Some(5)).await;
+        prov_engine
+            .reject_amendment(
+                &prov_id1,
+                &events[0].amendment_id,
+                Some(gen_reason("Too long")),
+            )
+            .await
+            .unwrap();
+    });
+
+    let result = req_market
+        .requestor_engine
+        .amend_agreement(&req_id, &negotiation.r_agreement, amendment, 3.0)
+        .await;
+    assert!(result.is_err());
+
+    // Agreement wasn't changed on any side.
+    let p_agreement = prov_market
+        .get_agreement(&negotiation.p_agreement, &prov_id)
+        .await
+        .unwrap();
+    let r_agreement = req_market
+        .get_agreement(&negotiation.r_agreement, &req_id)
+        .await
+        .unwrap();
+    assert_eq!(p_agreement.valid_to, r_agreement.valid_to);
+    assert!(r_agreement.valid_to < Utc::now() + Duration::hours(2));
+
+    // Rejected amendments aren't reported as applied.
+    let events = prov_market
+        .provider_engine
+        .query_amendments(&prov_id, 0.0, Some(5))
+        .await;
+    assert!(events.is_empty());
+}
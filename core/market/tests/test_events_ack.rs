@@ -0,0 +1,198 @@
+use std::sync::Arc;
+
+use ya_client::model::market::RequestorEvent;
+use ya_market::testing::mock_node::create_market_config_for_test;
+use ya_market::testing::mock_offer::client::{sample_demand, sample_offer};
+use ya_market::testing::{EventType, MarketsNetwork};
+
+const QUERY_TIMEOUT: f32 = 5.0;
+
+fn proposal_id(event: &RequestorEvent) -> String {
+    match event {
+        RequestorEvent::ProposalEvent { proposal, .. } => proposal.proposal_id.clone(),
+        _ => panic!("Invalid event Type. ProposalEvent expected: {:?}", event),
+    }
+}
+
+/// Events queried with acknowledgement stay in queue until acknowledged
+/// and can be fetched once more using cursor of their batch.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_resume_not_acknowledged_events() {
+    let _ = env_logger::builder().try_init();
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance("Requestor")
+        .await
+        .add_market_instance("Provider")
+        .await;
+
+    let req_mkt = network.get_market("Requestor");
+    let req_id = network.get_default_id("Requestor");
+    let prov_mkt = network.get_market("Provider");
+    let prov_id = network.get_default_id("Provider");
+
+    let demand_id = req_mkt
+        .subscribe_demand(&sample_demand(), &req_id)
+        .await
+        .unwrap();
+    prov_mkt
+        .subscribe_offer(&sample_offer(), &prov_id)
+        .await
+        .unwrap();
+
+    let (cursor, events) = req_mkt
+        .requestor_engine
+        .query_events_with_ack(&demand_id, QUERY_TIMEOUT, None, None, vec![])
+        .await
+        .unwrap();
+    let cursor = cursor.unwrap();
+    assert_eq!(events.len(), 1);
+
+    // Delivered event waits for acknowledgement.
+    let (next_cursor, next_events) = req_mkt
+        .requestor_engine
+        .query_events_with_ack(&demand_id, 0.2, None, None, vec![])
+        .await
+        .unwrap();
+    assert!(next_cursor.is_none());
+    assert!(next_events.is_empty());
+
+    // Client restarted and resumes from last cursor.
+    let (resumed_cursor, resumed) = req_mkt
+        .requestor_engine
+        .query_events_with_ack(&demand_id, 0.2, None, Some(cursor.clone()), vec![])
+        .await
+        .unwrap();
+    assert_eq!(resumed_cursor, Some(cursor.clone()));
+    assert_eq!(resumed.len(), 1);
+    assert_eq!(proposal_id(&resumed[0]), proposal_id(&events[0]));
+
+    req_mkt
+        .requestor_engine
+        .ack_events(&demand_id, &cursor)
+        .await
+        .unwrap();
+
+    let (_, events) = req_mkt
+        .requestor_engine
+        .query_events_with_ack(&demand_id, 0.2, None, Some(cursor), vec![])
+        .await
+        .unwrap();
+    assert!(events.is_empty());
+}
+
+/// Events not acknowledged in time are delivered again with new cursor.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_redeliver_not_acknowledged_events() {
+    let _ = env_logger::builder().try_init();
+    let mut config = create_market_config_for_test();
+    config.events.ack_timeout = chrono::Duration::zero();
+
+    let network = MarketsNetwork::new(None)
+        .await
+        .with_config(Arc::new(config))
+        .add_market_instance("Requestor")
+        .await
+        .add_market_instance("Provider")
+        .await;
+
+    let req_mkt = network.get_market("Requestor");
+    let req_id = network.get_default_id("Requestor");
+    let prov_mkt = network.get_market("Provider");
+    let prov_id = network.get_default_id("Provider");
+
+    let demand_id = req_mkt
+        .subscribe_demand(&sample_demand(), &req_id)
+        .await
+        .unwrap();
+    prov_mkt
+        .subscribe_offer(&sample_offer(), &prov_id)
+        .await
+        .unwrap();
+
+    let (cursor, events) = req_mkt
+        .requestor_engine
+        .query_events_with_ack(&demand_id, QUERY_TIMEOUT, None, None, vec![])
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+
+    let (redelivered_cursor, redelivered) = req_mkt
+        .requestor_engine
+        .query_events_with_ack(&demand_id, QUERY_TIMEOUT, None, None, vec![])
+        .await
+        .unwrap();
+    assert_ne!(redelivered_cursor, cursor);
+    assert_eq!(redelivered.len(), 1);
+    assert_eq!(proposal_id(&redelivered[0]), proposal_id(&events[0]));
+
+    // Acknowledging stale cursor has no effect.
+    req_mkt
+        .requestor_engine
+        .ack_events(&demand_id, &cursor.unwrap())
+        .await
+        .unwrap();
+    req_mkt
+        .requestor_engine
+        .ack_events(&demand_id, &redelivered_cursor.unwrap())
+        .await
+        .unwrap();
+
+    let events = req_mkt
+        .requestor_engine
+        .query_events(&demand_id, 0.2, None)
+        .await
+        .unwrap();
+    assert!(events.is_empty());
+}
+
+/// Events of types, that weren't queried, stay in queue for other clients.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_query_events_with_ack_of_chosen_types() {
+    let _ = env_logger::builder().try_init();
+    let network = MarketsNetwork::new(None)
+        .await
+        .add_market_instance("Requestor")
+        .await
+        .add_market_instance("Provider")
+        .await;
+
+    let req_mkt = network.get_market("Requestor");
+    let req_id = network.get_default_id("Requestor");
+    let prov_mkt = network.get_market("Provider");
+    let prov_id = network.get_default_id("Provider");
+
+    let demand_id = req_mkt
+        .subscribe_demand(&sample_demand(), &req_id)
+        .await
+        .unwrap();
+    prov_mkt
+        .subscribe_offer(&sample_offer(), &prov_id)
+        .await
+        .unwrap();
+
+    let (cursor, events) = req_mkt
+        .requestor_engine
+        .query_events_with_ack(
+            &demand_id,
+            1.0,
+            None,
+            None,
+            vec![EventType::RequestorProposalRejected],
+        )
+        .await
+        .unwrap();
+    assert!(cursor.is_none());
+    assert!(events.is_empty());
+
+    let events = req_mkt
+        .requestor_engine
+        .query_events(&demand_id, QUERY_TIMEOUT, None)
+        .await
+        .unwrap();
+    assert_eq!(events.len(), 1);
+    proposal_id(&events[0]);
+}
@@ -1,14 +1,18 @@
 use chrono::Utc;
 use futures::{channel::mpsc, prelude::*};
 use std::str::FromStr;
+use std::sync::Arc;
 use tokio::time::Duration;
 
 use ya_market::assert_err_eq;
 use ya_market::testing::discovery::{message::*, Discovery};
-use ya_market::testing::mock_node::{assert_offers_broadcasted, assert_unsunbscribes_broadcasted};
+use ya_market::testing::mock_identity::{generate_key_identity, sign_with_key};
+use ya_market::testing::mock_node::{
+    assert_offers_broadcasted, assert_unsunbscribes_broadcasted, create_market_config_for_test,
+};
 use ya_market::testing::mock_offer::{client, sample_offer, sample_offer_with_expiration};
+use ya_market::testing::{Config, QueryOfferError, SubscriptionId};
 use ya_market::testing::{MarketServiceExt, MarketsNetwork};
-use ya_market::testing::{QueryOfferError, SubscriptionId};
 
 /// Test adds offer. It should be broadcasted to other nodes in the network.
 /// Than sending unsubscribe should remove Offer from other nodes.
@@ -156,6 +160,85 @@ async fn test_broadcast_offer_id_validation() {
     );
 }
 
+/// Offer should be signed by Provider, that created it. Market should
+/// reject Offers signed by someone else, because they could be modified
+/// by nodes broadcasting them. Unsigned Offers are accepted until signatures
+/// are required by config.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_broadcast_offer_signature_validation() {
+    let _ = env_logger::builder().try_init();
+    let (network, unsigned, forged) =
+        broadcast_unsigned_and_forged(create_market_config_for_test()).await;
+    let mkt1 = network.get_market("Node-1");
+
+    assert_eq!(mkt1.get_offer(&unsigned).await.unwrap().id, unsigned);
+    assert_err_eq!(
+        QueryOfferError::NotFound(forged.clone()),
+        mkt1.get_offer(&forged).await,
+    );
+}
+
+/// Market requiring signatures should reject unsigned Offers as well.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_broadcast_offer_signature_required() {
+    let _ = env_logger::builder().try_init();
+    let mut config = create_market_config_for_test();
+    config.offer_signature.required = true;
+    let (network, unsigned, forged) = broadcast_unsigned_and_forged(config).await;
+    let mkt1 = network.get_market("Node-1");
+
+    assert_err_eq!(
+        QueryOfferError::NotFound(unsigned.clone()),
+        mkt1.get_offer(&unsigned).await,
+    );
+    assert_err_eq!(
+        QueryOfferError::NotFound(forged.clone()),
+        mkt1.get_offer(&forged).await,
+    );
+}
+
+/// Broadcasts Offer without signature and Offer signed by other identity
+/// to `Node-1` and returns their ids.
+async fn broadcast_unsigned_and_forged(
+    config: Config,
+) -> (MarketsNetwork, SubscriptionId, SubscriptionId) {
+    let network = MarketsNetwork::new(None)
+        .await
+        .with_config(Arc::new(config))
+        .add_market_instance("Node-1")
+        .await;
+
+    let mut unsigned = sample_offer();
+    unsigned.signature = None;
+
+    let (_, forger_key) = generate_key_identity("forger");
+    let mut forged = sample_offer();
+    forged.set_signature(&sign_with_key(&forger_key, &forged.signed_payload()).unwrap());
+
+    let offers = vec![unsigned.clone(), forged.clone()];
+    let discovery_builder = network.discovery_builder();
+    let network = network
+        .add_discovery_instance(
+            "Node-2",
+            discovery_builder.add_handler(move |_: String, _: RetrieveOffers| {
+                let offers = offers.clone();
+                async move { Ok(offers) }
+            }),
+        )
+        .await;
+    let discovery2: Discovery = network.get_discovery("Node-2");
+
+    discovery2
+        .bcast_offers(vec![unsigned.id.clone(), forged.id.clone()])
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+    (network, unsigned.id, forged.id)
+}
+
 /// Node should reject Offer, that already expired.
 #[cfg_attr(not(feature = "test-suite"), ignore)]
 #[serial_test::serial]
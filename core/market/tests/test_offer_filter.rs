@@ -0,0 +1,125 @@
+use std::sync::Arc;
+use tokio::time::Duration;
+
+use ya_market::assert_err_eq;
+use ya_market::testing::client::{not_matching_offer, sample_demand, sample_offer};
+use ya_market::testing::mock_node::create_market_config_for_test;
+use ya_market::testing::{MarketServiceExt, MarketsNetwork, QueryOfferError};
+
+fn filtering_network_config() -> Arc<ya_market::testing::Config> {
+    let mut config = create_market_config_for_test();
+    config.offer_filter.enabled = true;
+    Arc::new(config)
+}
+
+/// Node with Offers filtering enabled should store only Offers matching
+/// its Demands, even though it receives all broadcasted Offers.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_filter_not_matching_offers() {
+    let _ = env_logger::builder().try_init();
+    let network = MarketsNetwork::new(None)
+        .await
+        .with_config(filtering_network_config())
+        .add_market_instance("Requestor")
+        .await
+        .add_market_instance("Provider")
+        .await;
+
+    let req_mkt = network.get_market("Requestor");
+    let req_id = network.get_default_id("Requestor");
+    let prov_mkt = network.get_market("Provider");
+    let prov_id = network.get_default_id("Provider");
+
+    req_mkt
+        .subscribe_demand(&sample_demand(), &req_id)
+        .await
+        .unwrap();
+
+    let matching = prov_mkt
+        .subscribe_offer(&sample_offer(), &prov_id)
+        .await
+        .unwrap();
+    let mut not_matching = vec![];
+    for _ in 0..5 {
+        not_matching.push(
+            prov_mkt
+                .subscribe_offer(&not_matching_offer(), &prov_id)
+                .await
+                .unwrap(),
+        );
+    }
+
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+
+    // Requestor keeps only matching Offer, while Provider has all of them.
+    req_mkt.get_offer(&matching).await.unwrap();
+    for offer_id in not_matching.iter() {
+        assert_err_eq!(
+            QueryOfferError::NotFound(offer_id.clone()),
+            req_mkt.get_offer(offer_id).await,
+        );
+    }
+    assert_eq!(
+        req_mkt
+            .matcher
+            .store
+            .get_active_offer_ids(None)
+            .await
+            .unwrap()
+            .len(),
+        1
+    );
+    assert_eq!(
+        prov_mkt
+            .matcher
+            .store
+            .get_active_offer_ids(None)
+            .await
+            .unwrap()
+            .len(),
+        6
+    );
+}
+
+/// Offers cached only for re-broadcasts should be stored, when new Demand
+/// matching them is subscribed.
+#[cfg_attr(not(feature = "test-suite"), ignore)]
+#[serial_test::serial]
+async fn test_store_cached_offers_on_new_demand() {
+    let _ = env_logger::builder().try_init();
+    let network = MarketsNetwork::new(None)
+        .await
+        .with_config(filtering_network_config())
+        .add_market_instance("Requestor")
+        .await
+        .add_market_instance("Provider")
+        .await;
+
+    let req_mkt = network.get_market("Requestor");
+    let req_id = network.get_default_id("Requestor");
+    let prov_mkt = network.get_market("Provider");
+    let prov_id = network.get_default_id("Provider");
+
+    // Requestor's Demand doesn't match Offer, so Offer can be only cached.
+    req_mkt
+        .subscribe_demand(&sample_demand(), &req_id)
+        .await
+        .unwrap();
+    let offer_id = prov_mkt
+        .subscribe_offer(&not_matching_offer(), &prov_id)
+        .await
+        .unwrap();
+
+    tokio::time::sleep(Duration::from_millis(1000)).await;
+    assert_err_eq!(
+        QueryOfferError::NotFound(offer_id.clone()),
+        req_mkt.get_offer(&offer_id).await,
+    );
+
+    let mut demand = sample_demand();
+    demand.properties["custom"] = serde_json::json!({ "dontmatch": "true" });
+    req_mkt.subscribe_demand(&demand, &req_id).await.unwrap();
+
+    req_mkt.get_offer(&offer_id).await.unwrap();
+}
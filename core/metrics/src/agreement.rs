@@ -0,0 +1,381 @@
+//! Per-Agreement cost and usage gauges.
+//!
+//! `metrics` crate can't forget a series, so these are kept aside, for at most
+//! `MAX_AGREEMENTS` recently updated Agreements. They are exposed only on
+//! scrape endpoint and never pushed.
+
+use lazy_static::lazy_static;
+use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::prometheus::PrometheusText;
+
+const MAX_AGREEMENTS: usize = 100;
+const PROVIDER_ROLE: &str = "provider";
+/// Label value used instead of values set by the other side of Agreement,
+/// which aren't known locally.
+const OTHER: &str = "other";
+/// Runtimes of Offers of other nodes, which are used as label values.
+const KNOWN_RUNTIMES: &[&str] = &["vm", "wasmtime", "sgx", "sgx-js", "sgx-wasi"];
+
+/// Labels of Agreement. Platform, runtime and preset have bounded number of
+/// values, so they are used by global counters too. Peer is used only by
+/// gauges of `MAX_AGREEMENTS` Agreements.
+#[derive(Clone, Debug, Default)]
+pub struct AgreementLabels {
+    /// `provider` or `requestor`
+    pub role: String,
+    /// Node id of the other side of Agreement
+    pub peer: String,
+    pub platform: String,
+    pub runtime: String,
+    pub preset: String,
+}
+
+impl AgreementLabels {
+    /// Reads labels from flat or nested Demand and Offer properties.
+    ///
+    /// Only values of our own Demand or Offer are used as they are. Payment
+    /// platform chosen by Requestor has to be one of platforms of our Offer,
+    /// and runtime of Provider has to be known, otherwise they are `other`.
+    /// Preset is set by Provider agent with `set_preset`.
+    pub fn from_properties(
+        role: &str,
+        agreement_id: &str,
+        peer: String,
+        demand_properties: &Value,
+        offer_properties: &Value,
+    ) -> Self {
+        let mut platform = property(demand_properties, "golem.com.payment.chosen-platform");
+        let mut runtime = property(offer_properties, "golem.runtime.name");
+        let mut preset = String::new();
+
+        if role == PROVIDER_ROLE {
+            let address = format!("golem.com.payment.platform.{}.address", platform);
+            if !platform.is_empty() && property(offer_properties, &address).is_empty() {
+                platform = OTHER.to_string();
+            }
+            if let Some(name) = PRESETS.lock().unwrap().get(agreement_id) {
+                preset = name;
+            }
+        } else if !runtime.is_empty() && !KNOWN_RUNTIMES.contains(&runtime.as_str()) {
+            runtime = OTHER.to_string();
+        }
+
+        AgreementLabels {
+            role: role.to_string(),
+            peer,
+            platform,
+            runtime,
+            preset,
+        }
+    }
+}
+
+/// Preset names of recent Agreements, reported by local Provider agent.
+#[derive(Default)]
+struct Presets {
+    presets: HashMap<String, String>,
+    order: VecDeque<String>,
+}
+
+impl Presets {
+    fn insert(&mut self, agreement_id: &str, preset: &str) {
+        if self
+            .presets
+            .insert(agreement_id.to_string(), preset.to_string())
+            .is_none()
+        {
+            self.order.push_back(agreement_id.to_string());
+        }
+        while self.order.len() > MAX_AGREEMENTS {
+            if let Some(oldest) = self.order.pop_front() {
+                self.presets.remove(&oldest);
+            }
+        }
+    }
+
+    fn get(&self, agreement_id: &str) -> Option<String> {
+        self.presets.get(agreement_id).cloned()
+    }
+}
+
+/// Sets preset label of Agreement. Provider agent sets it before approving
+/// Agreement, because preset isn't part of Offer.
+pub fn set_preset(agreement_id: &str, preset: &str) {
+    PRESETS.lock().unwrap().insert(agreement_id, preset);
+}
+
+fn property(properties: &Value, name: &str) -> String {
+    properties
+        .get(name)
+        .or_else(|| properties.pointer(&format!("/{}", name.replace('.', "/"))))
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Increments global counter and its series labelled with payment platform,
+/// runtime and preset of Agreement. Unlabelled series is the total.
+///
+/// Expands to `metrics::counter!` of calling crate.
+#[macro_export]
+macro_rules! agreement_counter {
+    ($name:expr, $value:expr, $labels:expr) => {{
+        let labels: &$crate::agreement::AgreementLabels = &$labels;
+        metrics::counter!($name, $value);
+        metrics::counter!(
+            $name,
+            $value,
+            "platform" => labels.platform.clone(),
+            "runtime" => labels.runtime.clone(),
+            "preset" => labels.preset.clone()
+        );
+    }};
+}
+
+#[derive(Default)]
+struct AgreementMetrics {
+    labels: AgreementLabels,
+    /// Amount due of each Activity
+    costs: HashMap<String, f64>,
+    invoiced: Option<f64>,
+    /// Usage counters of each Activity
+    usage: HashMap<String, Vec<(String, f64)>>,
+    updated: u64,
+}
+
+#[derive(Default)]
+struct Agreements {
+    agreements: HashMap<String, AgreementMetrics>,
+    last_update: u64,
+}
+
+lazy_static! {
+    static ref AGREEMENTS: Mutex<Agreements> = Default::default();
+    static ref PRESETS: Mutex<Presets> = Default::default();
+}
+
+impl Agreements {
+    fn entry(&mut self, agreement_id: &str, labels: AgreementLabels) -> &mut AgreementMetrics {
+        if !self.agreements.contains_key(agreement_id) && self.agreements.len() >= MAX_AGREEMENTS {
+            let oldest = self
+                .agreements
+                .iter()
+                .min_by_key(|(_, metrics)| metrics.updated)
+                .map(|(id, _)| id.clone());
+            if let Some(oldest) = oldest {
+                self.agreements.remove(&oldest);
+            }
+        }
+
+        self.last_update += 1;
+        let metrics = self.agreements.entry(agreement_id.to_string()).or_default();
+        metrics.labels = labels;
+        metrics.updated = self.last_update;
+        metrics
+    }
+}
+
+/// Records amount due and usage counters from Activity Debit Note.
+pub fn record_activity(
+    agreement_id: &str,
+    labels: AgreementLabels,
+    activity_id: &str,
+    cost: f64,
+    usage: Vec<(String, f64)>,
+) {
+    let mut agreements = AGREEMENTS.lock().unwrap();
+    let metrics = agreements.entry(agreement_id, labels);
+    metrics.costs.insert(activity_id.to_string(), cost);
+    if !usage.is_empty() {
+        metrics.usage.insert(activity_id.to_string(), usage);
+    }
+}
+
+/// Invoiced amount replaces amounts due of Activities.
+pub fn record_invoice(agreement_id: &str, labels: AgreementLabels, amount: f64) {
+    let mut agreements = AGREEMENTS.lock().unwrap();
+    agreements.entry(agreement_id, labels).invoiced = Some(amount);
+}
+
+pub(crate) fn observe(observer: &mut PrometheusText) {
+    let agreements = AGREEMENTS.lock().unwrap();
+    for (agreement_id, metrics) in agreements.agreements.iter() {
+        let labels = &metrics.labels;
+        let common = [
+            ("agreement_id", agreement_id.as_str()),
+            ("role", labels.role.as_str()),
+            ("peer", labels.peer.as_str()),
+            ("platform", labels.platform.as_str()),
+            ("runtime", labels.runtime.as_str()),
+            ("preset", labels.preset.as_str()),
+        ];
+
+        let cost = metrics
+            .invoiced
+            .unwrap_or_else(|| metrics.costs.values().sum());
+        observer.add_sample(
+            "agreement.cost",
+            "Amount due for Agreement, or invoiced amount when Invoice was issued",
+            "gauge",
+            &common,
+            cost,
+        );
+
+        let mut usage: Vec<(&str, f64)> = Vec::new();
+        for counters in metrics.usage.values() {
+            for (counter, value) in counters {
+                match usage.iter_mut().find(|(name, _)| name == counter) {
+                    Some((_, total)) => *total += value,
+                    None => usage.push((counter, *value)),
+                }
+            }
+        }
+        for (counter, value) in usage {
+            let mut labels = common.to_vec();
+            labels.push(("counter", counter));
+            observer.add_sample(
+                "agreement.usage",
+                "Usage counters of Agreement, summed over its Activities",
+                "gauge",
+                &labels,
+                value,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_core::Drain;
+
+    #[test]
+    fn test_bounded_agreements() {
+        let labels = AgreementLabels {
+            role: "provider".to_string(),
+            ..Default::default()
+        };
+        for i in 0..MAX_AGREEMENTS + 10 {
+            record_activity(
+                &format!("agreement-{}", i),
+                labels.clone(),
+                "a1",
+                1.0,
+                vec![],
+            );
+        }
+        record_activity(
+            "agreement-200",
+            labels.clone(),
+            "a1",
+            0.5,
+            vec![("golem.usage.duration_sec".to_string(), 10.0)],
+        );
+        record_activity(
+            "agreement-200",
+            labels.clone(),
+            "a2",
+            0.25,
+            vec![("golem.usage.duration_sec".to_string(), 5.0)],
+        );
+
+        let mut observer = PrometheusText::default();
+        observe(&mut observer);
+        let output = observer.drain();
+
+        assert_eq!(AGREEMENTS.lock().unwrap().agreements.len(), MAX_AGREEMENTS);
+        assert!(!output.contains("agreement_id=\"agreement-0\""));
+        assert!(output.contains("agreement_id=\"agreement-109\""));
+        assert!(output.contains("agreement_cost{agreement_id=\"agreement-200\",role=\"provider\",peer=\"\",platform=\"\",runtime=\"\",preset=\"\"} 0.75\n"));
+        assert!(output.contains("counter=\"golem.usage.duration_sec\"} 15\n"));
+
+        record_invoice("agreement-200", labels, 2.0);
+        let mut observer = PrometheusText::default();
+        observe(&mut observer);
+        assert!(observer.drain().contains("preset=\"\"} 2\n"));
+    }
+
+    #[test]
+    fn test_labels_from_properties() {
+        let demand = serde_json::json!({
+            "golem.com.payment.chosen-platform": "erc20-rinkeby-tglm",
+        });
+        let offer = serde_json::json!({
+            "golem": {
+                "runtime": { "name": "vm" },
+                "com": { "payment": { "platform": {
+                    "erc20-rinkeby-tglm": { "address": "0x01" },
+                } } },
+            },
+        });
+
+        set_preset("agreement-1", "default");
+        let labels = AgreementLabels::from_properties(
+            "provider",
+            "agreement-1",
+            "0x02".to_string(),
+            &demand,
+            &offer,
+        );
+        assert_eq!(labels.platform, "erc20-rinkeby-tglm");
+        assert_eq!(labels.runtime, "vm");
+        assert_eq!(labels.preset, "default");
+
+        let labels = AgreementLabels::from_properties(
+            "requestor",
+            "agreement-2",
+            "0x01".to_string(),
+            &Value::Null,
+            &Value::Null,
+        );
+        assert_eq!(labels.platform, "");
+        assert_eq!(labels.preset, "");
+    }
+
+    #[test]
+    fn test_labels_set_by_peer_are_bounded() {
+        let demand = serde_json::json!({
+            "golem.com.payment.chosen-platform": "unknown-platform-1",
+        });
+        let offer = serde_json::json!({
+            "golem.runtime.name": "unknown-runtime-1",
+            "golem.com.payment.platform.erc20-rinkeby-tglm.address": "0x01",
+        });
+
+        let labels = AgreementLabels::from_properties(
+            "provider",
+            "agreement-3",
+            "0x02".to_string(),
+            &demand,
+            &offer,
+        );
+        assert_eq!(labels.platform, OTHER);
+        assert_eq!(labels.runtime, "unknown-runtime-1");
+
+        let labels = AgreementLabels::from_properties(
+            "requestor",
+            "agreement-3",
+            "0x01".to_string(),
+            &demand,
+            &offer,
+        );
+        assert_eq!(labels.platform, "unknown-platform-1");
+        assert_eq!(labels.runtime, OTHER);
+    }
+
+    #[test]
+    fn test_bounded_presets() {
+        let mut presets = Presets::default();
+        for i in 0..MAX_AGREEMENTS + 10 {
+            presets.insert(&format!("agreement-{}", i), "default");
+        }
+        presets.insert("agreement-109", "other");
+
+        assert_eq!(presets.presets.len(), MAX_AGREEMENTS);
+        assert_eq!(presets.get("agreement-0"), None);
+        assert_eq!(presets.get("agreement-109").as_deref(), Some("other"));
+    }
+}
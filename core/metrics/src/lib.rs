@@ -1,5 +1,7 @@
+pub mod agreement;
 mod exporter;
 mod metrics;
+mod prometheus;
 pub(crate) mod pusher;
 mod service;
 
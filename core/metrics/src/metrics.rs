@@ -1,13 +1,16 @@
 use futures::lock::Mutex;
+use metrics_core::{Drain, Observe};
 use metrics_runtime::{observers::PrometheusBuilder, Controller, Receiver, Sink};
 use std::sync::Arc;
 
 use crate::exporter::StringExporter;
+use crate::prometheus::PrometheusText;
 
 pub struct Metrics {
     //pub receiver: Receiver,
     pub root_sink: Sink,
     pub exporter: StringExporter<Controller, PrometheusBuilder>,
+    controller: Controller,
 }
 
 impl Metrics {
@@ -16,13 +19,15 @@ impl Metrics {
             .build()
             .expect("Metrics initialization failure");
         let root_sink = receiver.sink();
-        let exporter = StringExporter::new(receiver.controller(), PrometheusBuilder::new());
+        let controller = receiver.controller();
+        let exporter = StringExporter::new(controller.clone(), PrometheusBuilder::new());
         receiver.install();
 
         Arc::new(Mutex::new(Self {
             //receiver,
             root_sink,
             exporter,
+            controller,
         }))
     }
 
@@ -34,4 +39,12 @@ impl Metrics {
     pub fn export(&mut self) -> String {
         return self.exporter.turn();
     }
+
+    /// Exports for scrapers, together with per-Agreement gauges.
+    pub fn export_prometheus(&mut self) -> String {
+        let mut observer = PrometheusText::default();
+        self.controller.observe(&mut observer);
+        crate::agreement::observe(&mut observer);
+        observer.drain()
+    }
 }
@@ -0,0 +1,211 @@
+//! Prometheus text exposition format with `# HELP` and `# TYPE` lines,
+//! served for scrapers by `/metrics-api/v1/metrics`.
+
+use metrics_core::{Builder, Drain, Key, Observer};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+const QUANTILES: [f64; 3] = [0.5, 0.9, 0.99];
+
+pub struct PrometheusTextBuilder;
+
+impl Builder for PrometheusTextBuilder {
+    type Output = PrometheusText;
+
+    fn build(&self) -> Self::Output {
+        PrometheusText::default()
+    }
+}
+
+#[derive(Default)]
+pub struct PrometheusText {
+    families: BTreeMap<String, Family>,
+}
+
+struct Family {
+    help: String,
+    kind: &'static str,
+    samples: Vec<String>,
+}
+
+impl PrometheusText {
+    /// Adds sample of metric not tracked by `metrics` crate.
+    pub fn add_sample(
+        &mut self,
+        name: &str,
+        help: &str,
+        kind: &'static str,
+        labels: &[(&str, &str)],
+        value: f64,
+    ) {
+        let name = sanitize(name);
+        let sample = format!("{}{} {}", name, render_labels(labels), value);
+        self.family(name, help, kind).samples.push(sample);
+    }
+
+    fn family(&mut self, name: String, help: &str, kind: &'static str) -> &mut Family {
+        self.families.entry(name).or_insert_with(|| Family {
+            help: help.to_string(),
+            kind,
+            samples: Vec::new(),
+        })
+    }
+
+    fn observe(&mut self, key: Key, kind: &'static str, value: String) {
+        let (name, labels) = key.into_parts();
+        let labels = labels
+            .iter()
+            .map(|label| (label.key(), label.value()))
+            .collect::<Vec<_>>();
+        let sample = format!("{}{} {}", sanitize(&name), render_labels(&labels), value);
+        self.family(sanitize(&name), &name, kind)
+            .samples
+            .push(sample);
+    }
+}
+
+impl Observer for PrometheusText {
+    fn observe_counter(&mut self, key: Key, value: u64) {
+        self.observe(key, "counter", value.to_string());
+    }
+
+    fn observe_gauge(&mut self, key: Key, value: i64) {
+        self.observe(key, "gauge", value.to_string());
+    }
+
+    /// Histograms are exposed as summaries.
+    fn observe_histogram(&mut self, key: Key, values: &[u64]) {
+        let (name, labels) = key.into_parts();
+        let labels = labels
+            .iter()
+            .map(|label| (label.key(), label.value()))
+            .collect::<Vec<_>>();
+        let metric = sanitize(&name);
+
+        let mut sorted = values.to_vec();
+        sorted.sort_unstable();
+        let sum: u64 = sorted.iter().sum();
+
+        let mut samples = Vec::with_capacity(QUANTILES.len() + 2);
+        for quantile in QUANTILES.iter() {
+            let value = match sorted.len() {
+                0 => 0,
+                len => sorted[((len - 1) as f64 * quantile).round() as usize],
+            };
+            let quantile = quantile.to_string();
+            let mut quantile_labels = labels.clone();
+            quantile_labels.push(("quantile", quantile.as_str()));
+            samples.push(format!(
+                "{}{} {}",
+                metric,
+                render_labels(&quantile_labels),
+                value
+            ));
+        }
+        samples.push(format!("{}_sum{} {}", metric, render_labels(&labels), sum));
+        samples.push(format!(
+            "{}_count{} {}",
+            metric,
+            render_labels(&labels),
+            sorted.len()
+        ));
+
+        self.family(metric, &name, "summary")
+            .samples
+            .extend(samples);
+    }
+}
+
+impl Drain<String> for PrometheusText {
+    fn drain(&mut self) -> String {
+        let mut output = String::new();
+        for (name, family) in std::mem::take(&mut self.families) {
+            let _ = writeln!(output, "# HELP {} {}", name, escape_help(&family.help));
+            let _ = writeln!(output, "# TYPE {} {}", name, family.kind);
+            for sample in family.samples {
+                output.push_str(&sample);
+                output.push('\n');
+            }
+        }
+        output
+    }
+}
+
+/// Metric names may contain only `[a-zA-Z0-9_:]`.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | ':' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+fn render_labels(labels: &[(&str, &str)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+    let labels = labels
+        .iter()
+        .map(|(key, value)| format!("{}=\"{}\"", sanitize(key), escape_label(value)))
+        .collect::<Vec<_>>();
+    format!("{{{}}}", labels.join(","))
+}
+
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn escape_help(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_core::Label;
+
+    #[test]
+    fn test_render_types_and_labels() {
+        let mut observer = PrometheusTextBuilder.build();
+        observer.observe_counter(
+            Key::from_name_and_labels(
+                "payment.amount.sent",
+                vec![Label::new("platform", "erc20-rinkeby-tglm")],
+            ),
+            12,
+        );
+        observer.observe_gauge(Key::from_name("net.peer.rtt-ms"), 7);
+        observer.observe_histogram(Key::from_name("net.reconnect.time"), &[3, 1, 2]);
+        observer.add_sample(
+            "agreement.cost",
+            "Amount due",
+            "gauge",
+            &[("peer", "0x1\"2")],
+            0.5,
+        );
+
+        assert_eq!(
+            observer.drain(),
+            "# HELP agreement_cost Amount due\n\
+             # TYPE agreement_cost gauge\n\
+             agreement_cost{peer=\"0x1\\\"2\"} 0.5\n\
+             # HELP net_peer_rtt_ms net.peer.rtt-ms\n\
+             # TYPE net_peer_rtt_ms gauge\n\
+             net_peer_rtt_ms 7\n\
+             # HELP net_reconnect_time net.reconnect.time\n\
+             # TYPE net_reconnect_time summary\n\
+             net_reconnect_time{quantile=\"0.5\"} 2\n\
+             net_reconnect_time{quantile=\"0.9\"} 3\n\
+             net_reconnect_time{quantile=\"0.99\"} 3\n\
+             net_reconnect_time_sum 6\n\
+             net_reconnect_time_count 3\n\
+             # HELP payment_amount_sent payment.amount.sent\n\
+             # TYPE payment_amount_sent counter\n\
+             payment_amount_sent{platform=\"erc20-rinkeby-tglm\"} 12\n"
+        );
+    }
+}
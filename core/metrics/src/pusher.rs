@@ -140,6 +140,7 @@ mod test {
                 node_id: Default::default(),
                 is_locked: false,
                 is_default: false,
+                is_external: false,
             },
         )
         .unwrap();
@@ -155,6 +156,7 @@ mod test {
                 node_id: Default::default(),
                 is_locked: false,
                 is_default: false,
+                is_external: false,
             },
         )
         .unwrap();
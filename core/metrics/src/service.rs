@@ -10,6 +10,7 @@ use crate::metrics::Metrics;
 
 const YAGNA_METRICS_URL_ENV_VAR: &str = "YAGNA_METRICS_URL";
 const DEFAULT_YAGNA_METRICS_URL: &str = "https://metrics.golem.network:9092/";
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
 
 // TODO: enable showing metrics also via CLI
 #[derive(structopt::StructOpt, Debug)]
@@ -60,9 +61,30 @@ impl MetricsService {
         actix_web::Scope::new("metrics-api/v1")
             // TODO:: add wrapper injecting Bearer to avoid hack in auth middleware
             .route("/expose", actix_web::web::get().to(export_metrics))
+            .route("/metrics", actix_web::web::get().to(scrape_metrics))
+            .route(
+                "/agreements/{agreement_id}/preset",
+                actix_web::web::post().to(set_agreement_preset),
+            )
     }
 }
 
 pub async fn export_metrics() -> String {
     METRICS.lock().await.export()
 }
+
+async fn scrape_metrics() -> actix_web::HttpResponse {
+    let body = METRICS.lock().await.export_prometheus();
+    actix_web::HttpResponse::Ok()
+        .content_type(PROMETHEUS_CONTENT_TYPE)
+        .body(body)
+}
+
+/// Called by local Provider agent, which knows preset of Agreement.
+async fn set_agreement_preset(
+    agreement_id: actix_web::web::Path<String>,
+    preset: actix_web::web::Json<String>,
+) -> actix_web::HttpResponse {
+    crate::agreement::set_preset(&agreement_id, &preset);
+    actix_web::HttpResponse::NoContent().finish()
+}
@@ -219,6 +219,9 @@ pub struct Exec {
     pub batch_id: String,
     pub exe_script: Vec<ExeScriptCommand>,
     pub timeout: Option<f32>,
+    /// W3C `traceparent` of the caller, continued by ExeUnit.
+    #[serde(default)]
+    pub trace_context: Option<String>,
 }
 
 impl RpcMessage for Exec {
@@ -380,6 +383,19 @@ pub mod local {
         type Item = String;
         type Error = RpcMessageError;
     }
+
+    /// Count Activities of given Agreements, which aren't Terminated yet.
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct CountActive {
+        pub agreement_ids: Vec<String>,
+    }
+
+    impl RpcMessage for CountActive {
+        const ID: &'static str = "CountActive";
+        type Item = u64;
+        type Error = RpcMessageError;
+    }
 }
 
 /// Error message for activity service bus API.
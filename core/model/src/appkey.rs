@@ -34,6 +34,17 @@ pub struct Create {
     pub identity: NodeId,
 }
 
+/// Stores application key with given value, e.g. restored from backup.
+/// Returns `false` when the key already exists.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Import {
+    pub name: String,
+    pub key: String,
+    pub role: String,
+    pub identity: NodeId,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Get {
@@ -87,6 +98,12 @@ impl RpcMessage for Create {
     type Error = Error;
 }
 
+impl RpcMessage for Import {
+    const ID: &'static str = "Import";
+    type Item = bool;
+    type Error = Error;
+}
+
 impl RpcMessage for Get {
     const ID: &'static str = "Get";
     type Item = AppKey;
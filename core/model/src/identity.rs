@@ -48,6 +48,9 @@ pub struct IdentityInfo {
     pub node_id: NodeId,
     pub is_locked: bool,
     pub is_default: bool,
+    /// Key is held by external signer.
+    #[serde(default)]
+    pub is_external: bool,
 }
 
 impl RpcMessage for List {
@@ -69,6 +72,20 @@ impl RpcMessage for CreateGenerated {
     type Error = Error;
 }
 
+/// Registers identity which key is held by external signer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateExternal {
+    pub alias: Option<String>,
+    pub node_id: NodeId,
+}
+
+impl RpcMessage for CreateExternal {
+    const ID: &'static str = "CreateExternal";
+    type Item = IdentityInfo;
+    type Error = Error;
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 #[non_exhaustive]
@@ -199,6 +216,44 @@ impl RpcMessage for Sign {
     type Error = Error;
 }
 
+/// Signs Ethereum transaction. Local keys sign its `hash`, while external
+/// signer gets the whole transaction to review.
+/// Returns signature in the same format as `Sign`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignTx {
+    pub node_id: NodeId,
+    pub hash: Vec<u8>,
+    pub tx: EthTransaction,
+}
+
+/// Transaction fields as hex encoded quantities, the same as in
+/// `eth_signTransaction`.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EthTransaction {
+    pub from: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    pub gas: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gas_price: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_fee_per_gas: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_priority_fee_per_gas: Option<String>,
+    pub value: String,
+    pub nonce: String,
+    pub data: String,
+    pub chain_id: String,
+}
+
+impl RpcMessage for SignTx {
+    const ID: &'static str = "SignTx";
+    type Item = Vec<u8>;
+    type Error = Error;
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Subscribe {
@@ -1,8 +1,12 @@
 //! Market service bus API.
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::Role;
+pub use ya_client_model::market::agreement::State as AgreementState;
 pub use ya_client_model::market::Agreement;
+use ya_client_model::market::Reason;
+use ya_client_model::NodeId;
 use ya_service_bus::RpcMessage;
 
 /// Public Market bus address.
@@ -41,6 +45,107 @@ impl RpcMessage for GetAgreement {
     type Error = RpcMessageError;
 }
 
+/// Filters for listing Agreements. All conditions must be met.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgreementListFilter {
+    pub state: Option<AgreementState>,
+    /// Node on the other side of the Agreement.
+    pub peer_id: Option<NodeId>,
+    pub app_session_id: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub valid_to_after: Option<DateTime<Utc>>,
+    pub valid_to_before: Option<DateTime<Utc>>,
+    pub offset: Option<u32>,
+    pub limit: Option<u32>,
+}
+
+/// Summary of an Agreement returned by Agreement listing.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgreementListEntry {
+    pub agreement_id: String,
+    pub role: Role,
+    pub state: AgreementState,
+    pub peer_id: NodeId,
+    pub app_session_id: Option<String>,
+    pub timestamp: DateTime<Utc>,
+    pub valid_to: DateTime<Utc>,
+    pub approved_date: Option<DateTime<Utc>>,
+}
+
+/// Lists Agreements of the node, newest first.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListAgreements {
+    pub node_id: NodeId,
+    pub filter: AgreementListFilter,
+}
+
+impl RpcMessage for ListAgreements {
+    const ID: &'static str = "ListAgreements";
+    type Item = Vec<AgreementListEntry>;
+    type Error = RpcMessageError;
+}
+
+/// Terminates Approved Agreement on behalf of local `node_id`.
+/// Used to clean up Agreements, when the node is shutting down.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminateAgreement {
+    pub agreement_id: String,
+    pub node_id: NodeId,
+    pub reason: Option<Reason>,
+}
+
+impl RpcMessage for TerminateAgreement {
+    const ID: &'static str = "TerminateAgreement";
+    type Item = ();
+    type Error = RpcMessageError;
+}
+
+/// Requestor's request to change terms of an approved Agreement.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgreementAmendment {
+    pub valid_to: DateTime<Utc>,
+    /// New value of `golem.com.scheme.payu.payment-timeout-sec?` property.
+    pub payment_timeout_sec: Option<u32>,
+    /// New value of `golem.com.scheme.payu.debit-note.interval-sec?` property.
+    pub debit_note_interval_sec: Option<u32>,
+}
+
+/// Stage of Agreement amendment reported to Provider.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AmendmentEventType {
+    /// Amendment waits for Provider's decision.
+    Proposed,
+    /// Approved amendment was stored and new terms apply to the Agreement.
+    Amended,
+}
+
+impl Default for AmendmentEventType {
+    fn default() -> Self {
+        AmendmentEventType::Proposed
+    }
+}
+
+/// Amendment of an Agreement waiting for Provider's decision
+/// or already applied to the Agreement.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgreementAmendmentEvent {
+    pub amendment_id: String,
+    pub agreement_id: String,
+    pub event_date: DateTime<Utc>,
+    #[serde(default)]
+    pub event_type: AmendmentEventType,
+    #[serde(flatten)]
+    pub amendment: AgreementAmendment,
+}
+
 /// Error message for market service bus API.
 #[derive(thiserror::Error, Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -16,6 +16,7 @@ pub const DIAGNOSTIC: &str = "/public/diagnostic/net";
 ///
 ///
 pub mod local {
+    use chrono::{DateTime, Utc};
     use std::net::SocketAddr;
     use std::time::Duration;
 
@@ -151,6 +152,9 @@ pub mod local {
         pub node_id: NodeId,
         pub listen_address: Option<SocketAddr>,
         pub public_address: Option<SocketAddr>,
+        /// Relay server the node is connected to.
+        #[serde(default)]
+        pub relay_address: Option<SocketAddr>,
         pub sessions: usize,
         pub metrics: StatusMetrics,
     }
@@ -181,6 +185,32 @@ pub mod local {
         pub seen: Duration,
         pub duration: Duration,
         pub ping: Duration,
+        /// Traffic exchanged with the node. Not available for server sessions.
+        #[serde(default)]
+        pub stats: Option<SessionStats>,
+    }
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SessionStats {
+        pub tx_bytes: u64,
+        pub tx_packets: u64,
+        pub rx_bytes: u64,
+        pub rx_packets: u64,
+        pub p2p: Option<bool>,
+        /// Round trip time measured by the last `GsbPing`.
+        pub rtt: Option<Duration>,
+        pub reconnections: u64,
+        /// Traffic in consecutive time windows, the oldest first.
+        pub history: Vec<StatsSample>,
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct StatsSample {
+        pub timestamp: DateTime<Utc>,
+        pub tx_bytes: u64,
+        pub rx_bytes: u64,
     }
 
     #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq, Hash)]
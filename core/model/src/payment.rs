@@ -21,7 +21,7 @@ pub mod local {
     use super::*;
     use crate::driver::{AccountMode, GasDetails, PaymentConfirmation};
     use bigdecimal::{BigDecimal, Zero};
-    use chrono::{DateTime, Utc};
+    use chrono::{DateTime, NaiveTime, Utc};
     use std::fmt::Display;
     use std::time::Duration;
     use structopt::*;
@@ -404,6 +404,49 @@ pub mod local {
         type Error = GenericError;
     }
 
+    /// Decides when payments scheduled from an allocation are sent out.
+    /// Aggregated payments are merged into a single order per payee, but never later
+    /// than the earliest payment due date (derived from the negotiated `PaymentTimeout`).
+    #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub enum SettlementPolicy {
+        /// Pay every accepted invoice and debit note as soon as possible.
+        Immediate,
+        /// Aggregate payments per payee and settle them once a day at given UTC time.
+        Daily { settle_at: NaiveTime },
+        /// Aggregate payments per payee until their sum reaches the threshold.
+        Threshold { amount: BigDecimal },
+    }
+
+    impl Default for SettlementPolicy {
+        fn default() -> Self {
+            SettlementPolicy::Immediate
+        }
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct SetSettlementPolicy {
+        pub allocation_id: String,
+        pub policy: SettlementPolicy,
+    }
+
+    impl RpcMessage for SetSettlementPolicy {
+        const ID: &'static str = "SetSettlementPolicy";
+        type Item = ();
+        type Error = GenericError;
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct GetSettlementPolicy {
+        pub allocation_id: String,
+    }
+
+    impl RpcMessage for GetSettlementPolicy {
+        const ID: &'static str = "GetSettlementPolicy";
+        type Item = SettlementPolicy;
+        type Error = GenericError;
+    }
+
     #[derive(Clone, Debug, Serialize, Deserialize)]
     pub struct GetDrivers {}
 
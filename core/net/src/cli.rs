@@ -6,6 +6,7 @@ use humantime::format_duration;
 use structopt::*;
 
 use ya_core_model::net::local as model;
+use ya_core_model::NodeId;
 use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
@@ -16,7 +17,11 @@ pub enum NetCommand {
     /// Show network status
     Status {},
     /// List network sessions
-    Sessions {},
+    Sessions {
+        /// Show traffic history of given node
+        #[structopt(long)]
+        history: Option<NodeId>,
+    },
     /// List virtual sockets
     Sockets {},
     /// Ping connected nodes
@@ -35,6 +40,7 @@ impl NetCommand {
                     "nodeId": status.node_id,
                     "listenAddress": status.listen_address,
                     "publicAddress": status.public_address,
+                    "relayAddress": status.relay_address,
                     "sessions": status.sessions,
                     "bandwidth": {
                         "outKiBps": to_kib(status.metrics.tx_current, is_json),
@@ -46,12 +52,16 @@ impl NetCommand {
                     }
                 }))
             }
-            NetCommand::Sessions {} => {
+            NetCommand::Sessions { history } => {
                 let mut sessions: Vec<model::SessionResponse> = bus::service(model::BUS_ID)
                     .send(model::Sessions {})
                     .await
                     .map_err(|e| anyhow::Error::msg(e))??;
 
+                if let Some(node_id) = history {
+                    return session_history(sessions, node_id, is_json);
+                }
+
                 sessions.sort_by_key(|s| s.node_id.unwrap_or_default().into_array());
 
                 Ok(ResponseTable {
@@ -62,6 +72,10 @@ impl NetCommand {
                         "seen".into(),
                         "time".into(),
                         "ping".into(),
+                        "rtt".into(),
+                        "out [MiB]".into(),
+                        "in [MiB]".into(),
+                        "reconnections".into(),
                     ],
                     values: sessions
                         .into_iter()
@@ -69,6 +83,12 @@ impl NetCommand {
                             let seen = Duration::from_secs(s.seen.as_secs());
                             let duration = Duration::from_secs(s.duration.as_secs());
                             let ping = Duration::from_millis(s.ping.as_millis() as u64);
+                            let stats = s.stats.unwrap_or_default();
+                            let rtt = stats
+                                .rtt
+                                .map(|rtt| Duration::from_millis(rtt.as_millis() as u64))
+                                .map(|rtt| format_duration(rtt).to_string())
+                                .unwrap_or_default();
 
                             serde_json::json! {[
                                 s.node_id.map(|id| id.to_string()).unwrap_or_default(),
@@ -77,6 +97,10 @@ impl NetCommand {
                                 format_duration(seen).to_string(),
                                 format_duration(duration).to_string(),
                                 format_duration(ping).to_string(),
+                                rtt,
+                                to_mib(stats.tx_bytes as usize, is_json),
+                                to_mib(stats.rx_bytes as usize, is_json),
+                                stats.reconnections,
                             ]}
                         })
                         .collect(),
@@ -156,6 +180,34 @@ impl NetCommand {
     }
 }
 
+fn session_history(
+    sessions: Vec<model::SessionResponse>,
+    node_id: NodeId,
+    is_json: bool,
+) -> anyhow::Result<CommandOutput> {
+    let stats = sessions
+        .into_iter()
+        .find(|s| s.node_id == Some(node_id))
+        .and_then(|s| s.stats)
+        .ok_or_else(|| anyhow::anyhow!("No session with node {}", node_id))?;
+
+    Ok(ResponseTable {
+        columns: vec!["time".into(), "out [MiB]".into(), "in [MiB]".into()],
+        values: stats
+            .history
+            .into_iter()
+            .map(|sample| {
+                serde_json::json! {[
+                    sample.timestamp.to_rfc3339(),
+                    to_mib(sample.tx_bytes as usize, is_json),
+                    to_mib(sample.rx_bytes as usize, is_json),
+                ]}
+            })
+            .collect(),
+    }
+    .into())
+}
+
 #[inline]
 fn to_kib(value: f32, is_json: bool) -> serde_json::Value {
     format_number(value / 1024., is_json)
@@ -1,3 +1,5 @@
+use std::net::SocketAddrV4;
+use std::path::PathBuf;
 use std::time::Duration;
 use structopt::StructOpt;
 use strum::VariantNames;
@@ -9,6 +11,12 @@ use url::Url;
 pub enum NetType {
     Central,
     Hybrid,
+    /// LAN only network without relay server. Peers are discovered
+    /// using UDP multicast.
+    Local,
+    /// Connects nodes running on the same host through Unix sockets.
+    /// Intended for multi-node integration tests.
+    Loopback,
 }
 
 #[derive(StructOpt, Clone)]
@@ -16,8 +24,16 @@ pub enum NetType {
 pub struct Config {
     #[structopt(env = "YA_NET_TYPE", possible_values = NetType::VARIANTS, default_value = NetType::default().into())]
     pub net_type: NetType,
+    /// Comma separated list of relay servers. When not set, all relays
+    /// from SRV records are used.
     #[structopt(env = "YA_NET_RELAY_HOST")]
     pub host: Option<String>,
+    #[structopt(env = "YA_NET_RELAY_CHECK_INTERVAL", parse(try_from_str = humantime::parse_duration), default_value = "10s")]
+    pub relay_check_interval: Duration,
+    /// Number of consecutive failed health checks, after which node
+    /// switches to another relay server.
+    #[structopt(env = "YA_NET_RELAY_FAILOVER_THRESHOLD", default_value = "3")]
+    pub relay_failover_threshold: u32,
     #[structopt(env = "YA_NET_BIND_URL", default_value = "udp://0.0.0.0:11500")]
     pub bind_url: Url,
     #[structopt(env = "YA_NET_BROADCAST_SIZE", default_value = "10")]
@@ -26,6 +42,27 @@ pub struct Config {
     pub session_expiration: Duration,
     #[structopt(env = "YA_NET_VIRTUAL_TCP_BUFFER_SIZE_MULTIPLIER", default_value = "4")]
     pub vtcp_buffer_size_multiplier: usize,
+    #[structopt(
+        env = "YA_NET_LAN_MULTICAST_ADDR",
+        default_value = "239.255.11.50:11501"
+    )]
+    pub lan_multicast_addr: SocketAddrV4,
+    #[structopt(env = "YA_NET_LAN_ANNOUNCE_INTERVAL", parse(try_from_str = humantime::parse_duration), default_value = "5s")]
+    pub lan_announce_interval: Duration,
+    /// Directory shared by all nodes connected with loopback network.
+    #[structopt(env = "YA_NET_LOOPBACK_DIR")]
+    pub loopback_dir: Option<PathBuf>,
+    /// Nodes can communicate only with nodes in the same partition.
+    #[structopt(env = "YA_NET_LOOPBACK_PARTITION")]
+    pub loopback_partition: Option<String>,
+    #[structopt(env = "YA_NET_LOOPBACK_LATENCY", parse(try_from_str = humantime::parse_duration), default_value = "0s")]
+    pub loopback_latency: Duration,
+    /// Probability of dropping received message.
+    #[structopt(env = "YA_NET_LOOPBACK_LOSS", default_value = "0")]
+    pub loopback_loss: f64,
+    /// Seed for choosing lost messages, so tests are repeatable.
+    #[structopt(env = "YA_NET_LOOPBACK_SEED", default_value = "0")]
+    pub loopback_seed: u64,
 }
 
 impl Config {
@@ -11,7 +11,9 @@ use ya_service_bus::timeout::IntoTimeoutFuture;
 use ya_service_bus::typed::ServiceBinder;
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
+use crate::hybrid::relay::active_relay;
 use crate::hybrid::Net;
+use crate::stats;
 
 pub(crate) fn bind_service() {
     let _ = bus::bind(model::BUS_ID, |_: model::GsbPing| {
@@ -29,6 +31,7 @@ pub(crate) fn bind_service() {
                 node_id: client.node_id().await?,
                 listen_address: client.bind_addr().await?,
                 public_address: client.public_addr().await?,
+                relay_address: active_relay(),
                 sessions: client.sessions().await?.len(),
                 metrics: to_status_metrics(&mut client.metrics().await?),
             })
@@ -46,6 +49,7 @@ pub(crate) fn bind_service() {
                 let kind = match node_id {
                     Some(id) => {
                         let is_p2p = client.is_p2p(id).await?;
+                        stats::set_p2p(id, is_p2p);
                         is_p2p.then(|| "p2p").unwrap_or("relay")
                     }
                     None => "server",
@@ -59,6 +63,7 @@ pub(crate) fn bind_service() {
                     seen: now - session.last_seen,
                     duration: now - session.created,
                     ping: session.last_ping,
+                    stats: node_id.and_then(|id| stats::peer_stats(&id)),
                 });
             }
 
@@ -184,6 +189,9 @@ pub async fn cli_ping() -> anyhow::Result<Vec<GsbPingResponse>> {
             None => result.node_id,
         };
         result.is_p2p = client.is_p2p(main_id).await?;
+        if result.tcp_ping < ping_timeout {
+            stats::record_ping(result.node_id, result.tcp_ping, result.is_p2p);
+        }
     }
     Ok(results)
 }
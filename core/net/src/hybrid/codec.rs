@@ -5,15 +5,35 @@ use ya_sb_proto::codec::{GsbMessage, ProtocolError};
 use ya_sb_proto::CallReplyCode;
 use ya_service_bus::{Error, ResponseChunk};
 
+/// Extra field of GSB packet carrying trace context of a request. It is
+/// covered by length prefix, and nodes not aware of it skip it as unknown
+/// protobuf field.
+#[derive(Clone, PartialEq, prost::Message)]
+struct TraceContext {
+    #[prost(string, optional, tag = "1000")]
+    traceparent: Option<String>,
+}
+
 pub(crate) fn encode_message(msg: GsbMessage) -> Result<Vec<u8>, Error> {
+    encode_packet(msg, None)
+}
+
+fn encode_packet(msg: GsbMessage, trace_context: Option<TraceContext>) -> Result<Vec<u8>, Error> {
     let packet = ya_sb_proto::Packet { packet: Some(msg) };
-    let len: usize = packet.encoded_len();
+    let len: usize =
+        packet.encoded_len() + trace_context.as_ref().map_or(0, |ctx| ctx.encoded_len());
 
     let mut dst = Vec::with_capacity(4 + len);
     dst.extend((len as u32).to_be_bytes());
     packet
         .encode(&mut dst)
         .map_err(|e| Error::EncodingProblem(e.to_string()))?;
+    // Concatenated protobuf messages are decoded as a single merged one.
+    if let Some(trace_context) = trace_context {
+        trace_context
+            .encode(&mut dst)
+            .map_err(|e| Error::EncodingProblem(e.to_string()))?;
+    }
 
     Ok(dst)
 }
@@ -41,6 +61,19 @@ pub(crate) fn decode_message(src: &[u8]) -> Result<Option<GsbMessage>, Error> {
     }
 }
 
+/// Returns `traceparent` carried by encoded request, if any.
+pub(crate) fn decode_trace_context(src: &[u8]) -> Option<String> {
+    if src.len() < 4 {
+        return None;
+    }
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(&src[0..4]);
+    let msg_length = u32::from_be_bytes(buf) as usize;
+
+    let packet = src.get(4..4 + msg_length)?;
+    TraceContext::decode(packet).ok()?.traceparent
+}
+
 pub(crate) fn decode_reply(data: Vec<u8>) -> Result<Vec<u8>, Error> {
     use std::convert::TryInto;
 
@@ -73,6 +106,7 @@ pub(crate) fn encode_request(
     address: String,
     request_id: String,
     data: Vec<u8>,
+    trace_context: Option<String>,
 ) -> anyhow::Result<Vec<u8>> {
     let message = GsbMessage::CallRequest(ya_sb_proto::CallRequest {
         caller: caller.to_string(),
@@ -80,7 +114,10 @@ pub(crate) fn encode_request(
         request_id,
         data,
     });
-    Ok(encode_message(message)?)
+    let trace_context = trace_context.map(|traceparent| TraceContext {
+        traceparent: Some(traceparent),
+    });
+    Ok(encode_packet(message, trace_context)?)
 }
 
 #[inline]
@@ -139,7 +176,10 @@ pub(crate) fn reply_eos(request_id: impl ToString) -> GsbMessage {
 mod tests {
     use std::iter::FromIterator;
 
-    use crate::hybrid::codec::{decode_message, encode_message};
+    use crate::hybrid::codec::{
+        decode_message, decode_trace_context, encode_message, encode_request,
+    };
+    use ya_core_model::NodeId;
 
     #[test]
     fn encode_message_compat() {
@@ -163,4 +203,42 @@ mod tests {
         assert_eq!(encoded_orig, encoded);
         assert_eq!(decode_message(encoded.as_slice()).unwrap().unwrap(), msg);
     }
+
+    #[test]
+    fn encode_request_trace_context() {
+        use ya_sb_proto::codec::GsbMessage;
+
+        let traceparent = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+        let caller: NodeId = "0xbabe000000000000000000000000000000000000"
+            .parse()
+            .unwrap();
+        let encode = |trace_context: Option<&str>| {
+            encode_request(
+                caller,
+                "/net/0x0/test".to_string(),
+                "1".to_string(),
+                vec![1, 2, 3],
+                trace_context.map(ToString::to_string),
+            )
+            .unwrap()
+        };
+
+        let plain = encode(None);
+        let traced = encode(Some(traceparent));
+        assert_eq!(decode_trace_context(&plain), None);
+        assert_eq!(decode_trace_context(&traced).as_deref(), Some(traceparent));
+
+        assert!(matches!(
+            decode_message(&traced).unwrap(),
+            Some(GsbMessage::CallRequest(_))
+        ));
+        assert_eq!(
+            decode_message(&traced).unwrap(),
+            decode_message(&plain).unwrap()
+        );
+
+        // Length prefix covers trace context.
+        let len = u32::from_be_bytes([traced[0], traced[1], traced[2], traced[3]]) as usize;
+        assert_eq!(len + 4, traced.len());
+    }
 }
@@ -1,9 +1,10 @@
 mod api;
 pub(crate) mod cli;
 mod client;
-mod codec;
-mod crypto;
-mod service;
+pub(crate) mod codec;
+pub(crate) mod crypto;
+mod relay;
+pub(crate) mod service;
 
 pub use api::*;
 pub use service::{start_network, Net};
@@ -0,0 +1,176 @@
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, Context as AnyhowContext};
+
+use ya_relay_client::Client;
+use ya_utils_networking::resolver;
+
+use crate::config::Config;
+
+const DEFAULT_NET_RELAY_HOST: &str = "127.0.0.1:7464";
+
+lazy_static::lazy_static! {
+    static ref ACTIVE_RELAY: RwLock<Option<SocketAddr>> = Default::default();
+}
+
+/// Relay server, that node is currently connected to.
+pub(crate) fn active_relay() -> Option<SocketAddr> {
+    *ACTIVE_RELAY.read().unwrap()
+}
+
+pub(crate) fn set_active_relay(addr: Option<SocketAddr>) {
+    *ACTIVE_RELAY.write().unwrap() = addr;
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Relay {
+    pub addr: SocketAddr,
+    /// Last measured round trip time. Not set for unreachable relays.
+    pub latency: Option<Duration>,
+}
+
+/// Relay servers available to the node.
+#[derive(Clone, Debug)]
+pub(crate) struct Relays {
+    relays: Vec<Relay>,
+}
+
+impl Relays {
+    pub fn new(addrs: Vec<SocketAddr>) -> Self {
+        Relays {
+            relays: addrs
+                .into_iter()
+                .map(|addr| Relay {
+                    addr,
+                    latency: None,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.relays.len()
+    }
+
+    pub fn addrs(&self) -> Vec<SocketAddr> {
+        self.relays.iter().map(|relay| relay.addr).collect()
+    }
+
+    /// Returns true, if latency of any relay is known.
+    pub fn measured(&self) -> bool {
+        self.relays.iter().any(|relay| relay.latency.is_some())
+    }
+
+    pub fn set_latency(&mut self, addr: SocketAddr, latency: Option<Duration>) {
+        if let Some(relay) = self.relays.iter_mut().find(|relay| relay.addr == addr) {
+            relay.latency = latency;
+        }
+    }
+
+    /// Relays ordered from the most preferred one: reachable relays by latency,
+    /// then the rest in configured order. `failed` relay is always the last one.
+    pub fn candidates(&self, failed: Option<SocketAddr>) -> Vec<SocketAddr> {
+        let mut relays = self.relays.iter().enumerate().collect::<Vec<_>>();
+        relays.sort_by_key(|(idx, relay)| {
+            (
+                Some(relay.addr) == failed,
+                relay.latency.is_none(),
+                relay.latency,
+                *idx,
+            )
+        });
+        relays.into_iter().map(|(_, relay)| relay.addr).collect()
+    }
+}
+
+/// Resolves relays configured with `YA_NET_RELAY_HOST` or all relays
+/// from SRV records.
+pub(crate) async fn relay_addrs(config: &Config) -> anyhow::Result<Vec<SocketAddr>> {
+    let hosts = match &config.host {
+        Some(val) => val
+            .split(',')
+            .map(|host| host.trim().to_string())
+            .filter(|host| !host.is_empty())
+            .collect(),
+        None => resolver::resolve_yagna_srv_records("_net_relay._udp")
+            .await
+            // FIXME: remove
+            .unwrap_or_else(|_| vec![DEFAULT_NET_RELAY_HOST.to_string()]),
+    };
+
+    let mut addrs = Vec::new();
+    for host_port in hosts {
+        log::info!("Hybrid NET relay server configured on url: udp://{host_port}");
+        match resolve_relay(&host_port).await {
+            Ok(addr) if !addrs.contains(&addr) => addrs.push(addr),
+            Ok(_) => (),
+            Err(e) => log::warn!("Invalid relay server address {}: {}", host_port, e),
+        }
+    }
+
+    if addrs.is_empty() {
+        anyhow::bail!("No valid relay server address");
+    }
+    Ok(addrs)
+}
+
+async fn resolve_relay(host_port: &str) -> anyhow::Result<SocketAddr> {
+    let (host, port) = host_port
+        .split_once(':')
+        .context("Please use host:port format")?;
+    let ip = resolver::try_resolve_dns_record(host).await;
+    let socket = format!("{}:{}", ip, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| anyhow!("Invalid relay address: {ip}:{port}"))?;
+    Ok(socket)
+}
+
+/// Pings relay server session. Returns its latency or `None`,
+/// if relay didn't respond within `timeout`.
+pub(crate) async fn check_relay(
+    client: &Client,
+    relay: SocketAddr,
+    timeout: Duration,
+) -> Option<Duration> {
+    client.ping_sessions().await;
+
+    let now = Instant::now();
+    client
+        .sessions()
+        .await
+        .into_iter()
+        .find(|session| session.remote == relay)
+        .filter(|session| now - session.last_seen < timeout)
+        .map(|session| session.last_ping)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_relay_candidates_order() {
+        let addrs: Vec<SocketAddr> = vec![
+            "10.0.0.1:7464".parse().unwrap(),
+            "10.0.0.2:7464".parse().unwrap(),
+            "10.0.0.3:7464".parse().unwrap(),
+            "10.0.0.4:7464".parse().unwrap(),
+        ];
+        let mut relays = Relays::new(addrs.clone());
+        assert_eq!(relays.candidates(None), addrs);
+
+        relays.set_latency(addrs[2], Some(Duration::from_millis(20)));
+        relays.set_latency(addrs[3], Some(Duration::from_millis(10)));
+        assert_eq!(
+            relays.candidates(None),
+            vec![addrs[3], addrs[2], addrs[0], addrs[1]]
+        );
+        assert_eq!(
+            relays.candidates(Some(addrs[3])),
+            vec![addrs[2], addrs[0], addrs[1], addrs[3]]
+        );
+    }
+}
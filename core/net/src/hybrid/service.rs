@@ -1,17 +1,19 @@
 use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::future::Future;
-use std::net::{SocketAddr, ToSocketAddrs};
+use std::net::SocketAddr;
 use std::rc::Rc;
 use std::str::FromStr;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering::Relaxed;
 use std::sync::Arc;
 use std::task::Poll;
+use std::time::Instant;
 
 use actix::Actor;
 use anyhow::{anyhow, Context as AnyhowContext};
 use futures::channel::{mpsc, oneshot};
+use futures::future::LocalBoxFuture;
 use futures::lock::Mutex;
 use futures::stream::LocalBoxStream;
 use futures::{FutureExt, SinkExt, Stream, StreamExt, TryStreamExt};
@@ -27,25 +29,32 @@ use ya_relay_client::{Client, ClientBuilder, ForwardReceiver};
 use ya_sb_proto::codec::GsbMessage;
 use ya_sb_proto::CallReplyCode;
 use ya_service_bus::{typed, untyped as local_bus, Error, ResponseChunk, RpcEndpoint};
-use ya_utils_networking::resolver;
+use ya_utils_telemetry::{
+    self as telemetry, SpanKind, StatusCode, TraceContextExt, TraceFutureExt,
+};
 
 use crate::bcast::BCastService;
 use crate::config::Config;
 use crate::hybrid::client::{ClientActor, ClientProxy};
 use crate::hybrid::codec;
 use crate::hybrid::crypto::IdentityCryptoProvider;
-
-const DEFAULT_NET_RELAY_HOST: &str = "127.0.0.1:7464";
+use crate::hybrid::relay::{active_relay, check_relay, relay_addrs, set_active_relay, Relays};
+use crate::stats;
 
 pub type BCastHandler = Box<dyn FnMut(String, &[u8]) + Send>;
 
 type BusSender = mpsc::Sender<ResponseChunk>;
 type BusReceiver = mpsc::Receiver<ResponseChunk>;
-type NetSender = mpsc::Sender<Vec<u8>>;
-type NetReceiver = mpsc::Receiver<Vec<u8>>;
-type NetSinkKind = SinkKind<NetSender, mpsc::SendError>;
+pub(crate) type NetSender = mpsc::Sender<Vec<u8>>;
+pub(crate) type NetReceiver = mpsc::Receiver<Vec<u8>>;
+pub(crate) type NetSinkKind = SinkKind<NetSender, mpsc::SendError>;
 type NetSinkKey = (NodeId, bool);
 
+/// Transport used instead of ya-relay-client. Opens sink sending encoded
+/// GSB messages to the node with given id.
+pub(crate) type Transport =
+    Rc<dyn Fn(NodeId, bool) -> LocalBoxFuture<'static, anyhow::Result<NetSinkKind>>>;
+
 type ArcMap<K, V> = Arc<RwLock<HashMap<K, V>>>;
 
 lazy_static::lazy_static! {
@@ -117,51 +126,20 @@ pub async fn start_network(
     let broadcast_size = config.broadcast_size;
     let crypto = IdentityCryptoProvider::new(default_id);
 
-    let client = build_client(config, crypto.clone()).await?;
-    CLIENT.with(|inner| {
-        inner.borrow_mut().replace(client.clone());
-    });
-
-    ClientActor::new(client.clone()).start();
+    let mut relays = Relays::new(
+        relay_addrs(&config)
+            .await
+            .map_err(|e| anyhow!("Resolving hybrid NET relay server failed. Error: {}", e))?,
+    );
+    let state = bind_routing(default_id, ids, None);
+    let client = connect_relay(&config, crypto.clone(), &state, &mut relays, None).await?;
 
     let (btx, brx) = mpsc::channel(1);
     BCAST_SENDER.write().await.replace(btx);
 
-    let receiver = client.clone().forward_receiver().await.unwrap();
-    let mut services: HashSet<_> = Default::default();
-    ids.iter().for_each(|id| {
-        let service = net::net_service(id);
-        services.insert(format!("/udp{}", service));
-        services.insert(service);
-    });
-    let state = State::new(ids, services);
-
-    // outbound traffic
-    let net_handler = || {
-        move |_: &str, addr: &str| match parse_net_to_addr(addr) {
-            Ok((to, addr)) => Ok((default_id, to, addr)),
-            Err(err) => anyhow::bail!("invalid address: {}", err),
-        }
-    };
-    bind_local_bus(net::BUS_ID_UDP, state.clone(), false, net_handler());
-    bind_local_bus(net::BUS_ID, state.clone(), true, net_handler());
-
-    let from_handler = || {
-        let state_from = state.clone();
-        move |_: &str, addr: &str| {
-            let (from, to, addr) =
-                parse_from_to_addr(addr).map_err(|e| anyhow::anyhow!("invalid address: {}", e))?;
-            if !state_from.inner.borrow().ids.contains(&from) {
-                anyhow::bail!("unknown identity: {:?}", from);
-            }
-            Ok((from, to, addr))
-        }
-    };
-    bind_local_bus("/from", state.clone(), true, from_handler());
-    bind_local_bus("/udp/from", state.clone(), false, from_handler());
-
     tokio::task::spawn_local(broadcast_handler(brx, broadcast_size));
-    tokio::task::spawn_local(forward_handler(receiver, state.clone()));
+    stats::spawn_pruning(config.session_expiration);
+    tokio::task::spawn_local(relay_monitor(config, crypto.clone(), state, relays));
 
     bind_identity_event_handler(crypto).await;
 
@@ -175,13 +153,53 @@ pub async fn start_network(
     Ok(())
 }
 
+/// Binds local bus handlers routing `/net` and `/from` calls to other nodes
+/// through given transport or through ya-relay-client, if transport is not set.
+pub(crate) fn bind_routing(
+    default_id: NodeId,
+    ids: Vec<NodeId>,
+    transport: Option<Transport>,
+) -> State {
+    let state = State::new(default_id, ids, None, transport);
+    let router = Router::default();
+    router.add(state.clone());
+    bind_router(router);
+    state
+}
+
+/// Binds local bus handlers routing `/net` and `/from` calls of all nodes
+/// in the router. Calls to `/net` are made on behalf of the first node.
+pub(crate) fn bind_router(router: Router) {
+    // outbound traffic
+    let net_handler = || {
+        let router = router.clone();
+        move |_: &str, addr: &str| match parse_net_to_addr(addr) {
+            Ok((to, addr)) => {
+                let default_id = router
+                    .default_id()
+                    .ok_or_else(|| anyhow::anyhow!("network not started"))?;
+                Ok((default_id, to, addr))
+            }
+            Err(err) => anyhow::bail!("invalid address: {}", err),
+        }
+    };
+    bind_local_bus(net::BUS_ID_UDP, router.clone(), false, net_handler());
+    bind_local_bus(net::BUS_ID, router.clone(), true, net_handler());
+
+    let from_handler = || {
+        move |_: &str, addr: &str| {
+            parse_from_to_addr(addr).map_err(|e| anyhow::anyhow!("invalid address: {}", e))
+        }
+    };
+    bind_local_bus("/from", router.clone(), true, from_handler());
+    bind_local_bus("/udp/from", router, false, from_handler());
+}
+
 async fn build_client(
-    config: Arc<Config>,
+    config: &Config,
     crypto: impl CryptoProvider + 'static,
+    addr: SocketAddr,
 ) -> anyhow::Result<Client> {
-    let addr = relay_addr(&config)
-        .await
-        .map_err(|e| anyhow!("Resolving hybrid NET relay server failed. Error: {}", e))?;
     let url = Url::parse(&format!("udp://{addr}"))?;
 
     ClientBuilder::from_url(url)
@@ -194,46 +212,150 @@ async fn build_client(
         .await
 }
 
-async fn relay_addr(config: &Config) -> anyhow::Result<SocketAddr> {
-    let host_port = match &config.host {
-        Some(val) => val.to_string(),
-        None => resolver::resolve_yagna_srv_record("_net_relay._udp")
-            .await
-            // FIXME: remove
-            .unwrap_or_else(|_| DEFAULT_NET_RELAY_HOST.to_string()),
-    };
+/// Connects to the most preferred reachable relay server and routes network
+/// traffic through the new client. `failed` relay is tried as the last one.
+async fn connect_relay(
+    config: &Config,
+    crypto: IdentityCryptoProvider,
+    state: &State,
+    relays: &mut Relays,
+    failed: Option<SocketAddr>,
+) -> anyhow::Result<Client> {
+    // Latency is unknown until we connect, so relays are probed one by one.
+    if relays.len() > 1 && !relays.measured() {
+        for addr in relays.addrs() {
+            if Some(addr) == failed {
+                continue;
+            }
+
+            let started = Instant::now();
+            let latency = match build_client(config, crypto.clone(), addr).await {
+                Ok(mut client) => {
+                    let latency = started.elapsed();
+                    let _ = client.shutdown().await;
+                    Some(latency)
+                }
+                Err(e) => {
+                    log::debug!("Relay server {} unreachable: {}", addr, e);
+                    None
+                }
+            };
+            relays.set_latency(addr, latency);
+        }
+    }
+
+    for addr in relays.candidates(failed) {
+        match build_client(config, crypto.clone(), addr).await {
+            Ok(client) => {
+                log::info!("Connected to relay server {}", addr);
+                set_active_relay(Some(addr));
+                use_client(client.clone(), state).await;
+                return Ok(client);
+            }
+            Err(e) => {
+                log::warn!("Unable to connect to relay server {}: {}", addr, e);
+                relays.set_latency(addr, None);
+            }
+        }
+    }
+
+    set_active_relay(None);
+    Err(anyhow!("All relay servers are unreachable"))
+}
+
+/// Routes network traffic through given client. Routes to other nodes
+/// established by previous client are dropped and will be re-established
+/// on demand.
+async fn use_client(client: Client, state: &State) {
+    let receiver = client.clone().forward_receiver().await.unwrap();
+
+    CLIENT.with(|inner| {
+        inner.borrow_mut().replace(client.clone());
+    });
+    ClientActor::new(client).start();
+
+    state.clear_routes();
+    tokio::task::spawn_local(forward_handler(receiver, state.clone()));
+}
+
+/// Checks health of active relay server and switches to another one,
+/// when it stops responding.
+async fn relay_monitor(
+    config: Arc<Config>,
+    crypto: IdentityCryptoProvider,
+    state: State,
+    mut relays: Relays,
+) {
+    let mut interval = tokio::time::interval(config.relay_check_interval);
+    let mut failures = 0;
+
+    loop {
+        interval.tick().await;
 
-    log::info!("Hybrid NET relay server configured on url: udp://{host_port}");
+        let client = match CLIENT.with(|c| c.borrow().clone()) {
+            Some(client) => client,
+            None => return,
+        };
+        let active = active_relay();
+
+        if let Some(relay) = active {
+            match check_relay(&client, relay, config.session_expiration).await {
+                Some(latency) => {
+                    failures = 0;
+                    relays.set_latency(relay, Some(latency));
+                    continue;
+                }
+                None => {
+                    failures += 1;
+                    log::warn!(
+                        "Relay server {} not responding ({}/{})",
+                        relay,
+                        failures,
+                        config.relay_failover_threshold
+                    );
+                    if failures < config.relay_failover_threshold {
+                        continue;
+                    }
+                    relays.set_latency(relay, None);
+                }
+            }
+        }
 
-    let (host, port) = &host_port
-        .split_once(':')
-        .context("Please use host:port format")?;
-    let ip = resolver::try_resolve_dns_record(host).await;
-    let socket = format!("{}:{}", ip, port)
-        .to_socket_addrs()?
-        .next()
-        .ok_or_else(|| anyhow!("Invalid relay address: {ip}:{port}"))?;
-    Ok(socket)
+        // Don't reconnect, when network is being shut down.
+        if SHUTDOWN_TX.read().await.is_none() {
+            return;
+        }
+
+        failures = 0;
+        if active.is_some() {
+            let mut client = client;
+            let _ = client.shutdown().await;
+        }
+        if let Err(e) = connect_relay(&config, crypto.clone(), &state, &mut relays, active).await {
+            log::warn!("Relay server failover failed: {}", e);
+        }
+    }
 }
 
-fn bind_local_bus<F>(address: &'static str, state: State, reliable: bool, resolver: F)
+fn bind_local_bus<F>(address: &'static str, router: Router, reliable: bool, resolver: F)
 where
     F: Fn(&str, &str) -> anyhow::Result<(NodeId, NodeId, String)> + 'static,
 {
     let resolver = Rc::new(resolver);
 
     let resolver_ = resolver.clone();
-    let state_ = state.clone();
+    let router_ = router.clone();
     let rpc = move |caller: &str, addr: &str, msg: &[u8]| {
         log::trace!("local bus: rpc call (egress): {}", addr);
 
-        let (caller_id, remote_id, address) = match (*resolver_)(caller, addr) {
-            Ok(id) => id,
-            Err(err) => {
-                log::debug!("rpc {} forward error: {}", addr, err);
-                return async move { Err(Error::GsbFailure(err.to_string())) }.left_future();
-            }
-        };
+        let (caller_id, remote_id, address, state) =
+            match (*resolver_)(caller, addr).and_then(|ids| router_.resolve(ids)) {
+                Ok(resolved) => resolved,
+                Err(err) => {
+                    log::debug!("rpc {} forward error: {}", addr, err);
+                    return async move { Err(Error::GsbFailure(err.to_string())) }.left_future();
+                }
+            };
 
         log::trace!(
             "local bus: rpc call (egress): {} ({} -> {})",
@@ -242,16 +364,27 @@ where
             remote_id
         );
 
-        let mut rx = if state_.inner.borrow().ids.contains(&remote_id) {
+        let (mut rx, otel_cx) = if state.owns(&remote_id) {
             let (tx, rx) = mpsc::channel(1);
-            forward_bus_to_local(&caller_id.to_string(), addr, msg, &state_, tx);
-            rx
+            forward_bus_to_local(&caller_id.to_string(), addr, msg, &state, tx);
+            (rx, None)
         } else {
-            forward_bus_to_net(caller_id, remote_id, address, msg, &state_, reliable)
+            let otel_cx = start_call_span(&address);
+            let trace_context = otel_cx.as_ref().and_then(telemetry::inject);
+            let rx = forward_bus_to_net(
+                caller_id,
+                remote_id,
+                address,
+                msg,
+                &state,
+                reliable,
+                trace_context,
+            );
+            (rx, otel_cx)
         };
 
         async move {
-            match rx.next().await.ok_or(Error::Cancelled) {
+            let result = match rx.next().await.ok_or(Error::Cancelled) {
                 Ok(chunk) => match chunk {
                     ResponseChunk::Full(data) => codec::decode_reply(data),
                     ResponseChunk::Part(_) => {
@@ -259,7 +392,11 @@ where
                     }
                 },
                 Err(err) => Err(err),
+            };
+            if let (Some(otel_cx), Err(e)) = (&otel_cx, &result) {
+                otel_cx.span().set_status(StatusCode::Error, e.to_string());
             }
+            result
         }
         .right_future()
     };
@@ -267,17 +404,18 @@ where
     let stream = move |caller: &str, addr: &str, msg: &[u8]| {
         log::trace!("local bus: stream call (egress): {}", addr);
 
-        let (caller_id, remote_id, address) = match (*resolver)(caller, addr) {
-            Ok(id) => id,
-            Err(err) => {
-                log::debug!("local bus: stream call (egress) to {} error: {}", addr, err);
-                return futures::stream::once(
-                    async move { Err(Error::GsbFailure(err.to_string())) },
-                )
-                .boxed_local()
-                .left_stream();
-            }
-        };
+        let (caller_id, remote_id, address, state) =
+            match (*resolver)(caller, addr).and_then(|ids| router.resolve(ids)) {
+                Ok(resolved) => resolved,
+                Err(err) => {
+                    log::debug!("local bus: stream call (egress) to {} error: {}", addr, err);
+                    return futures::stream::once(async move {
+                        Err(Error::GsbFailure(err.to_string()))
+                    })
+                    .boxed_local()
+                    .left_stream();
+                }
+            };
 
         log::trace!(
             "local bus: stream call (egress): {} ({} -> {})",
@@ -286,12 +424,23 @@ where
             remote_id
         );
 
-        let rx = if state.inner.borrow().ids.contains(&remote_id) {
+        let (rx, otel_cx) = if state.owns(&remote_id) {
             let (tx, rx) = mpsc::channel(1);
             forward_bus_to_local(&caller_id.to_string(), addr, msg, &state, tx);
-            rx
+            (rx, None)
         } else {
-            forward_bus_to_net(caller_id, remote_id, address, msg, &state, reliable)
+            let otel_cx = start_call_span(&address);
+            let trace_context = otel_cx.as_ref().and_then(telemetry::inject);
+            let rx = forward_bus_to_net(
+                caller_id,
+                remote_id,
+                address,
+                msg,
+                &state,
+                reliable,
+                trace_context,
+            );
+            (rx, otel_cx)
         };
 
         let eos = Rc::new(AtomicBool::new(false));
@@ -312,6 +461,8 @@ where
                 Poll::Ready(Some(Ok(ResponseChunk::Full(Vec::new()))))
             }
         }))
+        // keeps the span open until the stream ends
+        .with_context(otel_cx.unwrap_or_default())
         .boxed_local()
         .right_stream()
     };
@@ -353,17 +504,19 @@ async fn bind_identity_event_handler(crypto: IdentityCryptoProvider) {
     }
 }
 
+/// Starts span of call forwarded to remote node, if the caller is traced.
+fn start_call_span(address: &str) -> Option<telemetry::Context> {
+    let parent = telemetry::Context::current();
+    if !telemetry::is_traced(&parent) {
+        return None;
+    }
+    let name = format!("gsb {}", address);
+    Some(telemetry::start_span(name, SpanKind::Client, &parent))
+}
+
 /// Forward requests from and to the local bus
 fn forward_bus_to_local(caller: &str, addr: &str, data: &[u8], state: &State, tx: BusSender) {
-    let address = match {
-        let inner = state.inner.borrow();
-        inner
-            .services
-            .iter()
-            .find(|&id| addr.starts_with(id))
-            // replaces  /net/<dest_node_id>/test/1 --> /public/test/1
-            .map(|s| addr.replacen(s, net::PUBLIC_PREFIX, 1))
-    } {
+    let address = match state.local_address(addr) {
         Some(address) => address,
         None => {
             let err = format!("unknown address: {}", addr);
@@ -390,6 +543,7 @@ fn forward_bus_to_net(
     msg: &[u8],
     state: &State,
     reliable: bool,
+    trace_context: Option<String>,
 ) -> BusReceiver {
     let address = address.to_string();
     let state = state.clone();
@@ -403,6 +557,7 @@ fn forward_bus_to_net(
         address.clone(),
         request_id.clone(),
         msg.to_vec(),
+        trace_context,
     ) {
         Ok(vec) => vec,
         Err(err) => {
@@ -431,10 +586,14 @@ fn forward_bus_to_net(
 
         match state.forward_sink(remote_id, reliable).await {
             Ok(mut sink) => {
-                let _ = sink.send(msg).await.map_err(|_| {
-                    let err = "error sending message: session closed".to_string();
-                    handler_reply_service_err(request_id, err, tx);
-                });
+                let size = msg.len();
+                match sink.send(msg).await {
+                    Ok(_) => stats::record_tx(remote_id, size),
+                    Err(_) => {
+                        let err = "error sending message: session closed".to_string();
+                        handler_reply_service_err(request_id, err, tx);
+                    }
+                }
             }
             Err(error) => {
                 let err = format!("error forwarding message: {}", error);
@@ -486,6 +645,9 @@ fn forward_handler(
                 } {
                     Some(cached) => cached,
                     None => {
+                        if fwd.reliable {
+                            stats::record_connection(fwd.node_id);
+                        }
                         let state = state.clone();
                         let (tx, rx) = forward_channel(fwd.reliable);
                         {
@@ -527,7 +689,7 @@ fn forward_channel<'a>(reliable: bool) -> (mpsc::Sender<Vec<u8>>, LocalBoxStream
 }
 
 /// Forward node GSB messages from the network to the local bus
-fn inbound_handler(
+pub(crate) fn inbound_handler(
     rx: impl Stream<Item = Vec<u8>> + 'static,
     remote_id: NodeId,
     reliable: bool,
@@ -536,18 +698,20 @@ fn inbound_handler(
     StreamExt::for_each(rx, move |payload| {
         let state = state.clone();
         log::trace!("local bus handler -> inbound message");
+        stats::record_rx(remote_id, payload.len());
 
         async move {
             match codec::decode_message(payload.as_slice()) {
                 Ok(Some(GsbMessage::CallRequest(request @ ya_sb_proto::CallRequest { .. }))) => {
-                    handle_request(request, remote_id, state, reliable)
+                    let trace_context = codec::decode_trace_context(payload.as_slice());
+                    handle_request(request, remote_id, state, reliable, trace_context)
                 }
                 Ok(Some(GsbMessage::CallReply(reply @ ya_sb_proto::CallReply { .. }))) => {
                     handle_reply(reply, remote_id, state)
                 }
                 Ok(Some(GsbMessage::BroadcastRequest(
                     request @ ya_sb_proto::BroadcastRequest { .. },
-                ))) => handle_broadcast(request, remote_id),
+                ))) => handle_broadcast(request, remote_id, &state),
                 Ok(None) => {
                     log::trace!("received a partial message");
                     Ok(())
@@ -571,6 +735,7 @@ fn handle_request(
     remote_id: NodeId,
     state: State,
     reliable: bool,
+    trace_context: Option<String>,
 ) -> anyhow::Result<()> {
     let caller_id = NodeId::from_str(&request.caller).ok();
     if !caller_id.map(|id| id == remote_id).unwrap_or(false) {
@@ -585,18 +750,23 @@ fn handle_request(
 
     log::debug!("Handle request {request_id} to {address} from {remote_id}");
 
+    // Requests are traced only as a part of caller's trace.
+    let otel_cx = trace_context
+        .as_deref()
+        .map(telemetry::extract)
+        .filter(telemetry::is_traced)
+        .map(|parent| {
+            let name = format!("gsb {}", address);
+            telemetry::start_span(name, SpanKind::Server, &parent)
+        })
+        .unwrap_or_default();
+    let otel_cx_map = otel_cx.clone();
+    let otel_guard = otel_cx.clone().attach();
+
     let eos = Rc::new(AtomicBool::new(false));
     let eos_map = eos.clone();
 
-    let stream = match {
-        let inner = state.inner.borrow();
-        inner
-            .services
-            .iter()
-            .find(|&id| address.starts_with(id))
-            // replaces  /net/<dest_node_id>/test/1 --> /public/test/1
-            .map(|s| address.replacen(s, net::PUBLIC_PREFIX, 1))
-    } {
+    let stream = match state.local_address(&address) {
         Some(address) => {
             log::trace!("handle request: calling: {}", address);
             local_bus::call_stream(&address, &request.caller, &request.data).left_stream()
@@ -620,6 +790,9 @@ fn handle_request(
         },
         Err(err) => {
             eos_map.store(true, Relaxed);
+            otel_cx_map
+                .span()
+                .set_status(StatusCode::Error, err.to_string());
             codec::reply_err(request_id.clone(), err)
         }
     })
@@ -639,6 +812,7 @@ fn handle_request(
                     request_id_filter,
                     vec.len()
                 );
+                stats::record_tx(remote_id, vec.len());
                 Some(Ok::<Vec<u8>, mpsc::SendError>(vec))
             }
             Err(e) => {
@@ -647,7 +821,9 @@ fn handle_request(
             }
         };
         async move { filtered }
-    });
+    })
+    .with_context(otel_cx);
+    drop(otel_guard);
 
     tokio::task::spawn_local(
         async move {
@@ -719,6 +895,7 @@ fn handle_reply(
 fn handle_broadcast(
     request: ya_sb_proto::BroadcastRequest,
     remote_id: NodeId,
+    state: &State,
 ) -> anyhow::Result<()> {
     let caller_id = NodeId::from_str(&request.caller).ok();
     if !caller_id.map(|id| id == remote_id).unwrap_or(false) {
@@ -732,6 +909,7 @@ fn handle_broadcast(
     );
 
     let caller = caller_id.unwrap().to_string();
+    let state = state.clone();
 
     tokio::task::spawn_local(async move {
         let data: Rc<[u8]> = request.data.into();
@@ -742,6 +920,7 @@ fn handle_broadcast(
             .resolve(&topic)
             .await
             .into_iter()
+            .filter(|e| state.owns_endpoint(e.as_ref()))
             .filter_map(|e| handlers.get(e.as_ref()))
         {
             let mut h = handler.lock().await;
@@ -753,30 +932,86 @@ fn handle_broadcast(
 }
 
 #[derive(Clone)]
-struct State {
+pub(crate) struct State {
     inner: Rc<RefCell<StateInner>>,
 }
 
-#[derive(Default)]
 struct StateInner {
+    default_id: NodeId,
     requests: HashMap<String, Request<BusSender>>,
     routes: HashMap<NetSinkKey, NetSender>,
     ids: HashSet<NodeId>,
     services: HashSet<String>,
+    prefix: Option<String>,
+    transport: Option<Transport>,
 }
 
 impl State {
-    fn new(ids: impl IntoIterator<Item = NodeId>, services: HashSet<String>) -> Self {
+    /// Calls to the node are routed to services bound under `prefix`
+    /// instead of `/public`, so several nodes can share local bus.
+    pub(crate) fn new(
+        default_id: NodeId,
+        ids: Vec<NodeId>,
+        prefix: Option<String>,
+        transport: Option<Transport>,
+    ) -> Self {
+        let mut services: HashSet<_> = Default::default();
+        ids.iter().for_each(|id| {
+            let service = net::net_service(id);
+            services.insert(format!("/udp{}", service));
+            services.insert(service);
+        });
+
         Self {
             inner: Rc::new(RefCell::new(StateInner {
+                default_id,
+                requests: Default::default(),
+                routes: Default::default(),
                 ids: ids.into_iter().collect(),
                 services,
-                ..Default::default()
+                prefix,
+                transport,
             })),
         }
     }
 
+    pub(crate) fn default_id(&self) -> NodeId {
+        self.inner.borrow().default_id
+    }
+
+    fn owns(&self, id: &NodeId) -> bool {
+        self.inner.borrow().ids.contains(id)
+    }
+
+    /// Replaces `/net/<node_id>/test/1` with `/public/test/1` or
+    /// `<prefix>/test/1`, when node has its own prefix.
+    fn local_address(&self, addr: &str) -> Option<String> {
+        let inner = self.inner.borrow();
+        let prefix = inner.prefix.as_deref().unwrap_or(net::PUBLIC_PREFIX);
+        inner
+            .services
+            .iter()
+            .find(|&id| addr.starts_with(id))
+            .map(|s| addr.replacen(s, prefix, 1))
+    }
+
+    /// Broadcast handlers of node with its own prefix are bound under it.
+    fn owns_endpoint(&self, endpoint: &str) -> bool {
+        match self.inner.borrow().prefix.as_deref() {
+            Some(prefix) => endpoint
+                .strip_prefix(prefix)
+                .map(|rest| rest.starts_with('/'))
+                .unwrap_or(false),
+            None => true,
+        }
+    }
+
     async fn forward_sink(&self, remote_id: NodeId, reliable: bool) -> anyhow::Result<NetSinkKind> {
+        let transport = { self.inner.borrow().transport.clone() };
+        if let Some(transport) = transport {
+            return transport(remote_id, reliable).await;
+        }
+
         let client = CLIENT
             .with(|c| c.borrow().clone())
             .ok_or_else(|| anyhow::anyhow!("network not started"))?;
@@ -797,12 +1032,58 @@ impl State {
         Ok(forward)
     }
 
+    fn clear_routes(&self) {
+        self.inner.borrow_mut().routes.clear();
+    }
+
     fn remove(&self, key: &NetSinkKey) {
         let mut inner = self.inner.borrow_mut();
         inner.routes.remove(key);
     }
 }
 
+/// Routing states of nodes sharing local bus. There is a single node in the
+/// process, unless loopback network runs more of them.
+#[derive(Clone, Default)]
+pub(crate) struct Router {
+    states: Rc<RefCell<Vec<State>>>,
+}
+
+impl Router {
+    /// Replaces state of the node with the same default identity.
+    pub fn add(&self, state: State) {
+        let default_id = state.default_id();
+        let mut states = self.states.borrow_mut();
+        states.retain(|s| s.default_id() != default_id);
+        states.push(state);
+    }
+
+    pub fn remove(&self, default_id: &NodeId) {
+        self.states
+            .borrow_mut()
+            .retain(|s| s.default_id() != *default_id);
+    }
+
+    fn default_id(&self) -> Option<NodeId> {
+        self.states.borrow().first().map(State::default_id)
+    }
+
+    /// Finds state of the calling node.
+    fn resolve(
+        &self,
+        (caller_id, remote_id, address): (NodeId, NodeId, String),
+    ) -> anyhow::Result<(NodeId, NodeId, String, State)> {
+        let state = self
+            .states
+            .borrow()
+            .iter()
+            .find(|s| s.owns(&caller_id))
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("unknown identity: {:?}", caller_id))?;
+        Ok((caller_id, remote_id, address, state))
+    }
+}
+
 #[derive(Clone)]
 struct Request<S: Clone> {
     #[allow(dead_code)]
@@ -8,7 +8,12 @@ pub use service::{bind_broadcast_with_caller, broadcast, Net};
 mod bcast;
 pub mod central;
 pub mod hybrid;
+pub mod local;
+#[cfg(unix)]
+pub mod loopback;
 mod service;
+mod stats;
 
 mod cli;
 mod config;
+mod rest;
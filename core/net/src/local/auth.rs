@@ -0,0 +1,107 @@
+//! Proofs of identity used by handshake and discovery. Without relay server
+//! nobody vouches for identities of nodes, so they have to prove them by
+//! signing data chosen by the verifier.
+use ethsign::{PublicKey, SecretKey, Signature};
+use futures::future::LocalBoxFuture;
+use futures::FutureExt;
+use sha3::{Digest, Sha3_256};
+
+use ya_core_model::NodeId;
+use ya_relay_client::crypto::Crypto;
+
+pub(crate) const SIGNATURE_SIZE: usize = 65;
+
+/// Signs hash of the payload. Signature is encoded as `v || r || s`.
+pub(crate) async fn sign(crypto: &dyn Crypto, payload: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let hash = Sha3_256::digest(payload);
+    let signature = crypto.sign(hash.as_slice()).await?;
+
+    let mut bytes = Vec::with_capacity(SIGNATURE_SIZE);
+    bytes.push(signature.v);
+    bytes.extend_from_slice(&signature.r);
+    bytes.extend_from_slice(&signature.s);
+    Ok(bytes)
+}
+
+/// Returns identity, which signed the payload.
+pub(crate) fn recover(signature: &[u8], payload: &[u8]) -> anyhow::Result<NodeId> {
+    if signature.len() != SIGNATURE_SIZE {
+        anyhow::bail!(
+            "invalid signature length: expected {} bytes, got {}",
+            SIGNATURE_SIZE,
+            signature.len()
+        );
+    }
+
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+    r.copy_from_slice(&signature[1..33]);
+    s.copy_from_slice(&signature[33..65]);
+
+    let hash = Sha3_256::digest(payload);
+    let key = Signature {
+        v: signature[0],
+        r,
+        s,
+    }
+    .recover(hash.as_slice())
+    .map_err(|e| anyhow::anyhow!("invalid signature: {}", e))?;
+    Ok(NodeId::from(key.address().as_ref()))
+}
+
+/// Signs with secret key held in memory instead of identity service.
+/// Used by nodes started without identity service, like in tests.
+pub(crate) struct KeyCrypto(SecretKey);
+
+impl KeyCrypto {
+    pub fn new(key: SecretKey) -> Self {
+        KeyCrypto(key)
+    }
+
+    #[cfg(test)]
+    pub fn generate() -> Self {
+        let raw: [u8; 32] = rand::random();
+        KeyCrypto(SecretKey::from_raw(&raw).unwrap())
+    }
+
+    pub fn node_id(&self) -> NodeId {
+        NodeId::from(self.0.public().address().as_ref())
+    }
+}
+
+impl Crypto for KeyCrypto {
+    fn public_key<'a>(&self) -> LocalBoxFuture<'a, anyhow::Result<PublicKey>> {
+        futures::future::ok(self.0.public()).boxed_local()
+    }
+
+    fn sign<'a>(&self, message: &'a [u8]) -> LocalBoxFuture<'a, anyhow::Result<Signature>> {
+        let result = self
+            .0
+            .sign(message)
+            .map_err(|e| anyhow::anyhow!("signing failed: {}", e));
+        futures::future::ready(result).boxed_local()
+    }
+
+    fn encrypt<'a>(
+        &self,
+        _message: &'a [u8],
+        _remote_key: &'a PublicKey,
+    ) -> LocalBoxFuture<'a, anyhow::Result<Vec<u8>>> {
+        futures::future::err(anyhow::anyhow!("encryption not supported")).boxed_local()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn test_recover_signer() {
+        let crypto = KeyCrypto::generate();
+        let signature = sign(&crypto, b"payload").await.unwrap();
+
+        assert_eq!(recover(&signature, b"payload").unwrap(), crypto.node_id());
+        assert_ne!(recover(&signature, b"other").unwrap(), crypto.node_id());
+        assert!(recover(&signature[1..], b"payload").is_err());
+    }
+}
@@ -0,0 +1,137 @@
+use anyhow::anyhow;
+use futures::future::join_all;
+use futures::TryFutureExt;
+use std::time::{Duration, Instant};
+
+use ya_core_model::net as ya_net;
+use ya_core_model::net::local::{GsbPingResponse, StatusError};
+use ya_core_model::net::{local as model, GsbRemotePing, RemoteEndpoint, DIAGNOSTIC};
+use ya_service_bus::timeout::IntoTimeoutFuture;
+use ya_service_bus::typed::ServiceBinder;
+use ya_service_bus::{typed as bus, RpcEndpoint};
+
+use crate::local::service::LocalNetwork;
+use crate::stats;
+
+pub(crate) fn bind_service() {
+    let _ = bus::bind(model::BUS_ID, |_: model::GsbPing| {
+        cli_ping().map_err(status_err)
+    });
+
+    ServiceBinder::new(DIAGNOSTIC, &(), ())
+        .bind(move |_, _caller: String, _msg: GsbRemotePing| async move { Ok(GsbRemotePing {}) });
+
+    let _ = bus::bind(model::BUS_ID, move |_: model::Status| {
+        async move {
+            let network = LocalNetwork::get()?;
+
+            Ok(model::StatusResponse {
+                node_id: network.transport.default_id,
+                listen_address: Some(network.listen_addr),
+                public_address: None,
+                relay_address: None,
+                sessions: network.transport.connections.list().len(),
+                metrics: empty_metrics(),
+            })
+        }
+        .map_err(status_err)
+    });
+    let _ = bus::bind(model::BUS_ID, move |_: model::Sessions| {
+        async move {
+            let network = LocalNetwork::get()?;
+            let now = Instant::now();
+
+            let responses = network
+                .transport
+                .connections
+                .list()
+                .into_iter()
+                .map(|(node_id, conn)| model::SessionResponse {
+                    node_id: Some(node_id),
+                    id: node_id.to_string(),
+                    session_type: "lan".to_string(),
+                    remote_address: conn.addr,
+                    seen: Duration::default(),
+                    duration: now - conn.created,
+                    ping: Duration::default(),
+                    stats: stats::peer_stats(&node_id),
+                })
+                .collect();
+
+            Ok(responses)
+        }
+        .map_err(status_err)
+    });
+    // Local network doesn't support virtual sockets.
+    let _ = bus::bind(model::BUS_ID, move |_: model::Sockets| async move {
+        Ok::<_, StatusError>(Vec::new())
+    });
+}
+
+pub async fn cli_ping() -> anyhow::Result<Vec<GsbPingResponse>> {
+    let network = LocalNetwork::get()?;
+    let our_node_id = network.transport.default_id;
+    let nodes = network.transport.peers.list();
+    let ping_timeout = Duration::from_secs(10);
+
+    log::debug!("Ping: Num discovered nodes: {}", nodes.len());
+
+    let results = join_all(nodes.iter().map(|peer| {
+        let target_id = peer.node_id;
+        async move {
+            let before = Instant::now();
+
+            ya_net::from(our_node_id)
+                .to(target_id)
+                .service(ya_net::DIAGNOSTIC)
+                .send(GsbRemotePing {})
+                .timeout(Some(ping_timeout))
+                .await???;
+
+            anyhow::Ok(before.elapsed())
+        }
+        .map_err(|e| anyhow!("(Tcp ping). {}", e))
+    }))
+    .await
+    .into_iter()
+    .zip(nodes.iter())
+    .map(|(result, peer)| {
+        let ping = match result {
+            Ok(ping) => {
+                stats::record_ping(peer.node_id, ping, true);
+                ping
+            }
+            Err(e) => {
+                log::warn!("Failed to ping node: {} {}", peer.node_id, e);
+                ping_timeout
+            }
+        };
+        // Connections are direct, so UDP messages take the same route as TCP.
+        GsbPingResponse {
+            node_id: peer.node_id,
+            node_alias: None,
+            tcp_ping: ping,
+            udp_ping: ping,
+            is_p2p: true,
+        }
+    })
+    .collect();
+
+    Ok(results)
+}
+
+fn empty_metrics() -> model::StatusMetrics {
+    model::StatusMetrics {
+        tx_total: 0,
+        tx_current: 0.0,
+        tx_avg: 0.0,
+        rx_total: 0,
+        rx_current: 0.0,
+        rx_avg: 0.0,
+    }
+}
+
+#[inline]
+fn status_err(e: anyhow::Error) -> StatusError {
+    StatusError::RuntimeException(e.to_string())
+}
@@ -0,0 +1,375 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use anyhow::bail;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::UdpSocket;
+
+use ya_core_model::NodeId;
+use ya_relay_client::crypto::Crypto;
+
+use crate::local::auth;
+
+const MAX_ANNOUNCEMENT_SIZE: usize = 8192;
+const OWNERSHIP_PREFIX: &[u8] = b"YLAN-ALIAS";
+
+/// Message periodically sent to multicast group by each node. Every
+/// identity is listed with proof, that it belongs to the node.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Announcement {
+    node_id: NodeId,
+    ids: Vec<NodeId>,
+    proofs: Vec<Vec<u8>>,
+    port: u16,
+    timestamp: i64,
+}
+
+/// Announcement signed by node's default identity. Timestamp limits
+/// the time, for which recorded announcement can be replayed.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedAnnouncement {
+    announcement: Announcement,
+    signature: Vec<u8>,
+}
+
+impl Announcement {
+    async fn sign(self, crypto: &dyn Crypto) -> anyhow::Result<SignedAnnouncement> {
+        let signature = auth::sign(crypto, &serde_json::to_vec(&self)?).await?;
+        Ok(SignedAnnouncement {
+            announcement: self,
+            signature,
+        })
+    }
+}
+
+impl SignedAnnouncement {
+    /// Returns announcement, if it is recent and all signatures are valid.
+    fn verify(self, max_age: Duration) -> anyhow::Result<Announcement> {
+        let announcement = self.announcement;
+        let signer = auth::recover(&self.signature, &serde_json::to_vec(&announcement)?)?;
+        if signer != announcement.node_id {
+            bail!(
+                "announcement of [{}] signed by [{}]",
+                announcement.node_id,
+                signer
+            );
+        }
+
+        let age = (Utc::now().timestamp() - announcement.timestamp).abs();
+        if age as u64 > max_age.as_secs() {
+            bail!("announcement of [{}] is {}s old", signer, age);
+        }
+
+        if !announcement.ids.contains(&announcement.node_id)
+            || announcement.ids.len() != announcement.proofs.len()
+        {
+            bail!("invalid identities in announcement of [{}]", signer);
+        }
+        let payload = ownership_payload(announcement.node_id);
+        for (id, proof) in announcement.ids.iter().zip(announcement.proofs.iter()) {
+            if auth::recover(proof, &payload)? != *id {
+                bail!("[{}] doesn't belong to [{}]", id, signer);
+            }
+        }
+        Ok(announcement)
+    }
+}
+
+/// Payload signed by each identity of the node, so other nodes can't
+/// claim them in their announcements.
+fn ownership_payload(node_id: NodeId) -> Vec<u8> {
+    let mut payload = OWNERSHIP_PREFIX.to_vec();
+    payload.extend_from_slice(&node_id.into_array());
+    payload
+}
+
+/// Proves ownership of identities, which are able to sign.
+async fn prove_ownership(
+    node_id: NodeId,
+    identities: &[(NodeId, Rc<dyn Crypto>)],
+) -> (Vec<NodeId>, Vec<Vec<u8>>) {
+    let payload = ownership_payload(node_id);
+    let mut ids = Vec::new();
+    let mut proofs = Vec::new();
+    for (id, crypto) in identities {
+        match auth::sign(crypto.as_ref(), &payload).await {
+            Ok(proof) => {
+                ids.push(*id);
+                proofs.push(proof);
+            }
+            Err(e) => log::debug!("Identity [{}] won't be announced: {}", id, e),
+        }
+    }
+    (ids, proofs)
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Peer {
+    pub node_id: NodeId,
+    pub ids: Vec<NodeId>,
+    pub addr: SocketAddr,
+    pub last_seen: Instant,
+}
+
+/// Nodes discovered in local network, keyed by their default identity.
+#[derive(Clone)]
+pub(crate) struct Peers {
+    inner: Rc<RefCell<HashMap<NodeId, Peer>>>,
+    expiration: Duration,
+}
+
+impl Peers {
+    pub fn new(expiration: Duration) -> Self {
+        Peers {
+            inner: Default::default(),
+            expiration,
+        }
+    }
+
+    /// Finds peer owning given identity.
+    pub fn find(&self, id: &NodeId) -> Option<Peer> {
+        self.list().into_iter().find(|peer| peer.ids.contains(id))
+    }
+
+    pub fn list(&self) -> Vec<Peer> {
+        let now = Instant::now();
+        let expiration = self.expiration;
+
+        let mut peers = self.inner.borrow_mut();
+        peers.retain(|_, peer| now - peer.last_seen < expiration);
+        peers.values().cloned().collect()
+    }
+
+    fn update(&self, announcement: Announcement, addr: SocketAddr) {
+        let mut peers = self.inner.borrow_mut();
+        if !peers.contains_key(&announcement.node_id) {
+            log::info!(
+                "Discovered node [{}] in local network at {}",
+                announcement.node_id,
+                addr
+            );
+        }
+
+        peers.insert(
+            announcement.node_id,
+            Peer {
+                node_id: announcement.node_id,
+                ids: announcement.ids,
+                addr,
+                last_seen: Instant::now(),
+            },
+        );
+    }
+}
+
+/// Announces our identities on multicast group and collects announcements
+/// of other nodes. Peers are forgotten after missing a few announcements.
+pub(crate) async fn start_discovery(
+    peers: Peers,
+    group: SocketAddrV4,
+    interval: Duration,
+    default_id: NodeId,
+    identities: Vec<(NodeId, Rc<dyn Crypto>)>,
+    port: u16,
+) -> anyhow::Result<()> {
+    let socket = Rc::new(multicast_socket(group)?);
+
+    let crypto = identities
+        .iter()
+        .find(|(id, _)| *id == default_id)
+        .map(|(_, crypto)| crypto.clone())
+        .ok_or_else(|| anyhow::anyhow!("no crypto for default identity"))?;
+    let (ids, proofs) = prove_ownership(default_id, &identities).await;
+
+    let sender = socket.clone();
+    let announced = ids.clone();
+    tokio::task::spawn_local(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+
+            let announcement = Announcement {
+                node_id: default_id,
+                ids: announced.clone(),
+                proofs: proofs.clone(),
+                port,
+                timestamp: Utc::now().timestamp(),
+            };
+            let result = match announcement.sign(crypto.as_ref()).await {
+                Ok(signed) => serde_json::to_vec(&signed).map_err(anyhow::Error::from),
+                Err(e) => Err(e),
+            };
+            let message = match result {
+                Ok(message) => message,
+                Err(e) => {
+                    log::debug!("Failed to sign local network announcement: {}", e);
+                    continue;
+                }
+            };
+
+            if let Err(e) = sender.send_to(&message, group).await {
+                log::debug!("Failed to send local network announcement: {}", e);
+            }
+        }
+    });
+
+    let receiver = peers;
+    tokio::task::spawn_local(async move {
+        let mut buf = vec![0u8; MAX_ANNOUNCEMENT_SIZE];
+        loop {
+            let (size, src) = match socket.recv_from(&mut buf).await {
+                Ok(received) => received,
+                Err(e) => {
+                    log::debug!("Failed to receive local network announcement: {}", e);
+                    continue;
+                }
+            };
+
+            let announcement = serde_json::from_slice::<SignedAnnouncement>(&buf[..size])
+                .map_err(anyhow::Error::from)
+                .and_then(|signed| signed.verify(receiver.expiration));
+            match announcement {
+                // Our own announcement is looped back to us.
+                Ok(announcement) if ids.contains(&announcement.node_id) => (),
+                Ok(announcement) => {
+                    let addr = SocketAddr::new(src.ip(), announcement.port);
+                    receiver.update(announcement, addr);
+                }
+                Err(e) => log::trace!("Invalid announcement from {}: {}", src, e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Multiple daemons on the same host must be able to join the group,
+/// so the port is bound with address reuse.
+fn multicast_socket(group: SocketAddrV4) -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    #[cfg(unix)]
+    socket.set_reuse_port(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, group.port())).into())?;
+    socket.join_multicast_v4(group.ip(), &Ipv4Addr::UNSPECIFIED)?;
+    socket.set_multicast_loop_v4(true)?;
+    socket.set_nonblocking(true)?;
+
+    UdpSocket::from_std(socket.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local::auth::KeyCrypto;
+    use std::str::FromStr;
+
+    fn announcement(ids: &[&str]) -> Announcement {
+        let ids: Vec<NodeId> = ids.iter().map(|id| NodeId::from_str(id).unwrap()).collect();
+        Announcement {
+            node_id: ids[0],
+            ids,
+            proofs: vec![],
+            port: 11500,
+            timestamp: Utc::now().timestamp(),
+        }
+    }
+
+    async fn signed_announcement(
+        default: &Rc<dyn Crypto>,
+        identities: &[(NodeId, Rc<dyn Crypto>)],
+    ) -> SignedAnnouncement {
+        let node_id = identities[0].0;
+        let (ids, proofs) = prove_ownership(node_id, identities).await;
+        Announcement {
+            node_id,
+            ids,
+            proofs,
+            port: 11500,
+            timestamp: Utc::now().timestamp(),
+        }
+        .sign(default.as_ref())
+        .await
+        .unwrap()
+    }
+
+    fn identity() -> (NodeId, Rc<dyn Crypto>) {
+        let crypto = KeyCrypto::generate();
+        (crypto.node_id(), Rc::new(crypto))
+    }
+
+    #[actix_rt::test]
+    async fn test_verify_announcement() {
+        let (default, alias) = (identity(), identity());
+        let identities = vec![default.clone(), alias.clone()];
+        let expiration = Duration::from_secs(15);
+
+        let signed = signed_announcement(&default.1, &identities).await;
+        let announcement = signed.clone().verify(expiration).unwrap();
+        assert_eq!(announcement.ids, vec![default.0, alias.0]);
+
+        // Announcement modified by other node.
+        let mut modified = signed.clone();
+        modified.announcement.port = 11600;
+        assert!(modified.verify(expiration).is_err());
+
+        // Announcement signed by node other than announced one.
+        let other = identity();
+        let forged = signed_announcement(&other.1, &identities).await;
+        assert!(forged.verify(expiration).is_err());
+
+        // Node claiming alias of other node with proof copied from its announcement.
+        let mut stolen = signed_announcement(&other.1, &[other.clone()]).await;
+        stolen.announcement.ids.push(alias.0);
+        stolen
+            .announcement
+            .proofs
+            .push(signed.announcement.proofs[1].clone());
+        let stolen = stolen.announcement.sign(other.1.as_ref()).await.unwrap();
+        assert!(stolen.verify(expiration).is_err());
+
+        // Replayed announcement.
+        let mut old = signed.announcement;
+        old.timestamp -= 60;
+        let old = old.sign(default.1.as_ref()).await.unwrap();
+        assert!(old.verify(expiration).is_err());
+    }
+
+    #[test]
+    fn test_find_peer_by_any_identity() {
+        let peers = Peers::new(Duration::from_secs(15));
+        let addr: SocketAddr = "192.168.1.10:11500".parse().unwrap();
+        peers.update(
+            announcement(&[
+                "0x0000000000000000000000000000000000000001",
+                "0x0000000000000000000000000000000000000002",
+            ]),
+            addr,
+        );
+
+        let alias = NodeId::from_str("0x0000000000000000000000000000000000000002").unwrap();
+        let peer = peers.find(&alias).unwrap();
+        assert_eq!(peer.addr, addr);
+        assert_ne!(peer.node_id, alias);
+
+        let unknown = NodeId::from_str("0x0000000000000000000000000000000000000003").unwrap();
+        assert!(peers.find(&unknown).is_none());
+    }
+
+    #[test]
+    fn test_peers_expire() {
+        let peers = Peers::new(Duration::from_secs(0));
+        peers.update(
+            announcement(&["0x0000000000000000000000000000000000000001"]),
+            "192.168.1.10:11500".parse().unwrap(),
+        );
+        assert!(peers.list().is_empty());
+    }
+}
@@ -0,0 +1,11 @@
+//! LAN only network. Nodes find each other by announcing their identities
+//! on UDP multicast group and exchange GSB messages over direct TCP
+//! connections, so no relay server is needed. Announcements and connections
+//! are authenticated with signatures of announced identities.
+pub(crate) mod auth;
+pub(crate) mod cli;
+mod discovery;
+mod service;
+pub(crate) mod transport;
+
+pub use service::Net;
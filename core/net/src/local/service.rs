@@ -0,0 +1,125 @@
+use std::cell::RefCell;
+use std::net::SocketAddr;
+use std::rc::Rc;
+
+use anyhow::anyhow;
+use futures::{SinkExt, StreamExt};
+
+use ya_core_model::NodeId;
+use ya_relay_client::crypto::{Crypto, CryptoProvider};
+
+use crate::config::Config;
+use crate::hybrid::crypto::IdentityCryptoProvider;
+use crate::hybrid::service::{bind_routing, NetReceiver, BCAST_SENDER};
+use crate::local::discovery::{start_discovery, Peers};
+use crate::local::transport::LanTransport;
+use crate::stats;
+
+thread_local! {
+    static NETWORK: RefCell<Option<LocalNetwork>> = Default::default();
+}
+
+#[derive(Clone)]
+pub(crate) struct LocalNetwork {
+    pub transport: LanTransport,
+    pub listen_addr: SocketAddr,
+}
+
+impl LocalNetwork {
+    pub fn get() -> anyhow::Result<LocalNetwork> {
+        NETWORK
+            .with(|net| net.borrow().clone())
+            .ok_or_else(|| anyhow!("network not started"))
+    }
+}
+
+pub struct Net;
+
+impl Net {
+    pub async fn gsb<Context>(_: Context, config: Config) -> anyhow::Result<()> {
+        let (default_id, ids) = crate::service::identities().await?;
+
+        log::info!(
+            "Local NET - Using default identity as network id: {:?}",
+            default_id
+        );
+
+        start_network(config, default_id, ids).await
+    }
+}
+
+async fn start_network(config: Config, default_id: NodeId, ids: Vec<NodeId>) -> anyhow::Result<()> {
+    let bind_addr = config
+        .bind_url
+        .socket_addrs(|| None)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("invalid bind url: {}", config.bind_url))?;
+
+    let identities = identities_crypto(default_id, &ids).await?;
+    let crypto = identities[0].1.clone();
+
+    let peers = Peers::new(config.lan_announce_interval * 3);
+    let transport = LanTransport::new(default_id, crypto, peers.clone());
+    let state = bind_routing(default_id, ids.clone(), Some(transport.transport()));
+    transport.set_state(state);
+
+    let listen_addr = transport.listen(bind_addr).await?;
+    log::info!("Started network (local) listening on {}", listen_addr);
+
+    start_discovery(
+        peers,
+        config.lan_multicast_addr,
+        config.lan_announce_interval,
+        default_id,
+        identities,
+        listen_addr.port(),
+    )
+    .await?;
+
+    let (btx, brx) = futures::channel::mpsc::channel(1);
+    BCAST_SENDER.write().await.replace(btx);
+    tokio::task::spawn_local(broadcast_handler(brx, transport.clone()));
+    stats::spawn_pruning(config.session_expiration);
+
+    NETWORK.with(|net| {
+        net.borrow_mut().replace(LocalNetwork {
+            transport,
+            listen_addr,
+        })
+    });
+    Ok(())
+}
+
+/// Nodes prove their identities by signing with them. Default identity
+/// goes first, identities without public key are skipped.
+async fn identities_crypto(
+    default_id: NodeId,
+    ids: &[NodeId],
+) -> anyhow::Result<Vec<(NodeId, Rc<dyn Crypto>)>> {
+    let provider = IdentityCryptoProvider::new(default_id);
+    let mut identities = vec![(default_id, provider.get(default_id).await?)];
+    for id in ids.iter().filter(|id| **id != default_id) {
+        match provider.get(*id).await {
+            Ok(crypto) => identities.push((*id, crypto)),
+            Err(e) => log::debug!("Identity [{}] won't be announced: {}", id, e),
+        }
+    }
+    Ok(identities)
+}
+
+/// There is no relay to spread broadcasts, so they are sent to every
+/// discovered node directly.
+async fn broadcast_handler(mut rx: NetReceiver, transport: LanTransport) {
+    while let Some(payload) = rx.next().await {
+        for peer in transport.peers.list() {
+            let result = match transport.sender(peer.node_id).await {
+                Ok(mut tx) => tx.send(payload.clone()).await.map_err(anyhow::Error::from),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                log::debug!("Unable to broadcast message to [{}]: {}", peer.node_id, e);
+            }
+        }
+    }
+}
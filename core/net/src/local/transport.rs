@@ -0,0 +1,435 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use anyhow::{anyhow, bail};
+use futures::channel::mpsc;
+use futures::stream::LocalBoxStream;
+use futures::{FutureExt, StreamExt};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use ya_core_model::NodeId;
+use ya_relay_client::crypto::Crypto;
+
+use crate::hybrid::service::{inbound_handler, NetSender, NetSinkKind, State, Transport};
+use crate::local::auth::{self, SIGNATURE_SIZE};
+use crate::local::discovery::Peers;
+use crate::stats;
+
+const HANDSHAKE_MAGIC: &[u8; 4] = b"YLAN";
+pub(crate) const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const CHALLENGE_SIZE: usize = 32;
+const MAX_FRAME_SIZE: usize = 128 * 1024 * 1024;
+
+#[derive(Clone)]
+pub(crate) struct Connection<A> {
+    pub tx: NetSender,
+    pub addr: A,
+    pub created: Instant,
+}
+
+/// Direct connections to other nodes, keyed by their default identity.
+pub(crate) struct Connections<A> {
+    inner: Rc<RefCell<HashMap<NodeId, Connection<A>>>>,
+}
+
+impl<A: Clone> Connections<A> {
+    pub fn get(&self, node_id: &NodeId) -> Option<Connection<A>> {
+        self.inner.borrow().get(node_id).cloned()
+    }
+
+    pub fn list(&self) -> Vec<(NodeId, Connection<A>)> {
+        self.inner
+            .borrow()
+            .iter()
+            .map(|(id, conn)| (*id, conn.clone()))
+            .collect()
+    }
+
+    fn insert(&self, node_id: NodeId, conn: Connection<A>) {
+        self.inner.borrow_mut().insert(node_id, conn);
+    }
+
+    fn remove(&self, node_id: &NodeId, tx: &NetSender) {
+        let mut inner = self.inner.borrow_mut();
+        // Connection could have been already replaced by newer one.
+        if inner.get(node_id).map(|conn| conn.tx.same_receiver(tx)) == Some(true) {
+            inner.remove(node_id);
+        }
+    }
+}
+
+impl<A> Clone for Connections<A> {
+    fn clone(&self) -> Self {
+        Connections {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<A> Default for Connections<A> {
+    fn default() -> Self {
+        Connections {
+            inner: Default::default(),
+        }
+    }
+}
+
+/// Sends GSB messages over direct TCP connections to nodes found by discovery.
+#[derive(Clone)]
+pub(crate) struct LanTransport {
+    pub default_id: NodeId,
+    pub connections: Connections<SocketAddr>,
+    pub peers: Peers,
+    crypto: Rc<dyn Crypto>,
+    state: Rc<RefCell<Option<State>>>,
+}
+
+impl LanTransport {
+    pub fn new(default_id: NodeId, crypto: Rc<dyn Crypto>, peers: Peers) -> Self {
+        LanTransport {
+            default_id,
+            connections: Default::default(),
+            peers,
+            crypto,
+            state: Default::default(),
+        }
+    }
+
+    /// Routing state must be set before any connection is established.
+    pub fn set_state(&self, state: State) {
+        self.state.borrow_mut().replace(state);
+    }
+
+    pub fn transport(&self) -> Transport {
+        let this = self.clone();
+        Rc::new(move |remote_id: NodeId, _reliable: bool| {
+            let this = this.clone();
+            // TCP is used for both reliable and unreliable messages.
+            async move { Ok(NetSinkKind::from(this.sender(remote_id).await?)) }.boxed_local()
+        })
+    }
+
+    pub async fn sender(&self, remote_id: NodeId) -> anyhow::Result<NetSender> {
+        if let Some(conn) = self.connections.get(&remote_id) {
+            return Ok(conn.tx);
+        }
+
+        let peer = self
+            .peers
+            .find(&remote_id)
+            .ok_or_else(|| anyhow!("node {} not found in local network", remote_id))?;
+        if let Some(conn) = self.connections.get(&peer.node_id) {
+            return Ok(conn.tx);
+        }
+
+        log::debug!("Connecting to [{}] at {}", peer.node_id, peer.addr);
+
+        let mut stream = TcpStream::connect(peer.addr).await?;
+        let handshake = send_handshake(&mut stream, self.default_id, self.crypto.as_ref());
+        let remote_id = tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake).await??;
+        if remote_id != peer.node_id {
+            bail!(
+                "node at {} is [{}], expected [{}]",
+                peer.addr,
+                remote_id,
+                peer.node_id
+            );
+        }
+
+        let state = self.state()?;
+        Ok(spawn_connection(
+            stream,
+            peer.node_id,
+            peer.addr,
+            &self.connections,
+            state,
+            |frames| frames,
+        ))
+    }
+
+    /// Accepts connections from other nodes.
+    pub async fn listen(&self, addr: SocketAddr) -> anyhow::Result<SocketAddr> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+
+        let this = self.clone();
+        tokio::task::spawn_local(async move {
+            loop {
+                let (stream, addr) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        log::debug!("Failed to accept local network connection: {}", e);
+                        continue;
+                    }
+                };
+
+                let this = this.clone();
+                tokio::task::spawn_local(async move {
+                    if let Err(e) = this.accept(stream, addr).await {
+                        log::debug!("Rejected connection from {}: {}", addr, e);
+                    }
+                });
+            }
+        });
+
+        Ok(local_addr)
+    }
+
+    async fn accept(&self, mut stream: TcpStream, addr: SocketAddr) -> anyhow::Result<()> {
+        let handshake = recv_handshake(&mut stream, self.default_id, self.crypto.as_ref());
+        let remote_id = tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake).await??;
+        log::debug!("Accepted connection from [{}] at {}", remote_id, addr);
+
+        let state = self.state()?;
+        spawn_connection(
+            stream,
+            remote_id,
+            addr,
+            &self.connections,
+            state,
+            |frames| frames,
+        );
+        Ok(())
+    }
+
+    fn state(&self) -> anyhow::Result<State> {
+        self.state
+            .borrow()
+            .clone()
+            .ok_or_else(|| anyhow!("network not started"))
+    }
+}
+
+/// Handshake of connecting node. Both nodes prove their default identity
+/// by signing random challenge chosen by the other side:
+///   -> magic, node id, challenge
+///   <- magic, node id, challenge, signed challenge of connecting node
+///   -> signed challenge of accepting node
+/// Returns identity of accepting node.
+pub(crate) async fn send_handshake<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    node_id: NodeId,
+    crypto: &dyn Crypto,
+) -> anyhow::Result<NodeId> {
+    let challenge: [u8; CHALLENGE_SIZE] = rand::random();
+    send_hello(stream, node_id, &challenge).await?;
+
+    let (remote_id, remote_challenge) = recv_hello(stream).await?;
+    recv_response(stream, remote_id, node_id, &challenge).await?;
+    send_response(stream, node_id, remote_id, &remote_challenge, crypto).await?;
+    Ok(remote_id)
+}
+
+/// Handshake of accepting node. Returns identity of connecting node.
+pub(crate) async fn recv_handshake<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    node_id: NodeId,
+    crypto: &dyn Crypto,
+) -> anyhow::Result<NodeId> {
+    let (remote_id, remote_challenge) = recv_hello(stream).await?;
+
+    let challenge: [u8; CHALLENGE_SIZE] = rand::random();
+    send_hello(stream, node_id, &challenge).await?;
+    send_response(stream, node_id, remote_id, &remote_challenge, crypto).await?;
+    recv_response(stream, remote_id, node_id, &challenge).await?;
+    Ok(remote_id)
+}
+
+async fn send_hello<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    node_id: NodeId,
+    challenge: &[u8; CHALLENGE_SIZE],
+) -> std::io::Result<()> {
+    stream.write_all(HANDSHAKE_MAGIC).await?;
+    stream.write_all(&node_id.into_array()).await?;
+    stream.write_all(challenge).await
+}
+
+async fn recv_hello<S: AsyncRead + Unpin>(
+    stream: &mut S,
+) -> anyhow::Result<(NodeId, [u8; CHALLENGE_SIZE])> {
+    let mut magic = [0u8; 4];
+    let mut node_id = [0u8; 20];
+    let mut challenge = [0u8; CHALLENGE_SIZE];
+    stream.read_exact(&mut magic).await?;
+    if &magic != HANDSHAKE_MAGIC {
+        bail!("invalid handshake");
+    }
+    stream.read_exact(&mut node_id).await?;
+    stream.read_exact(&mut challenge).await?;
+    Ok((NodeId::from(&node_id[..]), challenge))
+}
+
+async fn send_response<S: AsyncWrite + Unpin>(
+    stream: &mut S,
+    node_id: NodeId,
+    remote_id: NodeId,
+    remote_challenge: &[u8],
+    crypto: &dyn Crypto,
+) -> anyhow::Result<()> {
+    let payload = challenge_payload(remote_challenge, node_id, remote_id);
+    let signature = auth::sign(crypto, &payload).await?;
+    stream.write_all(&signature).await?;
+    Ok(())
+}
+
+async fn recv_response<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    remote_id: NodeId,
+    node_id: NodeId,
+    challenge: &[u8],
+) -> anyhow::Result<()> {
+    let mut signature = [0u8; SIGNATURE_SIZE];
+    stream.read_exact(&mut signature).await?;
+
+    let payload = challenge_payload(challenge, remote_id, node_id);
+    let signer = auth::recover(&signature, &payload)?;
+    if signer != remote_id {
+        bail!(
+            "challenge signed by [{}] instead of [{}]",
+            signer,
+            remote_id
+        );
+    }
+    Ok(())
+}
+
+/// Signed challenge is bound to both identities, so it can't be reused
+/// in handshake with another node.
+fn challenge_payload(challenge: &[u8], signer: NodeId, verifier: NodeId) -> Vec<u8> {
+    let mut payload = Vec::with_capacity(HANDSHAKE_MAGIC.len() + CHALLENGE_SIZE + 40);
+    payload.extend_from_slice(HANDSHAKE_MAGIC);
+    payload.extend_from_slice(challenge);
+    payload.extend_from_slice(&signer.into_array());
+    payload.extend_from_slice(&verifier.into_array());
+    payload
+}
+
+/// Starts exchanging GSB messages over established connection. Incoming
+/// messages are routed the same way as in Hybrid Net.
+/// `inbound` can alter the stream of received messages before routing them.
+pub(crate) fn spawn_connection<S, A, F>(
+    stream: S,
+    remote_id: NodeId,
+    addr: A,
+    connections: &Connections<A>,
+    state: State,
+    inbound: F,
+) -> NetSender
+where
+    S: AsyncRead + AsyncWrite + 'static,
+    A: Clone + 'static,
+    F: FnOnce(LocalBoxStream<'static, Vec<u8>>) -> LocalBoxStream<'static, Vec<u8>>,
+{
+    stats::record_connection(remote_id);
+
+    let (reader, mut writer) = tokio::io::split(stream);
+    let (tx, mut rx) = mpsc::channel::<Vec<u8>>(1);
+
+    connections.insert(
+        remote_id,
+        Connection {
+            tx: tx.clone(),
+            addr,
+            created: Instant::now(),
+        },
+    );
+
+    // Messages are already prefixed with their length by GSB codec.
+    tokio::task::spawn_local(async move {
+        while let Some(frame) = rx.next().await {
+            if let Err(e) = writer.write_all(&frame).await {
+                log::debug!("Failed to send message to [{}]: {}", remote_id, e);
+                break;
+            }
+        }
+    });
+
+    let frames = futures::stream::unfold(reader, move |mut reader| async move {
+        match read_frame(&mut reader).await {
+            Ok(frame) => Some((frame, reader)),
+            Err(e) => {
+                log::debug!("Connection with [{}] closed: {}", remote_id, e);
+                None
+            }
+        }
+    });
+    let frames = inbound(frames.boxed_local());
+
+    let connections = connections.clone();
+    let sender = tx.clone();
+    tokio::task::spawn_local(async move {
+        inbound_handler(frames, remote_id, true, state).await;
+        connections.remove(&remote_id, &sender);
+    });
+
+    tx
+}
+
+/// Reads single GSB message together with its length prefix.
+async fn read_frame<R: AsyncRead + Unpin>(reader: &mut R) -> anyhow::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len).await?;
+
+    let size = u32::from_be_bytes(len) as usize;
+    if size > MAX_FRAME_SIZE {
+        bail!("message too big: {} B", size);
+    }
+
+    let mut frame = Vec::with_capacity(4 + size);
+    frame.extend_from_slice(&len);
+    frame.resize(4 + size, 0);
+    reader.read_exact(&mut frame[4..]).await?;
+    Ok(frame)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::local::auth::KeyCrypto;
+
+    #[actix_rt::test]
+    async fn test_handshake_authenticates_both_nodes() {
+        let (mut a, mut b) = tokio::io::duplex(1024);
+        let (crypto_a, crypto_b) = (KeyCrypto::generate(), KeyCrypto::generate());
+        let (id_a, id_b) = (crypto_a.node_id(), crypto_b.node_id());
+
+        let (remote_b, remote_a) = futures::join!(
+            send_handshake(&mut a, id_a, &crypto_a),
+            recv_handshake(&mut b, id_b, &crypto_b),
+        );
+        assert_eq!(remote_b.unwrap(), id_b);
+        assert_eq!(remote_a.unwrap(), id_a);
+    }
+
+    #[actix_rt::test]
+    async fn test_handshake_rejects_impersonation() {
+        let (mut a, mut b) = tokio::io::duplex(1024);
+        let (victim, attacker, crypto_b) = (
+            KeyCrypto::generate(),
+            KeyCrypto::generate(),
+            KeyCrypto::generate(),
+        );
+
+        // Attacker claims identity of the victim without owning its key.
+        let (_, accepted) = futures::join!(
+            send_handshake(&mut a, victim.node_id(), &attacker),
+            recv_handshake(&mut b, crypto_b.node_id(), &crypto_b),
+        );
+        assert!(accepted.is_err());
+
+        // Accepting node can't impersonate other node either. Connecting
+        // node closes connection, so accepting one stops waiting for it.
+        let (mut a, mut b) = tokio::io::duplex(1024);
+        let connect = async move { send_handshake(&mut a, victim.node_id(), &victim).await };
+        let (connected, _) = futures::join!(
+            connect,
+            recv_handshake(&mut b, crypto_b.node_id(), &attacker),
+        );
+        assert!(connected.is_err());
+    }
+}
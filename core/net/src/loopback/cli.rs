@@ -0,0 +1,48 @@
+use std::time::Instant;
+
+use ya_core_model::net::local as model;
+use ya_core_model::net::{GsbRemotePing, DIAGNOSTIC};
+use ya_service_bus::typed as bus;
+use ya_service_bus::typed::ServiceBinder;
+
+use crate::loopback::service::Net;
+use crate::stats;
+
+pub(crate) fn bind_service() {
+    ServiceBinder::new(DIAGNOSTIC, &(), ())
+        .bind(move |_, _caller: String, _msg: GsbRemotePing| async move { Ok(GsbRemotePing {}) });
+
+    let _ = bus::bind(model::BUS_ID, move |_: model::Sessions| async move {
+        let now = Instant::now();
+        let sessions = Net::connections()
+            .into_iter()
+            .map(|(node_id, conn)| model::SessionResponse {
+                node_id: Some(node_id),
+                id: node_id.to_string(),
+                session_type: "loopback".to_string(),
+                remote_address: ([127, 0, 0, 1], 0).into(),
+                seen: Default::default(),
+                duration: now - conn.created,
+                ping: Default::default(),
+                stats: stats::peer_stats(&node_id),
+            })
+            .collect();
+        Ok::<_, model::StatusError>(sessions)
+    });
+
+    let error =
+        model::StatusError::RuntimeException("Not implemented for loopback network".to_string());
+
+    let err = error.clone();
+    let _ = bus::bind(model::BUS_ID, move |_: model::Status| {
+        futures::future::err(err.clone())
+    });
+    let err = error.clone();
+    let _ = bus::bind(model::BUS_ID, move |_: model::Sockets| {
+        futures::future::err(err.clone())
+    });
+    let err = error;
+    let _ = bus::bind(model::BUS_ID, move |_: model::GsbPing| {
+        futures::future::err(err.clone())
+    });
+}
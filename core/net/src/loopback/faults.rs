@@ -0,0 +1,178 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use futures::channel::mpsc;
+use futures::stream::LocalBoxStream;
+use futures::{FutureExt, StreamExt};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use ya_core_model::NodeId;
+
+use crate::loopback::registry::Registry;
+
+/// Network conditions simulated for messages received by the node.
+pub(crate) struct Faults {
+    node_id: NodeId,
+    registry: Registry,
+    latency: Duration,
+    loss: f64,
+    rng: RefCell<StdRng>,
+}
+
+impl Faults {
+    pub fn new(
+        node_id: NodeId,
+        registry: Registry,
+        latency: Duration,
+        loss: f64,
+        seed: u64,
+    ) -> anyhow::Result<Self> {
+        if !(0.0..=1.0).contains(&loss) {
+            anyhow::bail!("message loss probability must be in range [0, 1]: {}", loss);
+        }
+
+        Ok(Faults {
+            node_id,
+            registry,
+            latency,
+            loss,
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        })
+    }
+
+    /// Returns time, at which message from `remote_id` received now should
+    /// be delivered, or None, if it should be dropped. Each message is delayed
+    /// from its own arrival, so latencies of consecutive messages don't add up.
+    pub fn schedule(&self, remote_id: NodeId) -> Option<Instant> {
+        if !self.reachable(&remote_id) {
+            log::trace!("Dropping message from [{}]: partitioned", remote_id);
+            return None;
+        }
+        if self.loss > 0.0 && self.rng.borrow_mut().gen::<f64>() < self.loss {
+            log::trace!("Dropping message from [{}]: lost", remote_id);
+            return None;
+        }
+        Some(Instant::now() + self.latency)
+    }
+
+    /// Simulates network conditions for messages received from `remote_id`.
+    /// Messages are read as soon as they arrive and delivered at scheduled
+    /// time in the order they were received.
+    pub fn apply(
+        self: Rc<Self>,
+        frames: LocalBoxStream<'static, Vec<u8>>,
+        remote_id: NodeId,
+    ) -> LocalBoxStream<'static, Vec<u8>> {
+        let (tx, rx) = mpsc::unbounded();
+        tokio::task::spawn_local(frames.for_each(move |frame| {
+            if let Some(deadline) = self.schedule(remote_id) {
+                let _ = tx.unbounded_send((deadline, frame));
+            }
+            futures::future::ready(())
+        }));
+
+        rx.then(|(deadline, frame)| {
+            tokio::time::sleep_until(tokio::time::Instant::from_std(deadline)).map(|_| frame)
+        })
+        .boxed_local()
+    }
+
+    pub fn reachable(&self, remote_id: &NodeId) -> bool {
+        let partition = |id| self.registry.get(id).map(|node| node.partition);
+        match (partition(&self.node_id), partition(remote_id)) {
+            (Some(ours), Some(theirs)) => ours == theirs,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::loopback::registry::{set_partition, Registration};
+    use std::str::FromStr;
+
+    fn deliver_now(faults: &Faults, remote_id: NodeId) -> bool {
+        faults.schedule(remote_id).is_some()
+    }
+
+    fn register(registry: &Registry, id: &str) -> NodeId {
+        let node_id = NodeId::from_str(id).unwrap();
+        registry
+            .register(&Registration {
+                node_id,
+                ids: vec![node_id],
+                partition: None,
+            })
+            .unwrap();
+        node_id
+    }
+
+    #[test]
+    fn test_partitioned_nodes_unreachable() {
+        let dir = std::env::temp_dir().join("ya-net-loopback-faults");
+        let _ = std::fs::remove_dir_all(&dir);
+        let registry = Registry::new(dir.clone());
+        let ours = register(&registry, "0x0000000000000000000000000000000000000001");
+        let theirs = register(&registry, "0x0000000000000000000000000000000000000002");
+
+        let faults = Faults::new(ours, registry, Duration::default(), 0.0, 0).unwrap();
+        assert!(deliver_now(&faults, theirs));
+
+        set_partition(&dir, theirs, Some("isolated".to_string())).unwrap();
+        assert!(!deliver_now(&faults, theirs));
+
+        set_partition(&dir, ours, Some("isolated".to_string())).unwrap();
+        assert!(deliver_now(&faults, theirs));
+    }
+
+    #[test]
+    fn test_message_loss_is_repeatable() {
+        let dir = std::env::temp_dir().join("ya-net-loopback-loss");
+        let _ = std::fs::remove_dir_all(&dir);
+        let registry = Registry::new(dir);
+        let ours = register(&registry, "0x0000000000000000000000000000000000000001");
+        let theirs = register(&registry, "0x0000000000000000000000000000000000000002");
+
+        let mut results = Vec::new();
+        for _ in 0..2 {
+            let faults = Faults::new(ours, registry.clone(), Duration::default(), 0.5, 7).unwrap();
+            let mut delivered = Vec::new();
+            for _ in 0..32 {
+                delivered.push(deliver_now(&faults, theirs));
+            }
+            results.push(delivered);
+        }
+
+        assert_eq!(results[0], results[1]);
+        assert!(results[0].contains(&true));
+        assert!(results[0].contains(&false));
+        assert!(Faults::new(ours, registry, Duration::default(), 1.5, 0).is_err());
+    }
+
+    #[actix_rt::test]
+    async fn test_latency_applied_to_each_message_independently() {
+        let dir = std::env::temp_dir().join("ya-net-loopback-latency");
+        let _ = std::fs::remove_dir_all(&dir);
+        let registry = Registry::new(dir);
+        let ours = register(&registry, "0x0000000000000000000000000000000000000001");
+        let theirs = register(&registry, "0x0000000000000000000000000000000000000002");
+
+        let latency = Duration::from_millis(200);
+        let faults = Rc::new(Faults::new(ours, registry, latency, 0.0, 0).unwrap());
+        let frames: Vec<Vec<u8>> = (0..10u8).map(|i| vec![i]).collect();
+
+        let start = Instant::now();
+        let delivered: Vec<Vec<u8>> = faults
+            .apply(futures::stream::iter(frames.clone()).boxed_local(), theirs)
+            .collect()
+            .await;
+        let elapsed = start.elapsed();
+
+        assert_eq!(delivered, frames);
+        assert!(elapsed >= latency);
+        assert!(elapsed < latency * 3, "latency added up: {:?}", elapsed);
+    }
+}
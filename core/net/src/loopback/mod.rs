@@ -0,0 +1,16 @@
+//! Network connecting nodes running on the same host, without relay server.
+//! Every node listens on Unix socket in shared directory and registers its
+//! identities there. Latency, message loss and partitions can be simulated
+//! to test multi-node scenarios.
+//!
+//! Nodes can run in separate processes or in a single one. GSB router is
+//! global for the process, so nodes sharing it are started with
+//! `Net::start_node` and bind their services under distinct prefixes.
+pub(crate) mod cli;
+mod faults;
+mod registry;
+mod service;
+mod transport;
+
+pub use registry::set_partition;
+pub use service::Net;
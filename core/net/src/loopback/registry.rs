@@ -0,0 +1,135 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use ya_core_model::NodeId;
+
+const REGISTRATION_EXT: &str = "json";
+
+/// Written by each node next to its socket.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Registration {
+    pub node_id: NodeId,
+    pub ids: Vec<NodeId>,
+    pub partition: Option<String>,
+}
+
+/// Nodes connected to loopback network, stored in shared directory.
+/// Registrations are always read from disk, so changes made by other
+/// processes take effect immediately.
+#[derive(Clone)]
+pub(crate) struct Registry {
+    dir: PathBuf,
+}
+
+impl Registry {
+    pub fn new(dir: PathBuf) -> Self {
+        Registry { dir }
+    }
+
+    pub fn init(&self) -> std::io::Result<()> {
+        fs::create_dir_all(&self.dir)
+    }
+
+    pub fn socket_path(&self, node_id: &NodeId) -> PathBuf {
+        self.dir.join(format!("{}.sock", node_id))
+    }
+
+    fn registration_path(&self, node_id: &NodeId) -> PathBuf {
+        self.dir.join(format!("{}.{}", node_id, REGISTRATION_EXT))
+    }
+
+    pub fn register(&self, registration: &Registration) -> anyhow::Result<()> {
+        self.init()?;
+
+        // Write and rename, so other nodes never read partial file.
+        let path = self.registration_path(&registration.node_id);
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, serde_json::to_vec(registration)?)?;
+        fs::rename(tmp_path, path)?;
+        Ok(())
+    }
+
+    pub fn unregister(&self, node_id: &NodeId) {
+        let _ = fs::remove_file(self.registration_path(node_id));
+        let _ = fs::remove_file(self.socket_path(node_id));
+    }
+
+    pub fn get(&self, node_id: &NodeId) -> Option<Registration> {
+        let content = fs::read(self.registration_path(node_id)).ok()?;
+        serde_json::from_slice(&content).ok()
+    }
+
+    pub fn list(&self) -> Vec<Registration> {
+        let entries = match fs::read_dir(&self.dir) {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+
+        entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().map(|ext| ext == REGISTRATION_EXT) == Some(true))
+            .filter_map(|path| fs::read(path).ok())
+            .filter_map(|content| serde_json::from_slice(&content).ok())
+            .collect()
+    }
+
+    /// Finds node owning given identity.
+    pub fn find(&self, id: &NodeId) -> Option<Registration> {
+        self.list().into_iter().find(|node| node.ids.contains(id))
+    }
+}
+
+/// Moves node to another partition. Nodes in different partitions can't
+/// exchange messages. Nodes without partition belong to the default one.
+pub fn set_partition(dir: &Path, node_id: NodeId, partition: Option<String>) -> anyhow::Result<()> {
+    let registry = Registry::new(dir.to_path_buf());
+    let mut registration = registry
+        .get(&node_id)
+        .ok_or_else(|| anyhow::anyhow!("node {} not registered in {}", node_id, dir.display()))?;
+
+    registration.partition = partition;
+    registry.register(&registration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ya-net-loopback-{}", name));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_find_registered_node() {
+        let dir = test_dir("find");
+        let registry = Registry::new(dir.clone());
+        let node_id = NodeId::from_str("0x0000000000000000000000000000000000000001").unwrap();
+        let alias = NodeId::from_str("0x0000000000000000000000000000000000000002").unwrap();
+
+        registry
+            .register(&Registration {
+                node_id,
+                ids: vec![node_id, alias],
+                partition: None,
+            })
+            .unwrap();
+        assert_eq!(registry.find(&alias).unwrap().node_id, node_id);
+
+        set_partition(&dir, node_id, Some("a".to_string())).unwrap();
+        assert_eq!(
+            registry.get(&node_id).unwrap().partition,
+            Some("a".to_string())
+        );
+
+        registry.unregister(&node_id);
+        assert!(registry.find(&alias).is_none());
+        assert!(registry.list().is_empty());
+    }
+}
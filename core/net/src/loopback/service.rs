@@ -0,0 +1,202 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::str::FromStr;
+
+use ethsign::SecretKey;
+use futures::{SinkExt, StreamExt};
+
+use ya_core_model::NodeId;
+use ya_relay_client::crypto::{Crypto, CryptoProvider};
+use ya_sb_proto::codec::GsbMessage;
+
+use crate::config::{Config, NetType};
+use crate::hybrid::codec;
+use crate::hybrid::crypto::IdentityCryptoProvider;
+use crate::hybrid::service::{bind_router, NetReceiver, Router, State, BCAST_SENDER};
+use crate::local::auth::KeyCrypto;
+use crate::local::transport::Connection;
+use crate::loopback::faults::Faults;
+use crate::loopback::registry::{Registration, Registry};
+use crate::loopback::transport::LoopbackTransport;
+use crate::stats;
+
+const DEFAULT_LOOPBACK_DIR: &str = "yagna-net-loopback";
+
+/// Node running in this process.
+#[derive(Clone)]
+struct Node {
+    ids: Vec<NodeId>,
+    transport: LoopbackTransport,
+}
+
+thread_local! {
+    static ROUTER: Router = Default::default();
+    static NODES: RefCell<HashMap<NodeId, Node>> = Default::default();
+}
+
+pub struct Net;
+
+impl Net {
+    pub async fn gsb<Context>(_: Context, config: Config) -> anyhow::Result<()> {
+        let (default_id, ids) = crate::service::identities().await?;
+
+        log::info!(
+            "Loopback NET - Using default identity as network id: {:?}",
+            default_id
+        );
+
+        let crypto = IdentityCryptoProvider::new(default_id)
+            .get(default_id)
+            .await?;
+        start_network(config, default_id, ids, crypto, None).await
+    }
+
+    /// Starts another node in this process, without identity service. First
+    /// key is node's default identity. Calls to the node are routed to services
+    /// bound under `prefix` instead of `/public` and broadcasts are delivered
+    /// only to handlers bound under `prefix`, so every node in the process
+    /// must use distinct one.
+    pub async fn start_node(
+        config: Config,
+        keys: Vec<SecretKey>,
+        prefix: &str,
+    ) -> anyhow::Result<NodeId> {
+        let mut keys = keys.into_iter().map(KeyCrypto::new);
+        let crypto = keys
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("node requires at least one identity"))?;
+        let default_id = crypto.node_id();
+        let ids = std::iter::once(default_id)
+            .chain(keys.map(|key| key.node_id()))
+            .collect();
+
+        *crate::service::NET_TYPE.write().unwrap() = NetType::Loopback;
+
+        let crypto: Rc<dyn Crypto> = Rc::new(crypto);
+        start_network(config, default_id, ids, crypto, Some(prefix.to_string())).await?;
+        Ok(default_id)
+    }
+
+    /// Stops routing messages of node started in this process and removes it
+    /// from the registry, so other nodes won't connect to it anymore.
+    pub fn stop_node(default_id: &NodeId) {
+        if let Some(node) = NODES.with(|nodes| nodes.borrow_mut().remove(default_id)) {
+            node.transport.registry.unregister(default_id);
+        }
+        ROUTER.with(|router| router.remove(default_id));
+    }
+
+    pub(crate) fn connections() -> Vec<(NodeId, Connection<PathBuf>)> {
+        NODES.with(|nodes| {
+            nodes
+                .borrow()
+                .values()
+                .flat_map(|node| node.transport.connections.list())
+                .collect()
+        })
+    }
+
+    pub async fn shutdown() -> anyhow::Result<()> {
+        let ids: Vec<NodeId> = NODES.with(|nodes| nodes.borrow().keys().cloned().collect());
+        ids.iter().for_each(Self::stop_node);
+        Ok(())
+    }
+}
+
+async fn start_network(
+    config: Config,
+    default_id: NodeId,
+    ids: Vec<NodeId>,
+    crypto: Rc<dyn Crypto>,
+    prefix: Option<String>,
+) -> anyhow::Result<()> {
+    let dir = config
+        .loopback_dir
+        .clone()
+        .unwrap_or_else(|| std::env::temp_dir().join(DEFAULT_LOOPBACK_DIR));
+    let registry = Registry::new(dir);
+    let faults = Faults::new(
+        default_id,
+        registry.clone(),
+        config.loopback_latency,
+        config.loopback_loss,
+        config.loopback_seed,
+    )?;
+
+    let transport = LoopbackTransport::new(default_id, crypto, registry.clone(), faults);
+    let state = State::new(default_id, ids.clone(), prefix, Some(transport.transport()));
+    transport.set_state(state.clone());
+    ROUTER.with(|router| {
+        router.add(state);
+        bind_router(router.clone());
+    });
+
+    registry.init()?;
+    let path = transport.listen()?;
+    // Other nodes can find us only after we are able to accept connections.
+    registry.register(&Registration {
+        node_id: default_id,
+        ids: ids.clone(),
+        partition: config.loopback_partition.clone(),
+    })?;
+    log::info!("Started network (loopback) listening on {}", path.display());
+
+    // Broadcasts of all nodes in the process go through single channel.
+    let (btx, brx) = futures::channel::mpsc::channel(1);
+    BCAST_SENDER.write().await.replace(btx);
+    tokio::task::spawn_local(broadcast_handler(brx));
+    stats::spawn_pruning(config.session_expiration);
+
+    NODES.with(|nodes| {
+        nodes
+            .borrow_mut()
+            .insert(default_id, Node { ids, transport })
+    });
+    Ok(())
+}
+
+/// Broadcasts are sent by the node owning caller's identity to every other
+/// registered node directly.
+async fn broadcast_handler(mut rx: NetReceiver) {
+    while let Some(payload) = rx.next().await {
+        let node = match broadcast_caller(&payload).and_then(|caller| {
+            NODES.with(|nodes| {
+                nodes
+                    .borrow()
+                    .values()
+                    .find(|node| node.ids.contains(&caller))
+                    .cloned()
+            })
+        }) {
+            Some(node) => node,
+            None => {
+                log::debug!("Unable to broadcast message: unknown caller");
+                continue;
+            }
+        };
+
+        let transport = node.transport;
+        for remote in transport.registry.list() {
+            if remote.node_id == transport.default_id {
+                continue;
+            }
+
+            let result = match transport.sender(remote.node_id).await {
+                Ok(mut tx) => tx.send(payload.clone()).await.map_err(anyhow::Error::from),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                log::debug!("Unable to broadcast message to [{}]: {}", remote.node_id, e);
+            }
+        }
+    }
+}
+
+fn broadcast_caller(payload: &[u8]) -> Option<NodeId> {
+    match codec::decode_message(payload) {
+        Ok(Some(GsbMessage::BroadcastRequest(request))) => NodeId::from_str(&request.caller).ok(),
+        _ => None,
+    }
+}
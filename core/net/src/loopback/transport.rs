@@ -0,0 +1,149 @@
+use std::cell::RefCell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
+use anyhow::{anyhow, bail};
+use futures::FutureExt;
+use tokio::net::{UnixListener, UnixStream};
+
+use ya_core_model::NodeId;
+use ya_relay_client::crypto::Crypto;
+
+use crate::hybrid::service::{NetSender, NetSinkKind, State, Transport};
+use crate::local::transport::{
+    recv_handshake, send_handshake, spawn_connection, Connections, HANDSHAKE_TIMEOUT,
+};
+use crate::loopback::faults::Faults;
+use crate::loopback::registry::Registry;
+
+/// Sends GSB messages over Unix sockets to nodes found in the registry.
+#[derive(Clone)]
+pub(crate) struct LoopbackTransport {
+    pub default_id: NodeId,
+    pub registry: Registry,
+    pub connections: Connections<PathBuf>,
+    crypto: Rc<dyn Crypto>,
+    faults: Rc<Faults>,
+    state: Rc<RefCell<Option<State>>>,
+}
+
+impl LoopbackTransport {
+    pub fn new(
+        default_id: NodeId,
+        crypto: Rc<dyn Crypto>,
+        registry: Registry,
+        faults: Faults,
+    ) -> Self {
+        LoopbackTransport {
+            default_id,
+            registry,
+            connections: Default::default(),
+            crypto,
+            faults: Rc::new(faults),
+            state: Default::default(),
+        }
+    }
+
+    /// Routing state must be set before any connection is established.
+    pub fn set_state(&self, state: State) {
+        self.state.borrow_mut().replace(state);
+    }
+
+    pub fn transport(&self) -> Transport {
+        let this = self.clone();
+        Rc::new(move |remote_id: NodeId, _reliable: bool| {
+            let this = this.clone();
+            async move { Ok(NetSinkKind::from(this.sender(remote_id).await?)) }.boxed_local()
+        })
+    }
+
+    pub async fn sender(&self, remote_id: NodeId) -> anyhow::Result<NetSender> {
+        if let Some(conn) = self.connections.get(&remote_id) {
+            return Ok(conn.tx);
+        }
+
+        let node = self
+            .registry
+            .find(&remote_id)
+            .ok_or_else(|| anyhow!("node {} not found in loopback network", remote_id))?;
+        if let Some(conn) = self.connections.get(&node.node_id) {
+            return Ok(conn.tx);
+        }
+
+        let path = self.registry.socket_path(&node.node_id);
+        log::debug!("Connecting to [{}] at {}", node.node_id, path.display());
+
+        let mut stream = UnixStream::connect(&path).await?;
+        let handshake = send_handshake(&mut stream, self.default_id, self.crypto.as_ref());
+        let remote_id = tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake).await??;
+        if remote_id != node.node_id {
+            bail!("node at {} is [{}]", path.display(), remote_id);
+        }
+        self.spawn(stream, node.node_id, path)
+    }
+
+    /// Accepts connections from other nodes on our socket in the registry.
+    pub fn listen(&self) -> anyhow::Result<PathBuf> {
+        let path = self.registry.socket_path(&self.default_id);
+        // Socket could remain after previous run.
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)?;
+
+        let this = self.clone();
+        tokio::task::spawn_local(async move {
+            loop {
+                let mut stream = match listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        log::debug!("Failed to accept loopback network connection: {}", e);
+                        continue;
+                    }
+                };
+
+                let this = this.clone();
+                tokio::task::spawn_local(async move {
+                    let result = async {
+                        let handshake =
+                            recv_handshake(&mut stream, this.default_id, this.crypto.as_ref());
+                        let remote_id =
+                            tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake).await??;
+                        log::debug!("Accepted connection from [{}]", remote_id);
+
+                        let path = this.registry.socket_path(&remote_id);
+                        this.spawn(stream, remote_id, path)
+                    };
+                    if let Err(e) = result.await {
+                        log::debug!("Rejected loopback network connection: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(path)
+    }
+
+    fn spawn(
+        &self,
+        stream: UnixStream,
+        remote_id: NodeId,
+        path: PathBuf,
+    ) -> anyhow::Result<NetSender> {
+        let state = self
+            .state
+            .borrow()
+            .clone()
+            .ok_or_else(|| anyhow!("network not started"))?;
+
+        // Faults are simulated on receiving side, so they apply to messages
+        // sent over connections established by both nodes.
+        let faults = self.faults.clone();
+        Ok(spawn_connection(
+            stream,
+            remote_id,
+            path,
+            &self.connections,
+            state,
+            move |frames| faults.apply(frames, remote_id),
+        ))
+    }
+}
@@ -0,0 +1,20 @@
+use actix_web::{HttpResponse, Responder};
+
+use ya_client_model::ErrorMessage;
+use ya_core_model::net::local as model;
+use ya_service_bus::{typed as bus, RpcEndpoint};
+
+pub const NET_SESSIONS_API_PATH: &str = "net-api/v1/sessions";
+
+pub fn web_scope() -> actix_web::Scope {
+    actix_web::Scope::new(NET_SESSIONS_API_PATH).route("", actix_web::web::get().to(get_sessions))
+}
+
+/// Lists network sessions together with traffic statistics of each peer.
+async fn get_sessions() -> impl Responder {
+    match bus::service(model::BUS_ID).send(model::Sessions {}).await {
+        Ok(Ok(sessions)) => HttpResponse::Ok().json(sessions),
+        Ok(Err(e)) => HttpResponse::InternalServerError().json(ErrorMessage::new(e.to_string())),
+        Err(e) => HttpResponse::InternalServerError().json(ErrorMessage::new(e.to_string())),
+    }
+}
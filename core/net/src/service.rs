@@ -2,7 +2,7 @@ use std::sync::{Arc, RwLock};
 
 use ya_core_model::net::local::{BindBroadcastError, BroadcastMessage, SendBroadcastMessage};
 use ya_core_model::{identity, NodeId};
-use ya_service_api_interfaces::Service;
+use ya_service_api_interfaces::{Provider, Service};
 use ya_service_bus::{Error, RpcEndpoint, RpcMessage};
 
 use crate::config::{Config, NetType};
@@ -28,7 +28,7 @@ pub(crate) async fn identities() -> anyhow::Result<(NodeId, Vec<NodeId>)> {
     Ok((default_id, ids))
 }
 
-/// Both Hybrid and Central Net implementation. Only one of them is initialized.
+/// Central, Hybrid, Local and Loopback Net implementation. Only one of them is initialized.
 /// TODO: Remove after transitioning to Hybrid Net.
 pub struct Net;
 
@@ -57,9 +57,24 @@ impl Net {
                 crate::hybrid::cli::bind_service();
                 crate::hybrid::Net::gsb(ctx, config).await
             }
+            NetType::Local => {
+                crate::local::cli::bind_service();
+                crate::local::Net::gsb(ctx, config).await
+            }
+            #[cfg(unix)]
+            NetType::Loopback => {
+                crate::loopback::cli::bind_service();
+                crate::loopback::Net::gsb(ctx, config).await
+            }
+            #[cfg(not(unix))]
+            NetType::Loopback => anyhow::bail!("Loopback Net is supported only on unix"),
         }
     }
 
+    pub fn rest<C: Provider<Self, ()>>(_ctx: &C) -> actix_web::Scope {
+        crate::rest::web_scope()
+    }
+
     pub async fn shutdown() -> anyhow::Result<()> {
         let config = Config::from_env()?;
 
@@ -70,6 +85,11 @@ impl Net {
         match &config.net_type {
             NetType::Central => Ok(()),
             NetType::Hybrid => crate::hybrid::Net::shutdown().await,
+            NetType::Local => Ok(()),
+            #[cfg(unix)]
+            NetType::Loopback => crate::loopback::Net::shutdown().await,
+            #[cfg(not(unix))]
+            NetType::Loopback => Ok(()),
         }
     }
 }
@@ -93,7 +113,10 @@ where
     let net_type = { *NET_TYPE.read().unwrap() };
     match net_type {
         NetType::Central => crate::central::broadcast(caller, message).await,
-        NetType::Hybrid => crate::hybrid::broadcast(caller, message).await,
+        // Local and Loopback Net share broadcast implementation with Hybrid Net.
+        NetType::Hybrid | NetType::Local | NetType::Loopback => {
+            crate::hybrid::broadcast(caller, message).await
+        }
     }
 }
 
@@ -118,7 +141,7 @@ where
         NetType::Central => {
             crate::central::bind_broadcast_with_caller(broadcast_address, handler).await
         }
-        NetType::Hybrid => {
+        NetType::Hybrid | NetType::Local | NetType::Loopback => {
             crate::hybrid::bind_broadcast_with_caller(broadcast_address, handler).await
         }
     }
@@ -0,0 +1,178 @@
+use chrono::{DateTime, TimeZone, Utc};
+use metrics::counter;
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use ya_core_model::net::local::{SessionStats, StatsSample};
+use ya_core_model::NodeId;
+
+/// Length of single history window in seconds.
+const HISTORY_WINDOW: i64 = 60;
+/// Number of windows kept in history.
+const HISTORY_LEN: usize = 60;
+
+lazy_static::lazy_static! {
+    static ref STATS: Mutex<HashMap<NodeId, PeerStats>> = Default::default();
+}
+
+static PRUNING: AtomicBool = AtomicBool::new(false);
+
+struct PeerStats {
+    stats: SessionStats,
+    connections: u64,
+    history: VecDeque<StatsSample>,
+    last_active: Instant,
+}
+
+impl Default for PeerStats {
+    fn default() -> Self {
+        PeerStats {
+            stats: Default::default(),
+            connections: 0,
+            history: Default::default(),
+            last_active: Instant::now(),
+        }
+    }
+}
+
+impl PeerStats {
+    fn sample(&mut self, now: DateTime<Utc>) -> &mut StatsSample {
+        let timestamp = Utc.timestamp(now.timestamp() - now.timestamp() % HISTORY_WINDOW, 0);
+        if self.history.back().map(|s| s.timestamp) != Some(timestamp) {
+            if self.history.len() >= HISTORY_LEN {
+                self.history.pop_front();
+            }
+            self.history.push_back(StatsSample {
+                timestamp,
+                tx_bytes: 0,
+                rx_bytes: 0,
+            });
+        }
+        self.history.back_mut().unwrap()
+    }
+}
+
+fn with_peer<T>(node_id: NodeId, f: impl FnOnce(&mut PeerStats) -> T) -> T {
+    let mut stats = STATS.lock().unwrap();
+    let peer = stats.entry(node_id).or_default();
+    peer.last_active = Instant::now();
+    f(peer)
+}
+
+pub(crate) fn record_tx(node_id: NodeId, bytes: usize) {
+    with_peer(node_id, |peer| {
+        peer.stats.tx_bytes += bytes as u64;
+        peer.stats.tx_packets += 1;
+        peer.sample(Utc::now()).tx_bytes += bytes as u64;
+    });
+    counter!("net.peer.tx-bytes", bytes as u64);
+}
+
+pub(crate) fn record_rx(node_id: NodeId, bytes: usize) {
+    with_peer(node_id, |peer| {
+        peer.stats.rx_bytes += bytes as u64;
+        peer.stats.rx_packets += 1;
+        peer.sample(Utc::now()).rx_bytes += bytes as u64;
+    });
+    counter!("net.peer.rx-bytes", bytes as u64);
+}
+
+/// Every connection to the node after the first one is counted as reconnection,
+/// unless statistics of the node expired in the meantime.
+pub(crate) fn record_connection(node_id: NodeId) {
+    let reconnected = with_peer(node_id, |peer| {
+        peer.connections += 1;
+        peer.stats.reconnections = peer.connections - 1;
+        peer.connections > 1
+    });
+    if reconnected {
+        counter!("net.peer.reconnections", 1);
+    }
+}
+
+pub(crate) fn record_ping(node_id: NodeId, rtt: Duration, p2p: bool) {
+    with_peer(node_id, |peer| {
+        peer.stats.rtt = Some(rtt);
+        peer.stats.p2p = Some(p2p);
+    });
+}
+
+pub(crate) fn set_p2p(node_id: NodeId, p2p: bool) {
+    with_peer(node_id, |peer| peer.stats.p2p = Some(p2p));
+}
+
+/// Statistics of traffic exchanged with given node together with its history.
+pub(crate) fn peer_stats(node_id: &NodeId) -> Option<SessionStats> {
+    let stats = STATS.lock().unwrap();
+    stats.get(node_id).map(|peer| SessionStats {
+        history: peer.history.iter().cloned().collect(),
+        ..peer.stats.clone()
+    })
+}
+
+/// Periodically evicts statistics of nodes, which didn't exchange any messages
+/// for longer than `expiration`, like when their session was closed or expired.
+/// Started once for all networks in the process.
+pub(crate) fn spawn_pruning(expiration: Duration) {
+    if PRUNING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    tokio::task::spawn_local(async move {
+        let mut interval = tokio::time::interval(expiration);
+        loop {
+            interval.tick().await;
+            prune(Instant::now(), expiration);
+        }
+    });
+}
+
+fn prune(now: Instant, expiration: Duration) {
+    let mut stats = STATS.lock().unwrap();
+    stats.retain(|_, peer| now.saturating_duration_since(peer.last_active) <= expiration);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_history_windows() {
+        let mut peer = PeerStats::default();
+        let start = Utc.timestamp(HISTORY_WINDOW * 1000, 0);
+
+        peer.sample(start).tx_bytes += 10;
+        peer.sample(start + Duration::seconds(HISTORY_WINDOW - 1))
+            .rx_bytes += 5;
+        peer.sample(start + Duration::seconds(HISTORY_WINDOW))
+            .tx_bytes += 7;
+        assert_eq!(peer.history.len(), 2);
+        assert_eq!(peer.history[0].tx_bytes, 10);
+        assert_eq!(peer.history[0].rx_bytes, 5);
+        assert_eq!(peer.history[1].tx_bytes, 7);
+
+        for i in 0..(HISTORY_LEN as i64 * 2) {
+            peer.sample(start + Duration::seconds(HISTORY_WINDOW * i));
+        }
+        assert_eq!(peer.history.len(), HISTORY_LEN);
+    }
+
+    #[test]
+    fn test_prune_idle_peers() {
+        let expiration = std::time::Duration::from_secs(15);
+        let (active, idle) = (NodeId::from(&[1u8; 20][..]), NodeId::from(&[2u8; 20][..]));
+        record_connection(active);
+        record_connection(idle);
+        STATS.lock().unwrap().get_mut(&idle).unwrap().last_active -= expiration * 2;
+
+        prune(Instant::now(), expiration);
+        assert!(peer_stats(&active).is_some());
+        assert!(peer_stats(&idle).is_none());
+
+        // Statistics start from scratch after eviction.
+        record_connection(idle);
+        assert_eq!(peer_stats(&idle).unwrap().reconnections, 0);
+    }
+}
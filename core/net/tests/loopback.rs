@@ -0,0 +1,181 @@
+//! Several nodes connected with loopback network in a single process,
+//! exchanging market-like broadcasts and activity-like calls.
+#![cfg(unix)]
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ethsign::SecretKey;
+use serde::{Deserialize, Serialize};
+
+use ya_core_model::net::local::BroadcastMessage;
+use ya_core_model::NodeId;
+use ya_net::loopback::{set_partition, Net};
+use ya_net::{Config, RemoteEndpoint};
+use ya_service_bus::{typed as bus, RpcEndpoint, RpcMessage};
+
+const LATENCY: Duration = Duration::from_millis(50);
+const CALL_TIMEOUT: Duration = Duration::from_secs(1);
+const TRACEPARENT: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct OfferBroadcast {
+    offer_id: String,
+}
+
+impl BroadcastMessage for OfferBroadcast {
+    const TOPIC: &'static str = "loopback-test-offers";
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Exec {
+    command: String,
+}
+
+impl RpcMessage for Exec {
+    const ID: &'static str = "Exec";
+    type Item = String;
+    type Error = String;
+}
+
+struct TestNode {
+    id: NodeId,
+    offers: Arc<Mutex<Vec<(String, String)>>>,
+}
+
+impl TestNode {
+    async fn start(dir: &Path, name: &str, latency: Duration, loss: f64) -> TestNode {
+        let mut config = Config::from_env().unwrap();
+        config.loopback_dir = Some(dir.to_path_buf());
+        config.loopback_latency = latency;
+        config.loopback_loss = loss;
+        config.loopback_seed = 7;
+
+        let raw: [u8; 32] = rand::random();
+        let key = SecretKey::from_raw(&raw).unwrap();
+        let prefix = format!("/loopback-test/{}", name);
+        let id = Net::start_node(config, vec![key], &prefix).await.unwrap();
+
+        let node_name = name.to_string();
+        bus::bind_with_caller(&format!("{}/activity", prefix), move |caller, msg: Exec| {
+            let reply = format!("{} executed {} for {}", node_name, msg.command, caller);
+            async move { Ok(reply) }
+        });
+
+        let offers = Arc::new(Mutex::new(Vec::new()));
+        let received = offers.clone();
+        ya_net::bind_broadcast_with_caller(
+            &format!("{}/market/{}", prefix, OfferBroadcast::TOPIC),
+            move |caller, msg: ya_core_model::net::local::SendBroadcastMessage<OfferBroadcast>| {
+                received
+                    .lock()
+                    .unwrap()
+                    .push((caller, msg.body().offer_id.clone()));
+                async move { Ok(()) }
+            },
+        )
+        .await
+        .unwrap();
+
+        TestNode { id, offers }
+    }
+
+    fn offers(&self) -> Vec<(String, String)> {
+        self.offers.lock().unwrap().clone()
+    }
+
+    async fn exec(&self, remote: &TestNode, command: &str) -> anyhow::Result<String> {
+        let call = ya_net::from(self.id)
+            .to(remote.id)
+            .service("/public/activity")
+            .send(Exec {
+                command: command.to_string(),
+            });
+        match tokio::time::timeout(CALL_TIMEOUT, call).await {
+            Ok(Ok(Ok(reply))) => Ok(reply),
+            Ok(Ok(Err(e))) => anyhow::bail!("remote error: {}", e),
+            Ok(Err(e)) => anyhow::bail!("GSB error: {}", e),
+            Err(_) => anyhow::bail!("timeout"),
+        }
+    }
+
+    async fn broadcast(&self, offer_id: &str) {
+        let offer = OfferBroadcast {
+            offer_id: offer_id.to_string(),
+        };
+        ya_net::broadcast(self.id, offer).await.unwrap().unwrap();
+        // Broadcast is fire and forget. Wait until it is delivered.
+        tokio::time::sleep(LATENCY * 4).await;
+    }
+}
+
+fn test_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("ya-net-loopback-{:08x}", rand::random::<u32>()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+// Nodes share GSB router and thread local network state, so all scenarios
+// run in a single test.
+#[actix_rt::test]
+async fn test_nodes_in_single_process() {
+    let _ = env_logger::builder().is_test(true).try_init();
+    let dir = test_dir();
+
+    let requestor = TestNode::start(&dir, "requestor", LATENCY, 0.0).await;
+    let provider1 = TestNode::start(&dir, "provider-1", LATENCY, 0.0).await;
+    let provider2 = TestNode::start(&dir, "provider-2", LATENCY, 0.0).await;
+
+    // Market: offer reaches every other node, but only its own handler.
+    provider1.broadcast("offer-1").await;
+    let expected = vec![(provider1.id.to_string(), "offer-1".to_string())];
+    assert_eq!(requestor.offers(), expected);
+    assert_eq!(provider2.offers(), expected);
+    assert!(provider1.offers().is_empty());
+
+    // Activity: call is routed to the addressed node and delayed both ways.
+    let start = Instant::now();
+    let reply = requestor.exec(&provider1, "deploy").await.unwrap();
+    assert!(start.elapsed() >= LATENCY * 2);
+    assert_eq!(
+        reply,
+        format!("provider-1 executed deploy for {}", requestor.id)
+    );
+    let reply = requestor.exec(&provider2, "start").await.unwrap();
+    assert!(reply.starts_with("provider-2 executed start"));
+
+    // Tracing: trace context travels inside length prefixed frame, so traced
+    // call doesn't break the connection.
+    {
+        let _trace = ya_utils_telemetry::extract(TRACEPARENT).attach();
+        let reply = requestor.exec(&provider1, "traced").await.unwrap();
+        assert!(reply.starts_with("provider-1 executed traced"));
+    }
+    let reply = requestor.exec(&provider1, "untraced").await.unwrap();
+    assert!(reply.starts_with("provider-1 executed untraced"));
+
+    // Partition: isolated node can't be reached and doesn't receive offers.
+    set_partition(&dir, provider2.id, Some("isolated".to_string())).unwrap();
+    assert!(requestor.exec(&provider2, "run").await.is_err());
+    assert!(requestor.exec(&provider1, "run").await.is_ok());
+
+    requestor.broadcast("offer-2").await;
+    assert_eq!(provider1.offers().len(), 2);
+    assert_eq!(provider2.offers().len(), 1);
+
+    set_partition(&dir, provider2.id, None).unwrap();
+    assert!(requestor.exec(&provider2, "run").await.is_ok());
+
+    // Loss: some requests received by lossy node are dropped.
+    let lossy = TestNode::start(&dir, "lossy", LATENCY, 0.5).await;
+    let mut results = Vec::new();
+    for i in 0..10 {
+        results.push(requestor.exec(&lossy, &format!("cmd-{}", i)).await.is_ok());
+    }
+    assert!(results.iter().any(|ok| *ok));
+    assert!(results.iter().any(|ok| !*ok));
+
+    Net::shutdown().await.unwrap();
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,3 @@
+drop index transaction_replacement_tx_id_idx;
+
+DROP TABLE `transaction_replacement`;
@@ -0,0 +1,10 @@
+CREATE TABLE `transaction_replacement`(
+    tx_hash TEXT NOT NULL PRIMARY KEY,
+    tx_id TEXT NOT NULL,
+    gas_price TEXT NOT NULL,
+    max_priority_fee TEXT NULL,
+    time_sent DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY(tx_id) REFERENCES `transaction` (tx_id)
+);
+
+create index if not exists transaction_replacement_tx_id_idx on transaction_replacement (tx_id);
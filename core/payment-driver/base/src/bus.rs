@@ -14,6 +14,7 @@ use ya_core_model::driver::{
     driver_bus_id, AccountMode, GenericError, PaymentConfirmation, PaymentDetails,
 };
 use ya_core_model::identity;
+pub use ya_core_model::identity::EthTransaction;
 use ya_core_model::payment::local as payment_srv;
 use ya_service_bus::{
     typed::{service, ServiceBinder},
@@ -155,6 +156,21 @@ pub async fn sign(node_id: NodeId, payload: Vec<u8>) -> Result<Vec<u8>, GenericE
     Ok(signature)
 }
 
+/// Signs Ethereum transaction. External signers review the whole `tx`,
+/// local keys sign its `hash`.
+pub async fn sign_tx(
+    node_id: NodeId,
+    hash: Vec<u8>,
+    tx: EthTransaction,
+) -> Result<Vec<u8>, GenericError> {
+    let signature = service(identity::BUS_ID)
+        .send(identity::SignTx { node_id, hash, tx })
+        .await
+        .map_err(GenericError::new)?
+        .map_err(GenericError::new)?;
+    Ok(signature)
+}
+
 pub async fn notify_payment(
     driver_name: &str,
     platform: &str,
@@ -16,8 +16,11 @@ use ya_persistence::executor::{do_with_transaction, readonly_transaction, AsDao,
 use crate::{
     dao::DbResult,
     db::{
-        models::{Network, TransactionEntity, TransactionStatus, TxType},
+        models::{
+            Network, TransactionEntity, TransactionReplacementEntity, TransactionStatus, TxType,
+        },
         schema::transaction::dsl,
+        schema::transaction_replacement::dsl as replacement_dsl,
     },
 };
 use chrono::Utc;
@@ -329,4 +332,49 @@ impl<'c> TransactionDao<'c> {
         })
         .await
     }
+
+    pub async fn insert_replacement(
+        &self,
+        replacement: TransactionReplacementEntity,
+    ) -> DbResult<()> {
+        do_with_transaction(self.pool, move |conn| {
+            diesel::insert_or_ignore_into(replacement_dsl::transaction_replacement)
+                .values(replacement)
+                .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
+    pub async fn get_replacements(
+        &self,
+        tx_id: String,
+    ) -> DbResult<Vec<TransactionReplacementEntity>> {
+        readonly_transaction(self.pool, move |conn| {
+            let replacements: Vec<TransactionReplacementEntity> =
+                replacement_dsl::transaction_replacement
+                    .filter(replacement_dsl::tx_id.eq(tx_id))
+                    .order(replacement_dsl::time_sent.asc())
+                    .load(conn)?;
+            Ok(replacements)
+        })
+        .await
+    }
+
+    /// Finds transaction which was (re)submitted on chain under given hash.
+    pub async fn get_by_replacement_hash(
+        &self,
+        tx_hash: String,
+    ) -> DbResult<Option<TransactionEntity>> {
+        readonly_transaction(self.pool, move |conn| {
+            let tx: Option<TransactionEntity> = dsl::transaction
+                .inner_join(replacement_dsl::transaction_replacement)
+                .select(crate::db::schema::transaction::all_columns)
+                .filter(replacement_dsl::tx_hash.eq(tx_hash))
+                .first(conn)
+                .optional()?;
+            Ok(tx)
+        })
+        .await
+    }
 }
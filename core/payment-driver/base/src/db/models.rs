@@ -82,6 +82,19 @@ pub struct TransactionEntity {
     pub encoded: String,
 }
 
+/// Every on-chain submission of a `TransactionEntity`. Transactions stuck in the mempool are
+/// resubmitted with the same nonce and a bumped gas price, so any of these hashes can be mined.
+#[derive(Clone, Queryable, Debug, Identifiable, Insertable, PartialEq)]
+#[primary_key(tx_hash)]
+#[table_name = "transaction_replacement"]
+pub struct TransactionReplacementEntity {
+    pub tx_hash: String,
+    pub tx_id: String,
+    pub gas_price: String,
+    pub max_priority_fee: Option<String>,
+    pub time_sent: NaiveDateTime,
+}
+
 #[derive(Queryable, Clone, Debug, Identifiable, Insertable, PartialEq)]
 #[primary_key(order_id)]
 #[table_name = "payment"]
@@ -47,6 +47,16 @@ table! {
     }
 }
 
+table! {
+    transaction_replacement (tx_hash) {
+        tx_hash -> Text,
+        tx_id -> Text,
+        gas_price -> Text,
+        max_priority_fee -> Nullable<Text>,
+        time_sent -> Timestamp,
+    }
+}
+
 table! {
     transaction_status (status_id) {
         status_id -> Integer,
@@ -64,12 +74,14 @@ table! {
 joinable!(payment -> payment_status (status));
 joinable!(payment -> transaction (tx_id));
 joinable!(transaction -> transaction_status (status));
+joinable!(transaction_replacement -> transaction (tx_id));
 joinable!(transaction -> transaction_type (tx_type));
 
 allow_tables_to_appear_in_same_query!(
     payment,
     payment_status,
     transaction,
+    transaction_replacement,
     transaction_status,
     transaction_type,
 );
@@ -2,14 +2,15 @@
     Database Access Object, all you need to interact with the database.
 */
 
+use chrono::Utc;
 use web3::types::U256;
 
 // Workspace uses
 use ya_payment_driver::{
     dao::{payment::PaymentDao, transaction::TransactionDao, DbExecutor},
     db::models::{
-        Network, PaymentEntity, TransactionEntity, TransactionStatus, PAYMENT_STATUS_FAILED,
-        PAYMENT_STATUS_NOT_YET,
+        Network, PaymentEntity, TransactionEntity, TransactionReplacementEntity, TransactionStatus,
+        PAYMENT_STATUS_FAILED, PAYMENT_STATUS_NOT_YET,
     },
     model::{GenericError, SchedulePayment},
     utils,
@@ -271,6 +272,79 @@ impl Erc20Dao {
         }
     }
 
+    pub async fn transaction_pending(&self, tx_id: &str) {
+        if let Err(e) = self
+            .transaction()
+            .update_tx_status(tx_id.to_string(), TransactionStatus::Pending, None)
+            .await
+        {
+            log::error!("Failed to update for transaction {:?} : {:?}", tx_id, e)
+            // TO CHECK: Should it continue or stop the process...
+        }
+    }
+
+    pub async fn transaction_replacement_sent(
+        &self,
+        tx_id: &str,
+        tx_hash: &str,
+        gas_price: String,
+        max_priority_fee: Option<String>,
+    ) {
+        let replacement = TransactionReplacementEntity {
+            tx_hash: tx_hash.to_string(),
+            tx_id: tx_id.to_string(),
+            gas_price,
+            max_priority_fee,
+            time_sent: Utc::now().naive_utc(),
+        };
+        if let Err(e) = self.transaction().insert_replacement(replacement).await {
+            log::error!(
+                "Failed to store replacement {:?} of transaction {:?} : {:?}",
+                tx_hash,
+                tx_id,
+                e
+            )
+        }
+    }
+
+    pub async fn get_transaction_replacements(
+        &self,
+        tx_id: &str,
+    ) -> Vec<TransactionReplacementEntity> {
+        match self.transaction().get_replacements(tx_id.to_string()).await {
+            Ok(replacements) => replacements,
+            Err(e) => {
+                log::error!(
+                    "Failed to fetch replacements of transaction {:?} : {:?}",
+                    tx_id,
+                    e
+                );
+                vec![]
+            }
+        }
+    }
+
+    pub async fn get_transaction_by_replacement_hash(
+        &self,
+        tx_hash: &str,
+    ) -> Option<TransactionEntity> {
+        match self
+            .transaction()
+            .get_by_replacement_hash(tx_hash.to_string())
+            .await
+        {
+            Ok(tx) => tx,
+            Err(e) => {
+                log::error!(
+                    "Failed to fetch transaction by replacement {:?} : {:?}",
+                    tx_hash,
+                    e
+                );
+                None
+            }
+        }
+    }
+
     pub async fn transaction_failed_send(&self, tx_id: &str, new_resent_count: i32, error: &str) {
         if let Err(e) = self
             .transaction()
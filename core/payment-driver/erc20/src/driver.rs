@@ -192,7 +192,7 @@ impl PaymentDriver for Erc20Driver {
         _caller: String,
         msg: VerifyPayment,
     ) -> Result<PaymentDetails, GenericError> {
-        api::verify_payment(msg).await
+        api::verify_payment(&self.dao, msg).await
     }
 
     async fn validate_allocation(
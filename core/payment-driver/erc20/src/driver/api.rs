@@ -87,12 +87,36 @@ pub async fn schedule_payment(
     Ok(order_id)
 }
 
-pub async fn verify_payment(msg: VerifyPayment) -> Result<PaymentDetails, GenericError> {
+pub async fn verify_payment(
+    dao: &Erc20Dao,
+    msg: VerifyPayment,
+) -> Result<PaymentDetails, GenericError> {
     log::debug!("verify_payment: {:?}", msg);
     let (network, _) = network::platform_to_network_token(msg.platform())?;
     let tx_hash = format!("0x{}", hex::encode(msg.confirmation().confirmation));
     log::info!("Verifying transaction: {}", tx_hash);
-    wallet::verify_tx(&tx_hash, network).await
+    match wallet::verify_tx(&tx_hash, network).await {
+        Ok(details) => Ok(details),
+        Err(e) => {
+            // Given hash could have been replaced by a submission with bumped gas price.
+            let mined_tx = dao
+                .get_transaction_by_replacement_hash(&tx_hash)
+                .await
+                .and_then(|tx| tx.final_tx)
+                .filter(|final_tx| final_tx != &tx_hash);
+            match mined_tx {
+                Some(mined_tx) => {
+                    log::info!(
+                        "Transaction {} was replaced, verifying mined transaction: {}",
+                        tx_hash,
+                        mined_tx
+                    );
+                    wallet::verify_tx(&mined_tx, network).await
+                }
+                None => Err(e),
+            }
+        }
+    }
 }
 
 pub async fn validate_allocation(msg: ValidateAllocation) -> Result<bool, GenericError> {
@@ -165,6 +165,34 @@ pub async fn confirm_payments(dao: &Erc20Dao, name: &str, network_key: &str) {
                 }
             };
 
+            // Stuck transactions are resubmitted with the same nonce, so an earlier submission
+            // could have been mined instead of the newest one.
+            let (newest_tx, s) = if !s.exists_on_chain || s.pending {
+                match find_mined_replacement(
+                    dao,
+                    &tx.tx_id,
+                    &tmp_onchain_txs_vec,
+                    newest_tx,
+                    block_number,
+                    network,
+                )
+                .await
+                {
+                    Some((mined_tx, mined_status)) => {
+                        log::info!(
+                            "Earlier submission of transaction was mined. id={}, hash={}",
+                            &tx.tx_id,
+                            &mined_tx
+                        );
+                        (mined_tx, mined_status)
+                    }
+                    None => (newest_tx.to_string(), s),
+                }
+            } else {
+                (newest_tx.to_string(), s)
+            };
+            let newest_tx = newest_tx.as_str();
+
             let final_gas_price = match s.gas_price {
                 Some(gas_price) => Some(gas_price.to_string()),
                 None => None,
@@ -202,7 +230,7 @@ pub async fn confirm_payments(dao: &Erc20Dao, name: &str, network_key: &str) {
                         );
                         continue;
                     }
-                    if cur_gas_price >= max_gas_price {
+                    if !wallet::can_bump_gas_price(cur_gas_price, max_gas_price) {
                         log::debug!("Cannot bump gas more: Current gas price current_gas_price: {} max_gas_price: {}", cur_gas_price, max_gas_price);
                         continue;
                     }
@@ -309,6 +337,51 @@ pub async fn confirm_payments(dao: &Erc20Dao, name: &str, network_key: &str) {
     }
 }
 
+async fn find_mined_replacement(
+    dao: &Erc20Dao,
+    tx_id: &str,
+    tmp_onchain_txs: &[&str],
+    newest_tx: &str,
+    block_number: Option<u64>,
+    network: Network,
+) -> Option<(String, ethereum::TransactionChainStatus)> {
+    let mut tx_hashes: Vec<String> = dao
+        .get_transaction_replacements(tx_id)
+        .await
+        .into_iter()
+        .map(|replacement| replacement.tx_hash)
+        .collect();
+    for tx_hash in tmp_onchain_txs {
+        if !tx_hashes.iter().any(|known| known == tx_hash) {
+            tx_hashes.push(tx_hash.to_string());
+        }
+    }
+
+    for tx_hash in tx_hashes.iter().rev() {
+        if tx_hash == newest_tx || tx_hash.len() <= 2 {
+            continue;
+        }
+        let hex_hash = match H256::from_str(&tx_hash[2..]) {
+            Ok(hex_hash) => hex_hash,
+            Err(err) => {
+                log::error!("Error when getting transaction hex hash: {:?}", err);
+                continue;
+            }
+        };
+        match ethereum::get_tx_on_chain_status(hex_hash, block_number, network).await {
+            Ok(tcs) if tcs.exists_on_chain && !tcs.pending => {
+                return Some((tx_hash.clone(), tcs));
+            }
+            Ok(_) => continue,
+            Err(err) => {
+                log::error!("Error when getting get_tx_on_chain_status: {:?}", err);
+                continue;
+            }
+        }
+    }
+    None
+}
+
 pub async fn process_payments_for_account(
     dao: &Erc20Dao,
     node_id: &str,
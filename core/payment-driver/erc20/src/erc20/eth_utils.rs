@@ -1,12 +1,25 @@
 // PRIVATE RawTransaction.hash()
 
-use ethereum_types::U256;
+use ethereum_types::{H160, U256};
 use rlp::RlpStream;
 use tiny_keccak::{Hasher, Keccak};
 
+use ya_payment_driver::bus::EthTransaction;
+
 use crate::erc20::transaction::YagnaRawTransaction;
 
+/// EIP-2718 envelope type of EIP-1559 transactions
+const EIP1559_TX_TYPE: u8 = 0x02;
+
 pub fn get_tx_hash(tx: &YagnaRawTransaction, chain_id: u64) -> Vec<u8> {
+    if let Some(max_priority_fee_per_gas) = tx.max_priority_fee_per_gas {
+        let mut hash = RlpStream::new();
+        hash.begin_unbounded_list();
+        eip1559_tx_encode(tx, max_priority_fee_per_gas, chain_id, &mut hash);
+        hash.finalize_unbounded_list();
+        return keccak256_hash(&typed_tx_payload(hash));
+    }
+
     let mut hash = RlpStream::new();
     hash.begin_unbounded_list();
     tx_encode(tx, &mut hash);
@@ -17,6 +30,30 @@ pub fn get_tx_hash(tx: &YagnaRawTransaction, chain_id: u64) -> Vec<u8> {
     keccak256_hash(&hash.out())
 }
 
+/// Transaction fields reviewed by external signer.
+pub fn to_eth_transaction(from: H160, tx: &YagnaRawTransaction, chain_id: u64) -> EthTransaction {
+    let (gas_price, max_fee_per_gas) = match tx.max_priority_fee_per_gas {
+        Some(_) => (None, Some(quantity(tx.gas_price))),
+        None => (Some(quantity(tx.gas_price)), None),
+    };
+    EthTransaction {
+        from: format!("0x{:x}", from),
+        to: tx.to.map(|to| format!("0x{:x}", to)),
+        gas: quantity(tx.gas),
+        gas_price,
+        max_fee_per_gas,
+        max_priority_fee_per_gas: tx.max_priority_fee_per_gas.map(quantity),
+        value: quantity(tx.value),
+        nonce: quantity(tx.nonce),
+        data: format!("0x{}", hex::encode(&tx.data)),
+        chain_id: format!("0x{:x}", chain_id),
+    }
+}
+
+fn quantity(value: U256) -> String {
+    format!("0x{:x}", value)
+}
+
 pub fn keccak256_hash(bytes: &[u8]) -> Vec<u8> {
     let mut hasher = Keccak::v256();
     hasher.update(bytes);
@@ -38,6 +75,34 @@ fn tx_encode(tx: &YagnaRawTransaction, s: &mut RlpStream) {
     s.append(&tx.data);
 }
 
+fn eip1559_tx_encode(
+    tx: &YagnaRawTransaction,
+    max_priority_fee_per_gas: U256,
+    chain_id: u64,
+    s: &mut RlpStream,
+) {
+    s.append(&chain_id);
+    s.append(&tx.nonce);
+    s.append(&max_priority_fee_per_gas);
+    s.append(&tx.gas_price);
+    s.append(&tx.gas);
+    if let Some(ref t) = tx.to {
+        s.append(t);
+    } else {
+        s.append(&vec![]);
+    }
+    s.append(&tx.value);
+    s.append(&tx.data);
+    // Empty access list
+    s.begin_list(0);
+}
+
+fn typed_tx_payload(stream: RlpStream) -> Vec<u8> {
+    let mut payload = vec![EIP1559_TX_TYPE];
+    payload.extend_from_slice(&stream.out());
+    payload
+}
+
 // MISSING RawTransaction.encode_signed_tx()
 
 pub fn encode_signed_tx(
@@ -45,6 +110,23 @@ pub fn encode_signed_tx(
     signature: Vec<u8>,
     chain_id: u64,
 ) -> Vec<u8> {
+    if let Some(max_priority_fee_per_gas) = raw_tx.max_priority_fee_per_gas {
+        // Typed transactions carry bare y-parity instead of EIP-155 `v`
+        let (y_parity, sig_r, sig_s) = split_signature(signature);
+
+        let mut tx = RlpStream::new();
+        tx.begin_unbounded_list();
+
+        eip1559_tx_encode(&raw_tx, max_priority_fee_per_gas, chain_id, &mut tx);
+        tx.append(&(y_parity as u64));
+        tx.append(&sig_r);
+        tx.append(&sig_s);
+
+        tx.finalize_unbounded_list();
+
+        return typed_tx_payload(tx);
+    }
+
     let (sig_v, sig_r, sig_s) = prepare_signature(signature, chain_id);
 
     let mut tx = RlpStream::new();
@@ -62,11 +144,17 @@ pub fn encode_signed_tx(
 }
 
 fn prepare_signature(signature: Vec<u8>, chain_id: u64) -> (u64, Vec<u8>, Vec<u8>) {
+    let (sig_v, sig_r, sig_s) = split_signature(signature);
+    let sig_v = sig_v as u64 + chain_id * 2 + 35;
+
+    (sig_v, sig_r, sig_s)
+}
+
+fn split_signature(signature: Vec<u8>) -> (u8, Vec<u8>, Vec<u8>) {
     // TODO ugly solution
     assert_eq!(signature.len(), 65);
 
     let sig_v = signature[0];
-    let sig_v = sig_v as u64 + chain_id * 2 + 35;
 
     let mut sig_r = signature.to_owned().split_off(1);
     let mut sig_s = sig_r.split_off(32);
@@ -119,3 +207,110 @@ where
         .function(func)
         .and_then(|function| function.decode_input(&data))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rlp::Rlp;
+
+    const CHAIN_ID: u64 = 137;
+
+    fn raw_tx(max_priority_fee_per_gas: Option<U256>) -> YagnaRawTransaction {
+        YagnaRawTransaction {
+            nonce: U256::from(7),
+            to: Some(H160::repeat_byte(0x11)),
+            value: U256::zero(),
+            gas_price: U256::from(30_000_000_000u64),
+            max_priority_fee_per_gas,
+            gas: U256::from(55_000),
+            data: vec![0xa9, 0x05, 0x9c, 0xbb],
+        }
+    }
+
+    fn signature(v: u8) -> Vec<u8> {
+        let mut signature = vec![v];
+        signature.extend_from_slice(&[0x22; 32]);
+        signature.extend_from_slice(&[0x33; 32]);
+        signature
+    }
+
+    #[test]
+    fn eip1559_signed_tx_encoding() {
+        let priority_fee = U256::from(1_500_000_000u64);
+        let tx = raw_tx(Some(priority_fee));
+        let signed = encode_signed_tx(&tx, signature(1), CHAIN_ID);
+
+        assert_eq!(signed[0], EIP1559_TX_TYPE);
+        let rlp = Rlp::new(&signed[1..]);
+        assert_eq!(rlp.item_count().unwrap(), 12);
+        assert_eq!(rlp.val_at::<u64>(0).unwrap(), CHAIN_ID);
+        assert_eq!(rlp.val_at::<U256>(1).unwrap(), tx.nonce);
+        assert_eq!(rlp.val_at::<U256>(2).unwrap(), priority_fee);
+        assert_eq!(rlp.val_at::<U256>(3).unwrap(), tx.gas_price);
+        assert_eq!(rlp.val_at::<U256>(4).unwrap(), tx.gas);
+        assert_eq!(rlp.val_at::<H160>(5).unwrap(), tx.to.unwrap());
+        assert_eq!(rlp.val_at::<Vec<u8>>(7).unwrap(), tx.data);
+        assert_eq!(rlp.at(8).unwrap().item_count().unwrap(), 0);
+        // Bare y-parity instead of EIP-155 `v`.
+        assert_eq!(rlp.val_at::<u64>(9).unwrap(), 1);
+        assert_eq!(rlp.val_at::<Vec<u8>>(10).unwrap(), vec![0x22; 32]);
+        assert_eq!(rlp.val_at::<Vec<u8>>(11).unwrap(), vec![0x33; 32]);
+    }
+
+    #[test]
+    fn legacy_signed_tx_encoding() {
+        let tx = raw_tx(None);
+        let signed = encode_signed_tx(&tx, signature(1), CHAIN_ID);
+
+        let rlp = Rlp::new(&signed);
+        assert!(rlp.is_list());
+        assert_eq!(rlp.item_count().unwrap(), 9);
+        assert_eq!(rlp.val_at::<U256>(1).unwrap(), tx.gas_price);
+        assert_eq!(rlp.val_at::<u64>(6).unwrap(), CHAIN_ID * 2 + 36);
+    }
+
+    #[test]
+    fn eip1559_tx_hash() {
+        let legacy = raw_tx(None);
+        let typed = raw_tx(Some(U256::from(1_500_000_000u64)));
+
+        let mut unsigned = RlpStream::new();
+        unsigned.begin_unbounded_list();
+        eip1559_tx_encode(
+            &typed,
+            U256::from(1_500_000_000u64),
+            CHAIN_ID,
+            &mut unsigned,
+        );
+        unsigned.finalize_unbounded_list();
+        let payload = typed_tx_payload(unsigned);
+
+        assert_eq!(Rlp::new(&payload[1..]).item_count().unwrap(), 9);
+        assert_eq!(get_tx_hash(&typed, CHAIN_ID), keccak256_hash(&payload));
+        assert_ne!(
+            get_tx_hash(&typed, CHAIN_ID),
+            get_tx_hash(&legacy, CHAIN_ID)
+        );
+        assert_eq!(get_tx_hash(&typed, CHAIN_ID).len(), 32);
+    }
+
+    #[test]
+    fn eip1559_fields_for_external_signer() {
+        let typed = to_eth_transaction(
+            H160::zero(),
+            &raw_tx(Some(U256::from(1_500_000_000u64))),
+            CHAIN_ID,
+        );
+        assert_eq!(typed.gas_price, None);
+        assert_eq!(typed.max_fee_per_gas.as_deref(), Some("0x6fc23ac00"));
+        assert_eq!(
+            typed.max_priority_fee_per_gas.as_deref(),
+            Some("0x59682f00")
+        );
+        assert_eq!(typed.chain_id, "0x89");
+
+        let legacy = to_eth_transaction(H160::zero(), &raw_tx(None), CHAIN_ID);
+        assert_eq!(legacy.gas_price.as_deref(), Some("0x6fc23ac00"));
+        assert_eq!(legacy.max_fee_per_gas, None);
+    }
+}
@@ -22,6 +22,7 @@ use ya_payment_driver::{bus, model::GenericError};
 
 use crate::erc20::eth_utils::keccak256_hash;
 use crate::erc20::transaction::YagnaRawTransaction;
+use crate::erc20::utils::convert_float_gas_to_u256;
 use crate::erc20::{config, eth_utils};
 
 #[derive(Clone, Debug, thiserror::Error)]
@@ -112,6 +113,22 @@ pub fn get_polygon_max_gas_price_dynamic() -> f64 {
         .unwrap_or(1000.0f64);
 }
 
+/// Send transfers as EIP-1559 (type 2) transactions, with `gasPrice` used as max fee per gas
+pub fn get_eip1559_enabled() -> bool {
+    std::env::var("ERC20_USE_EIP1559")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(false)
+}
+
+/// Starting max priority fee (tip) for EIP-1559 transactions in Gwei
+pub fn get_max_priority_fee() -> f64 {
+    std::env::var("ERC20_MAX_PRIORITY_FEE")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(POLYGON_PREFERRED_GAS_PRICES_SLOW[1])
+}
+
 pub fn get_polygon_gas_price_method() -> PolygonGasPriceMethod {
     match std::env::var("POLYGON_GAS_PRICE_METHOD")
         .ok()
@@ -264,6 +281,7 @@ async fn sign_faucet_tx_with(
         to: Some(contract.address()),
         value: U256::from(0),
         gas_price,
+        max_priority_fee_per_gas: None,
         gas: *GLM_FAUCET_GAS,
         data,
     };
@@ -292,7 +310,13 @@ pub async fn sign_raw_transfer_transaction(
 ) -> Result<Vec<u8>, GenericError> {
     let chain_id = network as u64;
     let node_id = NodeId::from(address.as_ref());
-    let signature = bus::sign(node_id, eth_utils::get_tx_hash(&tx, chain_id)).await?;
+    let hash = eth_utils::get_tx_hash(&tx, chain_id);
+    let signature = bus::sign_tx(
+        node_id,
+        hash,
+        eth_utils::to_eth_transaction(address, &tx, chain_id),
+    )
+    .await?;
     Ok(signature)
 }
 
@@ -364,11 +388,17 @@ async fn prepare_raw_transaction_with(
         _ => gas_limit_override.map_or(*GLM_TRANSFER_GAS, |v| U256::from(v)),
     };
 
+    let max_priority_fee_per_gas = match get_eip1559_enabled() {
+        true => Some(gas_price.min(convert_float_gas_to_u256(get_max_priority_fee()))),
+        false => None,
+    };
+
     let tx = YagnaRawTransaction {
         nonce,
         to: Some(contract.address()),
         value: U256::from(0),
         gas_price,
+        max_priority_fee_per_gas,
         gas: gas_limit,
         data,
     };
@@ -9,9 +9,16 @@ pub struct YagnaRawTransaction {
     pub to: Option<H160>,
     /// Transferred value
     pub value: U256,
-    /// Gas price
+    /// Gas price. For EIP-1559 transactions this is the max fee per gas
     #[serde(rename = "gasPrice")]
     pub gas_price: U256,
+    /// Max priority fee per gas, set only for EIP-1559 (type 2) transactions
+    #[serde(
+        rename = "maxPriorityFeePerGas",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_priority_fee_per_gas: Option<U256>,
     /// Gas amount
     pub gas: U256,
     /// Transaction data
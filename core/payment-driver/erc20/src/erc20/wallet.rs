@@ -214,6 +214,9 @@ pub async fn make_transfer(
             raw_tx.gas_price = max_gas_price;
         }
     }
+    if let Some(priority_fee) = raw_tx.max_priority_fee_per_gas {
+        raw_tx.max_priority_fee_per_gas = Some(priority_fee.min(raw_tx.gas_price));
+    }
 
     Ok(ethereum::create_dao_entity(
         nonce,
@@ -248,20 +251,51 @@ pub async fn make_gasless_transfer(
     gasless_transfer::send_gasless_transfer(details, network).await
 }
 
-fn bump_gas_price(gas_in_gwei: U256) -> U256 {
-    let min_bump_num: U256 = U256::from(111u64);
-    let min_bump_den: U256 = U256::from(100u64);
-    let min_gas = gas_in_gwei * min_bump_num / min_bump_den;
+/// Replacement transaction is accepted by nodes only if it pays at least 10% more than
+/// the transaction it replaces. Bumping by 11% leaves a margin for rounding.
+const MIN_GAS_BUMP_PERCENT: u64 = 11;
+
+fn min_bumped_gas_price(gas_in_gwei: U256) -> U256 {
+    gas_in_gwei * U256::from(100 + MIN_GAS_BUMP_PERCENT) / U256::from(100u64)
+}
 
-    match get_polygon_gas_price_method() {
+/// Bumping is not possible when the minimal replacement price would exceed `max_gas_price`.
+pub fn can_bump_gas_price(gas_in_gwei: U256, max_gas_price: U256) -> bool {
+    min_bumped_gas_price(gas_in_gwei) <= max_gas_price
+}
+
+fn bump_gas_price_capped(
+    gas_in_gwei: U256,
+    max_gas_price: Option<U256>,
+    method: PolygonGasPriceMethod,
+    priority: PolygonPriority,
+) -> Option<U256> {
+    let new_gas = bump_gas_price(gas_in_gwei, method, priority);
+    match max_gas_price {
+        Some(max_gas_price) if new_gas > max_gas_price => {
+            match can_bump_gas_price(gas_in_gwei, max_gas_price) {
+                true => Some(max_gas_price),
+                false => None,
+            }
+        }
+        _ => Some(new_gas),
+    }
+}
+
+fn bump_gas_price(
+    gas_in_gwei: U256,
+    method: PolygonGasPriceMethod,
+    priority: PolygonPriority,
+) -> U256 {
+    let min_gas = min_bumped_gas_price(gas_in_gwei);
+
+    match method {
         PolygonGasPriceMethod::PolygonGasPriceDynamic => {
-            //ignore maximum gas price, because we have to bump at least 10% so the transaction will be accepted
+            //we have to bump at least 11% so the transaction will be accepted
             min_gas
         }
         PolygonGasPriceMethod::PolygonGasPriceStatic => {
-            let polygon_prices = get_polygon_priority();
-
-            let gas_prices: &[f64] = match polygon_prices {
+            let gas_prices: &[f64] = match priority {
                 PolygonPriority::PolygonPriorityExpress => {
                     &POLYGON_PREFERRED_GAS_PRICES_EXPRESS[..]
                 }
@@ -317,17 +351,35 @@ pub async fn send_transactions(
                     }
                     None => None,
                 };
-                let new_gas = bump_gas_price(gas_u256);
-                if let Some(max_gas_u256) = max_gas_u256 {
-                    if gas_u256 > max_gas_u256 {
+                match bump_gas_price_capped(
+                    gas_u256,
+                    max_gas_u256,
+                    get_polygon_gas_price_method(),
+                    get_polygon_priority(),
+                ) {
+                    Some(new_gas) => {
+                        log::info!(
+                            "Replacing stuck transaction. nonce={}, gas_price={} -> {}",
+                            raw_tx.nonce,
+                            gas_u256,
+                            new_gas
+                        );
+                        if let Some(priority_fee) = raw_tx.max_priority_fee_per_gas {
+                            raw_tx.max_priority_fee_per_gas =
+                                Some(min_bumped_gas_price(priority_fee).min(new_gas));
+                        }
+                        new_gas
+                    }
+                    None => {
                         log::warn!(
-                            "bump gas ({}) larger than max gas ({}) price",
+                            "Cannot bump gas price ({}) over max gas price ({:?}), waiting for pending transaction",
                             gas_u256,
                             max_gas_u256
-                        )
+                        );
+                        dao.transaction_pending(&tx.tx_id).await;
+                        continue;
                     }
                 }
-                new_gas
             } else {
                 U256::from_dec_str(&current_gas_price).map_err(GenericError::new)?
             }
@@ -337,6 +389,9 @@ pub async fn send_transactions(
             convert_float_gas_to_u256(get_polygon_starting_price())
         };
         raw_tx.gas_price = new_gas_price;
+        if let Some(priority_fee) = raw_tx.max_priority_fee_per_gas {
+            raw_tx.max_priority_fee_per_gas = Some(priority_fee.min(new_gas_price));
+        }
 
         let encoded = serde_json::to_string(&raw_tx).map_err(GenericError::new)?;
         let signature = ethereum::sign_raw_transfer_transaction(address, network, &raw_tx).await?;
@@ -354,12 +409,19 @@ pub async fn send_transactions(
 
         match ethereum::send_tx(signed, network).await {
             Ok(tx_hash) => {
-                let str_tx_hash = format!("0x{:x}", &tx_hash);
+                let sent_tx_hash = format!("0x{:x}", &tx_hash);
                 let str_tx_hash = if let Some(tmp_onchain_txs) = tx.tmp_onchain_txs {
-                    tmp_onchain_txs + ";" + str_tx_hash.as_str()
+                    tmp_onchain_txs + ";" + sent_tx_hash.as_str()
                 } else {
-                    str_tx_hash
+                    sent_tx_hash.clone()
                 };
+                dao.transaction_replacement_sent(
+                    &tx.tx_id,
+                    &sent_tx_hash,
+                    raw_tx.gas_price.to_string(),
+                    raw_tx.max_priority_fee_per_gas.map(|fee| fee.to_string()),
+                )
+                .await;
                 dao.transaction_sent(&tx.tx_id, &str_tx_hash, Some(raw_tx.gas_price.to_string()))
                     .await;
                 log::info!("Send transaction. hash={}", &str_tx_hash);
@@ -479,3 +541,51 @@ pub async fn verify_tx(tx_hash: &str, network: Network) -> Result<PaymentDetails
         )))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn min_bump_is_eleven_percent() {
+        assert_eq!(min_bumped_gas_price(U256::from(300u64)), U256::from(333u64));
+        // Rounded down, but still above required 10%.
+        assert_eq!(min_bumped_gas_price(U256::from(99u64)), U256::from(109u64));
+        assert!(can_bump_gas_price(U256::from(360u64), U256::from(400u64)));
+        assert!(!can_bump_gas_price(U256::from(370u64), U256::from(400u64)));
+    }
+
+    #[test]
+    fn bump_is_capped_by_max_gas_price() {
+        let wei = |amount: u64| U256::from(amount);
+        let max = Some(wei(400));
+        let dynamic = |gas, max| {
+            bump_gas_price_capped(
+                gas,
+                max,
+                PolygonGasPriceMethod::PolygonGasPriceDynamic,
+                PolygonPriority::PolygonPrioritySlow,
+            )
+        };
+
+        // Dynamic method bumps by the minimal step.
+        assert_eq!(dynamic(wei(300), max), Some(wei(333)));
+        assert_eq!(dynamic(wei(370), None), Some(wei(410)));
+        assert_eq!(dynamic(wei(370), max), None);
+
+        // Static method jumps to the next level above the minimal step.
+        let gwei = convert_float_gas_to_u256;
+        let slow = |gas, max| {
+            bump_gas_price_capped(
+                gas,
+                max,
+                PolygonGasPriceMethod::PolygonGasPriceStatic,
+                PolygonPriority::PolygonPrioritySlow,
+            )
+        };
+        assert_eq!(slow(gwei(20.01), Some(gwei(30.01))), Some(gwei(25.01)));
+        // Next level exceeds the cap, but the minimal step still fits.
+        assert_eq!(slow(gwei(25.0), Some(gwei(28.0))), Some(gwei(28.0)));
+        assert_eq!(slow(gwei(27.5), Some(gwei(30.01))), None);
+    }
+}
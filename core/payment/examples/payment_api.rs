@@ -130,6 +130,7 @@ fn fake_list_identities(identities: Vec<NodeId>) {
                 node_id: id,
                 is_default: false,
                 is_locked: false,
+                is_external: false,
             });
         }
         async move { Ok(accounts) }
@@ -0,0 +1,14 @@
+CREATE TABLE pay_allocation_settlement(
+    allocation_id VARCHAR(50) NOT NULL PRIMARY KEY,
+    policy VARCHAR(16) NOT NULL CHECK (policy in ('IMMEDIATE', 'DAILY', 'THRESHOLD')),
+    settle_at TIME NULL,
+    threshold VARCHAR(32) NULL,
+    FOREIGN KEY(allocation_id) REFERENCES pay_allocation (id)
+);
+
+-- Orders awaiting aggregated settlement have `settlement_due_date` set and no `batch_order_id`.
+-- Once settled, `batch_order_id` is the driver order paying all documents of the batch.
+ALTER TABLE pay_order ADD COLUMN settlement_due_date DATETIME NULL;
+ALTER TABLE pay_order ADD COLUMN batch_order_id VARCHAR(50) NULL;
+
+create index if not exists pay_order_batch_order_id_idx on pay_order (batch_order_id);
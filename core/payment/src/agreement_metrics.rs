@@ -0,0 +1,79 @@
+//! Feeds per-Agreement gauges of `ya-metrics` from Debit Notes and Invoices.
+
+use bigdecimal::{BigDecimal, ToPrimitive};
+use serde_json::Value;
+use ya_agreement_utils::agreement::expand;
+use ya_client_model::market::Agreement;
+use ya_metrics::agreement::{self, AgreementLabels};
+use ya_persistence::types::Role;
+
+/// Labels and usage vector of Agreement, taken before Agreement is moved
+/// to the database. Documents are recorded only after they are stored.
+pub struct AgreementMetrics {
+    agreement_id: String,
+    labels: AgreementLabels,
+    usage_vector: Vec<String>,
+}
+
+impl AgreementMetrics {
+    pub fn new(agreement: &Agreement, role: Role) -> Self {
+        let (role, peer) = match role {
+            Role::Provider => ("provider", agreement.requestor_id()),
+            Role::Requestor => ("requestor", agreement.provider_id()),
+        };
+        let usage_vector = expand(agreement.offer.properties.clone())
+            .pointer("/golem/com/usage/vector")
+            .and_then(|vector| serde_json::from_value(vector.clone()).ok())
+            .unwrap_or_default();
+
+        AgreementMetrics {
+            agreement_id: agreement.agreement_id.clone(),
+            labels: AgreementLabels::from_properties(
+                role,
+                &agreement.agreement_id,
+                peer.to_string(),
+                &agreement.demand.properties,
+                &agreement.offer.properties,
+            ),
+            usage_vector,
+        }
+    }
+
+    /// Labels of global counters.
+    pub fn labels(&self) -> &AgreementLabels {
+        &self.labels
+    }
+
+    pub fn record_debit_note(
+        &self,
+        activity_id: &str,
+        total_amount_due: &BigDecimal,
+        usage_counter_vector: Option<&Value>,
+    ) {
+        let values: Vec<f64> = usage_counter_vector
+            .and_then(|vector| serde_json::from_value(vector.clone()).ok())
+            .unwrap_or_default();
+        let usage = self
+            .usage_vector
+            .iter()
+            .cloned()
+            .zip(values.into_iter())
+            .collect();
+
+        agreement::record_activity(
+            &self.agreement_id,
+            self.labels.clone(),
+            activity_id,
+            total_amount_due.to_f64().unwrap_or_default(),
+            usage,
+        );
+    }
+
+    pub fn record_invoice(&self, amount: &BigDecimal) {
+        agreement::record_invoice(
+            &self.agreement_id,
+            self.labels.clone(),
+            amount.to_f64().unwrap_or_default(),
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use actix_web::web::Data;
 use actix_web::Scope;
+use std::borrow::Cow;
 use ya_client_model::payment::PAYMENT_API_PATH;
 use ya_persistence::executor::DbExecutor;
 use ya_service_api_web::scope::ExtendableScope;
@@ -10,6 +11,16 @@ mod debit_notes;
 mod invoices;
 mod payments;
 
+/// Invoice and DebitNote events returned, when client doesn't select them
+/// with `X-Requestor-Events` and `X-Provider-Events` headers.
+pub(crate) const DEFAULT_REQUESTOR_EVENTS: &[&str] = &["RECEIVED", "CANCELLED"];
+pub(crate) const DEFAULT_PROVIDER_EVENTS: &[&str] =
+    &["ACCEPTED", "REJECTED", "SETTLED", "CANCELLED"];
+
+pub(crate) fn default_events(events: &[&'static str]) -> Vec<Cow<'static, str>> {
+    events.iter().map(|event| Cow::Borrowed(*event)).collect()
+}
+
 pub fn api_scope(scope: Scope) -> Scope {
     scope
         .extend(accounts::register_endpoints)
@@ -13,6 +13,7 @@ use ya_core_model::payment::public::{
     AcceptDebitNote, AcceptRejectError, SendDebitNote, SendError, BUS_ID as PUBLIC_SERVICE,
 };
 use ya_core_model::payment::RpcMessageError;
+use ya_metrics::agreement_counter;
 use ya_net::RemoteEndpoint;
 use ya_persistence::executor::DbExecutor;
 use ya_persistence::types::Role;
@@ -21,6 +22,8 @@ use ya_service_bus::timeout::IntoTimeoutFuture;
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
 // Local uses
+use super::{default_events, DEFAULT_PROVIDER_EVENTS, DEFAULT_REQUESTOR_EVENTS};
+use crate::agreement_metrics::AgreementMetrics;
 use crate::dao::*;
 use crate::error::{DbError, Error};
 use crate::utils::provider::get_agreement_for_activity;
@@ -108,21 +111,14 @@ async fn get_debit_note_events(
         .get("X-Requestor-Events")
         .and_then(|v| v.to_str().ok())
         .map(|v| v.split(",").map(|s| Cow::Owned(s.to_owned())).collect())
-        .unwrap_or_else(|| vec!["RECEIVED".into(), "CANCELLED".into()]);
+        .unwrap_or_else(|| default_events(DEFAULT_REQUESTOR_EVENTS));
 
     let provider_events: Vec<Cow<'static, str>> = req
         .headers()
         .get("X-Provider-Events")
         .and_then(|v| v.to_str().ok())
         .map(|v| v.split(",").map(|s| Cow::Owned(s.to_owned())).collect())
-        .unwrap_or_else(|| {
-            vec![
-                "ACCEPTED".into(),
-                "REJECTED".into(),
-                "SETTLED".into(),
-                "CANCELLED".into(),
-            ]
-        });
+        .unwrap_or_else(|| default_events(DEFAULT_PROVIDER_EVENTS));
     let node_id = id.identity;
     let timeout_secs = query.timeout.unwrap_or(params::DEFAULT_EVENT_TIMEOUT);
     let after_timestamp = query.after_timestamp.map(|d| d.naive_utc());
@@ -174,6 +170,7 @@ async fn issue_debit_note(
     if &node_id != agreement.provider_id() {
         return response::unauthorized();
     }
+    let metrics = AgreementMetrics::new(&agreement, Role::Provider);
 
     match async move {
         db.as_dao::<AgreementDao>()
@@ -187,7 +184,14 @@ async fn issue_debit_note(
         let debit_note_id = dao.create_new(debit_note, node_id).await?;
         let debit_note = dao.get(debit_note_id, node_id).await?;
 
-        counter!("payment.debit_notes.provider.issued", 1);
+        if let Some(debit_note) = &debit_note {
+            metrics.record_debit_note(
+                &debit_note.activity_id,
+                &debit_note.total_amount_due,
+                debit_note.usage_counter_vector.as_ref(),
+            );
+        }
+        agreement_counter!("payment.debit_notes.provider.issued", 1, metrics.labels());
         Ok(debit_note)
     }
     .await
@@ -14,6 +14,7 @@ use ya_core_model::payment::public::{
     BUS_ID as PUBLIC_SERVICE,
 };
 use ya_core_model::payment::RpcMessageError;
+use ya_metrics::agreement_counter;
 use ya_net::RemoteEndpoint;
 use ya_persistence::executor::DbExecutor;
 use ya_persistence::types::Role;
@@ -22,6 +23,8 @@ use ya_service_bus::timeout::IntoTimeoutFuture;
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
 // Local uses
+use super::{default_events, DEFAULT_PROVIDER_EVENTS, DEFAULT_REQUESTOR_EVENTS};
+use crate::agreement_metrics::AgreementMetrics;
 use crate::dao::*;
 use crate::error::{DbError, Error};
 use crate::utils::provider::get_agreement_id;
@@ -94,21 +97,14 @@ async fn get_invoice_events(
         .get("X-Requestor-Events")
         .and_then(|v| v.to_str().ok())
         .map(|v| v.split(",").map(|s| Cow::Owned(s.to_owned())).collect())
-        .unwrap_or_else(|| vec!["RECEIVED".into(), "CANCELLED".into()]);
+        .unwrap_or_else(|| default_events(DEFAULT_REQUESTOR_EVENTS));
 
     let provider_events: Vec<Cow<'static, str>> = req
         .headers()
         .get("X-Provider-Events")
         .and_then(|v| v.to_str().ok())
         .map(|v| v.split(",").map(|s| Cow::Owned(s.to_owned())).collect())
-        .unwrap_or_else(|| {
-            vec![
-                "ACCEPTED".into(),
-                "REJECTED".into(),
-                "SETTLED".into(),
-                "CANCELLED".into(),
-            ]
-        });
+        .unwrap_or_else(|| default_events(DEFAULT_PROVIDER_EVENTS));
     let node_id = id.identity;
     let timeout_secs = query.timeout.unwrap_or(params::DEFAULT_EVENT_TIMEOUT);
     let after_timestamp = query.after_timestamp.map(|d| d.naive_utc());
@@ -169,6 +165,7 @@ async fn issue_invoice(db: Data<DbExecutor>, body: Json<NewInvoice>, id: Identit
     if &node_id != agreement.provider_id() {
         return response::unauthorized();
     }
+    let metrics = AgreementMetrics::new(&agreement, Role::Provider);
 
     match async move {
         db.as_dao::<AgreementDao>()
@@ -185,7 +182,10 @@ async fn issue_invoice(db: Data<DbExecutor>, body: Json<NewInvoice>, id: Identit
         let invoice_id = dao.create_new(invoice, node_id).await?;
         let invoice = dao.get(invoice_id, node_id).await?;
 
-        counter!("payment.invoices.provider.issued", 1);
+        if let Some(invoice) = &invoice {
+            metrics.record_invoice(&invoice.amount);
+        }
+        agreement_counter!("payment.invoices.provider.issued", 1, metrics.labels());
         Ok(invoice)
     }
     .await
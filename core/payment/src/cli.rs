@@ -1,6 +1,6 @@
 // External crates
 use bigdecimal::BigDecimal;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveTime, Utc};
 use std::str::FromStr;
 use std::time::UNIX_EPOCH;
 use structopt::*;
@@ -105,6 +105,36 @@ pub enum PaymentCli {
 
     /// Clear all existing allocations
     ReleaseAllocations,
+
+    /// Manage when payments from an allocation are sent out
+    Settlement {
+        #[structopt(subcommand)]
+        command: SettlementCommand,
+    },
+}
+
+#[derive(StructOpt, Debug)]
+pub enum SettlementCommand {
+    /// Display settlement policy of an allocation
+    Show { allocation_id: String },
+    /// Pay every accepted invoice and debit note as soon as possible
+    Immediate { allocation_id: String },
+    /// Aggregate payments per payee and settle them once a day
+    Daily {
+        allocation_id: String,
+        #[structopt(long, help = "UTC time of day, e.g. 18:00", parse(try_from_str = parse_time))]
+        at: NaiveTime,
+    },
+    /// Aggregate payments per payee until their sum reaches the amount
+    Threshold {
+        allocation_id: String,
+        #[structopt(long, help = "Amount in GLM for example 1.45")]
+        amount: BigDecimal,
+    },
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime, chrono::ParseError> {
+    NaiveTime::parse_from_str(s, "%H:%M:%S").or_else(|_| NaiveTime::parse_from_str(s, "%H:%M"))
 }
 
 #[derive(StructOpt, Debug)]
@@ -378,6 +408,35 @@ impl PaymentCli {
                     .await;
                 Ok(CommandOutput::NoOutput)
             }
+            PaymentCli::Settlement { command } => {
+                let (allocation_id, policy) = match command {
+                    SettlementCommand::Show { allocation_id } => {
+                        return CommandOutput::object(
+                            bus::service(pay::BUS_ID)
+                                .call(pay::GetSettlementPolicy { allocation_id })
+                                .await??,
+                        );
+                    }
+                    SettlementCommand::Immediate { allocation_id } => {
+                        (allocation_id, pay::SettlementPolicy::Immediate)
+                    }
+                    SettlementCommand::Daily { allocation_id, at } => (
+                        allocation_id,
+                        pay::SettlementPolicy::Daily { settle_at: at },
+                    ),
+                    SettlementCommand::Threshold {
+                        allocation_id,
+                        amount,
+                    } => (allocation_id, pay::SettlementPolicy::Threshold { amount }),
+                };
+                bus::service(pay::BUS_ID)
+                    .call(pay::SetSettlementPolicy {
+                        allocation_id,
+                        policy,
+                    })
+                    .await??;
+                Ok(CommandOutput::NoOutput)
+            }
         }
     }
 }
@@ -4,6 +4,7 @@ use crate::models::order::{ReadObj, WriteObj};
 use crate::schema::pay_debit_note::dsl as debit_note_dsl;
 use crate::schema::pay_invoice::dsl as invoice_dsl;
 use crate::schema::pay_order::dsl;
+use chrono::NaiveDateTime;
 use diesel::{
     self, BoolExpressionMethods, ExpressionMethods, JoinOnDsl, NullableExpressionMethods, QueryDsl,
     RunQueryDsl,
@@ -25,6 +26,21 @@ impl<'c> AsDao<'c> for OrderDao<'c> {
 
 impl<'c> OrderDao<'c> {
     pub async fn create(&self, msg: SchedulePayment, id: String, driver: String) -> DbResult<()> {
+        let order = WriteObj::new(msg.clone(), id, driver);
+        self.insert(msg, order).await
+    }
+
+    pub async fn create_deferred(
+        &self,
+        msg: SchedulePayment,
+        driver: String,
+        settlement_due_date: NaiveDateTime,
+    ) -> DbResult<()> {
+        let order = WriteObj::deferred(msg.clone(), driver, settlement_due_date);
+        self.insert(msg, order).await
+    }
+
+    async fn insert(&self, msg: SchedulePayment, order: WriteObj) -> DbResult<()> {
         do_with_transaction(self.pool, move |conn| {
             match &msg.title {
                 PaymentTitle::DebitNote(DebitNotePayment { activity_id, .. }) => {
@@ -44,7 +60,6 @@ impl<'c> OrderDao<'c> {
                     )?
                 }
             };
-            let order = WriteObj::new(msg, id, driver);
             allocation::spend_from_allocation(&order.allocation_id, &order.amount, conn)?;
             diesel::insert_into(dsl::pay_order)
                 .values(order)
@@ -67,7 +82,11 @@ impl<'c> OrderDao<'c> {
                         .eq(debit_note_dsl::id.nullable())
                         .and(dsl::payer_id.eq(debit_note_dsl::owner_id))),
                 )
-                .filter(dsl::id.eq_any(ids))
+                .filter(
+                    dsl::id
+                        .eq_any(ids.clone())
+                        .or(dsl::batch_order_id.eq_any(ids)),
+                )
                 .filter(dsl::driver.eq(driver))
                 .select((
                     dsl::id,
@@ -82,6 +101,48 @@ impl<'c> OrderDao<'c> {
                     dsl::debit_note_id,
                     dsl::allocation_id,
                     dsl::is_paid,
+                    dsl::settlement_due_date,
+                    dsl::batch_order_id,
+                    invoice_dsl::agreement_id.nullable(),
+                    debit_note_dsl::activity_id.nullable(),
+                ))
+                .load(conn)?;
+            Ok(orders)
+        })
+        .await
+    }
+
+    /// Orders awaiting aggregated settlement.
+    pub async fn get_deferred(&self) -> DbResult<Vec<ReadObj>> {
+        readonly_transaction(self.pool, move |conn| {
+            let orders = dsl::pay_order
+                .left_join(
+                    invoice_dsl::pay_invoice.on(dsl::invoice_id
+                        .eq(invoice_dsl::id.nullable())
+                        .and(dsl::payer_id.eq(invoice_dsl::owner_id))),
+                )
+                .left_join(
+                    debit_note_dsl::pay_debit_note.on(dsl::debit_note_id
+                        .eq(debit_note_dsl::id.nullable())
+                        .and(dsl::payer_id.eq(debit_note_dsl::owner_id))),
+                )
+                .filter(dsl::settlement_due_date.is_not_null())
+                .filter(dsl::batch_order_id.is_null())
+                .select((
+                    dsl::id,
+                    dsl::driver,
+                    dsl::amount,
+                    dsl::payee_id,
+                    dsl::payer_id,
+                    dsl::payee_addr,
+                    dsl::payer_addr,
+                    dsl::payment_platform,
+                    dsl::invoice_id,
+                    dsl::debit_note_id,
+                    dsl::allocation_id,
+                    dsl::is_paid,
+                    dsl::settlement_due_date,
+                    dsl::batch_order_id,
                     invoice_dsl::agreement_id.nullable(),
                     debit_note_dsl::activity_id.nullable(),
                 ))
@@ -90,4 +151,186 @@ impl<'c> OrderDao<'c> {
         })
         .await
     }
+
+    /// Marks deferred orders as being settled by `settlement_id`, so they aren't
+    /// picked up by another settlement. Returns false without claiming anything,
+    /// when any of them is already settled or being settled.
+    pub async fn claim(
+        &self,
+        ids: Vec<String>,
+        driver: String,
+        settlement_id: String,
+    ) -> DbResult<bool> {
+        do_with_transaction(self.pool, move |conn| {
+            let unsettled = dsl::pay_order
+                .filter(dsl::id.eq_any(ids.clone()))
+                .filter(dsl::driver.eq(driver.clone()))
+                .filter(dsl::settlement_due_date.is_not_null())
+                .filter(dsl::batch_order_id.is_null())
+                .count()
+                .get_result::<i64>(conn)?;
+            if unsettled != ids.len() as i64 {
+                return Ok(false);
+            }
+
+            diesel::update(
+                dsl::pay_order
+                    .filter(dsl::id.eq_any(ids))
+                    .filter(dsl::driver.eq(driver)),
+            )
+            .set(dsl::batch_order_id.eq(settlement_id))
+            .execute(conn)?;
+            Ok(true)
+        })
+        .await
+    }
+
+    /// Links orders claimed by `settlement_id` with the driver order paying all of them.
+    pub async fn settle(
+        &self,
+        settlement_id: String,
+        driver: String,
+        batch_order_id: String,
+    ) -> DbResult<()> {
+        do_with_transaction(self.pool, move |conn| {
+            diesel::update(
+                dsl::pay_order
+                    .filter(dsl::batch_order_id.eq(settlement_id))
+                    .filter(dsl::driver.eq(driver)),
+            )
+            .set(dsl::batch_order_id.eq(batch_order_id))
+            .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+
+    /// Returns orders claimed by `settlement_id` to deferred ones, when the driver
+    /// didn't schedule the payment.
+    pub async fn release(&self, settlement_id: String, driver: String) -> DbResult<()> {
+        do_with_transaction(self.pool, move |conn| {
+            diesel::update(
+                dsl::pay_order
+                    .filter(dsl::batch_order_id.eq(settlement_id))
+                    .filter(dsl::driver.eq(driver)),
+            )
+            .set(dsl::batch_order_id.eq(None::<String>))
+            .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dao::AllocationDao;
+    use bigdecimal::BigDecimal;
+    use ya_client_model::payment::NewAllocation;
+    use ya_client_model::NodeId;
+    use ya_persistence::executor::DbExecutor;
+
+    const NODE: &str = "0x0000000000000000000000000000000000000001";
+    const DRIVER: &str = "erc20";
+
+    async fn deferred_orders(db: &DbExecutor, ids: &[&str]) {
+        let node: NodeId = NODE.parse().unwrap();
+        let allocation_id = db
+            .as_dao::<AllocationDao>()
+            .create(
+                NewAllocation {
+                    address: None,
+                    payment_platform: None,
+                    total_amount: BigDecimal::from(10u64),
+                    timeout: None,
+                    make_deposit: false,
+                },
+                node,
+                "erc20-rinkeby-tglm".to_string(),
+                NODE.to_string(),
+            )
+            .await
+            .unwrap();
+
+        let orders = ids
+            .iter()
+            .map(|id| WriteObj {
+                id: id.to_string(),
+                driver: DRIVER.to_string(),
+                amount: BigDecimal::from(1u64).into(),
+                payee_id: node,
+                payer_id: node,
+                payee_addr: NODE.to_string(),
+                payer_addr: NODE.to_string(),
+                payment_platform: "erc20-rinkeby-tglm".to_string(),
+                invoice_id: None,
+                debit_note_id: Some(id.to_string()),
+                allocation_id: allocation_id.clone(),
+                is_paid: false,
+                settlement_due_date: Some(chrono::Utc::now().naive_utc()),
+                batch_order_id: None,
+            })
+            .collect::<Vec<_>>();
+        do_with_transaction(db.as_dao::<OrderDao>().pool, move |conn| {
+            diesel::insert_into(dsl::pay_order)
+                .values(orders)
+                .execute(conn)?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+    }
+
+    fn ids(orders: &[&str]) -> Vec<String> {
+        orders.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[actix_rt::test]
+    async fn orders_settled_once() {
+        let db = DbExecutor::in_memory("orders_settled_once").unwrap();
+        db.apply_migration(crate::migrations::run_with_output)
+            .unwrap();
+        deferred_orders(&db, &["1", "2", "3"]).await;
+        let dao = db.as_dao::<OrderDao>();
+
+        assert!(dao
+            .claim(ids(&["1", "2"]), DRIVER.into(), "first".into())
+            .await
+            .unwrap());
+        assert_eq!(dao.get_deferred().await.unwrap().len(), 1);
+        // Overlapping settlement doesn't claim anything.
+        assert!(!dao
+            .claim(ids(&["2", "3"]), DRIVER.into(), "second".into())
+            .await
+            .unwrap());
+        assert_eq!(dao.get_deferred().await.unwrap().len(), 1);
+
+        dao.settle("first".into(), DRIVER.into(), "batch".into())
+            .await
+            .unwrap();
+        let settled = dao.get_many(ids(&["batch"]), DRIVER.into()).await.unwrap();
+        assert_eq!(settled.len(), 2);
+        assert!(!dao
+            .claim(ids(&["1"]), DRIVER.into(), "third".into())
+            .await
+            .unwrap());
+    }
+
+    #[actix_rt::test]
+    async fn orders_released_after_failed_scheduling() {
+        let db = DbExecutor::in_memory("orders_released").unwrap();
+        db.apply_migration(crate::migrations::run_with_output)
+            .unwrap();
+        deferred_orders(&db, &["1", "2"]).await;
+        let dao = db.as_dao::<OrderDao>();
+
+        assert!(dao
+            .claim(ids(&["1", "2"]), DRIVER.into(), "first".into())
+            .await
+            .unwrap());
+        assert!(dao.get_deferred().await.unwrap().is_empty());
+        dao.release("first".into(), DRIVER.into()).await.unwrap();
+        assert_eq!(dao.get_deferred().await.unwrap().len(), 2);
+    }
 }
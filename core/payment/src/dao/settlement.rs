@@ -0,0 +1,44 @@
+use crate::error::DbResult;
+use crate::models::settlement::WriteObj;
+use crate::schema::pay_allocation_settlement::dsl;
+use diesel::{self, OptionalExtension, QueryDsl, RunQueryDsl};
+use ya_core_model::payment::local::SettlementPolicy;
+use ya_persistence::executor::{do_with_transaction, readonly_transaction, AsDao, PoolType};
+
+pub struct SettlementDao<'c> {
+    pool: &'c PoolType,
+}
+
+impl<'c> AsDao<'c> for SettlementDao<'c> {
+    fn as_dao(pool: &'c PoolType) -> Self {
+        Self { pool }
+    }
+}
+
+impl<'c> SettlementDao<'c> {
+    /// Allocations without explicitly configured policy are settled immediately.
+    pub async fn get(&self, allocation_id: String) -> DbResult<SettlementPolicy> {
+        readonly_transaction(self.pool, move |conn| {
+            let settlement: Option<WriteObj> = dsl::pay_allocation_settlement
+                .find(allocation_id)
+                .first(conn)
+                .optional()?;
+            match settlement {
+                Some(settlement) => settlement.policy(),
+                None => Ok(SettlementPolicy::default()),
+            }
+        })
+        .await
+    }
+
+    pub async fn set(&self, allocation_id: String, policy: SettlementPolicy) -> DbResult<()> {
+        let settlement = WriteObj::new(allocation_id, policy);
+        do_with_transaction(self.pool, move |conn| {
+            diesel::replace_into(dsl::pay_allocation_settlement)
+                .values(settlement)
+                .execute(conn)?;
+            Ok(())
+        })
+        .await
+    }
+}
@@ -12,6 +12,7 @@ use ya_service_bus::typed as bus;
 extern crate diesel;
 
 pub mod accounts;
+mod agreement_metrics;
 pub mod api;
 mod cli;
 pub mod dao;
@@ -22,6 +23,7 @@ pub mod schema;
 pub mod service;
 pub mod utils;
 mod wallet;
+pub mod webhook;
 
 pub mod migrations {
     #[derive(diesel_migrations::EmbedMigrations)]
@@ -54,6 +56,7 @@ impl PaymentService {
 
         let processor = PaymentProcessor::new(db.clone());
         self::service::bind_service(&db, processor.clone());
+        self::webhook::register_sources(&db);
 
         tokio::task::spawn(async move {
             processor.release_allocations(false).await;
@@ -7,3 +7,4 @@ pub mod invoice;
 pub mod invoice_event;
 pub mod order;
 pub mod payment;
+pub mod settlement;
@@ -1,4 +1,6 @@
 use crate::schema::pay_order;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
 use ya_client_model::NodeId;
 use ya_core_model::payment::local::{PaymentTitle, SchedulePayment};
 use ya_persistence::types::BigDecimalField;
@@ -18,6 +20,8 @@ pub struct WriteObj {
     pub debit_note_id: Option<String>,
     pub allocation_id: String,
     pub is_paid: bool,
+    pub settlement_due_date: Option<NaiveDateTime>,
+    pub batch_order_id: Option<String>,
 }
 
 #[derive(Queryable, Debug, Identifiable)]
@@ -35,12 +39,26 @@ pub struct ReadObj {
     pub debit_note_id: Option<String>,
     pub allocation_id: String,
     pub is_paid: bool,
+    pub settlement_due_date: Option<NaiveDateTime>,
+    pub batch_order_id: Option<String>,
 
     pub agreement_id: Option<String>, // From invoice
     pub activity_id: Option<String>,  // From debit note
 }
 
 impl WriteObj {
+    /// Order awaiting aggregated settlement, which has to happen before `settlement_due_date`.
+    pub fn deferred(
+        msg: SchedulePayment,
+        driver: String,
+        settlement_due_date: NaiveDateTime,
+    ) -> Self {
+        Self {
+            settlement_due_date: Some(settlement_due_date),
+            ..Self::new(msg, Uuid::new_v4().to_string(), driver)
+        }
+    }
+
     pub fn new(msg: SchedulePayment, id: String, driver: String) -> Self {
         let (invoice_id, debit_note_id) = match msg.title {
             PaymentTitle::DebitNote(title) => (None, Some(title.debit_note_id)),
@@ -59,6 +77,8 @@ impl WriteObj {
             debit_note_id,
             allocation_id: msg.allocation_id,
             is_paid: false,
+            settlement_due_date: None,
+            batch_order_id: None,
         }
     }
 }
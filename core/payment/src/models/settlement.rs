@@ -0,0 +1,49 @@
+use crate::error::{DbError, DbResult};
+use crate::schema::pay_allocation_settlement;
+use chrono::NaiveTime;
+use ya_core_model::payment::local::SettlementPolicy;
+use ya_persistence::types::BigDecimalField;
+
+const POLICY_IMMEDIATE: &str = "IMMEDIATE";
+const POLICY_DAILY: &str = "DAILY";
+const POLICY_THRESHOLD: &str = "THRESHOLD";
+
+#[derive(Debug, Insertable, Queryable, Identifiable)]
+#[table_name = "pay_allocation_settlement"]
+#[primary_key(allocation_id)]
+pub struct WriteObj {
+    pub allocation_id: String,
+    pub policy: String,
+    pub settle_at: Option<NaiveTime>,
+    pub threshold: Option<BigDecimalField>,
+}
+
+impl WriteObj {
+    pub fn new(allocation_id: String, policy: SettlementPolicy) -> Self {
+        let (policy, settle_at, threshold) = match policy {
+            SettlementPolicy::Immediate => (POLICY_IMMEDIATE, None, None),
+            SettlementPolicy::Daily { settle_at } => (POLICY_DAILY, Some(settle_at), None),
+            SettlementPolicy::Threshold { amount } => (POLICY_THRESHOLD, None, Some(amount.into())),
+        };
+        Self {
+            allocation_id,
+            policy: policy.to_string(),
+            settle_at,
+            threshold,
+        }
+    }
+
+    pub fn policy(self) -> DbResult<SettlementPolicy> {
+        match (self.policy.as_str(), self.settle_at, self.threshold) {
+            (POLICY_IMMEDIATE, _, _) => Ok(SettlementPolicy::Immediate),
+            (POLICY_DAILY, Some(settle_at), _) => Ok(SettlementPolicy::Daily { settle_at }),
+            (POLICY_THRESHOLD, _, Some(threshold)) => Ok(SettlementPolicy::Threshold {
+                amount: threshold.into(),
+            }),
+            _ => Err(DbError::Integrity(format!(
+                "Invalid settlement policy of allocation {}: {}",
+                self.allocation_id, self.policy
+            ))),
+        }
+    }
+}
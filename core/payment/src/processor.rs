@@ -1,5 +1,5 @@
 use crate::api::allocations::{forced_release_allocation, release_allocation_after};
-use crate::dao::{ActivityDao, AgreementDao, AllocationDao, OrderDao, PaymentDao};
+use crate::dao::{ActivityDao, AgreementDao, AllocationDao, OrderDao, PaymentDao, SettlementDao};
 use crate::error::processor::{
     AccountNotRegistered, GetStatusError, NotifyPaymentError, OrderValidationError,
     SchedulePaymentError, ValidateAllocationError, VerifyPaymentError,
@@ -7,11 +7,13 @@ use crate::error::processor::{
 use crate::models::order::ReadObj as DbOrder;
 use actix_web::web::Data;
 use bigdecimal::{BigDecimal, Zero};
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDateTime, TimeZone, Utc};
 use futures::FutureExt;
 use metrics::counter;
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::time::Duration;
+use uuid::Uuid;
 use ya_client_model::payment::{
     Account, ActivityPayment, AgreementPayment, DriverDetails, Network, Payment,
 };
@@ -21,7 +23,7 @@ use ya_core_model::driver::{
 };
 use ya_core_model::payment::local::{
     NotifyPayment, RegisterAccount, RegisterAccountError, RegisterDriver, RegisterDriverError,
-    SchedulePayment, UnregisterAccount, UnregisterDriver,
+    SchedulePayment, SettlementPolicy, UnregisterAccount, UnregisterDriver,
 };
 use ya_core_model::payment::public::{SendPayment, BUS_ID};
 use ya_net::RemoteEndpoint;
@@ -29,6 +31,16 @@ use ya_persistence::executor::DbExecutor;
 use ya_service_bus::typed::Endpoint;
 use ya_service_bus::{typed as bus, RpcEndpoint};
 
+lazy_static::lazy_static! {
+    /// Aggregated payments are handed over to the driver this long before their due date.
+    static ref SETTLEMENT_MARGIN: ChronoDuration = ChronoDuration::seconds(
+            std::env::var("PAYMENT_SETTLEMENT_MARGIN_SECS")
+                .ok()
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(300),
+        );
+}
+
 fn driver_endpoint(driver: &str) -> Endpoint {
     bus::service(driver_bus_id(driver))
 }
@@ -66,6 +78,78 @@ async fn validate_orders(
     Ok(())
 }
 
+/// Returns `None` when payment should be sent out immediately.
+fn settlement_due_date(
+    policy: &SettlementPolicy,
+    due_date: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> Option<NaiveDateTime> {
+    let latest = due_date - *SETTLEMENT_MARGIN;
+    let settlement_due_date = match policy {
+        SettlementPolicy::Immediate => return None,
+        SettlementPolicy::Daily { settle_at } => {
+            let today = now.date().and_time(*settle_at)?;
+            let next = match today > now {
+                true => today,
+                false => today + ChronoDuration::days(1),
+            };
+            next.min(latest)
+        }
+        SettlementPolicy::Threshold { .. } => latest,
+    };
+    match settlement_due_date > now {
+        true => Some(settlement_due_date.naive_utc()),
+        false => None,
+    }
+}
+
+/// Deferred orders are aggregated into a single payment per allocation and payee.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct SettlementKey {
+    driver: String,
+    allocation_id: String,
+    payer_addr: String,
+    payee_addr: String,
+    payment_platform: String,
+}
+
+impl From<&DbOrder> for SettlementKey {
+    fn from(order: &DbOrder) -> Self {
+        Self {
+            driver: order.driver.clone(),
+            allocation_id: order.allocation_id.clone(),
+            payer_addr: order.payer_addr.clone(),
+            payee_addr: order.payee_addr.clone(),
+            payment_platform: order.payment_platform.clone(),
+        }
+    }
+}
+
+/// Groups deferred orders, which are paid with a single payment.
+fn aggregate(orders: Vec<DbOrder>) -> HashMap<SettlementKey, Vec<DbOrder>> {
+    let mut batches: HashMap<SettlementKey, Vec<DbOrder>> = HashMap::new();
+    for order in orders {
+        batches
+            .entry(SettlementKey::from(&order))
+            .or_default()
+            .push(order);
+    }
+    batches
+}
+
+fn is_settlement_due(
+    policy: &SettlementPolicy,
+    amount: &BigDecimal,
+    due_date: NaiveDateTime,
+    now: NaiveDateTime,
+) -> bool {
+    match policy {
+        SettlementPolicy::Immediate => true,
+        SettlementPolicy::Threshold { amount: threshold } if amount >= threshold => true,
+        _ => due_date <= now,
+    }
+}
+
 #[derive(Clone, Debug)]
 struct AccountDetails {
     pub driver: String,
@@ -449,6 +533,26 @@ impl PaymentProcessor {
         let driver =
             self.registry
                 .driver(&msg.payment_platform, &msg.payer_addr, AccountMode::SEND)?;
+
+        let policy = self
+            .db_executor
+            .as_dao::<SettlementDao>()
+            .get(msg.allocation_id.clone())
+            .await?;
+        if let Some(settlement_due_date) = settlement_due_date(&policy, msg.due_date, Utc::now()) {
+            log::debug!(
+                "Deferring payment for document {} until {}. policy={:?}",
+                msg.document_id(),
+                settlement_due_date,
+                policy
+            );
+            self.db_executor
+                .as_dao::<OrderDao>()
+                .create_deferred(msg, driver, settlement_due_date)
+                .await?;
+            return Ok(());
+        }
+
         let order_id = driver_endpoint(&driver)
             .send(driver::SchedulePayment::new(
                 amount,
@@ -467,6 +571,88 @@ impl PaymentProcessor {
         Ok(())
     }
 
+    /// Sends out aggregated payments which reached their settlement date or threshold.
+    pub async fn process_settlements(&self) -> Result<(), SchedulePaymentError> {
+        let orders = self.db_executor.as_dao::<OrderDao>().get_deferred().await?;
+
+        let now = Utc::now().naive_utc();
+        for (key, orders) in aggregate(orders) {
+            let policy = self
+                .db_executor
+                .as_dao::<SettlementDao>()
+                .get(key.allocation_id.clone())
+                .await?;
+            let amount: BigDecimal = orders.iter().map(|order| &order.amount.0).sum();
+            let due_date = orders
+                .iter()
+                .filter_map(|order| order.settlement_due_date)
+                .min()
+                .unwrap_or(now);
+
+            if !is_settlement_due(&policy, &amount, due_date, now) {
+                continue;
+            }
+
+            if let Err(e) = self.settle(key, orders, amount, due_date).await {
+                log::error!("Failed to settle aggregated payment: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    async fn settle(
+        &self,
+        key: SettlementKey,
+        orders: Vec<DbOrder>,
+        amount: BigDecimal,
+        due_date: NaiveDateTime,
+    ) -> Result<(), SchedulePaymentError> {
+        log::info!(
+            "Settling {} payment(s) to {} with a single payment of {} {}",
+            orders.len(),
+            key.payee_addr,
+            amount,
+            key.payment_platform
+        );
+        // Orders are claimed before scheduling, so they can't be paid twice, even when
+        // settlements overlap or the driver order can't be linked with them afterwards.
+        let dao = self.db_executor.as_dao::<OrderDao>();
+        let settlement_id = Uuid::new_v4().to_string();
+        let ids = orders.into_iter().map(|order| order.id).collect();
+        if !dao
+            .claim(ids, key.driver.clone(), settlement_id.clone())
+            .await?
+        {
+            log::debug!("Payments to {} are already being settled", key.payee_addr);
+            return Ok(());
+        }
+
+        let scheduled: Result<String, SchedulePaymentError> = async {
+            Ok(driver_endpoint(&key.driver)
+                .send(driver::SchedulePayment::new(
+                    amount,
+                    key.payer_addr,
+                    key.payee_addr.clone(),
+                    key.payment_platform,
+                    Utc.from_utc_datetime(&due_date),
+                ))
+                .await??)
+        }
+        .await;
+
+        match scheduled {
+            Ok(batch_order_id) => {
+                dao.settle(settlement_id, key.driver, batch_order_id)
+                    .await?
+            }
+            Err(e) => {
+                dao.release(settlement_id, key.driver).await?;
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
     pub async fn verify_payment(
         &self,
         payment: Payment,
@@ -687,3 +873,135 @@ fn shut_down_driver(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveTime;
+
+    const PAYER: &str = "0x0000000000000000000000000000000000000001";
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.ymd(2022, 9, 15).and_hms(hour, minute, 0)
+    }
+
+    fn daily(hour: u32) -> SettlementPolicy {
+        SettlementPolicy::Daily {
+            settle_at: NaiveTime::from_hms(hour, 0, 0),
+        }
+    }
+
+    fn order(id: &str, payee_addr: &str, amount: u32, allocation_id: &str) -> DbOrder {
+        DbOrder {
+            id: id.to_string(),
+            driver: "erc20".to_string(),
+            amount: BigDecimal::from(amount).into(),
+            payee_id: PAYER.parse().unwrap(),
+            payer_id: PAYER.parse().unwrap(),
+            payee_addr: payee_addr.to_string(),
+            payer_addr: PAYER.to_string(),
+            payment_platform: "erc20-rinkeby-tglm".to_string(),
+            invoice_id: Some(id.to_string()),
+            debit_note_id: None,
+            allocation_id: allocation_id.to_string(),
+            is_paid: false,
+            settlement_due_date: Some(at(12, 0).naive_utc()),
+            batch_order_id: None,
+            agreement_id: None,
+            activity_id: None,
+        }
+    }
+
+    #[test]
+    fn immediate_settlement() {
+        let now = at(10, 0);
+        assert_eq!(
+            settlement_due_date(&SettlementPolicy::Immediate, at(23, 0), now),
+            None
+        );
+        let amount = BigDecimal::from(1);
+        let due_date = at(12, 0).naive_utc();
+        assert!(is_settlement_due(
+            &SettlementPolicy::Immediate,
+            &amount,
+            due_date,
+            now.naive_utc()
+        ));
+    }
+
+    #[test]
+    fn daily_settlement_due_date() {
+        let now = at(10, 0);
+        let due_date = now + ChronoDuration::days(7);
+
+        // Settled today, when settlement time didn't pass yet, otherwise tomorrow.
+        assert_eq!(
+            settlement_due_date(&daily(18), due_date, now),
+            Some(at(18, 0).naive_utc())
+        );
+        assert_eq!(
+            settlement_due_date(&daily(8), due_date, now),
+            Some((at(8, 0) + ChronoDuration::days(1)).naive_utc())
+        );
+
+        // Never later than payment due date, reduced by the margin.
+        assert_eq!(
+            settlement_due_date(&daily(18), at(13, 0), now),
+            Some((at(13, 0) - *SETTLEMENT_MARGIN).naive_utc())
+        );
+        // Payments due too soon are sent out immediately.
+        assert_eq!(settlement_due_date(&daily(18), at(10, 1), now), None);
+    }
+
+    #[test]
+    fn threshold_settlement() {
+        let now = at(10, 0);
+        let policy = SettlementPolicy::Threshold {
+            amount: BigDecimal::from(10),
+        };
+        assert_eq!(
+            settlement_due_date(&policy, at(13, 0), now),
+            Some((at(13, 0) - *SETTLEMENT_MARGIN).naive_utc())
+        );
+
+        let due_date = at(12, 0).naive_utc();
+        let due = |amount: u32, now: DateTime<Utc>| {
+            is_settlement_due(
+                &policy,
+                &BigDecimal::from(amount),
+                due_date,
+                now.naive_utc(),
+            )
+        };
+        assert!(!due(9, now));
+        assert!(due(10, now));
+        // Settled below the threshold, when the earliest payment is due.
+        assert!(due(9, at(12, 0)));
+        assert!(!is_settlement_due(
+            &daily(18),
+            &BigDecimal::from(100),
+            due_date,
+            now.naive_utc()
+        ));
+    }
+
+    #[test]
+    fn orders_aggregated_per_allocation_and_payee() {
+        let payee = "0x0000000000000000000000000000000000000002";
+        let other = "0x0000000000000000000000000000000000000003";
+        let batches = aggregate(vec![
+            order("1", payee, 1, "allocation"),
+            order("2", payee, 2, "allocation"),
+            order("3", other, 3, "allocation"),
+            order("4", payee, 4, "other-allocation"),
+        ]);
+        assert_eq!(batches.len(), 3);
+
+        let key = SettlementKey::from(&order("5", payee, 1, "allocation"));
+        let ids = batches[&key]
+            .iter()
+            .map(|order| order.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["1", "2"]);
+    }
+}
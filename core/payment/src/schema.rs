@@ -64,6 +64,15 @@ table! {
     }
 }
 
+table! {
+    pay_allocation_settlement (allocation_id) {
+        allocation_id -> Text,
+        policy -> Text,
+        settle_at -> Nullable<Time>,
+        threshold -> Nullable<Text>,
+    }
+}
+
 table! {
     pay_debit_note (id, owner_id) {
         id -> Text,
@@ -171,6 +180,8 @@ table! {
         debit_note_id -> Nullable<Text>,
         allocation_id -> Text,
         is_paid -> Bool,
+        settlement_due_date -> Nullable<Timestamp>,
+        batch_order_id -> Nullable<Text>,
     }
 }
 
@@ -191,6 +202,7 @@ table! {
 
 joinable!(pay_activity_payment -> pay_allocation (allocation_id));
 joinable!(pay_agreement_payment -> pay_allocation (allocation_id));
+joinable!(pay_allocation_settlement -> pay_allocation (allocation_id));
 joinable!(pay_debit_note -> pay_document_status (status));
 joinable!(pay_debit_note_event -> pay_event_type (event_type));
 joinable!(pay_invoice -> pay_document_status (status));
@@ -203,6 +215,7 @@ allow_tables_to_appear_in_same_query!(
     pay_agreement,
     pay_agreement_payment,
     pay_allocation,
+    pay_allocation_settlement,
     pay_debit_note,
     pay_debit_note_event,
     pay_debit_note_event_read,
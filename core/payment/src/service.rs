@@ -4,6 +4,7 @@ use futures::prelude::*;
 use metrics::counter;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use ya_core_model as core;
 use ya_persistence::executor::DbExecutor;
 use ya_service_bus::typed::ServiceBinder;
@@ -13,11 +14,31 @@ pub fn bind_service(db: &DbExecutor, processor: PaymentProcessor) {
 
     let processor = Arc::new(Mutex::new(processor));
     local::bind_service(db, processor.clone());
-    public::bind_service(db, processor);
+    public::bind_service(db, processor.clone());
+    tokio::task::spawn_local(process_settlements(processor));
 
     log::debug!("Successfully bound payment service to service bus");
 }
 
+lazy_static::lazy_static! {
+    static ref SETTLEMENT_INTERVAL: Duration = Duration::from_secs(
+            std::env::var("PAYMENT_SETTLEMENT_INTERVAL_SECS")
+                .ok()
+                .and_then(|x| x.parse().ok())
+                .unwrap_or(60),
+        );
+}
+
+async fn process_settlements(processor: Arc<Mutex<PaymentProcessor>>) {
+    let mut interval = tokio::time::interval(*SETTLEMENT_INTERVAL);
+    loop {
+        interval.tick().await;
+        if let Err(e) = processor.lock().await.process_settlements().await {
+            log::error!("Failed to process aggregated payments: {}", e);
+        }
+    }
+}
+
 mod local {
     use super::*;
     use crate::dao::*;
@@ -42,6 +63,8 @@ mod local {
             .bind_with_processor(get_accounts)
             .bind_with_processor(validate_allocation)
             .bind_with_processor(release_allocations)
+            .bind_with_processor(set_settlement_policy)
+            .bind_with_processor(get_settlement_policy)
             .bind_with_processor(get_drivers)
             .bind_with_processor(shut_down);
 
@@ -332,6 +355,30 @@ mod local {
         Ok(processor.lock().await.release_allocations(true).await)
     }
 
+    async fn set_settlement_policy(
+        db: DbExecutor,
+        processor: Arc<Mutex<PaymentProcessor>>,
+        _caller: String,
+        msg: SetSettlementPolicy,
+    ) -> Result<(), GenericError> {
+        db.as_dao::<SettlementDao>()
+            .set(msg.allocation_id, msg.policy)
+            .await
+            .map_err(GenericError::new)
+    }
+
+    async fn get_settlement_policy(
+        db: DbExecutor,
+        processor: Arc<Mutex<PaymentProcessor>>,
+        _caller: String,
+        msg: GetSettlementPolicy,
+    ) -> Result<SettlementPolicy, GenericError> {
+        db.as_dao::<SettlementDao>()
+            .get(msg.allocation_id)
+            .await
+            .map_err(GenericError::new)
+    }
+
     async fn get_drivers(
         db: DbExecutor,
         processor: Arc<Mutex<PaymentProcessor>>,
@@ -357,6 +404,7 @@ mod local {
 mod public {
     use super::*;
 
+    use crate::agreement_metrics::AgreementMetrics;
     use crate::dao::*;
     use crate::error::DbError;
     use crate::utils::*;
@@ -364,6 +412,7 @@ mod public {
     use crate::error::processor::VerifyPaymentError;
     use ya_client_model::payment::*;
     use ya_core_model::payment::public::*;
+    use ya_metrics::agreement_counter;
     use ya_persistence::types::Role;
 
     pub fn bind_service(db: &DbExecutor, processor: Arc<Mutex<PaymentProcessor>>) {
@@ -422,23 +471,35 @@ mod public {
         }
 
         let node_id = agreement.requestor_id().clone();
+        let metrics = AgreementMetrics::new(&agreement, Role::Requestor);
+        let total_amount_due = debit_note.total_amount_due.clone();
+        let usage_counter_vector = debit_note.usage_counter_vector.clone();
         match async move {
             db.as_dao::<AgreementDao>()
                 .create_if_not_exists(agreement, node_id, Role::Requestor)
                 .await?;
             db.as_dao::<ActivityDao>()
-                .create_if_not_exists(activity_id, node_id, Role::Requestor, agreement_id)
+                .create_if_not_exists(activity_id.clone(), node_id, Role::Requestor, agreement_id)
                 .await?;
             db.as_dao::<DebitNoteDao>()
                 .insert_received(debit_note)
                 .await?;
+            metrics.record_debit_note(
+                &activity_id,
+                &total_amount_due,
+                usage_counter_vector.as_ref(),
+            );
 
             log::info!(
                 "DebitNote [{}] received from node [{}].",
                 debit_note_id,
                 issuer_id
             );
-            counter!("payment.debit_notes.requestor.received", 1);
+            agreement_counter!(
+                "payment.debit_notes.requestor.received",
+                1,
+                metrics.labels()
+            );
             Ok(())
         }
         .await
@@ -580,6 +641,8 @@ mod public {
         }
 
         let node_id = agreement.requestor_id().clone();
+        let metrics = AgreementMetrics::new(&agreement, Role::Requestor);
+        let amount = invoice.amount.clone();
         match async move {
             db.as_dao::<AgreementDao>()
                 .create_if_not_exists(agreement, node_id, Role::Requestor)
@@ -597,9 +660,10 @@ mod public {
             }
 
             db.as_dao::<InvoiceDao>().insert_received(invoice).await?;
+            metrics.record_invoice(&amount);
 
             log::info!("Invoice [{}] received from node [{}].", node_id, invoice_id);
-            counter!("payment.invoices.requestor.received", 1);
+            agreement_counter!("payment.invoices.requestor.received", 1, metrics.labels());
             Ok(())
         }
         .await
@@ -0,0 +1,128 @@
+//! Invoice and DebitNote events delivered by webhooks.
+//! Cursor is the timestamp of last delivered event, the same way as
+//! `afterTimestamp` of REST API.
+
+use chrono::{DateTime, NaiveDateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use std::future::Future;
+use std::time::Duration;
+
+use ya_client_model::payment::{DebitNoteEvent, InvoiceEvent};
+use ya_client_model::NodeId;
+use ya_persistence::executor::DbExecutor;
+use ya_service_api_web::webhook::{register_source, EventBatch, EventSource};
+
+use crate::api::{default_events, DEFAULT_PROVIDER_EVENTS, DEFAULT_REQUESTOR_EVENTS};
+use crate::dao::{DebitNoteEventDao, InvoiceEventDao};
+use crate::error::DbResult;
+use crate::utils::listen_for_events;
+
+pub const INVOICE_EVENTS_SOURCE: &str = "invoices";
+pub const DEBIT_NOTE_EVENTS_SOURCE: &str = "debitNotes";
+
+const MAX_EVENTS: u32 = 100;
+
+pub fn register_sources(db: &DbExecutor) {
+    register_source(INVOICE_EVENTS_SOURCE, InvoiceEvents { db: db.clone() });
+    register_source(DEBIT_NOTE_EVENTS_SOURCE, DebitNoteEvents { db: db.clone() });
+}
+
+struct InvoiceEvents {
+    db: DbExecutor,
+}
+
+struct DebitNoteEvents {
+    db: DbExecutor,
+}
+
+#[async_trait::async_trait(?Send)]
+impl EventSource for InvoiceEvents {
+    async fn poll(
+        &self,
+        owner: NodeId,
+        subject: Option<&str>,
+        _event_types: &[String],
+        cursor: Option<String>,
+        timeout: Duration,
+    ) -> anyhow::Result<EventBatch> {
+        let dao: InvoiceEventDao = self.db.as_dao();
+        let app_session_id = subject.map(ToString::to_string);
+        poll_events(
+            cursor,
+            timeout,
+            |after_timestamp| {
+                dao.get_for_node_id(
+                    owner,
+                    Some(after_timestamp),
+                    Some(MAX_EVENTS),
+                    app_session_id.clone(),
+                    default_events(DEFAULT_REQUESTOR_EVENTS),
+                    default_events(DEFAULT_PROVIDER_EVENTS),
+                )
+            },
+            |event: &InvoiceEvent| event.event_date,
+        )
+        .await
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl EventSource for DebitNoteEvents {
+    async fn poll(
+        &self,
+        owner: NodeId,
+        subject: Option<&str>,
+        _event_types: &[String],
+        cursor: Option<String>,
+        timeout: Duration,
+    ) -> anyhow::Result<EventBatch> {
+        let dao: DebitNoteEventDao = self.db.as_dao();
+        let app_session_id = subject.map(ToString::to_string);
+        poll_events(
+            cursor,
+            timeout,
+            |after_timestamp| {
+                dao.get_for_node_id(
+                    owner,
+                    Some(after_timestamp),
+                    Some(MAX_EVENTS),
+                    app_session_id.clone(),
+                    default_events(DEFAULT_REQUESTOR_EVENTS),
+                    default_events(DEFAULT_PROVIDER_EVENTS),
+                )
+            },
+            |event: &DebitNoteEvent| event.event_date,
+        )
+        .await
+    }
+}
+
+/// New webhooks get only events created after their registration.
+async fn poll_events<E, G, F>(
+    cursor: Option<String>,
+    timeout: Duration,
+    get_events: G,
+    event_date: fn(&E) -> DateTime<Utc>,
+) -> anyhow::Result<EventBatch>
+where
+    E: Serialize,
+    G: Fn(NaiveDateTime) -> F,
+    F: Future<Output = DbResult<Vec<E>>>,
+{
+    let after_timestamp = match cursor {
+        Some(cursor) => DateTime::parse_from_rfc3339(&cursor)?.with_timezone(&Utc),
+        None => Utc::now(),
+    };
+
+    let getter = || get_events(after_timestamp.naive_utc());
+    let events = listen_for_events(getter, timeout.as_secs_f64()).await?;
+
+    let last_timestamp = events.last().map(event_date).unwrap_or(after_timestamp);
+    Ok(EventBatch {
+        events: events
+            .iter()
+            .map(serde_json::to_value)
+            .collect::<Result<_, _>>()?,
+        cursor: Some(last_timestamp.to_rfc3339_opts(SecondsFormat::Micros, true)),
+    })
+}
@@ -0,0 +1,18 @@
+//! Drain mode of the daemon. When draining, services finish work that was
+//! already started, but refuse to start anything new, so the node can be
+//! shut down without breaking Agreements in the middle of computation.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static DRAINING: AtomicBool = AtomicBool::new(false);
+
+/// Stops accepting new Offers, Demands and Activities. There is no way back,
+/// drain mode ends with the daemon shutdown.
+pub fn start_draining() {
+    log::info!("Node is draining. New Offers, Demands and Activities will be rejected.");
+    DRAINING.store(true, Ordering::SeqCst);
+}
+
+pub fn is_draining() -> bool {
+    DRAINING.load(Ordering::SeqCst)
+}
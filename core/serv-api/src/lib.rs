@@ -4,6 +4,8 @@ use serde::Serialize;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
+pub mod drain;
+
 #[derive(Clone, Debug, Default)]
 pub struct MetricsCtx {
     pub push_enabled: bool,
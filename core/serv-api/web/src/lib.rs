@@ -1,8 +1,22 @@
 pub mod middleware;
 pub mod scope;
+pub mod webhook;
+
+use std::path::PathBuf;
 
 pub use ya_client::web::{rest_api_url, DEFAULT_YAGNA_API_URL, YAGNA_API_URL_ENV_VAR};
 
+/// Scheme of API URL pointing to Unix domain socket, e.g. `unix:///run/yagna/api.sock`.
+pub const UNIX_SOCKET_SCHEME: &str = "unix";
+pub const TLS_SCHEME: &str = "https";
+
+/// Where REST API server listens, derived from API URL.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RestApiListener {
+    Tcp { host_port: String, tls: bool },
+    Unix(PathBuf),
+}
+
 pub fn rest_api_addr() -> String {
     rest_api_host_port(rest_api_url())
 }
@@ -18,3 +32,13 @@ pub fn rest_api_host_port(api_url: url::Url) -> String {
 
     format!("{}:{}", host, port)
 }
+
+pub fn rest_api_listener(api_url: url::Url) -> RestApiListener {
+    match api_url.scheme() {
+        UNIX_SOCKET_SCHEME => RestApiListener::Unix(PathBuf::from(api_url.path())),
+        scheme => RestApiListener::Tcp {
+            tls: scheme == TLS_SCHEME,
+            host_port: rest_api_host_port(api_url),
+        },
+    }
+}
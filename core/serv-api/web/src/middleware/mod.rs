@@ -1,3 +1,5 @@
 pub mod auth;
+pub mod trace;
 
 pub use auth::{ident::Identity, Auth, AuthMiddleware};
+pub use trace::{Trace, TraceMiddleware};
@@ -0,0 +1,102 @@
+//! Starts OpenTelemetry span for each REST API request, continuing trace
+//! passed in `traceparent` header.
+
+use actix_service::{Service, Transform};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::error::Error;
+use futures::future::{ok, Future, Ready};
+use std::cell::RefCell;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+
+use ya_utils_telemetry::{
+    self as telemetry, KeyValue, SpanKind, StatusCode, TraceContextExt, TraceFutureExt,
+};
+
+#[derive(Default)]
+pub struct Trace;
+
+impl<S, B> Transform<S, ServiceRequest> for Trace
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = TraceMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(TraceMiddleware {
+            service: Rc::new(RefCell::new(service)),
+        })
+    }
+}
+
+pub struct TraceMiddleware<S> {
+    service: Rc<RefCell<S>>,
+}
+
+impl<S, B> Service<ServiceRequest> for TraceMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.service.borrow_mut().poll_ready(cx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let parent = req
+            .headers()
+            .get(telemetry::TRACEPARENT)
+            .and_then(|value| value.to_str().ok())
+            .map(telemetry::extract)
+            .unwrap_or_else(telemetry::Context::new);
+
+        let method = req.method().to_string();
+        let otel_cx = telemetry::start_span(
+            format!("{} {}", method, req.path()),
+            SpanKind::Server,
+            &parent,
+        );
+        otel_cx
+            .span()
+            .set_attribute(KeyValue::new("http.method", method.clone()));
+        otel_cx
+            .span()
+            .set_attribute(KeyValue::new("http.target", req.uri().to_string()));
+
+        let fut = {
+            let _guard = otel_cx.clone().attach();
+            self.service.borrow_mut().call(req)
+        };
+
+        Box::pin(async move {
+            let result = fut.with_context(otel_cx.clone()).await;
+            let span = otel_cx.span();
+            match &result {
+                Ok(res) => {
+                    // Path parameters are replaced with their names, once request was routed.
+                    if let Some(pattern) = res.request().match_pattern() {
+                        span.update_name::<String>(format!("{} {}", method, pattern));
+                    }
+                    let status = res.status();
+                    span.set_attribute(KeyValue::new("http.status_code", status.as_u16() as i64));
+                    if status.is_server_error() {
+                        span.set_status(StatusCode::Error, status.to_string());
+                    }
+                }
+                Err(e) => span.set_status(StatusCode::Error, e.to_string()),
+            }
+            result
+        })
+    }
+}
@@ -0,0 +1,143 @@
+use actix_web::web::{Json, Path};
+use actix_web::{HttpResponse, Responder, ResponseError, Scope};
+use chrono::Utc;
+use serde::Deserialize;
+
+use ya_client::model::ErrorMessage;
+
+use super::source;
+use super::{dispatcher, registry, NewWebhook, Webhook, WebhookError};
+use crate::middleware::Identity;
+
+pub const WEBHOOK_API_PATH: &str = "webhook-api/v1";
+
+pub fn web_scope() -> Scope {
+    Scope::new(WEBHOOK_API_PATH)
+        .service(create_webhook)
+        .service(get_webhooks)
+        .service(get_webhook)
+        .service(delete_webhook)
+        .service(get_dead_letters)
+        .service(clear_dead_letters)
+        .service(retry_dead_letter)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PathWebhook {
+    webhook_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PathDeadLetter {
+    webhook_id: String,
+    delivery_id: String,
+}
+
+#[actix_web::post("/webhooks")]
+async fn create_webhook(body: Json<NewWebhook>, id: Identity) -> impl Responder {
+    let new_webhook = body.into_inner();
+    validate_url(&new_webhook.url)?;
+
+    let source = source::source(&new_webhook.source).ok_or_else(|| {
+        WebhookError::UnknownSource(
+            new_webhook.source.clone(),
+            source::source_names().join(", "),
+        )
+    })?;
+    source
+        .validate(id.identity, new_webhook.subject.as_deref())
+        .await
+        .map_err(|e| WebhookError::InvalidSubject(e.to_string()))?;
+
+    let secret = new_webhook.secret.unwrap_or_else(generate_secret);
+    let webhook = Webhook {
+        webhook_id: uuid::Uuid::new_v4().to_simple().to_string(),
+        url: new_webhook.url,
+        source: new_webhook.source,
+        subject: new_webhook.subject,
+        event_types: new_webhook.event_types,
+        secret: Some(secret.clone()),
+        created_at: Utc::now(),
+        delivered_events: 0,
+        dead_letters: 0,
+        last_error: None,
+    };
+
+    let hook = registry::insert(id.identity, webhook.clone(), secret);
+    dispatcher::spawn(hook);
+
+    log::info!(
+        "Registered webhook [{}] delivering '{}' events to {}",
+        webhook.webhook_id,
+        webhook.source,
+        webhook.url
+    );
+    Ok::<_, WebhookError>(HttpResponse::Created().json(webhook))
+}
+
+#[actix_web::get("/webhooks")]
+async fn get_webhooks(id: Identity) -> impl Responder {
+    HttpResponse::Ok().json(registry::list(id.identity))
+}
+
+#[actix_web::get("/webhooks/{webhook_id}")]
+async fn get_webhook(path: Path<PathWebhook>, id: Identity) -> impl Responder {
+    registry::get(id.identity, &path.webhook_id).map(|webhook| HttpResponse::Ok().json(webhook))
+}
+
+#[actix_web::delete("/webhooks/{webhook_id}")]
+async fn delete_webhook(path: Path<PathWebhook>, id: Identity) -> impl Responder {
+    registry::remove(id.identity, &path.webhook_id).map(|_| HttpResponse::NoContent().finish())
+}
+
+#[actix_web::get("/webhooks/{webhook_id}/deadLetters")]
+async fn get_dead_letters(path: Path<PathWebhook>, id: Identity) -> impl Responder {
+    registry::dead_letters(id.identity, &path.webhook_id)
+        .map(|letters| HttpResponse::Ok().json(letters))
+}
+
+#[actix_web::delete("/webhooks/{webhook_id}/deadLetters")]
+async fn clear_dead_letters(path: Path<PathWebhook>, id: Identity) -> impl Responder {
+    registry::clear_dead_letters(id.identity, &path.webhook_id)
+        .map(|_| HttpResponse::NoContent().finish())
+}
+
+#[actix_web::post("/webhooks/{webhook_id}/deadLetters/{delivery_id}/retry")]
+async fn retry_dead_letter(path: Path<PathDeadLetter>, id: Identity) -> impl Responder {
+    let (hook, letter) =
+        registry::take_dead_letter(id.identity, &path.webhook_id, &path.delivery_id)?;
+    dispatcher::spawn_retry(hook, letter);
+    Ok::<_, WebhookError>(HttpResponse::Accepted().finish())
+}
+
+fn validate_url(url: &str) -> Result<(), WebhookError> {
+    let parsed =
+        url::Url::parse(url).map_err(|e| WebhookError::InvalidUrl(url.into(), e.to_string()))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(()),
+        scheme => Err(WebhookError::InvalidUrl(
+            url.into(),
+            format!("unsupported scheme '{}'", scheme),
+        )),
+    }
+}
+
+fn generate_secret() -> String {
+    hex::encode(rand::random::<[u8; 32]>())
+}
+
+impl ResponseError for WebhookError {
+    fn error_response(&self) -> HttpResponse {
+        let msg = ErrorMessage::new(self.to_string());
+        match self {
+            WebhookError::NotFound(_) | WebhookError::DeadLetterNotFound(..) => {
+                HttpResponse::NotFound().json(msg)
+            }
+            WebhookError::UnknownSource(..)
+            | WebhookError::InvalidUrl(..)
+            | WebhookError::InvalidSubject(_) => HttpResponse::BadRequest().json(msg),
+        }
+    }
+}
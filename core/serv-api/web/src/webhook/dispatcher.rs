@@ -0,0 +1,503 @@
+use chrono::Utc;
+use hmac::{Hmac, Mac, NewMac};
+use sha2::Sha256;
+use std::time::Duration;
+
+use super::registry::{self, Hook};
+use super::source;
+use super::{DeadLetter, Delivery, DELIVERY_ID_HEADER, SIGNATURE_HEADER, WEBHOOK_ID_HEADER};
+
+const POLL_TIMEOUT: Duration = Duration::from_secs(10);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const RETRIES: Retries = Retries {
+    max_attempts: 5,
+    initial_backoff: Duration::from_secs(1),
+    max_backoff: Duration::from_secs(60),
+};
+
+#[derive(Clone, Copy)]
+struct Retries {
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+/// Starts delivering events of webhook's source until webhook is removed.
+pub(super) fn spawn(hook: Hook) {
+    actix_rt::spawn(async move {
+        log::debug!("Starting webhook [{}] for '{}'", hook.id, hook.source);
+        run(&hook).await;
+        log::debug!("Webhook [{}] stopped", hook.id);
+    });
+}
+
+/// Delivers dead letter once again. On failure letter returns to dead letters.
+pub(super) fn spawn_retry(hook: Hook, letter: DeadLetter) {
+    actix_rt::spawn(async move {
+        let client = client();
+        let delivery = Delivery {
+            webhook_id: hook.id.clone(),
+            delivery_id: letter.delivery_id,
+            source: hook.source.clone(),
+            events: letter.events,
+        };
+        deliver(&client, &hook, delivery, RETRIES).await;
+    });
+}
+
+async fn run(hook: &Hook) {
+    run_with(hook, RETRIES).await
+}
+
+async fn run_with(hook: &Hook, retries: Retries) {
+    let client = client();
+    let mut cursor = None;
+    let mut backoff = Backoff::new(retries);
+
+    while registry::is_active(&hook.id) {
+        let source = match source::source(&hook.source) {
+            Some(source) => source,
+            None => break,
+        };
+
+        let subject = hook.subject.as_deref();
+        let batch = match source
+            .poll(
+                hook.owner,
+                subject,
+                &hook.event_types,
+                cursor.clone(),
+                POLL_TIMEOUT,
+            )
+            .await
+        {
+            Ok(batch) => {
+                backoff.reset();
+                batch
+            }
+            Err(e) => {
+                log::debug!("Webhook [{}] failed to get events: {}", hook.id, e);
+                registry::record_error(&hook.id, format!("Failed to get events: {}", e));
+                backoff.wait().await;
+                continue;
+            }
+        };
+
+        let events: Vec<_> = batch
+            .events
+            .into_iter()
+            .filter(|event| hook.accepts(event))
+            .collect();
+        if !events.is_empty() {
+            let delivery = Delivery {
+                webhook_id: hook.id.clone(),
+                delivery_id: uuid::Uuid::new_v4().to_simple().to_string(),
+                source: hook.source.clone(),
+                events,
+            };
+            if !deliver(&client, hook, delivery, retries).await {
+                break;
+            }
+        }
+
+        if let Some(next) = batch.cursor {
+            if let Err(e) = source.ack(hook.owner, subject, &next).await {
+                log::warn!("Webhook [{}] failed to acknowledge events: {}", hook.id, e);
+            }
+            cursor = Some(next);
+        }
+    }
+}
+
+/// Returns false, when webhook was removed before delivery finished.
+async fn deliver(client: &awc::Client, hook: &Hook, delivery: Delivery, retries: Retries) -> bool {
+    let body = match serde_json::to_vec(&delivery) {
+        Ok(body) => body,
+        Err(e) => {
+            log::error!("Webhook [{}] failed to serialize events: {}", hook.id, e);
+            return true;
+        }
+    };
+    let signature = sign(&hook.secret, &body);
+
+    let mut backoff = Backoff::new(retries);
+    let mut error = String::new();
+    for attempt in 1..=retries.max_attempts {
+        match send(
+            client,
+            hook,
+            &delivery.delivery_id,
+            &signature,
+            body.clone(),
+        )
+        .await
+        {
+            Ok(()) => {
+                registry::record_delivered(&hook.id, delivery.events.len());
+                return true;
+            }
+            Err(e) => {
+                log::debug!(
+                    "Webhook [{}] delivery [{}] attempt {} failed: {}",
+                    hook.id,
+                    delivery.delivery_id,
+                    attempt,
+                    e
+                );
+                error = e.to_string();
+            }
+        }
+
+        if attempt < retries.max_attempts {
+            backoff.wait().await;
+            if !registry::is_active(&hook.id) {
+                return false;
+            }
+        }
+    }
+
+    log::warn!(
+        "Webhook [{}] delivery [{}] moved to dead letters after {} attempts: {}",
+        hook.id,
+        delivery.delivery_id,
+        retries.max_attempts,
+        error
+    );
+    registry::push_dead_letter(
+        &hook.id,
+        DeadLetter {
+            delivery_id: delivery.delivery_id,
+            timestamp: Utc::now(),
+            attempts: retries.max_attempts,
+            error,
+            events: delivery.events,
+        },
+    );
+    true
+}
+
+async fn send(
+    client: &awc::Client,
+    hook: &Hook,
+    delivery_id: &str,
+    signature: &str,
+    body: Vec<u8>,
+) -> anyhow::Result<()> {
+    let response = client
+        .post(&hook.url)
+        .content_type("application/json")
+        .insert_header((WEBHOOK_ID_HEADER, hook.id.as_str()))
+        .insert_header((DELIVERY_ID_HEADER, delivery_id))
+        .insert_header((SIGNATURE_HEADER, signature))
+        .send_body(body)
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e))?;
+
+    match response.status() {
+        status if status.is_success() => Ok(()),
+        status => anyhow::bail!("callback responded with {}", status),
+    }
+}
+
+fn client() -> awc::Client {
+    awc::Client::builder().timeout(REQUEST_TIMEOUT).finish()
+}
+
+/// Value of signature header: HMAC-SHA256 of request body.
+fn sign(secret: &str, body: &[u8]) -> String {
+    // HMAC accepts keys of any length.
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+struct Backoff {
+    retries: Retries,
+    next: Duration,
+}
+
+impl Backoff {
+    fn new(retries: Retries) -> Self {
+        Backoff {
+            retries,
+            next: retries.initial_backoff,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.next = self.retries.initial_backoff;
+    }
+
+    async fn wait(&mut self) {
+        actix_rt::time::sleep(self.next).await;
+        self.next = (self.next * 2).min(self.retries.max_backoff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::webhook::{register_source, EventBatch, EventSource, Webhook};
+    use actix_web::http::StatusCode;
+    use actix_web::web::Bytes;
+    use actix_web::{App, HttpRequest, HttpResponse, HttpServer};
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    use ya_client::model::NodeId;
+
+    const FAST_RETRIES: Retries = Retries {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(20),
+    };
+
+    /// Callback server responding with given statuses, then with 200.
+    #[derive(Clone, Default)]
+    struct Callback {
+        statuses: Arc<Mutex<VecDeque<u16>>>,
+        requests: Arc<Mutex<Vec<(String, Vec<u8>)>>>,
+    }
+
+    impl Callback {
+        fn start(statuses: &[u16]) -> (Callback, String) {
+            let callback = Callback {
+                statuses: Arc::new(Mutex::new(statuses.iter().cloned().collect())),
+                ..Default::default()
+            };
+            let state = callback.clone();
+            let server = HttpServer::new(move || {
+                let state = state.clone();
+                App::new().default_service(actix_web::web::to(
+                    move |request: HttpRequest, body: Bytes| {
+                        let state = state.clone();
+                        async move { state.respond(request, body) }
+                    },
+                ))
+            })
+            .workers(1)
+            .bind("127.0.0.1:0")
+            .unwrap();
+            let url = format!("http://{}/events", server.addrs()[0]);
+            actix_rt::spawn(server.run());
+            (callback, url)
+        }
+
+        fn respond(&self, request: HttpRequest, body: Bytes) -> HttpResponse {
+            let signature = request
+                .headers()
+                .get(SIGNATURE_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default()
+                .to_string();
+            self.requests
+                .lock()
+                .unwrap()
+                .push((signature, body.to_vec()));
+            let status = self.statuses.lock().unwrap().pop_front().unwrap_or(200);
+            HttpResponse::build(StatusCode::from_u16(status).unwrap()).finish()
+        }
+
+        fn requests(&self) -> Vec<(String, Vec<u8>)> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    /// Returns given events once and records, how it was used.
+    #[derive(Default)]
+    struct TestSource {
+        events: Mutex<Option<Vec<serde_json::Value>>>,
+        event_types: Mutex<Vec<String>>,
+        acks: Mutex<Vec<String>>,
+    }
+
+    #[derive(Clone)]
+    struct SharedSource(Arc<TestSource>);
+
+    #[async_trait::async_trait(?Send)]
+    impl EventSource for SharedSource {
+        async fn poll(
+            &self,
+            _owner: NodeId,
+            _subject: Option<&str>,
+            event_types: &[String],
+            _cursor: Option<String>,
+            _timeout: Duration,
+        ) -> anyhow::Result<EventBatch> {
+            *self.0.event_types.lock().unwrap() = event_types.to_vec();
+            match self.0.events.lock().unwrap().take() {
+                Some(events) => Ok(EventBatch {
+                    events,
+                    cursor: Some("cursor-1".to_string()),
+                }),
+                None => {
+                    actix_rt::time::sleep(Duration::from_millis(10)).await;
+                    Ok(EventBatch::default())
+                }
+            }
+        }
+
+        async fn ack(
+            &self,
+            _owner: NodeId,
+            _subject: Option<&str>,
+            cursor: &str,
+        ) -> anyhow::Result<()> {
+            self.0.acks.lock().unwrap().push(cursor.to_string());
+            Ok(())
+        }
+    }
+
+    fn owner() -> NodeId {
+        "0xbabe000000000000000000000000000000000000"
+            .parse()
+            .unwrap()
+    }
+
+    fn register(url: String, source: &str, event_types: &[&str]) -> Hook {
+        let webhook = Webhook {
+            webhook_id: uuid::Uuid::new_v4().to_simple().to_string(),
+            url,
+            source: source.to_string(),
+            subject: None,
+            event_types: event_types.iter().map(ToString::to_string).collect(),
+            secret: None,
+            created_at: Utc::now(),
+            delivered_events: 0,
+            dead_letters: 0,
+            last_error: None,
+        };
+        registry::insert(owner(), webhook, "secret".to_string())
+    }
+
+    fn delivery(hook: &Hook, events: Vec<serde_json::Value>) -> Delivery {
+        Delivery {
+            webhook_id: hook.id.clone(),
+            delivery_id: uuid::Uuid::new_v4().to_simple().to_string(),
+            source: hook.source.clone(),
+            events,
+        }
+    }
+
+    fn events(body: &[u8]) -> Vec<serde_json::Value> {
+        let body: serde_json::Value = serde_json::from_slice(body).unwrap();
+        body["events"].as_array().unwrap().clone()
+    }
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(
+            sign("secret", br#"{"events":[]}"#),
+            "sha256=a642b59553c93e227ec0f2f38910fbf71231a2197c00899833c00478cec86f34"
+        );
+        assert_ne!(
+            sign("other", br#"{"events":[]}"#),
+            sign("secret", br#"{"events":[]}"#)
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_delivery_signed_with_webhook_secret() {
+        let (callback, url) = Callback::start(&[]);
+        let hook = register(url, "test", &[]);
+        let event = serde_json::json!({"eventType": "ProposalEvent"});
+
+        assert!(
+            deliver(
+                &client(),
+                &hook,
+                delivery(&hook, vec![event.clone()]),
+                FAST_RETRIES
+            )
+            .await
+        );
+
+        let requests = callback.requests();
+        assert_eq!(requests.len(), 1);
+        let (signature, body) = &requests[0];
+        assert_eq!(signature, &sign("secret", body));
+        assert_eq!(events(body), vec![event]);
+        assert_eq!(
+            registry::get(owner(), &hook.id).unwrap().delivered_events,
+            1
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_failed_delivery_retried() {
+        let (callback, url) = Callback::start(&[500, 503]);
+        let hook = register(url, "test", &[]);
+        let event = serde_json::json!({"eventType": "ProposalEvent"});
+
+        assert!(deliver(&client(), &hook, delivery(&hook, vec![event]), FAST_RETRIES).await);
+
+        let requests = callback.requests();
+        assert_eq!(requests.len(), 3);
+        assert!(requests.iter().all(|request| request == &requests[0]));
+        assert!(registry::dead_letters(owner(), &hook.id)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_undelivered_events_moved_to_dead_letters() {
+        let (callback, url) = Callback::start(&[500, 500, 500, 500]);
+        let hook = register(url, "test", &[]);
+        let event = serde_json::json!({"eventType": "ProposalEvent"});
+        let delivery = delivery(&hook, vec![event.clone()]);
+        let delivery_id = delivery.delivery_id.clone();
+
+        assert!(deliver(&client(), &hook, delivery, FAST_RETRIES).await);
+        assert_eq!(callback.requests().len(), 3);
+
+        let letters = registry::dead_letters(owner(), &hook.id).unwrap();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].delivery_id, delivery_id);
+        assert_eq!(letters[0].attempts, 3);
+        assert_eq!(letters[0].events, vec![event]);
+
+        // Retried dead letter is delivered with the same id.
+        let (hook, letter) = registry::take_dead_letter(owner(), &hook.id, &delivery_id).unwrap();
+        let delivery = Delivery {
+            webhook_id: hook.id.clone(),
+            delivery_id: letter.delivery_id,
+            source: hook.source.clone(),
+            events: letter.events,
+        };
+        assert!(deliver(&client(), &hook, delivery, FAST_RETRIES).await);
+        assert_eq!(callback.requests().len(), 5);
+        assert!(registry::dead_letters(owner(), &hook.id)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_only_chosen_event_types_delivered() {
+        let accepted = serde_json::json!({"eventType": "ProposalEvent"});
+        let rejected = serde_json::json!({"eventType": "AgreementEvent"});
+        let source = Arc::new(TestSource {
+            events: Mutex::new(Some(vec![accepted.clone(), rejected])),
+            ..Default::default()
+        });
+        register_source("dispatcher-test", SharedSource(source.clone()));
+
+        let (callback, url) = Callback::start(&[]);
+        let hook = register(url, "dispatcher-test", &["ProposalEvent"]);
+        let hook_id = hook.id.clone();
+        actix_rt::spawn(async move { run_with(&hook, FAST_RETRIES).await });
+
+        for _ in 0..100 {
+            if !source.acks.lock().unwrap().is_empty() {
+                break;
+            }
+            actix_rt::time::sleep(Duration::from_millis(20)).await;
+        }
+        registry::remove(owner(), &hook_id).unwrap();
+
+        let requests = callback.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(events(&requests[0].1), vec![accepted]);
+        // Source can leave filtered events for other clients.
+        assert_eq!(*source.event_types.lock().unwrap(), vec!["ProposalEvent"]);
+        assert_eq!(*source.acks.lock().unwrap(), vec!["cursor-1"]);
+    }
+}
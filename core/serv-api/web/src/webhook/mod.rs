@@ -0,0 +1,108 @@
+//! Webhooks deliver events to client's callback URL, as an alternative
+//! to long-polling REST API.
+//!
+//! Events are POSTed in batches as `Delivery` JSON. Request body is signed
+//! with HMAC-SHA256 using webhook secret and signature is sent in
+//! `X-Yagna-Signature` header as `sha256=<hex>`. Failed deliveries are retried
+//! with exponential backoff and, after exhausting all attempts, moved to
+//! webhook's dead letters, from where they can be retried manually.
+//!
+//! Webhooks together with their dead letters are saved in data directory
+//! and resumed after restart.
+
+mod api;
+mod dispatcher;
+mod registry;
+mod source;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+pub use api::{web_scope, WEBHOOK_API_PATH};
+pub use source::{register_source, EventBatch, EventSource};
+
+pub const SIGNATURE_HEADER: &str = "X-Yagna-Signature";
+pub const WEBHOOK_ID_HEADER: &str = "X-Yagna-Webhook-Id";
+pub const DELIVERY_ID_HEADER: &str = "X-Yagna-Delivery-Id";
+
+const WEBHOOKS_FILE: &str = "webhooks.json";
+
+/// Restores webhooks saved in `data_dir` and resumes their delivery.
+/// Must be called after event sources are registered.
+pub fn init(data_dir: &Path) -> anyhow::Result<()> {
+    let hooks = registry::load(&data_dir.join(WEBHOOKS_FILE))?;
+    log::debug!("Restored {} webhooks", hooks.len());
+    hooks.into_iter().for_each(dispatcher::spawn);
+    Ok(())
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewWebhook {
+    /// Callback URL. Only `http` and `https` schemes are allowed.
+    pub url: String,
+    /// Name of registered event source, e.g. `offers` or `invoices`.
+    pub source: String,
+    /// Subscription id for market sources, `appSessionId` for other sources.
+    #[serde(default)]
+    pub subject: Option<String>,
+    /// Deliver only events with these `eventType`s. All events, when empty.
+    #[serde(default)]
+    pub event_types: Vec<String>,
+    /// Key used for signing requests. Generated, when not provided.
+    #[serde(default)]
+    pub secret: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub webhook_id: String,
+    pub url: String,
+    pub source: String,
+    pub subject: Option<String>,
+    pub event_types: Vec<String>,
+    /// Returned only once, when webhook is created.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_events: u64,
+    pub dead_letters: usize,
+    pub last_error: Option<String>,
+}
+
+/// Body of request sent to callback URL.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Delivery {
+    pub webhook_id: String,
+    pub delivery_id: String,
+    pub source: String,
+    pub events: Vec<serde_json::Value>,
+}
+
+/// Delivery, that failed after all attempts.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeadLetter {
+    pub delivery_id: String,
+    pub timestamp: DateTime<Utc>,
+    pub attempts: u32,
+    pub error: String,
+    pub events: Vec<serde_json::Value>,
+}
+
+#[derive(thiserror::Error, Debug)]
+pub enum WebhookError {
+    #[error("Unknown event source '{0}'. Available sources: {1}.")]
+    UnknownSource(String, String),
+    #[error("Invalid callback URL '{0}': {1}.")]
+    InvalidUrl(String, String),
+    #[error("Can't subscribe to events: {0}.")]
+    InvalidSubject(String),
+    #[error("Webhook [{0}] not found.")]
+    NotFound(String),
+    #[error("Dead letter [{1}] of webhook [{0}] not found.")]
+    DeadLetterNotFound(String, String),
+}
@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use ya_client::model::NodeId;
+
+use super::{DeadLetter, Webhook, WebhookError};
+
+/// Oldest dead letters are dropped, when limit is exceeded.
+const MAX_DEAD_LETTERS: usize = 100;
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Entry {
+    owner: NodeId,
+    webhook: Webhook,
+    secret: String,
+    dead_letters: VecDeque<DeadLetter>,
+}
+
+lazy_static::lazy_static! {
+    static ref WEBHOOKS: Mutex<HashMap<String, Entry>> = Default::default();
+    /// File, where webhooks are saved on every change. Nothing is saved,
+    /// until registry is loaded.
+    static ref STORE: Mutex<Option<PathBuf>> = Default::default();
+}
+
+/// Webhook settings needed by dispatcher.
+#[derive(Clone)]
+pub(super) struct Hook {
+    pub id: String,
+    pub owner: NodeId,
+    pub url: String,
+    pub source: String,
+    pub subject: Option<String>,
+    pub event_types: Vec<String>,
+    pub secret: String,
+}
+
+impl Hook {
+    pub fn accepts(&self, event: &serde_json::Value) -> bool {
+        if self.event_types.is_empty() {
+            return true;
+        }
+        event
+            .get("eventType")
+            .and_then(|event_type| event_type.as_str())
+            .map(|event_type| self.event_types.iter().any(|t| t == event_type))
+            .unwrap_or(false)
+    }
+}
+
+impl Entry {
+    fn hook(&self) -> Hook {
+        Hook {
+            id: self.webhook.webhook_id.clone(),
+            owner: self.owner,
+            url: self.webhook.url.clone(),
+            source: self.webhook.source.clone(),
+            subject: self.webhook.subject.clone(),
+            event_types: self.webhook.event_types.clone(),
+            secret: self.secret.clone(),
+        }
+    }
+}
+
+/// Restores webhooks saved in `path` and saves following changes there.
+/// Returns restored webhooks, so their delivery can be resumed.
+pub(super) fn load(path: &Path) -> anyhow::Result<Vec<Hook>> {
+    let entries = read_entries(path)?;
+    STORE.lock().unwrap().replace(path.to_path_buf());
+
+    let mut webhooks = WEBHOOKS.lock().unwrap();
+    let hooks = entries
+        .into_iter()
+        .map(|entry| {
+            let hook = entry.hook();
+            webhooks.insert(hook.id.clone(), entry);
+            hook
+        })
+        .collect();
+    save(&webhooks);
+    Ok(hooks)
+}
+
+fn save(webhooks: &HashMap<String, Entry>) {
+    let path = match STORE.lock().unwrap().clone() {
+        Some(path) => path,
+        None => return,
+    };
+    let mut entries: Vec<&Entry> = webhooks.values().collect();
+    entries.sort_by_key(|entry| entry.webhook.created_at);
+
+    if let Err(e) = write_entries(&path, &entries) {
+        log::warn!("Failed to save webhooks to {}: {}", path.display(), e);
+    }
+}
+
+fn read_entries(path: &Path) -> anyhow::Result<Vec<Entry>> {
+    match std::fs::read(path) {
+        Ok(content) => Ok(serde_json::from_slice(&content)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Writes to temporary file first, so file is never left partially written.
+fn write_entries(path: &Path, entries: &[&Entry]) -> anyhow::Result<()> {
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, serde_json::to_vec_pretty(entries)?)?;
+    std::fs::rename(&tmp_path, path)?;
+    Ok(())
+}
+
+pub(super) fn insert(owner: NodeId, mut webhook: Webhook, secret: String) -> Hook {
+    webhook.secret = None;
+    let entry = Entry {
+        owner,
+        webhook,
+        secret,
+        dead_letters: Default::default(),
+    };
+    let hook = entry.hook();
+
+    let mut webhooks = WEBHOOKS.lock().unwrap();
+    webhooks.insert(hook.id.clone(), entry);
+    save(&webhooks);
+    hook
+}
+
+pub(super) fn list(owner: NodeId) -> Vec<Webhook> {
+    let mut webhooks: Vec<_> = WEBHOOKS
+        .lock()
+        .unwrap()
+        .values()
+        .filter(|entry| entry.owner == owner)
+        .map(|entry| entry.webhook.clone())
+        .collect();
+    webhooks.sort_by_key(|webhook| webhook.created_at);
+    webhooks
+}
+
+pub(super) fn get(owner: NodeId, id: &str) -> Result<Webhook, WebhookError> {
+    with_entry(owner, id, |entry| entry.webhook.clone())
+}
+
+pub(super) fn remove(owner: NodeId, id: &str) -> Result<(), WebhookError> {
+    let mut webhooks = WEBHOOKS.lock().unwrap();
+    match webhooks.get(id) {
+        Some(entry) if entry.owner == owner => {
+            webhooks.remove(id);
+            save(&webhooks);
+            Ok(())
+        }
+        _ => Err(WebhookError::NotFound(id.to_string())),
+    }
+}
+
+pub(super) fn dead_letters(owner: NodeId, id: &str) -> Result<Vec<DeadLetter>, WebhookError> {
+    with_entry(owner, id, |entry| {
+        entry.dead_letters.iter().cloned().collect()
+    })
+}
+
+pub(super) fn clear_dead_letters(owner: NodeId, id: &str) -> Result<(), WebhookError> {
+    modify_entry(owner, id, |entry| {
+        entry.dead_letters.clear();
+        entry.webhook.dead_letters = 0;
+    })
+}
+
+/// Removes dead letter, so it can be delivered again.
+pub(super) fn take_dead_letter(
+    owner: NodeId,
+    id: &str,
+    delivery_id: &str,
+) -> Result<(Hook, DeadLetter), WebhookError> {
+    modify_entry(owner, id, |entry| {
+        let hook = entry.hook();
+        let position = entry
+            .dead_letters
+            .iter()
+            .position(|letter| letter.delivery_id == delivery_id)?;
+        let letter = entry.dead_letters.remove(position);
+        entry.webhook.dead_letters = entry.dead_letters.len();
+        letter.map(|letter| (hook, letter))
+    })?
+    .ok_or_else(|| WebhookError::DeadLetterNotFound(id.to_string(), delivery_id.to_string()))
+}
+
+pub(super) fn is_active(id: &str) -> bool {
+    WEBHOOKS.lock().unwrap().contains_key(id)
+}
+
+pub(super) fn record_delivered(id: &str, events: usize) {
+    update(id, |entry| {
+        entry.webhook.delivered_events += events as u64;
+        entry.webhook.last_error = None;
+    })
+}
+
+pub(super) fn record_error(id: &str, error: String) {
+    update(id, |entry| entry.webhook.last_error = Some(error))
+}
+
+pub(super) fn push_dead_letter(id: &str, letter: DeadLetter) {
+    let mut webhooks = WEBHOOKS.lock().unwrap();
+    if let Some(entry) = webhooks.get_mut(id) {
+        entry.webhook.last_error = Some(letter.error.clone());
+        entry.dead_letters.push_back(letter);
+        while entry.dead_letters.len() > MAX_DEAD_LETTERS {
+            entry.dead_letters.pop_front();
+        }
+        entry.webhook.dead_letters = entry.dead_letters.len();
+        save(&webhooks);
+    }
+}
+
+fn with_entry<T>(
+    owner: NodeId,
+    id: &str,
+    f: impl FnOnce(&mut Entry) -> T,
+) -> Result<T, WebhookError> {
+    match WEBHOOKS.lock().unwrap().get_mut(id) {
+        Some(entry) if entry.owner == owner => Ok(f(entry)),
+        _ => Err(WebhookError::NotFound(id.to_string())),
+    }
+}
+
+/// Like `with_entry`, but saves registry afterwards.
+fn modify_entry<T>(
+    owner: NodeId,
+    id: &str,
+    f: impl FnOnce(&mut Entry) -> T,
+) -> Result<T, WebhookError> {
+    let mut webhooks = WEBHOOKS.lock().unwrap();
+    match webhooks.get_mut(id) {
+        Some(entry) if entry.owner == owner => {
+            let result = f(entry);
+            save(&webhooks);
+            Ok(result)
+        }
+        _ => Err(WebhookError::NotFound(id.to_string())),
+    }
+}
+
+fn update(id: &str, f: impl FnOnce(&mut Entry)) {
+    if let Some(entry) = WEBHOOKS.lock().unwrap().get_mut(id) {
+        f(entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn entry(id: &str) -> Entry {
+        Entry {
+            owner: "0xbabe000000000000000000000000000000000000"
+                .parse()
+                .unwrap(),
+            webhook: Webhook {
+                webhook_id: id.to_string(),
+                url: "http://127.0.0.1:8000/events".to_string(),
+                source: "demands".to_string(),
+                subject: Some("subscription".to_string()),
+                event_types: vec!["ProposalEvent".to_string()],
+                secret: None,
+                created_at: Utc::now(),
+                delivered_events: 3,
+                dead_letters: 1,
+                last_error: Some("callback responded with 500".to_string()),
+            },
+            secret: "secret".to_string(),
+            dead_letters: vec![DeadLetter {
+                delivery_id: "delivery".to_string(),
+                timestamp: Utc::now(),
+                attempts: 5,
+                error: "callback responded with 500".to_string(),
+                events: vec![serde_json::json!({"eventType": "ProposalEvent"})],
+            }]
+            .into(),
+        }
+    }
+
+    #[test]
+    fn test_entries_saved_with_dead_letters() {
+        let path = std::env::temp_dir().join(format!(
+            "webhooks-{}.json",
+            uuid::Uuid::new_v4().to_simple()
+        ));
+        assert!(read_entries(&path).unwrap().is_empty());
+
+        let (first, second) = (entry("first"), entry("second"));
+        write_entries(&path, &[&first, &second]).unwrap();
+        let entries = read_entries(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(entries.len(), 2);
+        let hook = entries[0].hook();
+        assert_eq!(hook.id, "first");
+        assert_eq!(hook.owner, first.owner);
+        assert_eq!(hook.secret, "secret");
+        assert_eq!(hook.event_types, vec!["ProposalEvent"]);
+        assert_eq!(entries[0].webhook.delivered_events, 3);
+
+        let letters = &entries[1].dead_letters;
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].delivery_id, "delivery");
+        assert_eq!(letters[0].attempts, 5);
+        assert_eq!(letters[0].events, first.dead_letters[0].events);
+    }
+}
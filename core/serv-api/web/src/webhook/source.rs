@@ -0,0 +1,73 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use ya_client::model::NodeId;
+
+/// Events returned by single poll of `EventSource`.
+#[derive(Default)]
+pub struct EventBatch {
+    /// Events serialized the same way as in long-polling REST API.
+    pub events: Vec<serde_json::Value>,
+    /// Position after this batch. Passed to `EventSource::ack`, when batch
+    /// was delivered (or dead-lettered), and to following `poll`.
+    pub cursor: Option<String>,
+}
+
+/// Module producing events, that can be delivered by webhooks.
+/// Sources are polled by dispatcher the same way, as clients long-poll
+/// their REST API, so events are delivered only after they are committed.
+#[async_trait::async_trait(?Send)]
+pub trait EventSource: Send + Sync {
+    /// Checks, that `owner` can receive events from `subject`.
+    async fn validate(&self, _owner: NodeId, _subject: Option<&str>) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    /// Waits up to `timeout` for events of `owner` following `cursor`.
+    /// Sources, which remove acknowledged events, must return only events
+    /// of `event_types` (all, when empty), so others remain available
+    /// to REST API clients. Other sources can ignore it.
+    async fn poll(
+        &self,
+        owner: NodeId,
+        subject: Option<&str>,
+        event_types: &[String],
+        cursor: Option<String>,
+        timeout: Duration,
+    ) -> anyhow::Result<EventBatch>;
+
+    /// Confirms, that events up to `cursor` won't be needed anymore.
+    async fn ack(
+        &self,
+        _owner: NodeId,
+        _subject: Option<&str>,
+        _cursor: &str,
+    ) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref SOURCES: RwLock<HashMap<String, Arc<dyn EventSource>>> = Default::default();
+}
+
+/// Makes events of given source available for webhooks.
+/// Should be called by services during their initialization.
+pub fn register_source(name: &str, source: impl EventSource + 'static) {
+    log::debug!("Registering webhook event source: {}", name);
+    SOURCES
+        .write()
+        .unwrap()
+        .insert(name.to_string(), Arc::new(source));
+}
+
+pub(crate) fn source(name: &str) -> Option<Arc<dyn EventSource>> {
+    SOURCES.read().unwrap().get(name).cloned()
+}
+
+pub(crate) fn source_names() -> Vec<String> {
+    let mut names: Vec<_> = SOURCES.read().unwrap().keys().cloned().collect();
+    names.sort();
+    names
+}
@@ -0,0 +1,206 @@
+//! Security options of REST API listener.
+
+use anyhow::{Context, Result};
+use openssl::ssl::{SslAcceptor, SslAcceptorBuilder, SslFiletype, SslMethod, SslVerifyMode};
+use std::path::{Path, PathBuf};
+use structopt::StructOpt;
+
+#[derive(StructOpt, Debug)]
+pub struct ApiListenerOpts {
+    /// Permissions of REST API Unix domain socket (`unix://` API URL)
+    #[structopt(
+        long,
+        env = "YAGNA_API_SOCKET_MODE",
+        default_value = "600",
+        parse(try_from_str = parse_mode)
+    )]
+    pub api_socket_mode: u32,
+
+    /// PEM certificate chain of REST API served over `https://` API URL
+    #[structopt(long, env = "YAGNA_API_TLS_CERT")]
+    pub api_tls_cert: Option<PathBuf>,
+
+    /// PEM private key of REST API certificate
+    #[structopt(long, env = "YAGNA_API_TLS_KEY")]
+    pub api_tls_key: Option<PathBuf>,
+
+    /// PEM file with CA certificates. When set, REST API clients have
+    /// to present certificate signed by one of them.
+    #[structopt(long, env = "YAGNA_API_TLS_CLIENT_CA")]
+    pub api_tls_client_ca: Option<PathBuf>,
+}
+
+fn parse_mode(mode: &str) -> Result<u32, std::num::ParseIntError> {
+    u32::from_str_radix(mode, 8)
+}
+
+impl ApiListenerOpts {
+    pub fn tls_acceptor(&self) -> Result<SslAcceptorBuilder> {
+        let (cert, key) = match (&self.api_tls_cert, &self.api_tls_key) {
+            (Some(cert), Some(key)) => (cert, key),
+            _ => anyhow::bail!("`https` API URL requires --api-tls-cert and --api-tls-key"),
+        };
+
+        let mut builder = SslAcceptor::mozilla_intermediate(SslMethod::tls())?;
+        builder
+            .set_certificate_chain_file(cert)
+            .context(format!("Failed to load TLS certificate {}", cert.display()))?;
+        builder
+            .set_private_key_file(key, SslFiletype::PEM)
+            .context(format!("Failed to load TLS private key {}", key.display()))?;
+        builder.check_private_key()?;
+
+        if let Some(ca) = &self.api_tls_client_ca {
+            builder
+                .set_ca_file(ca)
+                .context(format!("Failed to load client CA {}", ca.display()))?;
+            builder.set_verify(SslVerifyMode::PEER | SslVerifyMode::FAIL_IF_NO_PEER_CERT);
+        }
+        Ok(builder)
+    }
+
+    /// Binds REST API to Unix domain socket under `socket` path.
+    ///
+    /// `bind` is called with path in private directory, so nobody can connect
+    /// before socket permissions are set. Then socket is moved to its path.
+    #[cfg(unix)]
+    pub fn bind_socket<T>(
+        &self,
+        socket: &Path,
+        bind: impl FnOnce(&Path) -> Result<T>,
+    ) -> Result<T> {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+        remove_stale_socket(socket)?;
+
+        let name = socket
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("Invalid API socket path {}", socket.display()))?;
+        let private_dir = socket.with_file_name(format!(
+            ".{}.{}",
+            name.to_string_lossy(),
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&private_dir);
+        std::fs::DirBuilder::new()
+            .mode(0o700)
+            .create(&private_dir)
+            .context(format!(
+                "Failed to create API socket directory {}",
+                private_dir.display()
+            ))?;
+
+        let private_socket = private_dir.join(name);
+        let result = bind(&private_socket).and_then(|bound| {
+            std::fs::set_permissions(
+                &private_socket,
+                std::fs::Permissions::from_mode(self.api_socket_mode),
+            )
+            .context(format!(
+                "Failed to set permissions of API socket {}",
+                socket.display()
+            ))?;
+            std::fs::rename(&private_socket, socket)
+                .context(format!("Failed to create API socket {}", socket.display()))?;
+            Ok(bound)
+        });
+        let _ = std::fs::remove_dir_all(&private_dir);
+        result
+    }
+}
+
+/// Removes socket left by daemon, which is not running anymore.
+#[cfg(unix)]
+fn remove_stale_socket(socket: &Path) -> Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let metadata = match std::fs::symlink_metadata(socket) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => {
+            return Err(e).context(format!("Failed to check API socket {}", socket.display()))
+        }
+    };
+    if !metadata.file_type().is_socket() {
+        anyhow::bail!("{} exists and is not a socket", socket.display());
+    }
+    if std::os::unix::net::UnixStream::connect(socket).is_ok() {
+        anyhow::bail!("API socket {} is in use", socket.display());
+    }
+    log::info!("Removing stale API socket {}", socket.display());
+    std::fs::remove_file(socket)
+        .context(format!("Failed to remove API socket {}", socket.display()))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixListener;
+
+    fn opts(api_socket_mode: u32) -> ApiListenerOpts {
+        ApiListenerOpts {
+            api_socket_mode,
+            api_tls_cert: None,
+            api_tls_key: None,
+            api_tls_client_ca: None,
+        }
+    }
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("yagna-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn bind(path: &Path) -> Result<UnixListener> {
+        Ok(UnixListener::bind(path)?)
+    }
+
+    #[test]
+    fn test_bind_socket_with_mode() {
+        let dir = test_dir("api-socket-mode");
+        let socket = dir.join("api.sock");
+
+        let _listener = opts(0o660).bind_socket(&socket, bind).unwrap();
+
+        let mode = std::fs::metadata(&socket).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o660);
+        // Private directory is gone.
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::os::unix::net::UnixStream::connect(&socket).unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_bind_socket_removes_stale_socket() {
+        let dir = test_dir("api-socket-stale");
+        let socket = dir.join("api.sock");
+        drop(UnixListener::bind(&socket).unwrap());
+        assert!(socket.exists());
+
+        let _listener = opts(0o600).bind_socket(&socket, bind).unwrap();
+        std::os::unix::net::UnixStream::connect(&socket).unwrap();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_bind_socket_in_use() {
+        let dir = test_dir("api-socket-in-use");
+        let socket = dir.join("api.sock");
+        let _running = UnixListener::bind(&socket).unwrap();
+
+        assert!(opts(0o600).bind_socket(&socket, bind).is_err());
+        std::os::unix::net::UnixStream::connect(&socket).unwrap();
+
+        let file = dir.join("api.txt");
+        std::fs::write(&file, "").unwrap();
+        assert!(opts(0o600).bind_socket(&file, bind).is_err());
+        assert!(file.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
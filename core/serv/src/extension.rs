@@ -2,17 +2,12 @@ use std::collections::HashMap;
 use std::fs::OpenOptions;
 use std::io::Write;
 use std::path::{Path, PathBuf};
-use std::process::Stdio;
-use std::time::Duration;
 use std::{env, fs};
 
 use anyhow::{anyhow, bail, Context};
-use futures::{FutureExt, StreamExt, TryFutureExt};
 use serde::{Deserialize, Serialize};
 use structopt::StructOpt;
-use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
-use tokio_stream::wrappers::LinesStream;
 
 use ya_client_model::NodeId;
 use ya_core_model as model;
@@ -20,6 +15,10 @@ use ya_core_model::appkey::AppKey;
 use ya_service_api::{CliCtx, CommandOutput};
 use ya_service_bus::typed as bus;
 
+mod supervisor;
+
+pub use supervisor::{status, HealthCheck, Probe, Supervision};
+
 const APP_NAME: &'static str = structopt::clap::crate_name!();
 const DIR_NAME: &'static str = "extensions";
 
@@ -55,6 +54,7 @@ pub async fn run<T: StructOpt>(
 
 pub async fn autostart(
     data_dir: impl AsRef<Path>,
+    log_dir: Option<PathBuf>,
     api_url: &url::Url,
     gsb_url: &Option<url::Url>,
 ) -> anyhow::Result<()> {
@@ -76,7 +76,7 @@ pub async fn autostart(
     };
 
     extensions.into_iter().for_each(|ext| {
-        tokio::task::spawn_local(monitor(ext, ctx.clone()));
+        tokio::task::spawn_local(monitor(ext, ctx.clone(), log_dir.clone()));
     });
 
     Ok(())
@@ -107,29 +107,13 @@ async fn resolve_identity_and_key() -> anyhow::Result<(NodeId, Option<AppKey>)>
     Ok((node_id, app_key))
 }
 
-async fn monitor(extension: Extension, ctx: ExtensionCtx) {
-    let name = extension.name.clone();
+async fn monitor(extension: Extension, ctx: ExtensionCtx, log_dir: Option<PathBuf>) {
     let interrupted = tokio::signal::ctrl_c();
-    let restart_loop = async move {
-        loop {
-            log::info!("Extension `{name}` starting");
-
-            match extension.execute(ctx.clone()).await {
-                Ok(0) => {
-                    log::info!("Extension '{name}' finished");
-                    break;
-                }
-                Ok(c) => log::warn!("Extension '{name}' failed with exit code {c}"),
-                Err(e) => log::warn!("Extension '{name}' failed with error: {e}"),
-            }
-
-            tokio::time::sleep(Duration::from_secs(3)).await;
-        }
-    };
+    let supervised = supervisor::supervise(extension, ctx, log_dir);
 
     futures::pin_mut!(interrupted);
-    futures::pin_mut!(restart_loop);
-    let _ = futures::future::select(interrupted, restart_loop).await;
+    futures::pin_mut!(supervised);
+    let _ = futures::future::select(interrupted, supervised).await;
 }
 
 #[derive(Debug, Clone)]
@@ -149,13 +133,6 @@ pub enum ExtensionCtx {
 }
 
 impl ExtensionCtx {
-    pub fn is_autostart(&self) -> bool {
-        match self {
-            Self::Autostart { .. } => true,
-            _ => false,
-        }
-    }
-
     fn set_env(&self, command: &mut Command) -> anyhow::Result<()> {
         let (data_dir, gsb_url) = match self {
             Self::Cli {
@@ -214,6 +191,8 @@ pub struct ExtensionConf {
     pub env: HashMap<String, String>,
     #[serde(default)]
     pub autostart: bool,
+    #[serde(default, skip_serializing_if = "Supervision::is_default")]
+    pub supervision: Supervision,
 }
 
 impl ExtensionConf {
@@ -351,62 +330,19 @@ impl Extension {
         self.conf.write(&path).await
     }
 
-    pub async fn execute(&self, ctx: ExtensionCtx) -> anyhow::Result<i32> {
+    fn command(&self, ctx: &ExtensionCtx) -> anyhow::Result<Command> {
         let mut command = Command::new(&self.path);
         command.args(&self.conf.args);
         command.envs(self.conf.env.clone().into_iter());
 
         ctx.set_env(&mut command)?;
+        Ok(command)
+    }
 
-        let fut = if ctx.is_autostart() {
-            command.stdout(Stdio::piped());
-            command.stderr(Stdio::piped());
-            command.stdin(Stdio::null());
-
-            let name = self.name.clone();
-            async move {
-                let mut child = command
-                    .spawn()
-                    .map_err(|e| anyhow!("unable to spawn the binary: {}", e))?;
-
-                let stdout = child
-                    .stdout
-                    .take()
-                    .ok_or_else(|| anyhow!("unable to capture stdout"))?;
-
-                let name_ = name.clone();
-                tokio::task::spawn_local(
-                    LinesStream::new(BufReader::new(stdout).lines()).for_each(move |s| {
-                        let name_ = name_.clone();
-                        async move {
-                            let _ = s.map(|s| log::info!("{name_}: {s}"));
-                        }
-                    }),
-                );
-
-                let stderr = child
-                    .stderr
-                    .take()
-                    .ok_or_else(|| anyhow!("unable to capture stderr"))?;
-
-                tokio::task::spawn_local(
-                    LinesStream::new(BufReader::new(stderr).lines()).for_each(move |s| {
-                        let name_ = name.clone();
-                        async move {
-                            let _ = s.map(|s| log::info!("{name_}: {s}"));
-                        }
-                    }),
-                );
-
-                child.stdin.take();
-                child.wait().await.map_err(anyhow::Error::new)
-            }
-            .boxed()
-        } else {
-            command.status().map_err(anyhow::Error::new).boxed()
-        };
+    pub async fn execute(&self, ctx: ExtensionCtx) -> anyhow::Result<i32> {
+        let mut command = self.command(&ctx)?;
 
-        match fut.await {
+        match command.status().await {
             Ok(status) => match status.code() {
                 Some(code) => Ok(code),
                 None => {
@@ -0,0 +1,603 @@
+//! Supervision of autostart extensions: restarts with exponential backoff,
+//! health checks and capturing of extension output.
+
+use anyhow::{anyhow, bail};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+
+use super::{Extension, ExtensionCtx};
+use crate::model::{ExtensionState, ExtensionStatus};
+
+const LOG_DIR_NAME: &str = "extensions";
+const MAX_LOG_SIZE: u64 = 10 * 1024 * 1024;
+const ROTATED_LOGS: usize = 3;
+/// Backoff is reset, when extension was running at least that long.
+const STABLE_RUN: Duration = Duration::from_secs(60);
+
+lazy_static::lazy_static! {
+    static ref STATUS: Mutex<BTreeMap<String, ExtensionStatus>> = Default::default();
+}
+
+/// Restart policy and health check of autostart extension.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Supervision {
+    /// Restarts allowed in a row, unlimited when not set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_restarts: Option<u32>,
+    #[serde(default = "default_backoff_initial_secs")]
+    pub backoff_initial_secs: u64,
+    #[serde(default = "default_backoff_max_secs")]
+    pub backoff_max_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health_check: Option<HealthCheck>,
+}
+
+impl Default for Supervision {
+    fn default() -> Self {
+        Supervision {
+            max_restarts: None,
+            backoff_initial_secs: default_backoff_initial_secs(),
+            backoff_max_secs: default_backoff_max_secs(),
+            health_check: None,
+        }
+    }
+}
+
+impl Supervision {
+    pub fn is_default(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    #[serde(flatten)]
+    pub probe: Probe,
+    #[serde(default = "default_interval_secs")]
+    pub interval_secs: u64,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Failed checks in a row, after which extension is restarted.
+    #[serde(default = "default_retries")]
+    pub retries: u32,
+}
+
+impl HealthCheck {
+    pub fn new(probe: Probe) -> Self {
+        HealthCheck {
+            probe,
+            interval_secs: default_interval_secs(),
+            timeout_secs: default_timeout_secs(),
+            retries: default_retries(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Probe {
+    /// Command exiting with 0, when extension is healthy.
+    Command(Vec<String>),
+    /// URL responding with 2xx status to GET request, when extension is healthy.
+    Http(url::Url),
+}
+
+fn default_backoff_initial_secs() -> u64 {
+    1
+}
+
+fn default_backoff_max_secs() -> u64 {
+    300
+}
+
+fn default_interval_secs() -> u64 {
+    30
+}
+
+fn default_timeout_secs() -> u64 {
+    10
+}
+
+fn default_retries() -> u32 {
+    3
+}
+
+pub fn status() -> Vec<ExtensionStatus> {
+    STATUS.lock().unwrap().values().cloned().collect()
+}
+
+fn update(name: &str, f: impl FnOnce(&mut ExtensionStatus)) {
+    if let Some(status) = STATUS.lock().unwrap().get_mut(name) {
+        f(status)
+    }
+}
+
+enum Exit {
+    Code(i32),
+    /// Terminated by signal, e.g. killed by OOM killer.
+    Signal(ExitStatus),
+    Unhealthy,
+}
+
+/// Delay before restart, doubled after each failure up to `max`.
+struct Backoff {
+    initial: Duration,
+    max: Duration,
+    next: Duration,
+}
+
+impl Backoff {
+    fn new(policy: &Supervision) -> Self {
+        let initial = Duration::from_secs(policy.backoff_initial_secs);
+        Backoff {
+            initial,
+            max: Duration::from_secs(policy.backoff_max_secs),
+            next: initial,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.next = self.initial;
+    }
+
+    fn next(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+}
+
+/// Runs extension until it finishes successfully, or exceeds `max_restarts`.
+/// Output is written to `<log_dir>/extensions/<name>.log`, or to yagna log
+/// when `log_dir` is not set.
+pub async fn supervise(extension: Extension, ctx: ExtensionCtx, log_dir: Option<PathBuf>) {
+    let name = extension.name.clone();
+    let policy = extension.conf.supervision.clone();
+    let log_file = log_dir.map(|dir| dir.join(LOG_DIR_NAME).join(format!("{}.log", name)));
+
+    STATUS.lock().unwrap().insert(
+        name.clone(),
+        ExtensionStatus {
+            name: name.clone(),
+            state: ExtensionState::Running,
+            pid: None,
+            restarts: 0,
+            last_exit_code: None,
+            last_error: None,
+            healthy: None,
+            started_at: None,
+            log_file: log_file.clone(),
+        },
+    );
+
+    let mut backoff = Backoff::new(&policy);
+    let mut failures = 0;
+
+    loop {
+        log::info!("Extension `{name}` starting");
+        let started = Instant::now();
+
+        let error = match run(&extension, &ctx, &policy, log_file.as_deref()).await {
+            Ok(Exit::Code(0)) => {
+                log::info!("Extension '{name}' finished");
+                update(&name, |status| {
+                    status.state = ExtensionState::Finished;
+                    status.pid = None;
+                });
+                break;
+            }
+            Ok(Exit::Code(c)) => {
+                update(&name, |status| status.last_exit_code = Some(c));
+                format!("exit code {c}")
+            }
+            Ok(Exit::Signal(status)) => format!("termination by {status}"),
+            Ok(Exit::Unhealthy) => "health check failure".to_string(),
+            Err(e) => e.to_string(),
+        };
+        log::warn!("Extension '{name}' failed with {error}");
+
+        if started.elapsed() >= STABLE_RUN {
+            failures = 0;
+            backoff.reset();
+        }
+        failures += 1;
+
+        if policy
+            .max_restarts
+            .map(|max| failures > max)
+            .unwrap_or(false)
+        {
+            log::error!("Extension '{name}' failed {failures} times in a row, giving up");
+            update(&name, |status| {
+                status.state = ExtensionState::Crashed;
+                status.pid = None;
+                status.last_error = Some(error);
+            });
+            break;
+        }
+
+        update(&name, |status| {
+            status.state = ExtensionState::Restarting;
+            status.pid = None;
+            status.restarts += 1;
+            status.last_error = Some(error);
+        });
+        let delay = backoff.next();
+        log::info!("Restarting extension '{name}' in {delay:?}");
+        tokio::time::sleep(delay).await;
+    }
+}
+
+async fn run(
+    extension: &Extension,
+    ctx: &ExtensionCtx,
+    policy: &Supervision,
+    log_file: Option<&Path>,
+) -> anyhow::Result<Exit> {
+    let name = extension.name.as_str();
+    let output = Output::new(name, log_file)?;
+
+    let mut command = extension.command(ctx)?;
+    command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .stdin(Stdio::null())
+        .kill_on_drop(true);
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| anyhow!("unable to spawn the binary: {}", e))?;
+
+    update(name, |status| {
+        status.state = ExtensionState::Running;
+        status.pid = child.id();
+        status.healthy = None;
+        status.started_at = Some(Utc::now());
+    });
+
+    if let Some(stdout) = child.stdout.take() {
+        tokio::task::spawn_local(capture(stdout, "stdout", output.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tokio::task::spawn_local(capture(stderr, "stderr", output));
+    }
+
+    let health_check = async {
+        match &policy.health_check {
+            Some(check) => watch_health(name, check).await,
+            None => futures::future::pending().await,
+        }
+    };
+
+    tokio::select! {
+        status = child.wait() => {
+            let status = status?;
+            Ok(status.code().map(Exit::Code).unwrap_or(Exit::Signal(status)))
+        }
+        _ = health_check => {
+            child.kill().await?;
+            Ok(Exit::Unhealthy)
+        }
+    }
+}
+
+/// Resolves after `retries` failed health checks in a row.
+async fn watch_health(name: &str, check: &HealthCheck) {
+    let timeout = Duration::from_secs(check.timeout_secs);
+    let mut failures = 0;
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(check.interval_secs)).await;
+
+        let healthy = match tokio::time::timeout(timeout, probe(&check.probe)).await {
+            Ok(Ok(())) => true,
+            Ok(Err(e)) => {
+                log::debug!("Extension '{name}' health check failed: {e}");
+                false
+            }
+            Err(_) => {
+                log::debug!("Extension '{name}' health check timed out");
+                false
+            }
+        };
+        update(name, |status| status.healthy = Some(healthy));
+
+        if healthy {
+            failures = 0;
+        } else {
+            failures += 1;
+            if failures >= check.retries {
+                log::warn!("Extension '{name}' is unhealthy");
+                return;
+            }
+        }
+    }
+}
+
+async fn probe(probe: &Probe) -> anyhow::Result<()> {
+    match probe {
+        Probe::Command(args) => {
+            let (program, args) = args
+                .split_first()
+                .ok_or_else(|| anyhow!("empty health check command"))?;
+            let status = Command::new(program)
+                .args(args)
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .kill_on_drop(true)
+                .status()
+                .await?;
+            if !status.success() {
+                bail!("health check command failed with {}", status);
+            }
+        }
+        Probe::Http(url) => {
+            let response = awc::Client::default()
+                .get(url.as_str())
+                .send()
+                .await
+                .map_err(|e| anyhow!("health check request failed: {}", e))?;
+            if !response.status().is_success() {
+                bail!("health check responded with {}", response.status());
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn capture(stream: impl AsyncRead + Unpin, stream_name: &'static str, output: Output) {
+    let mut lines = BufReader::new(stream).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        output.write(stream_name, &line);
+    }
+}
+
+#[derive(Clone)]
+struct Output {
+    name: String,
+    file: Option<Arc<Mutex<RotatingFile>>>,
+}
+
+impl Output {
+    fn new(name: &str, log_file: Option<&Path>) -> anyhow::Result<Self> {
+        let file = match log_file {
+            Some(path) => {
+                let file = RotatingFile::open(path)
+                    .map_err(|e| anyhow!("unable to open log file {}: {}", path.display(), e))?;
+                Some(Arc::new(Mutex::new(file)))
+            }
+            None => None,
+        };
+        Ok(Output {
+            name: name.to_string(),
+            file,
+        })
+    }
+
+    fn write(&self, stream_name: &str, line: &str) {
+        match &self.file {
+            Some(file) => {
+                if let Err(e) = file.lock().unwrap().write_line(stream_name, line) {
+                    log::warn!("Unable to write output of extension '{}': {}", self.name, e);
+                }
+            }
+            None => log::info!("{}: {}", self.name, line),
+        }
+    }
+}
+
+/// Log file rotated, when it exceeds `MAX_LOG_SIZE`. Keeps `ROTATED_LOGS`
+/// previous files with `.1`, `.2`, ... suffixes.
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_size: u64,
+}
+
+impl RotatingFile {
+    fn open(path: &Path) -> io::Result<Self> {
+        Self::with_max_size(path, MAX_LOG_SIZE)
+    }
+
+    fn with_max_size(path: &Path, max_size: u64) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_size,
+        })
+    }
+
+    fn write_line(&mut self, stream_name: &str, line: &str) -> io::Result<()> {
+        if self.size >= self.max_size {
+            self.rotate()?;
+        }
+        let line = format!(
+            "[{} {}] {}\n",
+            Utc::now().format("%Y-%m-%dT%H:%M:%S%.3f%z"),
+            stream_name,
+            line
+        );
+        self.file.write_all(line.as_bytes())?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        for n in (1..ROTATED_LOGS).rev() {
+            let from = rotated_path(&self.path, n);
+            if from.exists() {
+                fs::rename(from, rotated_path(&self.path, n + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    let mut path = path.as_os_str().to_owned();
+    path.push(format!(".{}", n));
+    PathBuf::from(path)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::extension::ExtensionConf;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("yagna-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn policy(max_restarts: u32) -> Supervision {
+        Supervision {
+            max_restarts: Some(max_restarts),
+            backoff_initial_secs: 0,
+            backoff_max_secs: 0,
+            health_check: None,
+        }
+    }
+
+    async fn supervise_script(
+        name: &str,
+        script: &str,
+        supervision: Supervision,
+    ) -> ExtensionStatus {
+        let dir = test_dir(name);
+        let extension = Extension {
+            name: name.to_string(),
+            path: PathBuf::from("/bin/sh"),
+            conf: ExtensionConf {
+                args: vec!["-c".to_string(), script.to_string()],
+                supervision,
+                ..Default::default()
+            },
+        };
+        let ctx = ExtensionCtx::Cli {
+            data_dir: dir.clone(),
+            gsb_url: None,
+            json_output: false,
+        };
+        supervise(extension, ctx, Some(dir.clone())).await;
+        fs::remove_dir_all(&dir).unwrap();
+
+        status().into_iter().find(|s| s.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_backoff() {
+        let mut backoff = Backoff::new(&Supervision {
+            backoff_initial_secs: 1,
+            backoff_max_secs: 5,
+            ..Default::default()
+        });
+        let delays: Vec<_> = (0..5).map(|_| backoff.next().as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 5, 5]);
+
+        backoff.reset();
+        assert_eq!(backoff.next(), Duration::from_secs(1));
+    }
+
+    #[actix_rt::test]
+    async fn test_finished() {
+        let status = supervise_script("ext-finished", "exit 0", policy(2)).await;
+        assert_eq!(status.state, ExtensionState::Finished);
+        assert_eq!(status.restarts, 0);
+    }
+
+    #[actix_rt::test]
+    async fn test_max_restarts() {
+        let status = supervise_script("ext-failing", "exit 3", policy(2)).await;
+        assert_eq!(status.state, ExtensionState::Crashed);
+        assert_eq!(status.restarts, 2);
+        assert_eq!(status.last_exit_code, Some(3));
+        assert_eq!(status.last_error.as_deref(), Some("exit code 3"));
+    }
+
+    #[actix_rt::test]
+    async fn test_killed_by_signal() {
+        let status = supervise_script("ext-killed", "kill -9 $$", policy(1)).await;
+        assert_eq!(status.state, ExtensionState::Crashed);
+        assert_eq!(status.restarts, 1);
+        assert_eq!(status.last_exit_code, None);
+        assert!(status.last_error.unwrap().contains("signal"));
+    }
+
+    #[actix_rt::test]
+    async fn test_unhealthy() {
+        let mut check = HealthCheck::new(Probe::Command(vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "exit 1".to_string(),
+        ]));
+        check.interval_secs = 0;
+        check.retries = 2;
+        let supervision = Supervision {
+            health_check: Some(check),
+            ..policy(0)
+        };
+
+        let status = supervise_script("ext-unhealthy", "sleep 30", supervision).await;
+        assert_eq!(status.state, ExtensionState::Crashed);
+        assert_eq!(status.healthy, Some(false));
+        assert_eq!(status.last_error.as_deref(), Some("health check failure"));
+    }
+
+    #[test]
+    fn test_log_rotation() {
+        let dir = test_dir("ext-log-rotation");
+        let path = dir.join("ext.log");
+        let mut file = RotatingFile::with_max_size(&path, 100).unwrap();
+
+        for i in 0..20 {
+            file.write_line("stdout", &format!("line {:02} {}", i, "x".repeat(40)))
+                .unwrap();
+        }
+
+        let content = |path: &Path| fs::read_to_string(path).unwrap();
+        assert!(content(&path).contains("line 19"));
+        for n in 1..=ROTATED_LOGS {
+            assert!(fs::metadata(rotated_path(&path, n)).unwrap().len() >= 100);
+        }
+        assert!(!rotated_path(&path, ROTATED_LOGS + 1).exists());
+        assert!(content(&rotated_path(&path, 1)).contains("line 17"));
+        assert!(!content(&rotated_path(&path, 3)).contains("line 00"));
+
+        // Size of existing file is taken into account after reopening.
+        drop(file);
+        let mut file = RotatingFile::with_max_size(&path, 100).unwrap();
+        file.write_line("stderr", "last").unwrap();
+        assert_eq!(content(&path).lines().count(), 1);
+        assert!(content(&rotated_path(&path, 1)).contains("line 19"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
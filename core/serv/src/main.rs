@@ -27,8 +27,8 @@ use ya_sb_proto::{DEFAULT_GSB_URL, GSB_URL_ENV_VAR};
 use ya_service_api::{CliCtx, CommandOutput, ResponseTable};
 use ya_service_api_interfaces::Provider;
 use ya_service_api_web::{
-    middleware::{auth, Identity},
-    rest_api_host_port, DEFAULT_YAGNA_API_URL, YAGNA_API_URL_ENV_VAR,
+    middleware::{auth, Identity, Trace},
+    rest_api_listener, webhook, RestApiListener, DEFAULT_YAGNA_API_URL, YAGNA_API_URL_ENV_VAR,
 };
 use ya_sgx::SgxService;
 use ya_utils_path::data_dir::DataDir;
@@ -38,11 +38,14 @@ use ya_vpn::VpnService;
 
 use ya_service_bus::typed as gsb;
 
+mod api_listener;
 mod autocomplete;
 mod extension;
 mod model;
+mod shutdown;
 
-use crate::extension::Extension;
+use crate::api_listener::ApiListenerOpts;
+use crate::extension::{Extension, HealthCheck, Probe};
 use autocomplete::CompleteCommand;
 
 use ya_activity::TrackerRef;
@@ -246,11 +249,11 @@ enum Services {
     Metrics(MetricsService),
     #[enable(gsb, rest, cli)]
     Version(VersionService),
-    #[enable(gsb, cli)]
+    #[enable(gsb, rest, cli)]
     Net(NetService),
     #[enable(rest)]
     Vpn(VpnService),
-    #[enable(gsb, rest)]
+    #[enable(gsb, rest, cli)]
     Market(MarketService),
     #[enable(gsb, rest, cli)]
     Activity(ActivityService),
@@ -336,9 +339,23 @@ enum ExtensionCommand {
     /// List available extensions
     List {},
     /// Autostart extension
-    Register { args: Vec<String> },
+    Register {
+        /// Restarts in a row allowed after failures [default: unlimited]
+        #[structopt(long)]
+        max_restarts: Option<u32>,
+        /// Command checking extension health as JSON array of program and its arguments,
+        /// e.g. '["curl", "-f", "http://127.0.0.1:8080"]'. Has to exit with 0
+        #[structopt(long, conflicts_with = "health-check-url")]
+        health_check_cmd: Option<String>,
+        /// URL checking extension health, has to respond with 2xx status
+        #[structopt(long)]
+        health_check_url: Option<Url>,
+        args: Vec<String>,
+    },
     /// Remove extension from autostart
     Unregister { name: String },
+    /// Show state of autostarted extensions
+    Status {},
 }
 
 impl ExtensionCommand {
@@ -353,12 +370,31 @@ impl ExtensionCommand {
                     Self::table(extensions.into_iter())
                 }
             }
-            ExtensionCommand::Register { mut args } => {
+            ExtensionCommand::Register {
+                max_restarts,
+                health_check_cmd,
+                health_check_url,
+                mut args,
+            } => {
+                let health_check_cmd = match health_check_cmd {
+                    Some(cmd) => match serde_json::from_str::<Vec<String>>(&cmd) {
+                        Ok(cmd) if !cmd.is_empty() => Some(cmd),
+                        _ => anyhow::bail!(
+                            "--health-check-cmd has to be non-empty JSON array of strings"
+                        ),
+                    },
+                    None => None,
+                };
                 let mut ext = Extension::find(args.clone())?;
                 args.remove(0);
 
                 ext.conf.args = args;
                 ext.conf.autostart = true;
+                ext.conf.supervision.max_restarts = max_restarts;
+                ext.conf.supervision.health_check = health_check_cmd
+                    .map(Probe::Command)
+                    .or(health_check_url.map(Probe::Http))
+                    .map(HealthCheck::new);
                 ext.write_conf().await?;
 
                 Ok(CommandOutput::NoOutput)
@@ -370,9 +406,51 @@ impl ExtensionCommand {
 
                 Ok(CommandOutput::NoOutput)
             }
+            ExtensionCommand::Status {} => {
+                let statuses = gsb::service(model::BUS_ID)
+                    .call(model::GetExtensionStatus {})
+                    .await
+                    .context("Failed to connect to yagna service")?
+                    .map_err(anyhow::Error::msg)?;
+
+                if ctx.json_output {
+                    CommandOutput::object(statuses)
+                } else {
+                    Self::status_table(statuses)
+                }
+            }
         }
     }
 
+    fn status_table(statuses: Vec<model::ExtensionStatus>) -> Result<CommandOutput> {
+        Ok(ResponseTable {
+            columns: vec![
+                "name".into(),
+                "state".into(),
+                "pid".into(),
+                "restarts".into(),
+                "exit code".into(),
+                "healthy".into(),
+                "last error".into(),
+            ],
+            values: statuses
+                .into_iter()
+                .map(|status| {
+                    serde_json::json! {[
+                        status.name,
+                        status.state,
+                        status.pid,
+                        status.restarts,
+                        status.last_exit_code,
+                        status.healthy,
+                        status.last_error,
+                    ]}
+                })
+                .collect(),
+        }
+        .into())
+    }
+
     fn map<I: Iterator<Item = Extension>>(extensions: I) -> Result<CommandOutput> {
         Ok(CommandOutput::object(
             extensions
@@ -426,6 +504,9 @@ struct ServiceCommandOpts {
     )]
     api_url: Url,
 
+    #[structopt(flatten)]
+    api_listener: ApiListenerOpts,
+
     #[structopt(flatten)]
     metrics_opts: MetricsPusherOpts,
 
@@ -461,8 +542,16 @@ async fn sd_notify(unset_environment: bool, state: &str) -> std::io::Result<()>
 
 #[derive(StructOpt, Debug)]
 struct ShutdownOpts {
+    /// Wait for open REST connections to finish
     #[structopt(long)]
     gracefully: bool,
+    /// Stop accepting new Offers, Demands and Activities and wait for
+    /// running Activities, before terminating Agreements
+    #[structopt(long)]
+    drain: bool,
+    /// Maximum time (in seconds) to wait for running Activities in drain mode
+    #[structopt(long, default_value = "60")]
+    timeout: u64,
 }
 
 #[cfg(not(unix))]
@@ -479,6 +568,7 @@ impl ServiceCommand {
         match self {
             Self::Run(ServiceCommandOpts {
                 api_url,
+                api_listener,
                 metrics_opts,
                 max_rest_timeout,
                 log_dir,
@@ -495,14 +585,13 @@ impl ServiceCommand {
                 //this force_debug flag sets default log level to debug
                 //if the --debug option is set
                 let force_debug = *debug;
+                let log_dir = log_dir
+                    .clone()
+                    .or_else(|| Some(ctx.data_dir.clone()))
+                    .filter(|path| path.components().count() > 0);
                 let logger_handle = start_logger(
                     "info",
-                    log_dir.as_deref().or(Some(&ctx.data_dir)).and_then(|path| {
-                        match path.components().count() {
-                            0 => None,
-                            _ => Some(path),
-                        }
-                    }),
+                    log_dir.as_deref(),
                     &vec![
                         ("actix_http::response", log::LevelFilter::Off),
                         ("h2", log::LevelFilter::Off),
@@ -527,6 +616,10 @@ impl ServiceCommand {
                 );
                 log::info!("Data directory: {}", ctx.data_dir.display());
 
+                ya_utils_telemetry::init(app_name)
+                    .map_err(|e| log::warn!("Failed to initialize tracing: {}", e))
+                    .ok();
+
                 let _lock = ProcLock::new(app_name, &ctx.data_dir)?.lock(std::process::id())?;
 
                 ya_sb_router::bind_gsb_router(ctx.gsb_url.clone())
@@ -548,60 +641,105 @@ impl ServiceCommand {
                 payment_accounts::init_accounts(&ctx.data_dir)
                     .await
                     .unwrap_or_else(|e| log::error!("Initializing payment accounts failed: {}", e));
+                webhook::init(&ctx.data_dir)
+                    .unwrap_or_else(|e| log::error!("Restoring webhooks failed: {}", e));
 
-                let api_host_port = rest_api_host_port(api_url.clone());
-                let rest_address = api_host_port.clone();
+                let rest_address = api_url.clone();
 
                 let server = HttpServer::new(move || {
                     let app = App::new()
                         .wrap(middleware::Logger::default())
                         .wrap(auth::Auth::default())
+                        .wrap(Trace::default())
                         .route("/me", web::get().to(me))
-                        .service(forward_gsb);
+                        .route("/extensions", web::get().to(extensions))
+                        .service(forward_gsb)
+                        .service(webhook::web_scope());
 
                     let rest = Services::rest(app, &context);
                     log::info!("Http server thread started on: {}", rest_address);
                     rest
                 })
                 // this is maximum supported timeout for our REST API
-                .keep_alive(std::time::Duration::from_secs(*max_rest_timeout))
-                .bind(api_host_port.clone())
-                .context(format!("Failed to bind http server on {:?}", api_host_port))?;
+                .keep_alive(std::time::Duration::from_secs(*max_rest_timeout));
+
+                let server = match rest_api_listener(api_url.clone()) {
+                    RestApiListener::Tcp { host_port, tls } if tls => server
+                        .bind_openssl(&host_port, api_listener.tls_acceptor()?)
+                        .context(format!("Failed to bind https server on {:?}", host_port))?,
+                    RestApiListener::Tcp { host_port, .. } => server
+                        .bind(&host_port)
+                        .context(format!("Failed to bind http server on {:?}", host_port))?,
+                    #[cfg(unix)]
+                    RestApiListener::Unix(path) => {
+                        api_listener.bind_socket(&path, |private_path| {
+                            server
+                                .bind_uds(private_path)
+                                .context(format!("Failed to bind http server on {:?}", path))
+                        })?
+                    }
+                    #[cfg(not(unix))]
+                    RestApiListener::Unix(_) => {
+                        anyhow::bail!("REST API over Unix domain socket is not supported")
+                    }
+                }
+                .run();
 
-                let _ = extension::autostart(&ctx.data_dir, &api_url, &ctx.gsb_url)
+                let _ = extension::autostart(&ctx.data_dir, log_dir, &api_url, &ctx.gsb_url)
                     .await
                     .map_err(|e| log::warn!("Failed to autostart extensions: {e}"));
-
-                gsb::bind(model::BUS_ID, move |_request: model::ShutdownRequest| {
-                    log::warn!("ShutdownRequest not supported after migrating to new actix.");
-                    // actix_rt::spawn(async move {
-                    //     actix_rt::time::sleep(std::time::Duration::from_secs(1)).await;
-                    //     actix_rt::System::current().stop()
-                    // });
-
-                    async move { Ok(()) }
+                gsb::bind(model::BUS_ID, |_: model::GetExtensionStatus| async move {
+                    Ok(extension::status())
                 });
 
-                future::try_join(server.run(), sd_notify(false, "READY=1")).await?;
+                shutdown::bind_gsb(server.handle());
+
+                future::try_join(server, sd_notify(false, "READY=1")).await?;
 
                 log::info!("{} service successfully finished!", app_name);
 
-                PaymentService::shut_down().await;
+                if !shutdown::payments_flushed() {
+                    PaymentService::shut_down().await;
+                }
                 NetService::shutdown()
                     .await
                     .map_err(|e| log::error!("Error shutting down NET: {}", e))
                     .ok();
 
+                ya_utils_telemetry::shutdown();
                 logger_handle.shutdown();
                 Ok(CommandOutput::NoOutput)
             }
             Self::Shutdown(opts) => {
-                let result = gsb::service(model::BUS_ID)
+                gsb::service(model::BUS_ID)
                     .call(model::ShutdownRequest {
                         graceful: opts.gracefully,
+                        drain: opts.drain,
+                        drain_timeout: opts.timeout,
+                    })
+                    .await?
+                    .map_err(anyhow::Error::msg)?;
+
+                // Daemon answers until it exits, so failed call means, that shutdown is done.
+                let mut progress = Vec::new();
+                while let Ok(Ok(messages)) = gsb::service(model::BUS_ID)
+                    .call(model::ShutdownStatus {
+                        since: progress.len(),
                     })
-                    .await?;
-                CommandOutput::object(&result)
+                    .await
+                {
+                    if !ctx.json_output {
+                        messages.iter().for_each(|message| println!("{}", message));
+                    }
+                    progress.extend(messages);
+                    actix_rt::time::sleep(std::time::Duration::from_millis(500)).await;
+                }
+
+                if ctx.json_output {
+                    CommandOutput::object(progress)
+                } else {
+                    Ok(CommandOutput::Object("Service stopped.".into()))
+                }
             }
         }
     }
@@ -641,6 +779,10 @@ async fn me(id: Identity) -> impl Responder {
     web::Json(id)
 }
 
+async fn extensions(_id: Identity) -> impl Responder {
+    web::Json(extension::status())
+}
+
 #[actix_web::post("/_gsb/{service:.*}")]
 async fn forward_gsb(
     id: Identity,
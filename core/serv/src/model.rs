@@ -1,13 +1,23 @@
 //! Private service control api.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use ya_service_bus::RpcMessage;
 
 pub const BUS_ID: &str = "/local/control";
 
+/// Starts daemon shutdown. Progress can be followed with `ShutdownStatus`.
 #[derive(Serialize, Deserialize, Default)]
 pub struct ShutdownRequest {
     pub graceful: bool,
+    /// Stop accepting new work and wait for running Activities, before
+    /// terminating Agreements.
+    #[serde(default)]
+    pub drain: bool,
+    /// Maximum time in seconds to wait for running Activities.
+    #[serde(default)]
+    pub drain_timeout: u64,
 }
 
 impl RpcMessage for ShutdownRequest {
@@ -15,3 +25,53 @@ impl RpcMessage for ShutdownRequest {
     type Item = ();
     type Error = String;
 }
+
+/// Returns shutdown progress messages, skipping first `since` of them.
+#[derive(Serialize, Deserialize, Default)]
+pub struct ShutdownStatus {
+    pub since: usize,
+}
+
+impl RpcMessage for ShutdownStatus {
+    const ID: &'static str = "ShutdownStatus";
+    type Item = Vec<String>;
+    type Error = String;
+}
+
+/// State of an autostart extension supervised by the daemon.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ExtensionState {
+    Running,
+    /// Waiting to be restarted after failure.
+    Restarting,
+    /// Failed more times than allowed by `maxRestarts`.
+    Crashed,
+    /// Exited with code 0.
+    Finished,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExtensionStatus {
+    pub name: String,
+    pub state: ExtensionState,
+    pub pid: Option<u32>,
+    pub restarts: u32,
+    pub last_exit_code: Option<i32>,
+    pub last_error: Option<String>,
+    /// `None` when health check is not configured or not performed yet.
+    pub healthy: Option<bool>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub log_file: Option<PathBuf>,
+}
+
+/// Returns status of autostart extensions.
+#[derive(Serialize, Deserialize, Default)]
+pub struct GetExtensionStatus {}
+
+impl RpcMessage for GetExtensionStatus {
+    const ID: &'static str = "GetExtensionStatus";
+    type Item = Vec<ExtensionStatus>;
+    type Error = String;
+}
@@ -0,0 +1,283 @@
+//! Graceful daemon shutdown requested by `yagna service shutdown`.
+//!
+//! Shutdown is executed in background. CLI follows it by polling
+//! `ShutdownStatus` until the daemon disappears from the bus.
+
+use actix_web::dev::ServerHandle;
+use anyhow::anyhow;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use ya_client_model::market::Reason;
+use ya_client_model::NodeId;
+use ya_core_model::activity;
+use ya_core_model::identity;
+use ya_core_model::market::{self, AgreementListEntry, AgreementListFilter, AgreementState};
+use ya_payment::PaymentService;
+use ya_service_bus::{typed as bus, RpcEndpoint};
+
+use crate::model::{ShutdownRequest, ShutdownStatus, BUS_ID};
+
+const TERMINATION_REASON: &str = "Node is shutting down";
+const ACTIVITIES_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Default)]
+struct Progress {
+    started: bool,
+    payments_flushed: bool,
+    messages: Vec<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref PROGRESS: Mutex<Progress> = Default::default();
+}
+
+pub fn bind_gsb(server: ServerHandle) {
+    bus::bind(BUS_ID, move |request: ShutdownRequest| {
+        start(request, server.clone());
+        async move { Ok(()) }
+    });
+    bus::bind(BUS_ID, |status: ShutdownStatus| async move {
+        let progress = PROGRESS.lock().unwrap();
+        Ok(progress
+            .messages
+            .iter()
+            .skip(status.since)
+            .cloned()
+            .collect())
+    });
+}
+
+/// Payments are flushed during shutdown, so they don't have to be flushed
+/// again after the server has stopped.
+pub fn payments_flushed() -> bool {
+    PROGRESS.lock().unwrap().payments_flushed
+}
+
+fn start(request: ShutdownRequest, server: ServerHandle) {
+    {
+        let mut progress = PROGRESS.lock().unwrap();
+        if progress.started {
+            log::info!("Shutdown already in progress.");
+            return;
+        }
+        progress.started = true;
+    }
+
+    actix_rt::spawn(async move {
+        if request.drain {
+            ya_service_api::drain::start_draining();
+            report("Stopped accepting new Offers, Demands and Activities.");
+            wait_for_activities(Duration::from_secs(request.drain_timeout)).await;
+        }
+
+        report("Sending out pending payments.");
+        PaymentService::shut_down().await;
+        PROGRESS.lock().unwrap().payments_flushed = true;
+
+        // Without draining Agreements are left to be continued after restart.
+        if request.drain {
+            terminate_agreements().await;
+        }
+
+        report("Stopping service.");
+        server.stop(request.graceful).await;
+    });
+}
+
+fn report(message: impl ToString) {
+    let message = message.to_string();
+    log::info!("Shutdown: {}", message);
+    PROGRESS.lock().unwrap().messages.push(message);
+}
+
+async fn wait_for_activities(timeout: Duration) {
+    let deadline = actix_rt::time::Instant::now() + timeout;
+    let mut last_running = None;
+
+    loop {
+        let running = match running_activities().await {
+            Ok(running) => running,
+            Err(e) => {
+                report(format!("Failed to check running Activities: {}", e));
+                return;
+            }
+        };
+
+        if running == 0 {
+            report("No Activities running.");
+            return;
+        }
+        if actix_rt::time::Instant::now() >= deadline {
+            report(format!(
+                "Timeout reached with {} Activities still running.",
+                running
+            ));
+            return;
+        }
+        if last_running != Some(running) {
+            report(format!("Waiting for {} running Activities.", running));
+            last_running = Some(running);
+        }
+        actix_rt::time::sleep(ACTIVITIES_CHECK_INTERVAL).await;
+    }
+}
+
+/// Activities of Agreements, that were already terminated or expired,
+/// don't hold the shutdown, even if they were never marked as Terminated.
+async fn running_activities() -> anyhow::Result<u64> {
+    let mut agreement_ids = Vec::new();
+    for node_id in list_identities().await? {
+        let agreements = approved_agreements(node_id)
+            .await
+            .map_err(|e| anyhow!("Failed to list Agreements of {}: {}", node_id, e))?;
+        agreement_ids.extend(agreements.into_iter().map(|a| a.agreement_id));
+    }
+    if agreement_ids.is_empty() {
+        return Ok(0);
+    }
+
+    Ok(bus::service(activity::local::BUS_ID)
+        .send(activity::local::CountActive { agreement_ids })
+        .await??)
+}
+
+async fn approved_agreements(node_id: NodeId) -> anyhow::Result<Vec<AgreementListEntry>> {
+    let filter = AgreementListFilter {
+        state: Some(AgreementState::Approved),
+        ..Default::default()
+    };
+    Ok(bus::service(market::local::BUS_ID)
+        .send(market::ListAgreements { node_id, filter })
+        .await??)
+}
+
+async fn terminate_agreements() {
+    let identities = match list_identities().await {
+        Ok(identities) => identities,
+        Err(e) => {
+            report(format!("Failed to list identities: {}", e));
+            return;
+        }
+    };
+
+    let mut terminated = 0;
+    for node_id in identities {
+        let agreements = match approved_agreements(node_id).await {
+            Ok(agreements) => agreements,
+            Err(e) => {
+                report(format!("Failed to list Agreements of {}: {}", node_id, e));
+                continue;
+            }
+        };
+
+        for agreement in agreements {
+            let msg = market::TerminateAgreement {
+                agreement_id: agreement.agreement_id.clone(),
+                node_id,
+                reason: Some(Reason::new(TERMINATION_REASON)),
+            };
+            match bus::service(market::local::BUS_ID).send(msg).await {
+                Ok(Ok(())) => terminated += 1,
+                Ok(Err(e)) => report(format!(
+                    "Failed to terminate Agreement [{}]: {}",
+                    agreement.agreement_id, e
+                )),
+                Err(e) => report(format!(
+                    "Failed to terminate Agreement [{}]: {}",
+                    agreement.agreement_id, e
+                )),
+            }
+        }
+    }
+
+    report(format!("Terminated {} Agreements.", terminated));
+}
+
+async fn list_identities() -> anyhow::Result<Vec<NodeId>> {
+    Ok(bus::service(identity::BUS_ID)
+        .send(identity::List {})
+        .await??
+        .into_iter()
+        .map(|identity| identity.node_id)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::sync::Arc;
+    use ya_core_model::Role;
+
+    fn node_id(n: u8) -> NodeId {
+        format!("0x{:040x}", n).parse().unwrap()
+    }
+
+    fn agreement(agreement_id: &str) -> AgreementListEntry {
+        AgreementListEntry {
+            agreement_id: agreement_id.to_string(),
+            role: Role::Provider,
+            state: AgreementState::Approved,
+            peer_id: node_id(0xff),
+            app_session_id: None,
+            timestamp: Utc::now(),
+            valid_to: Utc::now(),
+            approved_date: Some(Utc::now()),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_running_activities_of_approved_agreements() {
+        let agreements: Arc<Mutex<Vec<(NodeId, AgreementListEntry)>>> = Default::default();
+        let counted: Arc<Mutex<Vec<Vec<String>>>> = Default::default();
+
+        bus::bind(identity::BUS_ID, |_: identity::List| async move {
+            Ok((1..=2)
+                .map(|n| identity::IdentityInfo {
+                    alias: None,
+                    node_id: node_id(n),
+                    is_locked: false,
+                    is_default: n == 1,
+                    is_external: false,
+                })
+                .collect())
+        });
+        let listed = agreements.clone();
+        bus::bind(market::local::BUS_ID, move |msg: market::ListAgreements| {
+            assert_eq!(msg.filter.state, Some(AgreementState::Approved));
+            let result = listed
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(owner, _)| *owner == msg.node_id)
+                .map(|(_, agreement)| agreement.clone())
+                .collect();
+            async move { Ok(result) }
+        });
+        let requests = counted.clone();
+        bus::bind(
+            activity::local::BUS_ID,
+            move |msg: activity::local::CountActive| {
+                let active = msg.agreement_ids.len() as u64 * 2;
+                requests.lock().unwrap().push(msg.agreement_ids);
+                async move { Ok(active) }
+            },
+        );
+
+        // No Approved Agreements, so there is nothing to wait for.
+        assert_eq!(running_activities().await.unwrap(), 0);
+        assert!(counted.lock().unwrap().is_empty());
+
+        // Agreements of all identities are checked.
+        agreements.lock().unwrap().extend(vec![
+            (node_id(1), agreement("first")),
+            (node_id(2), agreement("second")),
+        ]);
+        assert_eq!(running_activities().await.unwrap(), 4);
+        assert_eq!(
+            *counted.lock().unwrap(),
+            vec![vec!["first".to_string(), "second".to_string()]]
+        );
+    }
+}
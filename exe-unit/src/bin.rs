@@ -19,6 +19,7 @@ use ya_exe_unit::service::signal::SignalMonitor;
 use ya_exe_unit::service::transfer::TransferService;
 use ya_exe_unit::state::Supervision;
 use ya_exe_unit::{ExeUnit, ExeUnitContext};
+use ya_file_logging::LogContext;
 use ya_utils_path::normalize_path;
 
 #[derive(structopt::StructOpt, Debug)]
@@ -176,6 +177,7 @@ async fn send_script(
         batch_id: hex::encode(&rand::random::<[u8; 16]>()),
         exe_script,
         timeout: None,
+        trace_context: None,
     };
     if let Err(e) = exe_unit
         .send(RpcEnvelope::with_caller(String::new(), msg))
@@ -261,6 +263,16 @@ async fn run() -> anyhow::Result<()> {
         )
     })?;
 
+    ya_file_logging::set_global(LogContext {
+        node_id: agreement
+            .pointer("/offer/providerId")
+            .and_then(|id| id.as_str())
+            .map(ToString::to_string),
+        agreement_id: Some(agreement.inner.agreement_id.clone()),
+        activity_id: ctx_activity_id.clone(),
+        batch_id: None,
+    });
+
     log::info!("Attempting to read app manifest ..");
 
     let manifest_ctx =
@@ -318,11 +330,17 @@ async fn main() {
         log::warn!("Using fallback logging due to an error: {:?}", error);
     };
 
-    std::process::exit(match run().await {
+    ya_utils_telemetry::init("exe-unit")
+        .map_err(|e| log::warn!("Failed to initialize tracing: {}", e))
+        .ok();
+
+    let code = match run().await {
         Ok(_) => 0,
         Err(error) => {
             log::error!("{}", error);
             1
         }
-    })
+    };
+    ya_utils_telemetry::shutdown();
+    std::process::exit(code)
 }
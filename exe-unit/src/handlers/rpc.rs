@@ -10,6 +10,7 @@ use tokio::time::timeout;
 use ya_client_model::activity::encrypted::RpcMessageError as SgxMessageError;
 use ya_client_model::activity::{ActivityState, ActivityUsage, ExeScriptCommandResult};
 use ya_core_model::activity::*;
+use ya_file_logging::{in_log_context, LogContext};
 use ya_service_bus::{Error as RpcError, RpcEnvelope, RpcStreamCall};
 
 use crate::error::Error;
@@ -41,14 +42,14 @@ impl<R: Runtime> Handler<RpcEnvelope<Exec>> for ExeUnit<R> {
         let (tx, rx) = oneshot::channel();
         self.state.start_batch(msg.clone(), tx);
 
-        RuntimeRef::from_ctx(&ctx)
-            .exec(
-                msg,
-                self.runtime.clone(),
-                self.transfers.clone(),
-                self.events.tx.clone(),
-                rx,
-            )
+        let exec = RuntimeRef::from_ctx(&ctx).exec(
+            msg,
+            self.runtime.clone(),
+            self.transfers.clone(),
+            self.events.tx.clone(),
+            rx,
+        );
+        in_log_context(LogContext::batch(&batch_id), exec)
             .into_actor(self)
             .spawn(ctx);
 
@@ -240,6 +241,7 @@ impl<R: Runtime> Handler<RpcEnvelope<sgx::CallEncryptedService>> for ExeUnit<R>
                         batch_id,
                         timeout,
                         exe_script,
+                        trace_context: None,
                     };
                     Response::Exec(
                         me.send(RpcEnvelope::local(msg))
@@ -17,6 +17,9 @@ use ya_core_model::activity;
 use ya_core_model::activity::local::Credentials;
 use ya_runtime_api::deploy;
 use ya_service_bus::{actix_rpc, RpcEndpoint, RpcMessage};
+use ya_utils_telemetry::{
+    self as telemetry, KeyValue, SpanKind, StatusCode, TraceContextExt, TraceFutureExt,
+};
 
 use crate::acl::Acl;
 use crate::agreement::Agreement;
@@ -151,6 +154,21 @@ impl<R: Runtime> RuntimeRef<R> {
         mut control: oneshot::Receiver<()>,
     ) {
         let batch_id = exec.batch_id.clone();
+        // Batches are traced only as a part of the requestor's trace.
+        let otel_cx = exec
+            .trace_context
+            .as_deref()
+            .map(telemetry::extract)
+            .filter(telemetry::is_traced)
+            .map(|parent| {
+                let otel_cx = telemetry::start_span("exe-unit batch", SpanKind::Consumer, &parent);
+                let span = otel_cx.span();
+                span.set_attribute(KeyValue::new("activity_id", exec.activity_id.clone()));
+                span.set_attribute(KeyValue::new("batch_id", batch_id.clone()));
+                otel_cx
+            })
+            .unwrap_or_default();
+
         for (idx, command) in exec.exe_script.into_iter().enumerate() {
             if let Ok(Some(_)) = control.try_recv() {
                 log::warn!("Batch {} execution aborted", batch_id);
@@ -169,11 +187,26 @@ impl<R: Runtime> RuntimeRef<R> {
                 log::error!("Unable to report event: {:?}", e);
             }
 
+            let cmd_cx = if telemetry::is_traced(&otel_cx) {
+                let cmd_cx =
+                    telemetry::start_span("exe-script command", SpanKind::Internal, &otel_cx);
+                cmd_cx
+                    .span()
+                    .set_attribute(KeyValue::new("command_index", idx as i64));
+                cmd_cx
+            } else {
+                otel_cx.clone()
+            };
+
             let (return_code, message) = match {
                 if runtime_cmd.stateless() {
-                    self.exec_stateless(&runtime_cmd).await
+                    self.exec_stateless(&runtime_cmd)
+                        .with_context(cmd_cx.clone())
+                        .await
                 } else {
-                    self.exec_stateful(runtime_cmd, &runtime, &transfers).await
+                    self.exec_stateful(runtime_cmd, &runtime, &transfers)
+                        .with_context(cmd_cx.clone())
+                        .await
                 }
             } {
                 Ok(_) => (0, None),
@@ -191,6 +224,8 @@ impl<R: Runtime> RuntimeRef<R> {
             if return_code != 0 {
                 let message = message.unwrap_or_else(|| "reason unspecified".into());
                 log::warn!("Batch {} execution interrupted: {}", batch_id, message);
+                cmd_cx.span().set_status(StatusCode::Error, message.clone());
+                otel_cx.span().set_status(StatusCode::Error, message);
                 break;
             }
         }
@@ -1,6 +1,7 @@
 use chrono::{DateTime, Local};
 use flexi_logger::{DeferredNow, Record};
 use std::time::SystemTime;
+use ya_file_logging::LogFormat;
 
 const ENV_VAR_LOG_DIR: &'static str = "EXE_UNIT_LOG_DIR";
 const ENV_VAR_FILE_LOG_LEVEL: &'static str = "EXE_UNIT_FILE_LOG_LEVEL";
@@ -15,7 +16,12 @@ pub fn start_file_logger() -> anyhow::Result<flexi_logger::LoggerHandle> {
     let log_level =
         std::env::var(ENV_VAR_FILE_LOG_LEVEL).unwrap_or(DEFAULT_FILE_LOG_LEVEL.to_string());
 
-    Ok(build_logger(Some(log_level))?
+    let mut logger = build_logger(Some(log_level))?;
+    if LogFormat::from_env()? == LogFormat::Json {
+        logger = logger.format_for_files(log_format_json);
+    }
+
+    Ok(logger
         .log_to_file(flexi_logger::FileSpec::default().directory(log_dir))
         .duplicate_to_stderr(log_tty_dup_level()?)
         .start()?)
@@ -77,3 +83,12 @@ fn log_format(
         record.args()
     )
 }
+
+fn log_format_json(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    let now = SystemTime::from(*now.now());
+    ya_file_logging::write_json(w, DateTime::<Local>::from(now), record)
+}
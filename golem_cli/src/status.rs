@@ -7,6 +7,7 @@ use futures::prelude::*;
 use prettytable::{cell, format, row, Table};
 use strum::VariantNames;
 
+use ya_client::model::market::Offer;
 use ya_core_model::payment::local::{NetworkName, StatusResult};
 use ya_core_model::NodeId;
 
@@ -240,9 +241,7 @@ pub async fn run() -> Result</*exit code*/ i32> {
 async fn get_payment_network() -> Result<(usize, NetworkName)> {
     // Dirty hack: we determine currently used payment network by checking latest offer properties
     let app_key = appkey::get_app_key().await?;
-    let mkt_api: ya_client::market::MarketProviderApi =
-        ya_client::web::WebClient::with_token(&app_key).interface()?;
-    let offers = mkt_api.get_offers().await?;
+    let offers = get_offers(&app_key).await?;
 
     let latest_offer = offers.iter().max_by_key(|o| o.timestamp).ok_or(anyhow!(
         "Provider is not functioning properly. No offers Subscribed."
@@ -265,3 +264,26 @@ async fn get_payment_network() -> Result<(usize, NetworkName)> {
     ))?;
     Ok((offers.len(), network))
 }
+
+async fn get_offers(app_key: &str) -> Result<Vec<Offer>> {
+    #[cfg(unix)]
+    {
+        if let Some(socket) =
+            ya_utils_networking::unix_socket::socket_path(&crate::utils::yagna_api_url()?)
+        {
+            // `ya-client` connects only over TCP.
+            return ya_utils_networking::unix_socket::client(&socket)
+                .get("http://localhost/market-api/v1/offers")
+                .bearer_auth(app_key)
+                .send()
+                .await
+                .map_err(|e| anyhow!("Failed to get offers: {}", e))?
+                .json()
+                .await
+                .map_err(|e| anyhow!("Failed to get offers: {}", e));
+        }
+    }
+    let mkt_api: ya_client::market::MarketProviderApi =
+        ya_client::web::WebClient::with_token(app_key).interface()?;
+    Ok(mkt_api.get_offers().await?)
+}
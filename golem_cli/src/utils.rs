@@ -71,16 +71,20 @@ async fn wait_for_socket(addr: std::net::SocketAddr) -> Result<()> {
     }
 }
 
-fn yagna_addr() -> Result<std::net::SocketAddr> {
-    Ok(Url::parse(
+pub fn yagna_api_url() -> Result<Url> {
+    Url::parse(
         &std::env::var("YAGNA_API_URL").unwrap_or_else(|_| "http://127.0.0.1:7465".to_string()),
     )
-    .context("Failed to parse yagna API URL")?
-    .socket_addrs(|| None)
-    .context("Failed to resolve yagna API URL")?
-    .drain(..)
-    .next()
-    .unwrap())
+    .context("Failed to parse yagna API URL")
+}
+
+fn yagna_addr() -> Result<std::net::SocketAddr> {
+    Ok(yagna_api_url()?
+        .socket_addrs(|| None)
+        .context("Failed to resolve yagna API URL")?
+        .drain(..)
+        .next()
+        .unwrap())
 }
 
 #[cfg(not(unix))]
@@ -89,6 +93,12 @@ pub async fn wait_for_yagna() -> Result<()> {
 }
 
 pub async fn is_yagna_running() -> Result<bool> {
+    #[cfg(unix)]
+    {
+        if let Some(socket) = ya_utils_networking::unix_socket::socket_path(&yagna_api_url()?) {
+            return Ok(tokio::net::UnixStream::connect(socket).await.is_ok());
+        }
+    }
     Ok(TcpStream::connect(yagna_addr()?).await.is_ok())
 }
 
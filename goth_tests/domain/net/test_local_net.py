@@ -0,0 +1,82 @@
+"""Tests of yagna daemons connected with LAN only network."""
+
+import json
+import logging
+from pathlib import Path
+from typing import List
+
+import pytest
+
+from goth.configuration import load_yaml, Override
+from goth.runner import Runner
+from goth.runner.probe import RequestorProbe
+
+from goth_tests.helpers.activity import wasi_exe_script, wasi_task_package
+from goth_tests.helpers.negotiation import DemandBuilder, negotiate_agreements
+from goth_tests.helpers.payment import pay_all
+from goth_tests.helpers.probe import ProviderProbe
+
+logger = logging.getLogger("goth.test.local_net")
+
+
+@pytest.mark.asyncio
+async def test_local_net(
+    common_assets: Path,
+    config_overrides: List[Override],
+    log_dir: Path,
+):
+    """Test market, activity and payment flow between two daemons.
+
+    Daemons find each other with multicast announcements and exchange
+    messages over authenticated direct connections, without relay server.
+    """
+
+    goth_config = load_yaml(Path(__file__).parent / "goth-config.yml", config_overrides)
+
+    runner = Runner(
+        base_log_dir=log_dir,
+        compose_config=goth_config.compose_config,
+        web_root_path=common_assets / "web-root",
+    )
+
+    async with runner(goth_config.containers):
+        requestor = runner.get_probes(probe_type=RequestorProbe)[0]
+        providers = runner.get_probes(probe_type=ProviderProbe)
+        assert providers
+
+        # Market
+        task_package = wasi_task_package.format(
+            web_server_addr=runner.host_address, web_server_port=runner.web_server_port
+        )
+        demand = (
+            DemandBuilder(requestor)
+            .props_from_template(task_package)
+            .constraints("(&(golem.runtime.name=wasmtime))")
+            .build()
+        )
+
+        agreement_providers = await negotiate_agreements(
+            requestor,
+            demand,
+            providers,
+        )
+
+        # Activity
+        exe_script = wasi_exe_script(runner)
+
+        for agreement_id, provider in agreement_providers:
+            logger.info("Running activity on %s", provider.name)
+            activity_id = await requestor.create_activity(agreement_id)
+            await provider.wait_for_exeunit_started()
+            batch_id = await requestor.call_exec(activity_id, json.dumps(exe_script))
+            await requestor.collect_results(
+                activity_id, batch_id, len(exe_script), timeout=30
+            )
+            await requestor.destroy_activity(activity_id)
+            await provider.wait_for_exeunit_finished()
+
+            await requestor.terminate_agreement(agreement_id, None)
+            await provider.wait_for_agreement_terminated()
+
+        # Payment
+        await pay_all(requestor, agreement_providers)
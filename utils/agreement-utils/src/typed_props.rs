@@ -1,6 +1,7 @@
 use crate::OfferTemplate;
 
 use serde_json::Value;
+use std::collections::BTreeMap;
 
 pub trait OfferBuilder {
     fn build(&self) -> Value;
@@ -103,6 +104,7 @@ pub struct InfNodeInfo {
     mem_gib: Option<f64>,
     storage_gib: Option<f64>,
     cpu_info: Option<CpuInfo>,
+    custom: BTreeMap<String, Value>,
 }
 
 impl InfNodeInfo {
@@ -132,6 +134,12 @@ impl InfNodeInfo {
         }
     }
 
+    /// Sets `golem.inf.<name>` property.
+    pub fn with_custom(mut self, name: impl ToString, value: impl Into<Value>) -> Self {
+        self.custom.insert(name.to_string(), value.into());
+        self
+    }
+
     fn write_json(self, map: &mut serde_json::Map<String, Value>) {
         let mut inf_map = serde_json::Map::new();
         if let Some(mem) = self.mem_gib {
@@ -143,6 +151,7 @@ impl InfNodeInfo {
         if let Some(cpu) = self.cpu_info {
             cpu.write_json(&mut inf_map);
         }
+        inf_map.extend(self.custom);
         let _ = map.insert("inf".to_string(), inf_map.into());
     }
 }
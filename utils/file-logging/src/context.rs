@@ -0,0 +1,130 @@
+//! Identifiers attached to JSON log lines.
+//!
+//! Process-wide fields are set once with [`set_global`]. Code handling
+//! particular Agreement or Activity either [`enter`]s its context for the
+//! duration of synchronous code, or wraps futures with [`in_log_context`],
+//! which re-enters context on each poll.
+
+use lazy_static::lazy_static;
+use pin_project::pin_project;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::RwLock;
+use std::task::{Context, Poll};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LogContext {
+    pub node_id: Option<String>,
+    pub agreement_id: Option<String>,
+    pub activity_id: Option<String>,
+    pub batch_id: Option<String>,
+}
+
+impl LogContext {
+    pub fn agreement(agreement_id: impl ToString) -> Self {
+        LogContext {
+            agreement_id: Some(agreement_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn activity(agreement_id: impl ToString, activity_id: impl ToString) -> Self {
+        LogContext {
+            agreement_id: Some(agreement_id.to_string()),
+            activity_id: Some(activity_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn batch(batch_id: impl ToString) -> Self {
+        LogContext {
+            batch_id: Some(batch_id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    /// Fields set in `other` take precedence.
+    fn merge(&self, other: &LogContext) -> LogContext {
+        LogContext {
+            node_id: other.node_id.clone().or_else(|| self.node_id.clone()),
+            agreement_id: other
+                .agreement_id
+                .clone()
+                .or_else(|| self.agreement_id.clone()),
+            activity_id: other
+                .activity_id
+                .clone()
+                .or_else(|| self.activity_id.clone()),
+            batch_id: other.batch_id.clone().or_else(|| self.batch_id.clone()),
+        }
+    }
+}
+
+lazy_static! {
+    static ref GLOBAL: RwLock<LogContext> = Default::default();
+}
+
+thread_local! {
+    static CURRENT: RefCell<LogContext> = RefCell::new(LogContext::default());
+}
+
+/// Sets fields attached to all log lines of this process.
+pub fn set_global(context: LogContext) {
+    let mut global = GLOBAL.write().unwrap();
+    *global = global.merge(&context);
+}
+
+/// Context of log lines emitted from the current thread.
+pub fn current() -> LogContext {
+    let global = GLOBAL.read().unwrap().clone();
+    CURRENT.with(|current| global.merge(&current.borrow()))
+}
+
+/// Restores previous context of the thread when dropped.
+#[must_use]
+pub struct LogContextGuard {
+    previous: LogContext,
+}
+
+/// Adds fields of `context` to log lines emitted from the current thread.
+pub fn enter(context: &LogContext) -> LogContextGuard {
+    CURRENT.with(|current| {
+        let mut current = current.borrow_mut();
+        let previous = current.clone();
+        *current = previous.merge(context);
+        LogContextGuard { previous }
+    })
+}
+
+impl Drop for LogContextGuard {
+    fn drop(&mut self) {
+        let previous = std::mem::take(&mut self.previous);
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
+
+#[pin_project]
+pub struct InLogContext<F> {
+    context: LogContext,
+    #[pin]
+    inner: F,
+}
+
+/// Adds fields of `context` to log lines emitted while polling `future`.
+pub fn in_log_context<F: Future>(context: LogContext, future: F) -> InLogContext<F> {
+    InLogContext {
+        context,
+        inner: future,
+    }
+}
+
+impl<F: Future> Future for InLogContext<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let _guard = enter(this.context);
+        this.inner.poll(cx)
+    }
+}
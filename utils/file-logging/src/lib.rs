@@ -7,15 +7,52 @@ use flexi_logger::{
     LogSpecification, Logger, Naming, Record,
 };
 use std::path::Path;
+use std::str::FromStr;
 
 pub use flexi_logger::LoggerHandle;
 
+pub use context::{current, enter, in_log_context, set_global, LogContext};
+
+pub mod context;
+
+pub const ENV_LOG_FORMAT: &str = "YAGNA_LOG_FORMAT";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogFormat {
+    Text,
+    /// One JSON object per line, with `LogContext` fields when known
+    Json,
+}
+
+impl LogFormat {
+    /// Format of log files, set in `YAGNA_LOG_FORMAT` env var.
+    pub fn from_env() -> Result<LogFormat> {
+        match std::env::var(ENV_LOG_FORMAT) {
+            Ok(format) => format.parse(),
+            Err(_) => Ok(LogFormat::Text),
+        }
+    }
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "" | "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => anyhow::bail!("Invalid log format: {}. Expected `text` or `json`", s),
+        }
+    }
+}
+
+//format date as following: 2020-08-27T07:56:22.348+02:00 (local date + time zone with milliseconds precision)
+const DATE_FORMAT_STR: &str = "%Y-%m-%dT%H:%M:%S%.3f%z";
+
 fn log_format_date(now: &mut DeferredNow) -> DelayedFormat<StrftimeItems> {
     //use DateTime::<Local> instead of DateTime::<UTC> to obtain local date
     let local_date = DateTime::<Local>::from(*now.now());
 
-    //format date as following: 2020-08-27T07:56:22.348+02:00 (local date + time zone with milliseconds precision)
-    const DATE_FORMAT_STR: &str = "%Y-%m-%dT%H:%M:%S%.3f%z";
     local_date.format(DATE_FORMAT_STR)
 }
 
@@ -34,6 +71,46 @@ fn log_format(
     )
 }
 
+/// Writes `record` as JSON object with stable set of fields: `timestamp`,
+/// `level`, `target`, `message` and `node_id`, `agreement_id`, `activity_id`,
+/// `batch_id` of current `LogContext`, when set.
+pub fn write_json(
+    w: &mut dyn std::io::Write,
+    now: DateTime<Local>,
+    record: &log::Record,
+) -> Result<(), std::io::Error> {
+    let context = context::current();
+    let fields = [
+        ("timestamp", Some(now.format(DATE_FORMAT_STR).to_string())),
+        ("level", Some(record.level().to_string())),
+        ("target", Some(record.target().to_string())),
+        ("node_id", context.node_id),
+        ("agreement_id", context.agreement_id),
+        ("activity_id", context.activity_id),
+        ("batch_id", context.batch_id),
+        ("message", Some(record.args().to_string())),
+    ];
+
+    write!(w, "{{")?;
+    let mut separator = "";
+    for (name, value) in fields.iter() {
+        if let Some(value) = value {
+            write!(w, "{}\"{}\":", separator, name)?;
+            serde_json::to_writer(&mut *w, value)?;
+            separator = ",";
+        }
+    }
+    write!(w, "}}")
+}
+
+fn log_format_json(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write_json(w, DateTime::<Local>::from(*now.now()), record)
+}
+
 fn log_format_color(
     w: &mut dyn std::io::Write,
     now: &mut DeferredNow,
@@ -50,7 +127,11 @@ fn log_format_color(
     )
 }
 
-fn set_logging_to_files(logger: Logger, log_dir: &Path) -> Logger {
+fn set_logging_to_files(logger: Logger, log_dir: &Path, format: LogFormat) -> Logger {
+    let logger = match format {
+        LogFormat::Text => logger,
+        LogFormat::Json => logger.format_for_files(log_format_json),
+    };
     logger
         .log_to_file()
         .directory(log_dir)
@@ -85,7 +166,7 @@ pub fn start_logger(
     let log_spec = log_spec_builder.finalize();
     let mut logger = Logger::with(log_spec).format(log_format);
     if let Some(log_dir) = log_dir {
-        logger = set_logging_to_files(logger, log_dir);
+        logger = set_logging_to_files(logger, log_dir, LogFormat::from_env()?);
     }
     logger = logger
         .adaptive_format_for_stderr(AdaptiveFormat::Custom(log_format, log_format_color))
@@ -93,3 +174,45 @@ pub fn start_logger(
 
     Ok(logger.start()?)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_json_format() {
+        let now = Local.ymd(2020, 8, 27).and_hms_milli(7, 56, 22, 348);
+        let record = |message| {
+            let mut out = Vec::new();
+            write_json(
+                &mut out,
+                now,
+                &log::Record::builder()
+                    .level(log::Level::Info)
+                    .target("ya_provider::tasks")
+                    .args(format_args!("{}", message))
+                    .build(),
+            )
+            .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&out).unwrap()
+        };
+
+        let line = record("Activity \"a1\" created");
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["target"], "ya_provider::tasks");
+        assert_eq!(line["message"], "Activity \"a1\" created");
+        assert!(line.get("agreement_id").is_none());
+
+        let _guard = enter(&LogContext::activity("agr1", "act1"));
+        let line = record("Activity destroyed");
+        assert_eq!(line["agreement_id"], "agr1");
+        assert_eq!(line["activity_id"], "act1");
+        assert!(line.get("batch_id").is_none());
+        {
+            let _guard = enter(&LogContext::batch("b1"));
+            assert_eq!(record("Batch started")["batch_id"], "b1");
+        }
+        assert!(record("Batch finished").get("batch_id").is_none());
+    }
+}
@@ -1,4 +1,6 @@
 #[cfg(feature = "dns")]
 pub mod resolver;
+#[cfg(all(unix, feature = "unix-socket"))]
+pub mod unix_socket;
 #[cfg(feature = "vpn")]
 pub mod vpn;
@@ -0,0 +1,100 @@
+//! HTTP client of REST API served over Unix domain socket (`unix://` API URL).
+//!
+//! Client connects straight to the socket, whatever host is given in request
+//! URL, so API paths are built as for `http://` URL.
+
+use actix_service::fn_service;
+use actix_tls::connect::{ConnectError, ConnectInfo, Connection};
+use awc::http::Uri;
+use awc::{Client, Connector};
+use std::path::{Path, PathBuf};
+use tokio::net::UnixStream;
+use url::Url;
+
+pub const UNIX_SOCKET_SCHEME: &str = "unix";
+
+/// Returns socket path, when `api_url` points to Unix domain socket.
+pub fn socket_path(api_url: &Url) -> Option<PathBuf> {
+    match api_url.scheme() {
+        UNIX_SOCKET_SCHEME => Some(PathBuf::from(api_url.path())),
+        _ => None,
+    }
+}
+
+/// Builds `awc` client sending every request to `socket`.
+pub fn client(socket: &Path) -> Client {
+    let socket = socket.to_path_buf();
+    let connector = Connector::new().connector(fn_service(move |req: ConnectInfo<Uri>| {
+        let socket = socket.clone();
+        async move {
+            let io = UnixStream::connect(&socket)
+                .await
+                .map_err(ConnectError::Io)?;
+            Ok::<_, ConnectError>(Connection::new(req.request().clone(), io))
+        }
+    }));
+    Client::builder().connector(connector).finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixListener;
+
+    fn serve_once(socket: &Path) -> std::thread::JoinHandle<String> {
+        let listener = UnixListener::bind(socket).unwrap();
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            reader
+                .into_inner()
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok")
+                .unwrap();
+            request_line
+        })
+    }
+
+    #[test]
+    fn test_socket_path() {
+        let url = Url::parse("unix:///run/yagna/api.sock").unwrap();
+        assert_eq!(
+            socket_path(&url),
+            Some(PathBuf::from("/run/yagna/api.sock"))
+        );
+        let url = Url::parse("http://127.0.0.1:7465").unwrap();
+        assert_eq!(socket_path(&url), None);
+    }
+
+    #[actix_rt::test]
+    async fn test_request_sent_to_socket() {
+        let dir = std::env::temp_dir().join(format!("ya-unix-socket-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let socket = dir.join("api.sock");
+        let _ = std::fs::remove_file(&socket);
+        let server = serve_once(&socket);
+
+        let mut response = client(&socket)
+            .get("http://localhost/market-api/v1/offers")
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(response.body().await.unwrap().as_ref(), b"ok");
+        assert_eq!(
+            server.join().unwrap(),
+            "GET /market-api/v1/offers HTTP/1.1\r\n"
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,110 @@
+//! OpenTelemetry tracing of yagna components.
+//!
+//! Tracing is enabled by pointing `OTEL_EXPORTER_OTLP_ENDPOINT` to OTLP (gRPC)
+//! collector. Trace context crosses process boundaries as W3C `traceparent`:
+//! in HTTP header of REST API requests, along with GSB messages forwarded by
+//! `ya-net` and inside `Exec` messages sent to ExeUnits.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::env;
+
+use opentelemetry::global::{self, BoxedTracer};
+use opentelemetry::propagation::TextMapPropagator;
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::sdk::{trace, Resource};
+use opentelemetry::trace::Tracer;
+use opentelemetry_otlp::WithExportConfig;
+
+pub use opentelemetry::trace::{
+    FutureExt as TraceFutureExt, SpanKind, StatusCode, TraceContextExt,
+};
+pub use opentelemetry::{Context, KeyValue};
+
+pub const ENV_OTLP_ENDPOINT: &str = "OTEL_EXPORTER_OTLP_ENDPOINT";
+pub const TRACEPARENT: &str = "traceparent";
+
+const TRACER_NAME: &str = "yagna";
+
+/// Installs OTLP exporter if collector endpoint is set.
+/// Returns whether spans are exported.
+pub fn init(service_name: &'static str) -> anyhow::Result<bool> {
+    let endpoint = match env::var(ENV_OTLP_ENDPOINT) {
+        Ok(endpoint) if !endpoint.is_empty() => endpoint,
+        _ => return Ok(false),
+    };
+
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint.clone()),
+        )
+        .with_trace_config(
+            trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                service_name,
+            )])),
+        )
+        .install_batch(opentelemetry::runtime::TokioCurrentThread)?;
+
+    log::info!("Exporting {} traces to {}", service_name, endpoint);
+    Ok(true)
+}
+
+/// Exports remaining spans.
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}
+
+pub fn tracer() -> BoxedTracer {
+    global::tracer(TRACER_NAME)
+}
+
+/// Starts span as a child of span in `parent` context, or as a new trace
+/// root when there is none. Span ends when returned context is dropped.
+pub fn start_span(name: impl Into<Cow<'static, str>>, kind: SpanKind, parent: &Context) -> Context {
+    let tracer = tracer();
+    let span = tracer
+        .span_builder(name)
+        .with_kind(kind)
+        .start_with_context(&tracer, parent);
+    parent.with_span(span)
+}
+
+/// Checks whether context belongs to a trace, so it is worth propagating.
+pub fn is_traced(cx: &Context) -> bool {
+    cx.span().span_context().is_valid()
+}
+
+pub fn inject(cx: &Context) -> Option<String> {
+    let mut carrier = HashMap::new();
+    TraceContextPropagator::new().inject_context(cx, &mut carrier);
+    carrier.remove(TRACEPARENT)
+}
+
+/// Returns context of remote span, or empty context if `traceparent` is invalid.
+pub fn extract(traceparent: &str) -> Context {
+    let mut carrier = HashMap::new();
+    carrier.insert(TRACEPARENT.to_string(), traceparent.to_string());
+    TraceContextPropagator::new().extract_with_context(&Context::new(), &carrier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "00-0af7651916cd43dd8448eb211c80319c-b7ad6b7169203331-01";
+
+    #[test]
+    fn test_propagation_roundtrip() {
+        let cx = extract(SAMPLE);
+        assert!(is_traced(&cx));
+        assert_eq!(inject(&cx).as_deref(), Some(SAMPLE));
+
+        let invalid = extract("00-xyz");
+        assert!(!is_traced(&invalid));
+        assert_eq!(inject(&invalid), None);
+    }
+}